@@ -4,11 +4,14 @@ use crate::dirs::{JwtSecretPath, PlatformPath};
 use clap::Args;
 use jsonrpsee::{core::Error as RpcError, server::ServerHandle};
 use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory};
-use reth_rpc::{JwtError, JwtSecret};
+use reth_primitives::U256;
+use reth_provider::{
+    BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory,
+};
+use reth_rpc::{eth::gas_oracle::GasPriceOracleConfig, JwtError, JwtSecret};
 use reth_rpc_builder::{
-    constants, IpcServerBuilder, RethRpcModule, RpcModuleSelection, RpcServerConfig,
-    RpcServerHandle, ServerBuilder, TransportRpcModuleConfig,
+    constants, EthConfig, IpcServerBuilder, RethRpcModule, RpcModuleConfig, RpcModuleSelection,
+    RpcServerConfig, RpcServerHandle, ServerBuilder, TransportRpcModuleConfig,
 };
 use reth_rpc_engine_api::EngineApiHandle;
 use reth_tasks::TaskSpawner;
@@ -66,6 +69,22 @@ pub struct RpcServerArgs {
     #[arg(long)]
     pub ipcpath: Option<String>,
 
+    /// Number of recent blocks the gas price oracle samples
+    #[arg(long = "gpo.blocks")]
+    pub gas_price_oracle_blocks: Option<u32>,
+
+    /// Percentile of the sampled tips the gas price oracle suggests
+    #[arg(long = "gpo.percentile")]
+    pub gas_price_oracle_percentile: Option<u32>,
+
+    /// Maximum priority fee the gas price oracle suggests, in wei
+    #[arg(long = "gpo.maxprice")]
+    pub gas_price_oracle_max_price: Option<u64>,
+
+    /// Tip in wei below which transactions are ignored by the gas price oracle
+    #[arg(long = "gpo.ignoreprice")]
+    pub gas_price_oracle_ignore_price: Option<u64>,
+
     /// Auth server address to listen on
     #[arg(long = "authrpc.addr")]
     pub auth_addr: Option<IpAddr>,
@@ -116,6 +135,7 @@ impl RpcServerArgs {
             + HeaderProvider
             + StateProviderFactory
             + EvmEnvProvider
+            + ChainSpecProvider
             + Clone
             + Unpin
             + 'static,
@@ -174,7 +194,8 @@ impl RpcServerArgs {
 
     /// Creates the [TransportRpcModuleConfig] from cli args.
     fn transport_rpc_module_config(&self) -> TransportRpcModuleConfig {
-        let mut config = TransportRpcModuleConfig::default();
+        let mut config = TransportRpcModuleConfig::default()
+            .with_config(RpcModuleConfig::builder().eth(self.eth_config()).build());
         let rpc_modules =
             RpcModuleSelection::Selection(vec![RethRpcModule::Admin, RethRpcModule::Eth]);
         if self.http {
@@ -188,6 +209,25 @@ impl RpcServerArgs {
         config
     }
 
+    /// Creates the [EthConfig] from cli args.
+    fn eth_config(&self) -> EthConfig {
+        let mut gas_oracle = GasPriceOracleConfig::default();
+        if let Some(blocks) = self.gas_price_oracle_blocks {
+            gas_oracle.blocks = blocks;
+        }
+        if let Some(percentile) = self.gas_price_oracle_percentile {
+            gas_oracle.percentile = percentile;
+        }
+        if let Some(max_price) = self.gas_price_oracle_max_price {
+            gas_oracle.max_price = Some(U256::from(max_price));
+        }
+        if let Some(ignore_price) = self.gas_price_oracle_ignore_price {
+            gas_oracle.ignore_price = Some(U256::from(ignore_price));
+        }
+
+        EthConfig { gas_oracle, ..Default::default() }
+    }
+
     /// Creates the [RpcServerConfig] from cli args.
     fn rpc_server_config(&self) -> RpcServerConfig {
         let mut config = RpcServerConfig::default();
@@ -266,6 +306,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eth_config() {
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--gpo.blocks",
+            "10",
+            "--gpo.maxprice",
+            "1000",
+        ])
+        .args;
+        let config = args.eth_config();
+        assert_eq!(
+            config.gas_oracle,
+            GasPriceOracleConfig {
+                blocks: 10,
+                max_price: Some(U256::from(1000)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(config.cache, Default::default());
+
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.eth_config(), EthConfig::default());
+    }
+
     #[test]
     fn test_rpc_server_config() {
         let args = CommandParser::<RpcServerArgs>::parse_from([
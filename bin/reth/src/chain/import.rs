@@ -138,7 +138,8 @@ impl ImportCommand {
             .into_task();
 
         let (tip_tx, tip_rx) = watch::channel(H256::zero());
-        let factory = reth_executor::Factory::new(self.chain.clone());
+        // pre-Byzantium receipts are verified along with the state roots they contain
+        let factory = reth_executor::Factory::new(self.chain.clone()).with_receipt_state_roots();
 
         let mut pipeline = Pipeline::builder()
             .with_tip_sender(tip_tx)
@@ -159,7 +160,10 @@ impl ImportCommand {
                 .set(SenderRecoveryStage {
                     commit_threshold: config.stages.sender_recovery.commit_threshold,
                 })
-                .set(ExecutionStage::new(factory, config.stages.execution.commit_threshold)),
+                .set(
+                    ExecutionStage::new(factory, config.stages.execution.commit_threshold)
+                        .with_prefetch_threads(config.stages.execution.prefetch_threads),
+                ),
             )
             .with_max_block(0)
             .build();
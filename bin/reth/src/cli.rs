@@ -2,9 +2,9 @@
 use std::str::FromStr;
 
 use crate::{
-    chain, db,
+    chain, db, debug_cmd,
     dirs::{LogsDir, PlatformPath},
-    drop_stage, dump_stage, node, p2p,
+    dns, drop_stage, dump_stage, node, p2p, profile,
     runner::CliRunner,
     stage, test_eth_chain, test_vectors,
 };
@@ -32,7 +32,10 @@ pub fn run() -> eyre::Result<()> {
         Commands::Stage(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::DumpStage(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::DropStage(command) => runner.run_until_ctrl_c(command.execute()),
+        Commands::Profile(command) => runner.run_until_ctrl_c(command.execute()),
+        Commands::Debug(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::P2P(command) => runner.run_until_ctrl_c(command.execute()),
+        Commands::Dns(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::TestVectors(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::TestEthChain(command) => runner.run_until_ctrl_c(command.execute()),
     }
@@ -67,9 +70,18 @@ pub enum Commands {
     /// Drops a stage's tables from the database.
     #[command(name = "drop-stage")]
     DropStage(drop_stage::Command),
+    /// Re-executes a range of blocks and reports where gas and time are spent.
+    #[command(name = "profile")]
+    Profile(profile::Command),
+    /// Various debug routines
+    #[command(name = "debug")]
+    Debug(debug_cmd::Command),
     /// P2P Debugging utilities
     #[command(name = "p2p")]
     P2P(p2p::Command),
+    /// DNS discovery utilities
+    #[command(name = "dns")]
+    Dns(dns::Command),
     /// Run Ethereum blockchain tests
     #[command(name = "test-chain")]
     TestEthChain(test_eth_chain::Command),
@@ -0,0 +1,232 @@
+//! Versioned archive format of exported tables.
+//!
+//! An archive is a single zstd stream, with the content checksum of zstd enabled:
+//!
+//! ```text
+//! MAGIC | VERSION (u32)
+//! for each table:
+//!     NAME_LEN (u8) | NAME | TABLE_TYPE (u8)
+//!     for each row:
+//!         KEY_LEN (u32) | KEY | VALUE_LEN (u32) | VALUE
+//!     END_OF_ROWS (u32) | ROWS (u64)
+//! 0 (u8)
+//! ```
+//!
+//! Keys and values are stored with the `Encode` and `Compress` codecs of the tables, all integers
+//! are little endian.
+use eyre::{ensure, WrapErr};
+use reth_db::tables::{TableType, Tables};
+use std::io::{BufRead, Read, Write};
+
+/// Magic bytes at the start of every archive.
+const MAGIC: &[u8; 8] = b"RETHDBAR";
+
+/// Version of the archive format.
+const VERSION: u32 = 1;
+
+/// Marker in place of a key length after the last row of a table.
+const END_OF_ROWS: u32 = u32::MAX;
+
+/// zstd compression level of archives.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Writes tables into an archive.
+pub(crate) struct ArchiveWriter<W: Write> {
+    encoder: zstd::Encoder<'static, W>,
+    /// Rows written of the current table.
+    rows: u64,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Creates a new archive.
+    pub(crate) fn new(writer: W) -> eyre::Result<Self> {
+        let mut encoder = zstd::Encoder::new(writer, COMPRESSION_LEVEL)?;
+        encoder.include_checksum(true)?;
+        encoder.write_all(MAGIC)?;
+        encoder.write_all(&VERSION.to_le_bytes())?;
+        Ok(Self { encoder, rows: 0 })
+    }
+
+    /// Starts the rows of a table.
+    pub(crate) fn begin_table(&mut self, table: Tables) -> eyre::Result<()> {
+        let name = table.name().as_bytes();
+        self.encoder.write_all(&[name.len() as u8])?;
+        self.encoder.write_all(name)?;
+        self.encoder.write_all(&[table.is_dupsort() as u8])?;
+        self.rows = 0;
+        Ok(())
+    }
+
+    /// Writes an encoded key and compressed value of the current table.
+    pub(crate) fn write_row(&mut self, key: &[u8], value: &[u8]) -> eyre::Result<()> {
+        self.encoder.write_all(&(key.len() as u32).to_le_bytes())?;
+        self.encoder.write_all(key)?;
+        self.encoder.write_all(&(value.len() as u32).to_le_bytes())?;
+        self.encoder.write_all(value)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Finishes the rows of the current table, returning the number of written rows.
+    pub(crate) fn end_table(&mut self) -> eyre::Result<u64> {
+        self.encoder.write_all(&END_OF_ROWS.to_le_bytes())?;
+        self.encoder.write_all(&self.rows.to_le_bytes())?;
+        Ok(self.rows)
+    }
+
+    /// Finishes the archive and returns the underlying writer.
+    pub(crate) fn finish(mut self) -> eyre::Result<W> {
+        self.encoder.write_all(&[0])?;
+        let mut writer = self.encoder.finish()?;
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+/// Reads tables from an archive.
+pub(crate) struct ArchiveReader<R: BufRead> {
+    decoder: zstd::Decoder<'static, R>,
+    /// Rows read of the current table.
+    rows: u64,
+}
+
+impl<R: BufRead> ArchiveReader<R> {
+    /// Opens an archive, checking its magic bytes and version.
+    pub(crate) fn new(reader: R) -> eyre::Result<Self> {
+        let mut decoder = zstd::Decoder::with_buffer(reader)?;
+        let mut magic = [0; MAGIC.len()];
+        decoder.read_exact(&mut magic).wrap_err("Failed to read archive header")?;
+        ensure!(&magic == MAGIC, "Not a database archive");
+
+        let mut reader = Self { decoder, rows: 0 };
+        let version = reader.read_u32()?;
+        ensure!(version == VERSION, "Unsupported archive version {version}, expected {VERSION}");
+        Ok(reader)
+    }
+
+    /// Returns the next table of the archive, or `None` at the end of the archive.
+    pub(crate) fn next_table(&mut self) -> eyre::Result<Option<Tables>> {
+        let name_len = self.read_u8()?;
+        if name_len == 0 {
+            // reading to the end also verifies the checksum of the zstd frame
+            let mut trailing = [0; 1];
+            ensure!(self.decoder.read(&mut trailing)? == 0, "Unexpected data after end of archive");
+            return Ok(None)
+        }
+        let name = String::from_utf8(self.read_bytes(name_len as usize)?)?;
+        let table = name.parse::<Tables>().map_err(|err| eyre::eyre!(err))?;
+
+        let table_type = if self.read_u8()? == 1 { TableType::DupSort } else { TableType::Table };
+        ensure!(
+            table.table_type() == table_type,
+            "Table {table} has type {table_type:?} in the archive, expected {:?}",
+            table.table_type()
+        );
+
+        self.rows = 0;
+        Ok(Some(table))
+    }
+
+    /// Returns the next encoded key and compressed value of the current table, or `None` after
+    /// the last row.
+    ///
+    /// After the last row, the number of read rows is checked against the number of written rows.
+    pub(crate) fn next_row(&mut self) -> eyre::Result<Option<(Vec<u8>, Vec<u8>)>> {
+        let key_len = self.read_u32()?;
+        if key_len == END_OF_ROWS {
+            let rows = self.read_u64()?;
+            ensure!(rows == self.rows, "Archive contains {} rows, expected {rows}", self.rows);
+            return Ok(None)
+        }
+        let key = self.read_bytes(key_len as usize)?;
+        let value_len = self.read_u32()?;
+        let value = self.read_bytes(value_len as usize)?;
+        self.rows += 1;
+        Ok(Some((key, value)))
+    }
+
+    /// Skips the remaining rows of the current table, returning their number.
+    pub(crate) fn skip_table(&mut self) -> eyre::Result<u64> {
+        let mut skipped = 0;
+        while self.next_row()?.is_some() {
+            skipped += 1;
+        }
+        Ok(skipped)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> eyre::Result<Vec<u8>> {
+        let mut buf = vec![0; len];
+        self.decoder.read_exact(&mut buf).wrap_err("Unexpected end of archive")?;
+        Ok(buf)
+    }
+
+    fn read_u8(&mut self) -> eyre::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> eyre::Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn read_u64(&mut self) -> eyre::Result<u64> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().expect("8 bytes")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> Vec<u8> {
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        writer.begin_table(Tables::CanonicalHeaders).unwrap();
+        writer.write_row(&[1], &[2, 3]).unwrap();
+        writer.write_row(&[4], &[]).unwrap();
+        assert_eq!(writer.end_table().unwrap(), 2);
+        writer.begin_table(Tables::PlainStorageState).unwrap();
+        assert_eq!(writer.end_table().unwrap(), 0);
+        writer.finish().unwrap()
+    }
+
+    fn read_all(archive: &[u8]) -> eyre::Result<()> {
+        let mut reader = ArchiveReader::new(archive)?;
+        while reader.next_table()?.is_some() {
+            reader.skip_table()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn archive_roundtrip() {
+        let archive = archive();
+        let mut reader = ArchiveReader::new(archive.as_slice()).unwrap();
+        assert_eq!(reader.next_table().unwrap(), Some(Tables::CanonicalHeaders));
+        assert_eq!(reader.next_row().unwrap(), Some((vec![1], vec![2, 3])));
+        assert_eq!(reader.next_row().unwrap(), Some((vec![4], vec![])));
+        assert_eq!(reader.next_row().unwrap(), None);
+        assert_eq!(reader.next_table().unwrap(), Some(Tables::PlainStorageState));
+        assert_eq!(reader.skip_table().unwrap(), 0);
+        assert_eq!(reader.next_table().unwrap(), None);
+    }
+
+    #[test]
+    fn reject_invalid_archives() {
+        assert!(ArchiveReader::new(&b"not an archive"[..]).is_err());
+
+        // truncated archives fail before the end of the archive is reached
+        let archive = archive();
+        let truncated = zstd::stream::encode_all(
+            &zstd::stream::decode_all(archive.as_slice()).unwrap()[..20],
+            COMPRESSION_LEVEL,
+        )
+        .unwrap();
+        assert!(read_all(&truncated).is_err());
+
+        // corrupted data fails the checksum of zstd
+        let mut corrupted = archive.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(read_all(&corrupted).is_err());
+        assert!(read_all(&archive).is_ok());
+    }
+}
@@ -0,0 +1,486 @@
+//! Consistency checks of the database tables.
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{storage_sharded_key::StorageShardedKey, ShardedKey, TransitionIdAddress},
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{keccak256, Address, BlockHash, BlockNumber, Header, TxNumber, H256};
+use reth_provider::static_files::{StaticFileProvider, StaticFileSegment};
+use reth_stages::stages::{
+    ACCOUNT_HASHING, EXECUTION, INDEX_ACCOUNT_HISTORY, INDEX_STORAGE_HISTORY, SENDER_RECOVERY,
+};
+use std::ops::Range;
+use tracing::info;
+
+/// The arguments for the `reth db check` command
+#[derive(Parser, Debug)]
+pub struct CheckArgs {
+    /// Rewrite inconsistent entries of the `HeaderNumbers` and `TransactionBlock` index tables.
+    ///
+    /// All other inconsistencies are only reported.
+    #[arg(long)]
+    repair: bool,
+    /// Maximum number of inconsistencies that are printed per check.
+    #[arg(long, default_value = "10")]
+    max_reported: usize,
+}
+
+impl CheckArgs {
+    /// Runs all checks, prints the report and optionally repairs the index tables.
+    pub(crate) fn execute<DB: Database>(
+        &self,
+        db: &DB,
+        static_files: Option<&StaticFileProvider>,
+    ) -> eyre::Result<()> {
+        let reports =
+            db.view(|tx| DbChecker { tx, static_files, max_reported: self.max_reported }.run())??;
+        println!("{}", report_table(&reports));
+
+        for report in reports.iter() {
+            if let Some(reason) = &report.skipped {
+                println!("{}: skipped, {reason}", report.name);
+            }
+            if report.issues.is_empty() {
+                continue
+            }
+            println!("{}:", report.name);
+            for issue in report.issues.iter() {
+                println!("  - {issue}");
+            }
+            if report.total_issues > report.issues.len() as u64 {
+                println!("  ... and {} more", report.total_issues - report.issues.len() as u64);
+            }
+        }
+
+        let repairs = reports.iter().flat_map(|report| report.repairs.iter()).collect::<Vec<_>>();
+        if self.repair && !repairs.is_empty() {
+            db.update(|tx| {
+                for repair in repairs.iter() {
+                    repair.apply(tx)?;
+                }
+                Ok::<_, reth_db::Error>(())
+            })??;
+            info!(target: "reth::cli", repaired = repairs.len(), "Repaired index tables");
+        } else if !repairs.is_empty() {
+            println!("Run with `--repair` to fix {} index table entries", repairs.len());
+        }
+
+        Ok(())
+    }
+}
+
+/// The result of a single consistency check.
+#[derive(Debug)]
+pub(crate) struct CheckReport {
+    /// Name of the check.
+    pub(crate) name: &'static str,
+    /// Number of checked entries.
+    pub(crate) checked: u64,
+    /// The first reported inconsistencies.
+    pub(crate) issues: Vec<String>,
+    /// Total number of inconsistencies.
+    pub(crate) total_issues: u64,
+    /// Fixes for inconsistent index table entries.
+    pub(crate) repairs: Vec<Repair>,
+    /// The reason why the check was skipped, if it was.
+    pub(crate) skipped: Option<String>,
+    max_reported: usize,
+}
+
+impl CheckReport {
+    fn new(name: &'static str, max_reported: usize) -> Self {
+        Self {
+            name,
+            checked: 0,
+            issues: Vec::new(),
+            total_issues: 0,
+            repairs: Vec::new(),
+            skipped: None,
+            max_reported,
+        }
+    }
+
+    fn skip(mut self, reason: impl Into<String>) -> Self {
+        self.skipped = Some(reason.into());
+        self
+    }
+
+    /// Records an inconsistency, the message is only created if it is reported.
+    fn issue(&mut self, message: impl FnOnce() -> String) {
+        if self.issues.len() < self.max_reported {
+            self.issues.push(message());
+        }
+        self.total_issues += 1;
+    }
+}
+
+/// A fix for an entry of an index table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Repair {
+    /// Sets the number of the block hash in `HeaderNumbers`.
+    HeaderNumber(BlockHash, BlockNumber),
+    /// Sets the block of the last transaction in a block in `TransactionBlock`.
+    TransactionBlock(TxNumber, BlockNumber),
+}
+
+impl Repair {
+    fn apply<'a, TX: DbTxMut<'a>>(&self, tx: &TX) -> Result<(), reth_db::Error> {
+        match *self {
+            Repair::HeaderNumber(hash, number) => tx.put::<tables::HeaderNumbers>(hash, number),
+            Repair::TransactionBlock(tx_number, number) => {
+                tx.put::<tables::TransactionBlock>(tx_number, number)
+            }
+        }
+    }
+}
+
+/// Runs the consistency checks within a single read transaction.
+struct DbChecker<'t, TX> {
+    tx: &'t TX,
+    /// Static files that hold headers and transactions moved out of the database.
+    static_files: Option<&'t StaticFileProvider>,
+    max_reported: usize,
+}
+
+impl<'a, 't, TX: DbTx<'a>> DbChecker<'t, TX> {
+    fn run(&self) -> eyre::Result<Vec<CheckReport>> {
+        Ok(vec![
+            self.check_canonical_headers()?,
+            self.check_block_bodies()?,
+            self.check_senders()?,
+            self.check_block_transitions()?,
+            self.check_account_history()?,
+            self.check_storage_history()?,
+            self.check_hashed_accounts()?,
+        ])
+    }
+
+    /// `CanonicalHeaders`, `HeaderNumbers` and `Headers` have to agree on every canonical block.
+    fn check_canonical_headers(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("canonical headers", self.max_reported);
+        for entry in self.tx.cursor_read::<tables::CanonicalHeaders>()?.walk(None)? {
+            let (number, hash) = entry?;
+            report.checked += 1;
+
+            let header_number = self.tx.get::<tables::HeaderNumbers>(hash)?;
+            if header_number != Some(number) {
+                report.issue(|| {
+                    format!("HeaderNumbers of canonical block #{number} ({hash:?}) is {header_number:?}")
+                });
+                report.repairs.push(Repair::HeaderNumber(hash, number));
+            }
+
+            match self.header(number)? {
+                None => report.issue(|| format!("Header of canonical block #{number} is missing")),
+                Some(header) if header.hash_slow() != hash => report.issue(|| {
+                    format!("Header of canonical block #{number} does not hash to {hash:?}")
+                }),
+                Some(_) => {}
+            }
+        }
+        Ok(report)
+    }
+
+    /// Block bodies have to cover consecutive transactions that all exist, and the last
+    /// transaction of every block has to map to the block in `TransactionBlock`.
+    fn check_block_bodies(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("block bodies", self.max_reported);
+        let mut next_tx_id = None;
+        for entry in self.tx.cursor_read::<tables::BlockBodies>()?.walk(None)? {
+            let (number, body) = entry?;
+            report.checked += 1;
+
+            if let Some(next_tx_id) = next_tx_id.filter(|next| *next != body.start_tx_id) {
+                report.issue(|| {
+                    format!(
+                        "Body of block #{number} starts at transaction {}, expected {next_tx_id}",
+                        body.start_tx_id
+                    )
+                });
+            }
+            next_tx_id = Some(body.start_tx_id + body.tx_count);
+
+            let found = self.count_transactions(body.tx_id_range())?;
+            if found != body.tx_count {
+                report.issue(|| {
+                    format!(
+                        "Block #{number} has {} transactions, found {found} in Transactions",
+                        body.tx_count
+                    )
+                });
+            }
+
+            if body.tx_count > 0 {
+                let last_tx = body.last_tx_index();
+                let tx_block = self.tx.get::<tables::TransactionBlock>(last_tx)?;
+                if tx_block != Some(number) {
+                    report.issue(|| {
+                        format!("TransactionBlock of transaction {last_tx} is {tx_block:?}, expected block #{number}")
+                    });
+                    report.repairs.push(Repair::TransactionBlock(last_tx, number));
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// The sender of every transaction up to the progress of the sender recovery stage has to be
+    /// stored.
+    fn check_senders(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("transaction senders", self.max_reported);
+        let Some(progress) = SENDER_RECOVERY.get_progress(self.tx)? else {
+            return Ok(report.skip("senders were not recovered yet"))
+        };
+        let Some(body) = self.tx.get::<tables::BlockBodies>(progress)? else {
+            return Ok(report.skip(format!("body of block #{progress} is missing")))
+        };
+
+        let expected = body.tx_id_range().end;
+        let found = self.tx.cursor_read::<tables::TxSenders>()?.walk(None)?.count() as u64;
+        report.checked = found;
+        if found != expected {
+            report.issue(|| {
+                format!("Found {found} senders, expected {expected} up to block #{progress}")
+            });
+        }
+        Ok(report)
+    }
+
+    /// `BlockTransitionIndex` has to contain consecutive blocks with non-decreasing transitions.
+    fn check_block_transitions(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("block transitions", self.max_reported);
+        let mut previous: Option<(BlockNumber, u64)> = None;
+        for entry in self.tx.cursor_read::<tables::BlockTransitionIndex>()?.walk(None)? {
+            let (number, transition) = entry?;
+            report.checked += 1;
+
+            if let Some((previous_number, previous_transition)) = previous {
+                if number != previous_number + 1 {
+                    report.issue(|| {
+                        format!("Block transitions jump from block #{previous_number} to #{number}")
+                    });
+                }
+                if transition < previous_transition {
+                    report.issue(|| {
+                        format!(
+                            "Transition {transition} of block #{number} is lower than {previous_transition} of block #{previous_number}"
+                        )
+                    });
+                }
+            }
+            previous = Some((number, transition));
+        }
+        Ok(report)
+    }
+
+    /// Every account changeset up to the progress of the account history index has to be indexed.
+    fn check_account_history(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("account history", self.max_reported);
+        let Some(end) =
+            self.indexed_transitions_end(INDEX_ACCOUNT_HISTORY.get_progress(self.tx)?)?
+        else {
+            return Ok(report.skip("account history was not indexed yet"))
+        };
+
+        let mut history_cursor = self.tx.cursor_read::<tables::AccountHistory>()?;
+        for entry in self.tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(..end)? {
+            let (transition, account) = entry?;
+            report.checked += 1;
+
+            let indexed = history_cursor
+                .seek(ShardedKey::new(account.address, transition))?
+                .filter(|(key, _)| key.key == account.address)
+                .map_or(false, |(_, list)| is_indexed(list, transition));
+            if !indexed {
+                report.issue(|| {
+                    format!(
+                        "Change of {:?} at transition {transition} is not indexed",
+                        account.address
+                    )
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    /// Every storage changeset up to the progress of the storage history index has to be indexed.
+    fn check_storage_history(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("storage history", self.max_reported);
+        let Some(end) =
+            self.indexed_transitions_end(INDEX_STORAGE_HISTORY.get_progress(self.tx)?)?
+        else {
+            return Ok(report.skip("storage history was not indexed yet"))
+        };
+
+        let mut history_cursor = self.tx.cursor_read::<tables::StorageHistory>()?;
+        let range =
+            TransitionIdAddress((0, Address::zero()))..TransitionIdAddress((end, Address::zero()));
+        for entry in self.tx.cursor_read::<tables::StorageChangeSet>()?.walk_range(range)? {
+            let (key, storage) = entry?;
+            let (transition, address) = (key.transition_id(), key.address());
+            report.checked += 1;
+
+            let indexed = history_cursor
+                .seek(StorageShardedKey::new(address, storage.key, transition))?
+                .filter(|(key, _)| key.address == address && key.sharded_key.key == storage.key)
+                .map_or(false, |(_, list)| is_indexed(list, transition));
+            if !indexed {
+                report.issue(|| {
+                    format!(
+                        "Change of slot {:?} of {address:?} at transition {transition} is not indexed",
+                        storage.key
+                    )
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    /// `HashedAccount` has to contain exactly the accounts of `PlainAccountState` once account
+    /// hashing caught up with execution.
+    fn check_hashed_accounts(&self) -> eyre::Result<CheckReport> {
+        let mut report = CheckReport::new("hashed accounts", self.max_reported);
+        let hashing = ACCOUNT_HASHING.get_progress(self.tx)?;
+        let execution = EXECUTION.get_progress(self.tx)?;
+        if hashing.is_none() || hashing != execution {
+            return Ok(report.skip(format!(
+                "account hashing progress {hashing:?} differs from execution progress {execution:?}"
+            )))
+        }
+
+        for entry in self.tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+            let (address, account) = entry?;
+            report.checked += 1;
+
+            let hashed = self.tx.get::<tables::HashedAccount>(keccak256(address))?;
+            if hashed != Some(account) {
+                report.issue(|| {
+                    format!("Hashed account of {address:?} is {hashed:?}, expected {account:?}")
+                });
+            }
+        }
+
+        let plain_count = report.checked;
+        let hashed_count =
+            self.tx.cursor_read::<tables::HashedAccount>()?.walk(None)?.count() as u64;
+        if hashed_count != plain_count {
+            report
+                .issue(|| format!("Found {hashed_count} hashed accounts, expected {plain_count}"));
+        }
+        Ok(report)
+    }
+
+    /// Returns the header of the block from the static files or the database.
+    fn header(&self, number: BlockNumber) -> eyre::Result<Option<Header>> {
+        if let Some(static_files) = self.static_files {
+            if static_files.contains::<tables::Headers>(number) {
+                return Ok(static_files.get::<tables::Headers>(number)?)
+            }
+        }
+        Ok(self.tx.get::<tables::Headers>(number)?)
+    }
+
+    /// Returns the number of transactions in the range that are stored in the static files or the
+    /// database.
+    fn count_transactions(&self, range: Range<TxNumber>) -> eyre::Result<u64> {
+        let mut start = range.start;
+        let mut count = 0;
+        if let Some(static_files) = self.static_files {
+            let segment = StaticFileSegment::Transactions;
+            if let (Some(lowest), Some(highest)) =
+                (static_files.lowest(segment), static_files.highest(segment))
+            {
+                count += range.end.min(highest + 1).saturating_sub(start.max(lowest));
+                start = start.max(highest + 1);
+            }
+        }
+        if start < range.end {
+            count += self
+                .tx
+                .cursor_read::<tables::Transactions>()?
+                .walk_range(start..range.end)?
+                .count() as u64;
+        }
+        Ok(count)
+    }
+
+    /// Returns the end of the transitions that were indexed up to the given block.
+    fn indexed_transitions_end(&self, progress: Option<BlockNumber>) -> eyre::Result<Option<u64>> {
+        let Some(progress) = progress else { return Ok(None) };
+        Ok(self.tx.get::<tables::BlockTransitionIndex>(progress)?)
+    }
+}
+
+/// Returns true if the transition is in the list of a history shard.
+fn is_indexed(list: tables::TransitionList, transition: u64) -> bool {
+    list.0.enable_rank().successor(transition as usize) == Some(transition as usize)
+}
+
+/// Creates the summary table of the reports.
+fn report_table(reports: &[CheckReport]) -> ComfyTable {
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(["Check", "Checked", "Inconsistencies", "Repairable"]);
+    for report in reports {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(report.name))
+            .add_cell(Cell::new(report.checked))
+            .add_cell(Cell::new(if report.skipped.is_some() {
+                "skipped".to_string()
+            } else {
+                report.total_issues.to_string()
+            }))
+            .add_cell(Cell::new(report.repairs.len()));
+        table.add_row(row);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_provider::insert_canonical_block;
+
+    fn run_checks<DB: Database>(db: &DB) -> Vec<CheckReport> {
+        db.view(|tx| DbChecker { tx, static_files: None, max_reported: 10 }.run()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn check_and_repair_indices() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = random_block_range(0..5, H256::zero(), 1..3);
+        db.update(|tx| {
+            for block in blocks.iter() {
+                insert_canonical_block(tx, block.clone(), None, false).unwrap();
+            }
+        })
+        .unwrap();
+
+        let reports = run_checks(db.as_ref());
+        assert!(reports.iter().all(|report| report.total_issues == 0), "{reports:?}");
+
+        let last_tx = blocks[..=2].iter().map(|block| block.body.len() as u64).sum::<u64>() - 1;
+        db.update(|tx| {
+            tx.delete::<tables::HeaderNumbers>(blocks[1].hash(), None).unwrap();
+            tx.put::<tables::TransactionBlock>(last_tx, 4).unwrap();
+        })
+        .unwrap();
+
+        let reports = run_checks(db.as_ref());
+        let repairs = reports.iter().flat_map(|report| report.repairs.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            repairs,
+            vec![Repair::HeaderNumber(blocks[1].hash(), 1), Repair::TransactionBlock(last_tx, 2)]
+        );
+
+        db.update(|tx| repairs.iter().try_for_each(|repair| repair.apply(tx))).unwrap().unwrap();
+        let reports = run_checks(db.as_ref());
+        assert!(reports.iter().all(|report| report.total_issues == 0), "{reports:?}");
+    }
+}
@@ -0,0 +1,119 @@
+//! Exporting tables into an archive.
+use super::archive::ArchiveWriter;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::WrapErr;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    table::{Compress, Decode, Encode, Table},
+    tables::{TableViewer, Tables},
+    transaction::DbTx,
+};
+use reth_primitives::BlockNumber;
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufWriter, Write},
+    ops::Bound,
+    path::PathBuf,
+};
+use tracing::info;
+
+/// The arguments for the `reth db export` command
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    /// The path of the archive to create.
+    path: PathBuf,
+    /// The tables to export, all tables by default.
+    #[arg(long, value_delimiter = ',')]
+    tables: Vec<Tables>,
+    /// The first block to export of tables that are keyed by block number.
+    #[arg(long)]
+    from: Option<BlockNumber>,
+    /// The last block to export of tables that are keyed by block number.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+}
+
+impl ExportArgs {
+    /// Exports the selected tables into a new archive.
+    pub(crate) fn execute<DB: Database>(&self, db: &DB) -> eyre::Result<()> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            eyre::ensure!(from <= to, "Invalid block range {from}..={to}")
+        }
+        let tables = if self.tables.is_empty() { Tables::ALL } else { self.tables.as_slice() };
+
+        let file = File::create(&self.path)
+            .wrap_err_with(|| format!("Could not create archive at {}", self.path.display()))?;
+        let writer = RefCell::new(ArchiveWriter::new(BufWriter::new(file))?);
+
+        let mut summary = ComfyTable::new();
+        summary.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        summary.set_header(["Table Name", "# Rows"]);
+        for table in tables {
+            let (from, to) =
+                if is_block_keyed(*table) { (self.from, self.to) } else { (None, None) };
+            writer.borrow_mut().begin_table(*table)?;
+            table.view(&ExportViewer { db, writer: &writer, from, to })?;
+            let rows = writer.borrow_mut().end_table()?;
+            info!(target: "reth::cli", %table, rows, "Exported table");
+
+            let mut row = Row::new();
+            row.add_cell(Cell::new(table)).add_cell(Cell::new(rows));
+            summary.add_row(row);
+        }
+        writer.into_inner().finish()?.into_inner()?.sync_all()?;
+
+        println!("{summary}");
+        Ok(())
+    }
+}
+
+/// Returns true if the table is keyed by block number, so it can be exported for a block range.
+fn is_block_keyed(table: Tables) -> bool {
+    matches!(
+        table,
+        Tables::CanonicalHeaders |
+            Tables::HeaderTD |
+            Tables::Headers |
+            Tables::BlockBodies |
+            Tables::BlockOmmers |
+            Tables::BlockWithdrawals |
+            Tables::BlockTransitionIndex
+    )
+}
+
+/// Writes the rows of a table into the archive.
+struct ExportViewer<'a, DB, W: Write> {
+    db: &'a DB,
+    writer: &'a RefCell<ArchiveWriter<W>>,
+    /// The first block to export, only set for block-keyed tables.
+    from: Option<BlockNumber>,
+    /// The last block to export, only set for block-keyed tables.
+    to: Option<BlockNumber>,
+}
+
+impl<DB: Database, W: Write> TableViewer<()> for ExportViewer<'_, DB, W> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        // block-keyed tables use the encoding of block numbers for their keys
+        let bound = |block: Option<BlockNumber>| -> eyre::Result<Bound<T::Key>> {
+            Ok(match block {
+                Some(block) => Bound::Included(T::Key::decode(block.encode().to_vec())?),
+                None => Bound::Unbounded,
+            })
+        };
+        let range = (bound(self.from)?, bound(self.to)?);
+
+        let mut writer = self.writer.borrow_mut();
+        self.db.view(|tx| {
+            for entry in tx.cursor_read::<T>()?.walk_range(range)? {
+                let (key, value) = entry?;
+                writer.write_row(key.encode().as_ref(), value.compress().as_ref())?;
+            }
+            Ok::<_, eyre::Report>(())
+        })?
+    }
+}
@@ -0,0 +1,168 @@
+//! Fetching single entries of a table.
+use clap::Parser;
+use eyre::WrapErr;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    table::{DupSort, Table},
+    tables::{TableViewer, Tables},
+    transaction::DbTx,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The arguments for the `reth db get` command
+#[derive(Parser, Debug)]
+pub struct GetArgs {
+    /// The table name
+    table: Tables,
+    /// The key of the entry.
+    ///
+    /// Keys are given as JSON, plain strings don't need to be quoted, e.g. `15537394`, `0x..` or
+    /// `{"key":"0x..","highest_transition_id":100}` for sharded keys.
+    #[arg(verbatim_doc_comment)]
+    key: String,
+    /// The subkey of the entry in `DUPSORT` tables, e.g. the storage slot.
+    ///
+    /// Without a subkey all values of the key are printed.
+    #[arg(long)]
+    subkey: Option<String>,
+}
+
+impl GetArgs {
+    /// Prints the decoded value of the entry as JSON.
+    pub(crate) fn execute<DB: Database>(&self, db: &DB) -> eyre::Result<()> {
+        self.table.view(&GetValueViewer { db, args: self })
+    }
+}
+
+/// Looks up the entry in the table selected by the `reth db get` arguments.
+struct GetValueViewer<'a, DB: Database> {
+    db: &'a DB,
+    args: &'a GetArgs,
+}
+
+impl<DB: Database> TableViewer<()> for GetValueViewer<'_, DB> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        if self.args.subkey.is_some() {
+            eyre::bail!("{} is not a DUPSORT table and has no subkeys", T::NAME)
+        }
+        let key = parse_key::<T::Key>(&self.args.key)?;
+        let value = self.db.view(|tx| tx.get::<T>(key))??;
+        print_value(T::NAME, value)
+    }
+
+    fn view_dupsort<T: DupSort>(&self) -> Result<(), Self::Error> {
+        let key = parse_key::<T::Key>(&self.args.key)?;
+        let Some(subkey) = &self.args.subkey else {
+            let values = self.db.view(|tx| {
+                let mut cursor = tx.cursor_dup_read::<T>()?;
+                cursor
+                    .walk_dup(Some(key), None)?
+                    .map(|entry| entry.map(|(_, value)| value))
+                    .collect::<Result<Vec<_>, _>>()
+            })??;
+            return print_value(T::NAME, (!values.is_empty()).then_some(values))
+        };
+
+        let subkey = parse_key::<T::SubKey>(subkey)?;
+        print_value(T::NAME, get_dup_value::<T, _>(self.db, key, subkey)?)
+    }
+}
+
+/// Returns the value of the key with exactly the given subkey.
+fn get_dup_value<T: DupSort, DB: Database>(
+    db: &DB,
+    key: T::Key,
+    subkey: T::SubKey,
+) -> eyre::Result<Option<T::Value>> {
+    let value =
+        db.view(|tx| tx.cursor_dup_read::<T>()?.seek_by_key_subkey(key, subkey.clone()))??;
+    // the cursor returns the next value of the key if the subkey doesn't exist
+    Ok(value.filter(|value| T::subkey(value) == subkey))
+}
+
+/// Parses a key from JSON, falling back to a JSON string for unquoted strings.
+fn parse_key<K: DeserializeOwned>(key: &str) -> eyre::Result<K> {
+    serde_json::from_str(key)
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(key.to_string())))
+        .wrap_err_with(|| format!("Failed to parse key: {key}"))
+}
+
+/// Prints the value as pretty JSON.
+fn print_value<V: Serialize>(table: &str, value: Option<V>) -> eyre::Result<()> {
+    match value {
+        Some(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+        None => println!("No entry found in {table}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::{storage_sharded_key::StorageShardedKey, ShardedKey},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{Address, PruneSegment, StorageEntry, H256, U256};
+
+    #[test]
+    fn parse_table_keys() {
+        assert_eq!(parse_key::<u64>("15537394").unwrap(), 15537394);
+        assert_eq!(parse_key::<String>("Execution").unwrap(), "Execution");
+        assert_eq!(parse_key::<PruneSegment>("receipts").unwrap(), PruneSegment::Receipts);
+        assert_eq!(
+            parse_key::<H256>("0x0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap(),
+            H256::from_low_u64_be(1)
+        );
+
+        let address = Address::from_low_u64_be(2);
+        assert_eq!(
+            parse_key::<ShardedKey<Address>>(&format!(
+                r#"{{"key":"{address:?}","highest_transition_id":100}}"#
+            ))
+            .unwrap(),
+            ShardedKey::new(address, 100)
+        );
+        assert_eq!(
+            parse_key::<StorageShardedKey>(&format!(
+                r#"{{"address":"{address:?}","sharded_key":{{"key":"{:?}","highest_transition_id":5}}}}"#,
+                H256::zero()
+            ))
+            .unwrap(),
+            StorageShardedKey::new(address, H256::zero(), 5)
+        );
+        assert!(parse_key::<u64>("0xzz").is_err());
+    }
+
+    #[test]
+    fn get_dup_value_by_subkey() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let address = Address::from_low_u64_be(1);
+        let entry =
+            |slot| StorageEntry { key: H256::from_low_u64_be(slot), value: U256::from(slot) };
+        db.update(|tx| {
+            tx.put::<tables::PlainStorageState>(address, entry(1))?;
+            tx.put::<tables::PlainStorageState>(address, entry(3))
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            get_dup_value::<tables::PlainStorageState, _>(&*db, address, H256::from_low_u64_be(3))
+                .unwrap(),
+            Some(entry(3))
+        );
+        // the next slot of the address is not returned for a missing slot
+        assert_eq!(
+            get_dup_value::<tables::PlainStorageState, _>(&*db, address, H256::from_low_u64_be(2))
+                .unwrap(),
+            None
+        );
+    }
+}
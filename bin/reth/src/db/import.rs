@@ -0,0 +1,279 @@
+//! Importing tables from an archive.
+use super::archive::ArchiveReader;
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::WrapErr;
+use reth_db::{
+    cursor::{DbCursorRW, DbDupCursorRW},
+    database::Database,
+    table::{Decode, Decompress, DupSort, Table},
+    tables::{TableViewer, Tables},
+    transaction::DbTxMut,
+};
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+use tracing::info;
+
+/// Maximum number of rows imported in one database transaction.
+const BATCH_SIZE: usize = 100_000;
+
+/// The arguments for the `reth db import` command
+#[derive(Parser, Debug)]
+pub struct ImportArgs {
+    /// The path of the archive to import.
+    path: PathBuf,
+    /// The tables to import, all tables of the archive by default.
+    #[arg(long, value_delimiter = ',')]
+    tables: Vec<Tables>,
+}
+
+impl ImportArgs {
+    /// Imports the selected tables of the archive.
+    ///
+    /// Rows are appended, so their keys have to be greater than the keys of the rows that are
+    /// already in the tables.
+    pub(crate) fn execute<DB: Database>(&self, db: &DB) -> eyre::Result<()> {
+        // Truncated or corrupted archives are only detected when the row counts and the checksum
+        // at their end are read, so the archive is read completely before the first row is
+        // written.
+        self.validate()?;
+
+        let reader = self.open()?;
+        let mut summary = ComfyTable::new();
+        summary.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        summary.set_header(["Table Name", "# Rows"]);
+        loop {
+            let Some(table) = reader.borrow_mut().next_table()? else { break };
+            if !self.tables.is_empty() && !self.tables.contains(&table) {
+                let rows = reader.borrow_mut().skip_table()?;
+                info!(target: "reth::cli", %table, rows, "Skipped table");
+                continue
+            }
+
+            let rows = table
+                .view(&ImportViewer { db, reader: &reader })
+                .wrap_err_with(|| format!("Failed to import table {table}"))?;
+            info!(target: "reth::cli", %table, rows, "Imported table");
+
+            let mut row = Row::new();
+            row.add_cell(Cell::new(table)).add_cell(Cell::new(rows));
+            summary.add_row(row);
+        }
+
+        println!("{summary}");
+        Ok(())
+    }
+
+    /// Opens the archive.
+    fn open(&self) -> eyre::Result<RefCell<ArchiveReader<BufReader<File>>>> {
+        let file = File::open(&self.path)
+            .wrap_err_with(|| format!("Could not open archive at {}", self.path.display()))?;
+        Ok(RefCell::new(ArchiveReader::new(BufReader::new(file))?))
+    }
+
+    /// Reads and decodes all rows of the selected tables of the archive, without importing them.
+    fn validate(&self) -> eyre::Result<()> {
+        let reader = self.open()?;
+        loop {
+            let Some(table) = reader.borrow_mut().next_table()? else { break };
+            if !self.tables.is_empty() && !self.tables.contains(&table) {
+                reader.borrow_mut().skip_table()?;
+                continue
+            }
+            table
+                .view(&ValidateViewer { reader: &reader })
+                .wrap_err_with(|| format!("Invalid rows of table {table} in archive"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the rows of the current table of the archive.
+struct ValidateViewer<'a, R: BufRead> {
+    reader: &'a RefCell<ArchiveReader<R>>,
+}
+
+impl<R: BufRead> TableViewer<()> for ValidateViewer<'_, R> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        let mut reader = self.reader.borrow_mut();
+        while let Some((key, value)) = reader.next_row()? {
+            T::Key::decode(key)?;
+            T::Value::decompress(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends the rows of the current table of the archive to the table.
+struct ImportViewer<'a, DB, R: BufRead> {
+    db: &'a DB,
+    reader: &'a RefCell<ArchiveReader<R>>,
+}
+
+impl<DB, R: BufRead> ImportViewer<'_, DB, R> {
+    /// Reads and decodes the next batch of rows, returning an empty batch after the last row.
+    fn next_batch<T: Table>(&self) -> eyre::Result<Vec<(T::Key, T::Value)>> {
+        let mut reader = self.reader.borrow_mut();
+        let mut rows = Vec::new();
+        while rows.len() < BATCH_SIZE {
+            let Some((key, value)) = reader.next_row()? else { break };
+            rows.push((T::Key::decode(key)?, T::Value::decompress(value)?));
+        }
+        Ok(rows)
+    }
+}
+
+impl<DB: Database, R: BufRead> TableViewer<usize> for ImportViewer<'_, DB, R> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<usize, Self::Error> {
+        let mut imported = 0;
+        loop {
+            let rows = self.next_batch::<T>()?;
+            if rows.is_empty() {
+                return Ok(imported)
+            }
+            imported += rows.len();
+            self.db.update(|tx| {
+                let mut cursor = tx.cursor_write::<T>()?;
+                rows.into_iter().try_for_each(|(key, value)| cursor.append(key, value))
+            })??;
+        }
+    }
+
+    fn view_dupsort<T: DupSort>(&self) -> Result<usize, Self::Error> {
+        let mut imported = 0;
+        loop {
+            let rows = self.next_batch::<T>()?;
+            if rows.is_empty() {
+                return Ok(imported)
+            }
+            imported += rows.len();
+            self.db.update(|tx| {
+                let mut cursor = tx.cursor_dup_write::<T>()?;
+                rows.into_iter().try_for_each(|(key, value)| cursor.append_dup(key, value))
+            })??;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::export::ExportArgs;
+    use reth_db::{
+        cursor::DbCursorRO,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::DbTx,
+    };
+    use reth_primitives::{Header, StorageEntry, H160, H256, U256};
+
+    #[test]
+    fn export_and_import() {
+        let source = create_test_db::<WriteMap>(EnvKind::RW);
+        let address = H160::from_low_u64_be(1);
+        source
+            .update(|tx| {
+                for number in 0..10 {
+                    tx.put::<tables::Headers>(number, Header { number, ..Default::default() })?;
+                    tx.put::<tables::CanonicalHeaders>(number, H256::from_low_u64_be(number))?;
+                }
+                for slot in 0..3 {
+                    tx.put::<tables::PlainStorageState>(
+                        address,
+                        StorageEntry { key: H256::from_low_u64_be(slot), value: U256::from(slot) },
+                    )?;
+                }
+                Ok::<_, reth_db::Error>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive");
+        ExportArgs::parse_from([
+            "export",
+            path.to_str().unwrap(),
+            "--tables",
+            "Headers,PlainStorageState,CanonicalHeaders",
+            "--from",
+            "3",
+            "--to",
+            "6",
+        ])
+        .execute(source.as_ref())
+        .unwrap();
+
+        let destination = create_test_db::<WriteMap>(EnvKind::RW);
+        ImportArgs::parse_from([
+            "import",
+            path.to_str().unwrap(),
+            "--tables",
+            "Headers,PlainStorageState",
+        ])
+        .execute(destination.as_ref())
+        .unwrap();
+
+        let tx = destination.tx().unwrap();
+        let headers = tx
+            .cursor_read::<tables::Headers>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|entry| entry.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(headers, vec![3, 4, 5, 6]);
+        assert_eq!(tx.cursor_read::<tables::CanonicalHeaders>().unwrap().first().unwrap(), None);
+        assert_eq!(
+            tx.cursor_read::<tables::PlainStorageState>().unwrap().walk(None).unwrap().count(),
+            3
+        );
+
+        // importing the same rows again fails, as keys have to be appended
+        assert!(ImportArgs::parse_from(["import", path.to_str().unwrap()])
+            .execute(destination.as_ref())
+            .is_err());
+    }
+
+    #[test]
+    fn reject_truncated_archive_before_import() {
+        let source = create_test_db::<WriteMap>(EnvKind::RW);
+        source
+            .update(|tx| {
+                for number in 0..10 {
+                    tx.put::<tables::Headers>(number, Header { number, ..Default::default() })?;
+                }
+                Ok::<_, reth_db::Error>(())
+            })
+            .unwrap()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive");
+        ExportArgs::parse_from(["export", path.to_str().unwrap(), "--tables", "Headers"])
+            .execute(source.as_ref())
+            .unwrap();
+
+        // cut the archive within the rows of the table
+        let archive = std::fs::read(&path).unwrap();
+        let content = zstd::stream::decode_all(archive.as_slice()).unwrap();
+        let truncated = zstd::stream::encode_all(&content[..content.len() / 2], 3).unwrap();
+        std::fs::write(&path, truncated).unwrap();
+
+        let destination = create_test_db::<WriteMap>(EnvKind::RW);
+        assert!(ImportArgs::parse_from(["import", path.to_str().unwrap()])
+            .execute(destination.as_ref())
+            .is_err());
+        assert_eq!(
+            destination.tx().unwrap().cursor_read::<tables::Headers>().unwrap().first().unwrap(),
+            None
+        );
+    }
+}
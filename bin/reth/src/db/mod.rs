@@ -7,12 +7,33 @@ use clap::{Parser, Subcommand};
 use comfy_table::{Cell, Row, Table as ComfyTable};
 use eyre::WrapErr;
 use human_bytes::human_bytes;
-use reth_db::{database::Database, tables};
+use reth_db::{
+    database::Database,
+    mdbx::{Env, WriteMap},
+    table::Table,
+    tables::{TableViewer, Tables},
+};
+use reth_provider::static_files::StaticFileProvider;
 use tracing::error;
 
 /// DB List TUI
 mod tui;
 
+/// Consistency checks
+mod check;
+
+/// Single entry lookups
+mod get;
+
+/// Archive format of exported tables
+mod archive;
+
+/// Exporting tables
+mod export;
+
+/// Importing tables
+mod import;
+
 /// `reth db` command
 #[derive(Debug, Parser)]
 pub struct Command {
@@ -39,21 +60,31 @@ pub enum Subcommands {
     Stats,
     /// Lists the contents of a table
     List(ListArgs),
+    /// Gets the decoded value of a single entry of a table
+    Get(get::GetArgs),
     /// Seeds the database with random blocks on top of each other
     Seed {
         /// How many blocks to generate
         #[arg(default_value = DEFAULT_NUM_ITEMS)]
         len: u64,
     },
+    /// Exports tables into a compressed archive
+    ///
+    /// Rows that were moved to static files are not exported.
+    Export(export::ExportArgs),
+    /// Imports tables from an archive created by `reth db export`
+    Import(import::ImportArgs),
     /// Deletes all database entries
     Drop,
+    /// Checks the consistency of the tables and optionally repairs the index tables
+    Check(check::CheckArgs),
 }
 
 #[derive(Parser, Debug)]
 /// The arguments for the `reth db list` command
 pub struct ListArgs {
     /// The table name
-    table: String, // TODO: Convert to enum
+    table: Tables,
     /// Where to start iterating
     #[arg(long, short, default_value = "0")]
     start: usize,
@@ -68,10 +99,7 @@ impl Command {
         std::fs::create_dir_all(&self.db)?;
 
         // TODO: Auto-impl for Database trait
-        let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
-            self.db.as_ref(),
-            reth_db::mdbx::EnvKind::RW,
-        )?;
+        let db = Env::<WriteMap>::open(self.db.as_ref(), reth_db::mdbx::EnvKind::RW)?;
 
         let mut tool = DbTool::new(&db)?;
 
@@ -90,7 +118,7 @@ impl Command {
                 ]);
 
                 tool.db.view(|tx| {
-                    for table in tables::TABLES.iter().map(|(_, name)| name) {
+                    for table in Tables::ALL.iter().map(|table| table.name()) {
                         let table_db =
                             tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
 
@@ -127,59 +155,69 @@ impl Command {
                 tool.seed(*len)?;
             }
             Subcommands::List(args) => {
-                macro_rules! table_tui {
-                    ($arg:expr, $start:expr, $len:expr => [$($table:ident),*]) => {
-                        match $arg {
-                            $(stringify!($table) => {
-                                tool.db.view(|tx| {
-                                    let table_db = tx.inner.open_db(Some(stringify!($table))).wrap_err("Could not open db.")?;
-                                    let stats = tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {}", stringify!($table)))?;
-                                    let total_entries = stats.entries();
-                                    if $start > total_entries - 1 {
-                                        error!(
-                                            target: "reth::cli",
-                                            "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
-                                            start = $start,
-                                            final_entry_idx = total_entries - 1,
-                                            table = stringify!($table)
-                                        );
-                                        return Ok(());
-                                    }
-
-                                    tui::DbListTUI::<_, tables::$table>::new(|start, count| {
-                                        tool.list::<tables::$table>(start, count).unwrap()
-                                    }, $start, $len, total_entries).run()
-                                })??
-                            },)*
-                            _ => {
-                                error!(target: "reth::cli", "Unknown table.");
-                                return Ok(());
-                            }
-                        }
-                    }
-                }
-
-                table_tui!(args.table.as_str(), args.start, args.len => [
-                    CanonicalHeaders,
-                    HeaderTD,
-                    HeaderNumbers,
-                    Headers,
-                    BlockBodies,
-                    BlockOmmers,
-                    TxHashNumber,
-                    PlainStorageState,
-                    PlainAccountState,
-                    BlockTransitionIndex,
-                    TxTransitionIndex,
-                    SyncStage,
-                    Transactions
-                ]);
+                args.table.view(&ListTableViewer { tool: &tool, args })?;
+            }
+            Subcommands::Get(args) => {
+                args.execute(tool.db)?;
+            }
+            Subcommands::Export(args) => {
+                args.execute(tool.db)?;
+            }
+            Subcommands::Import(args) => {
+                args.execute(tool.db)?;
             }
             Subcommands::Drop => {
                 tool.drop(&self.db)?;
             }
+            Subcommands::Check(args) => {
+                let static_files_path = self.db.as_ref().join("static_files");
+                let static_files = static_files_path
+                    .exists()
+                    .then(|| StaticFileProvider::open(static_files_path))
+                    .transpose()?;
+                args.execute(tool.db, static_files.as_ref())?;
+            }
         }
 
         Ok(())
     }
 }
+
+/// Opens the [`tui::DbListTUI`] for the table selected by the `reth db list` arguments.
+struct ListTableViewer<'a> {
+    tool: &'a DbTool<'a, Env<WriteMap>>,
+    args: &'a ListArgs,
+}
+
+impl TableViewer<()> for ListTableViewer<'_> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<(), Self::Error> {
+        self.tool.db.view(|tx| {
+            let table_db = tx.inner.open_db(Some(T::NAME)).wrap_err("Could not open db.")?;
+            let stats = tx
+                .inner
+                .db_stat(&table_db)
+                .wrap_err(format!("Could not find table: {}", T::NAME))?;
+            let total_entries = stats.entries();
+            if self.args.start >= total_entries {
+                error!(
+                    target: "reth::cli",
+                    "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
+                    start = self.args.start,
+                    final_entry_idx = total_entries.saturating_sub(1),
+                    table = T::NAME
+                );
+                return Ok(())
+            }
+
+            tui::DbListTUI::<_, T>::new(
+                |start, count| self.tool.list::<T>(start, count).unwrap(),
+                self.args.start,
+                self.args.len,
+                total_entries,
+            )
+            .run()
+        })?
+    }
+}
@@ -0,0 +1,30 @@
+//! `reth debug` command
+use clap::{Parser, Subcommand};
+
+/// Re-execution of historical blocks
+mod re_execute;
+
+/// `reth debug` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth debug` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Re-executes historical blocks and compares the results to the stored receipts and
+    /// changesets, printing the first divergence.
+    #[command(name = "re-execute")]
+    ReExecute(re_execute::Command),
+}
+
+impl Command {
+    /// Execute `debug` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        match &self.command {
+            Subcommands::ReExecute(command) => command.execute().await,
+        }
+    }
+}
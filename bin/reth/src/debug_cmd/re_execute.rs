@@ -0,0 +1,505 @@
+//! `reth debug re-execute` command
+use crate::dirs::{DbPath, PlatformPath};
+use clap::Parser;
+use eyre::eyre;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::{storage_sharded_key::StorageShardedKey, TransitionIdAddress},
+    tables,
+    transaction::DbTx,
+};
+use reth_executor::executor::Executor;
+use reth_primitives::{
+    Account, Address, Block, Bloom, ChainSpec, Receipt, TransitionId, H256, U256,
+};
+use reth_provider::{
+    post_state::{Change, PostState},
+    static_files::StaticFileProvider,
+    BlockProvider, HeaderProvider, ReceiptProvider, ShareableDatabase, StateProvider,
+    StateProviderFactory,
+};
+use reth_revm::database::{State, SubState};
+use reth_staged_sync::utils::{chainspec::chain_spec_value_parser, init::init_db};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    ops::Range,
+    sync::Arc,
+};
+use tracing::*;
+
+/// The values of accounts before a transition, keyed by transition and address.
+type AccountChangesets = BTreeMap<(TransitionId, Address), Option<Account>>;
+
+/// The values of storage slots before a transition, keyed by transition, address and slot.
+type StorageChangesets = BTreeMap<(TransitionId, Address, H256), U256>;
+
+/// `reth debug re-execute` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the database folder.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
+    /// - macOS: `$HOME/Library/Application Support/reth/db`
+    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
+    db: PlatformPath<DbPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// The first block to re-execute.
+    #[arg(long)]
+    from: u64,
+
+    /// The last block to re-execute.
+    #[arg(long, short)]
+    to: u64,
+}
+
+impl Command {
+    /// Execute `debug re-execute` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        if self.from == 0 || self.from > self.to {
+            return Err(eyre!("Invalid block range {}..={}", self.from, self.to))
+        }
+
+        info!(target: "reth::cli", "Re-executing blocks {}..={}", self.from, self.to);
+
+        let db = Arc::new(init_db(&self.db)?);
+        let static_files = StaticFileProvider::open(self.db.as_ref().join("static_files"))?;
+        let provider =
+            ShareableDatabase::new(db.clone(), self.chain.clone()).with_static_files(static_files);
+
+        for number in self.from..=self.to {
+            let block = provider
+                .block_by_number(number)?
+                .ok_or_else(|| eyre!("Block {number} not found"))?;
+            let total_difficulty = provider
+                .header_td_by_number(number)?
+                .ok_or_else(|| eyre!("Total difficulty of block {number} not found"))?;
+            let receipts = provider
+                .receipts_by_block(number.into())?
+                .ok_or_else(|| eyre!("Receipts of block {number} not found"))?;
+
+            // the state at the end of the parent block
+            let state = provider.history_by_block_number(number - 1)?;
+            let mut executor = Executor::new(self.chain.clone(), SubState::new(State::new(state)));
+            let (mut post_state, _) =
+                executor.execute_transactions(&block, total_difficulty, None)?;
+
+            if let Some(divergence) = diff_receipts(&block, &receipts, post_state.receipts()) {
+                divergence.print(number);
+                return Err(eyre!("Re-execution of block {number} diverged"))
+            }
+
+            executor.apply_post_block_changes(&block, total_difficulty, &mut post_state)?;
+
+            let tx = db.tx()?;
+            let first_transition = stored_block_transition(&tx, number - 1)?;
+            let transitions = first_transition..stored_block_transition(&tx, number)?;
+            let (accounts, storage) = stored_changesets(&tx, transitions)?;
+            let (produced_accounts, mut produced_storage, wiped) =
+                produced_changesets(&post_state, first_transition);
+
+            // the changesets of wiped storage hold the slots of the account before the block
+            let state = provider.history_by_block_number(number - 1)?;
+            for (transition, address) in wiped {
+                for slot in known_slots(&tx, address)? {
+                    let value = state.storage(address, slot)?.unwrap_or_default();
+                    if value != U256::ZERO {
+                        produced_storage.insert((transition, address, slot), value);
+                    }
+                }
+            }
+
+            if let Some(divergence) = diff_changesets(
+                &block,
+                first_transition,
+                &accounts,
+                &produced_accounts,
+                &storage,
+                &produced_storage,
+            ) {
+                divergence.print(number);
+                return Err(eyre!("Re-execution of block {number} diverged"))
+            }
+
+            debug!(target: "reth::cli", number, txs = block.body.len(), "Re-executed block");
+        }
+
+        info!(target: "reth::cli", "Re-executed blocks {}..={} without divergence", self.from, self.to);
+
+        Ok(())
+    }
+}
+
+/// A difference between the results of a re-executed block and the stored data.
+#[derive(Debug)]
+enum Divergence {
+    /// The number of receipts differs.
+    ReceiptCount { expected: usize, got: usize },
+    /// The receipt of a transaction differs.
+    Receipt { index: usize, hash: H256, expected: Box<Receipt>, got: Box<Receipt> },
+    /// The gas used by the block differs from the header.
+    GasUsed { expected: u64, got: u64 },
+    /// The logs bloom of the block differs from the header.
+    LogsBloom { expected: Box<Bloom>, got: Box<Bloom> },
+    /// The changesets of a transition differ.
+    Changesets {
+        transition: TransitionId,
+        /// The transaction of the transition, `None` for the post block changes.
+        transaction: Option<(usize, H256)>,
+        accounts: Vec<((TransitionId, Address), Option<Option<Account>>, Option<Option<Account>>)>,
+        storage: Vec<((TransitionId, Address, H256), Option<U256>, Option<U256>)>,
+    },
+}
+
+impl Divergence {
+    /// Prints the divergence of the block.
+    fn print(&self, number: u64) {
+        println!("Block {number} diverged:");
+        match self {
+            Divergence::ReceiptCount { expected, got } => {
+                println!("  receipt count: expected {expected}, got {got}");
+            }
+            Divergence::Receipt { index, hash, expected, got } => {
+                println!("  receipt of transaction {index} ({hash:?}):");
+                if expected.success != got.success {
+                    println!("    success: expected {}, got {}", expected.success, got.success);
+                }
+                if expected.cumulative_gas_used != got.cumulative_gas_used {
+                    println!(
+                        "    cumulative gas used: expected {}, got {}",
+                        expected.cumulative_gas_used, got.cumulative_gas_used
+                    );
+                }
+                if expected.logs.len() != got.logs.len() {
+                    println!(
+                        "    log count: expected {}, got {}",
+                        expected.logs.len(),
+                        got.logs.len()
+                    );
+                }
+                if let Some((index, (expected, got))) =
+                    expected.logs.iter().zip(&got.logs).enumerate().find(|(_, (e, g))| e != g)
+                {
+                    println!(
+                        "    log {index}:\n      expected {expected:?}\n      got      {got:?}"
+                    );
+                }
+            }
+            Divergence::GasUsed { expected, got } => {
+                println!("  gas used: expected {expected}, got {got}");
+            }
+            Divergence::LogsBloom { expected, got } => {
+                println!("  logs bloom:\n    expected {expected:?}\n    got      {got:?}");
+            }
+            Divergence::Changesets { transition, transaction, accounts, storage } => {
+                match transaction {
+                    Some((index, hash)) => println!(
+                        "  changesets of transition {transition}, transaction {index} ({hash:?}):"
+                    ),
+                    None => {
+                        println!("  changesets of transition {transition}, post block changes:")
+                    }
+                }
+                for ((_, address), expected, got) in accounts {
+                    println!(
+                        "    account {address:?} before:\n      expected {}\n      got      {}",
+                        format_entry(expected),
+                        format_entry(got)
+                    );
+                }
+                for ((_, address, slot), expected, got) in storage {
+                    println!(
+                        "    storage {address:?} {slot:?} before:\n      expected {}\n      got      {}",
+                        format_entry(expected),
+                        format_entry(got)
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Formats a changeset entry, which is missing if the value wasn't changed.
+fn format_entry<V: Debug>(entry: &Option<V>) -> String {
+    entry.as_ref().map_or_else(|| "<unchanged>".to_string(), |value| format!("{value:?}"))
+}
+
+/// Compares the receipts produced by the transactions of the block to the stored receipts and the
+/// gas used and logs bloom of the header.
+fn diff_receipts(block: &Block, expected: &[Receipt], got: &[Receipt]) -> Option<Divergence> {
+    if let Some((index, (expected, got))) =
+        expected.iter().zip(got).enumerate().find(|(_, (expected, got))| expected != got)
+    {
+        return Some(Divergence::Receipt {
+            index,
+            hash: block.body[index].hash,
+            expected: Box::new(expected.clone()),
+            got: Box::new(got.clone()),
+        })
+    }
+    if expected.len() != got.len() {
+        return Some(Divergence::ReceiptCount { expected: expected.len(), got: got.len() })
+    }
+
+    let gas_used = got.last().map(|receipt| receipt.cumulative_gas_used).unwrap_or_default();
+    if gas_used != block.gas_used {
+        return Some(Divergence::GasUsed { expected: block.gas_used, got: gas_used })
+    }
+
+    let logs_bloom = got.iter().fold(Bloom::zero(), |bloom, receipt| bloom | receipt.bloom_slow());
+    if logs_bloom != block.logs_bloom {
+        return Some(Divergence::LogsBloom {
+            expected: Box::new(block.logs_bloom),
+            got: Box::new(logs_bloom),
+        })
+    }
+
+    None
+}
+
+/// Compares the changesets and returns the differences in the first diverging transition.
+fn diff_changesets(
+    block: &Block,
+    first_transition: TransitionId,
+    expected_accounts: &AccountChangesets,
+    got_accounts: &AccountChangesets,
+    expected_storage: &StorageChangesets,
+    got_storage: &StorageChangesets,
+) -> Option<Divergence> {
+    let accounts = diff_maps(expected_accounts, got_accounts);
+    let storage = diff_maps(expected_storage, got_storage);
+
+    let first_account = accounts.first().map(|((transition, _), ..)| *transition);
+    let first_storage = storage.first().map(|((transition, ..), ..)| *transition);
+    let transition = first_account.into_iter().chain(first_storage).min()?;
+
+    let index = (transition - first_transition) as usize;
+    Some(Divergence::Changesets {
+        transition,
+        transaction: block.body.get(index).map(|tx| (index, tx.hash)),
+        accounts: accounts.into_iter().filter(|((t, _), ..)| *t == transition).collect(),
+        storage: storage.into_iter().filter(|((t, ..), ..)| *t == transition).collect(),
+    })
+}
+
+/// Returns the keys whose values differ between the maps, with the expected and the produced
+/// value.
+fn diff_maps<K: Ord + Copy, V: PartialEq + Copy>(
+    expected: &BTreeMap<K, V>,
+    got: &BTreeMap<K, V>,
+) -> Vec<(K, Option<V>, Option<V>)> {
+    expected
+        .keys()
+        .chain(got.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|key| {
+            let (expected, got) = (expected.get(key).copied(), got.get(key).copied());
+            (expected != got).then_some((*key, expected, got))
+        })
+        .collect()
+}
+
+/// Returns the last transition of the block.
+fn stored_block_transition<'a>(tx: &impl DbTx<'a>, number: u64) -> eyre::Result<TransitionId> {
+    tx.get::<tables::BlockTransitionIndex>(number)?
+        .ok_or_else(|| eyre!("Transition of block {number} not found"))
+}
+
+/// Reads the stored changesets of the transitions.
+fn stored_changesets<'a>(
+    tx: &impl DbTx<'a>,
+    transitions: Range<TransitionId>,
+) -> eyre::Result<(AccountChangesets, StorageChangesets)> {
+    let mut accounts = AccountChangesets::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(transitions.clone())? {
+        let (transition, account) = entry?;
+        accounts.insert((transition, account.address), account.info);
+    }
+
+    let mut storage = StorageChangesets::new();
+    let range = TransitionIdAddress((transitions.start, Address::zero()))..
+        TransitionIdAddress((transitions.end, Address::zero()));
+    for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk_range(range)? {
+        let (key, entry) = entry?;
+        storage.insert((key.transition_id(), key.address(), entry.key), entry.value);
+    }
+
+    Ok((accounts, storage))
+}
+
+/// Returns the slots the account ever had a value in: the slots of its current storage and the
+/// slots with a history of changes.
+fn known_slots<'a>(tx: &impl DbTx<'a>, address: Address) -> eyre::Result<BTreeSet<H256>> {
+    let mut slots = BTreeSet::new();
+    for entry in tx.cursor_dup_read::<tables::PlainStorageState>()?.walk_dup(Some(address), None)? {
+        slots.insert(entry?.1.key);
+    }
+
+    let start = StorageShardedKey::new(address, H256::zero(), 0);
+    for entry in tx.cursor_read::<tables::StorageHistory>()?.walk(Some(start))? {
+        let (key, _) = entry?;
+        if key.address != address {
+            break
+        }
+        slots.insert(key.sharded_key.key);
+    }
+
+    Ok(slots)
+}
+
+/// Returns the changesets the post state would write, along with the accounts whose storage was
+/// wiped in a transition.
+fn produced_changesets(
+    post_state: &PostState,
+    first_transition: TransitionId,
+) -> (AccountChangesets, StorageChangesets, BTreeSet<(TransitionId, Address)>) {
+    let mut accounts = AccountChangesets::new();
+    let mut storage = StorageChangesets::new();
+    let mut wiped = BTreeSet::new();
+    for change in post_state.changes() {
+        let transition = first_transition + change.transition_id();
+        match change {
+            Change::AccountCreated { address, .. } => {
+                accounts.insert((transition, *address), None);
+            }
+            Change::AccountChanged { address, old, .. } |
+            Change::AccountDestroyed { address, old, .. } => {
+                accounts.insert((transition, *address), Some(*old));
+            }
+            Change::StorageChanged { address, changeset, .. } => {
+                for (slot, (old, _)) in changeset {
+                    storage.insert((transition, *address, H256(slot.to_be_bytes())), *old);
+                }
+            }
+            Change::StorageWiped { address, .. } => {
+                wiped.insert((transition, *address));
+            }
+        }
+    }
+    (accounts, storage, wiped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, Log, TransactionSigned};
+
+    fn receipt(cumulative_gas_used: u64, logs: Vec<Log>) -> Receipt {
+        Receipt { success: true, cumulative_gas_used, logs, ..Default::default() }
+    }
+
+    fn block(receipts: &[Receipt]) -> Block {
+        let hashes = (0..receipts.len() as u64).map(H256::from_low_u64_be);
+        Block {
+            header: Header {
+                gas_used: receipts.last().map_or(0, |receipt| receipt.cumulative_gas_used),
+                logs_bloom: receipts
+                    .iter()
+                    .fold(Bloom::zero(), |bloom, receipt| bloom | receipt.bloom_slow()),
+                ..Default::default()
+            },
+            body: hashes.map(|hash| TransactionSigned { hash, ..Default::default() }).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_maps_returns_differing_keys() {
+        let expected = BTreeMap::from([(1, 10), (2, 20), (3, 30)]);
+        let got = BTreeMap::from([(1, 10), (2, 21), (4, 40)]);
+
+        assert_eq!(
+            diff_maps(&expected, &got),
+            vec![(2, Some(20), Some(21)), (3, Some(30), None), (4, None, Some(40))]
+        );
+        assert!(diff_maps(&expected, &expected).is_empty());
+    }
+
+    #[test]
+    fn diff_receipts_finds_first_divergence() {
+        let log = Log { address: Address::from_low_u64_be(1), ..Default::default() };
+        let receipts = vec![receipt(21_000, vec![]), receipt(42_000, vec![log.clone()])];
+        let block = block(&receipts);
+        assert!(diff_receipts(&block, &receipts, &receipts).is_none());
+
+        let mut got = receipts.clone();
+        got[1].success = false;
+        assert!(matches!(
+            diff_receipts(&block, &receipts, &got),
+            Some(Divergence::Receipt { index: 1, hash, .. }) if hash == block.body[1].hash
+        ));
+
+        assert!(matches!(
+            diff_receipts(&block, &receipts, &receipts[..1]),
+            Some(Divergence::ReceiptCount { expected: 2, got: 1 })
+        ));
+
+        // the stored receipts match, but the header doesn't
+        let mut header_block = block.clone();
+        header_block.header.gas_used = 50_000;
+        assert!(matches!(
+            diff_receipts(&header_block, &receipts, &receipts),
+            Some(Divergence::GasUsed { expected: 50_000, got: 42_000 })
+        ));
+
+        let mut header_block = block;
+        header_block.header.logs_bloom = Bloom::zero();
+        assert!(matches!(
+            diff_receipts(&header_block, &receipts, &receipts),
+            Some(Divergence::LogsBloom { .. })
+        ));
+    }
+
+    #[test]
+    fn produced_changesets_of_post_state() {
+        let address = Address::from_low_u64_be(1);
+        let created = Account { nonce: 1, ..Default::default() };
+        let changed = Account { nonce: 2, ..Default::default() };
+        let slot = U256::from(1);
+
+        let mut post_state = PostState::new();
+        post_state.create_account(address, created);
+        post_state.finish_transition();
+        post_state.change_account(address, created, changed);
+        post_state.change_storage(address, BTreeMap::from([(slot, (U256::ZERO, U256::from(7)))]));
+        post_state.finish_transition();
+        post_state.destroy_account(address, changed);
+
+        let (accounts, storage, wiped) = produced_changesets(&post_state, 10);
+        assert_eq!(
+            accounts,
+            AccountChangesets::from([
+                ((10, address), None),
+                ((11, address), Some(created)),
+                ((12, address), Some(changed)),
+            ])
+        );
+        assert_eq!(
+            storage,
+            StorageChangesets::from([((11, address, H256::from_low_u64_be(1)), U256::ZERO)])
+        );
+        assert_eq!(wiped, BTreeSet::from([(12, address)]));
+    }
+}
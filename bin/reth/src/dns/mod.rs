@@ -0,0 +1,131 @@
+//! DNS discovery utilities
+use clap::{Parser, Subcommand};
+use enr::Enr;
+use eyre::WrapErr;
+use reth_dns_discovery::{tree::LinkEntry, DnsTree};
+use secp256k1::SecretKey;
+use std::{path::PathBuf, str::FromStr};
+use tracing::info;
+
+/// `reth dns` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Subcommand, Debug)]
+/// `reth dns` subcommands
+pub enum Subcommands {
+    /// Build and sign an EIP-1459 node tree and print its TXT records as zone file.
+    Tree(TreeCommand),
+}
+
+/// `reth dns tree` command
+#[derive(Debug, Parser)]
+pub struct TreeCommand {
+    /// The domain the tree is published at, e.g. `nodes.example.org`.
+    #[arg(long, value_name = "DOMAIN")]
+    domain: String,
+
+    /// Path to a file containing the hex encoded secp256k1 secret key used to sign the tree.
+    #[arg(long, value_name = "FILE")]
+    key: PathBuf,
+
+    /// Path to a file containing the node records to include in the tree, one ENR (`enr:...`)
+    /// per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment)]
+    nodes: PathBuf,
+
+    /// Links to other trees to include in the tree, e.g. `enrtree://<key>@<domain>`.
+    #[arg(long = "link", value_name = "LINK")]
+    links: Vec<LinkEntry>,
+
+    /// The sequence number of the tree.
+    ///
+    /// This must be increased whenever the tree is republished.
+    #[arg(long, default_value = "1")]
+    seq: u64,
+
+    /// The TTL of the records in seconds.
+    #[arg(long, default_value = "3600")]
+    ttl: u64,
+
+    /// Write the zone file to the given path instead of stdout.
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl Command {
+    /// Execute `dns` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Tree(command) => command.execute().await,
+        }
+    }
+}
+
+impl TreeCommand {
+    /// Execute `dns tree` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let key = std::fs::read_to_string(&self.key)
+            .wrap_err_with(|| format!("Could not read key file {}", self.key.display()))?;
+        let key = SecretKey::from_str(key.trim().trim_start_matches("0x"))
+            .wrap_err("Invalid secret key")?;
+
+        let nodes = std::fs::read_to_string(&self.nodes)
+            .wrap_err_with(|| format!("Could not read nodes file {}", self.nodes.display()))?;
+        let records = parse_records(&nodes)?;
+
+        let tree = DnsTree::new(records, self.links, self.seq, &key);
+        info!(target: "reth::cli", entries = tree.entries().len(), link = %tree.link(self.domain.clone()), "Built DNS tree");
+
+        let zone = tree.to_zone_file(&self.domain, self.ttl);
+        match self.output {
+            Some(path) => std::fs::write(&path, zone)
+                .wrap_err_with(|| format!("Could not write zone file {}", path.display()))?,
+            None => print!("{zone}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the ENRs of the nodes file, one per line.
+fn parse_records(nodes: &str) -> eyre::Result<Vec<Enr<SecretKey>>> {
+    let mut records = Vec::new();
+    for (idx, line) in nodes.lines().enumerate().map(|(idx, line)| (idx + 1, line.trim())) {
+        if line.is_empty() || line.starts_with('#') {
+            continue
+        }
+        if line.starts_with("enode://") {
+            eyre::bail!(
+                "Line {idx}: node records must be provided as signed ENRs, enode URLs can't be published"
+            )
+        }
+        let record =
+            Enr::from_str(line).map_err(|err| eyre::eyre!("Line {idx}: invalid ENR: {err}"))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nodes_file() {
+        let nodes = r#"
+# comment
+enr:-HW4QES8QIeXTYlDzbfr1WEzE-XKY4f8gJFJzjJL-9D7TC9lJb4Z3JPRRz1lP4pL_N_QpT6rGQjAU9Apnc-C1iMP36OAgmlkgnY0iXNlY3AyNTZrMaED5IdwfMxdmR8W37HqSFdQLjDkIwBd4Q_MjxgZifgKSdM
+"#;
+        let records = parse_records(nodes).unwrap();
+        assert_eq!(records.len(), 1);
+
+        let nodes = "enode://6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0@10.3.58.6:30303?discport=30301";
+        assert!(parse_records(nodes).is_err());
+    }
+}
@@ -67,7 +67,7 @@ impl Command {
                     tx.clear::<tables::AccountChangeSet>()?;
                     tx.clear::<tables::StorageChangeSet>()?;
                     tx.clear::<tables::Bytecodes>()?;
-                    tx.put::<tables::SyncStage>(EXECUTION.0.to_string(), 0)?;
+                    tx.put::<tables::SyncStage>(EXECUTION.0.to_string(), Default::default())?;
                     insert_genesis_state::<Env<WriteMap>>(tx, self.chain.genesis())?;
                     Ok::<_, eyre::Error>(())
                 })??;
@@ -99,8 +99,8 @@ async fn unwind_and_copy<DB: Database>(
 
     output_db.update(|tx| tx.import_table::<tables::HashedAccount, _>(unwind_inner_tx))??;
     output_db.update(|tx| tx.import_dupsort::<tables::HashedStorage, _>(unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_table::<tables::AccountsTrie, _>(unwind_inner_tx))??;
-    output_db.update(|tx| tx.import_dupsort::<tables::StoragesTrie, _>(unwind_inner_tx))??;
+    output_db.update(|tx| tx.import_table::<tables::AccountBranches, _>(unwind_inner_tx))??;
+    output_db.update(|tx| tx.import_dupsort::<tables::StorageBranches, _>(unwind_inner_tx))??;
 
     unwind_tx.drop()?;
 
@@ -11,11 +11,14 @@ pub mod args;
 pub mod chain;
 pub mod cli;
 pub mod db;
+pub mod debug_cmd;
 pub mod dirs;
+pub mod dns;
 pub mod drop_stage;
 pub mod dump_stage;
 pub mod node;
 pub mod p2p;
+pub mod profile;
 pub mod prometheus_exporter;
 pub mod runner;
 pub mod stage;
@@ -30,25 +30,47 @@ impl NodeState {
     /// Processes an event emitted by the pipeline
     async fn handle_pipeline_event(&mut self, event: PipelineEvent) {
         match event {
-            PipelineEvent::Running { stage_id, stage_progress } => {
+            PipelineEvent::Running { stage_id, checkpoint } => {
                 let notable = self.current_stage.is_none();
+                let from = checkpoint.map(|checkpoint| checkpoint.block_number);
                 self.current_stage = Some(stage_id);
-                self.current_checkpoint = stage_progress.unwrap_or_default();
+                self.current_checkpoint = from.unwrap_or_default();
 
                 if notable {
-                    info!(target: "reth::cli", stage = %stage_id, from = stage_progress, "Executing stage");
+                    info!(target: "reth::cli", stage = %stage_id, from, "Executing stage");
                 }
             }
-            PipelineEvent::Ran { stage_id, result } => {
-                let notable = result.stage_progress > self.current_checkpoint;
+            PipelineEvent::Ran { stage_id, result, checkpoint } => {
+                // stages with stage-specific progress commit progress within the same block range
+                let entities = checkpoint.entities();
+                let notable = result.stage_progress > self.current_checkpoint || entities.is_some();
                 self.current_checkpoint = result.stage_progress;
                 if result.done {
                     self.current_stage = None;
                     info!(target: "reth::cli", stage = %stage_id, checkpoint = result.stage_progress, "Stage finished executing");
                 } else if notable {
-                    info!(target: "reth::cli", stage = %stage_id, checkpoint = result.stage_progress, "Stage committed progress");
+                    info!(
+                        target: "reth::cli",
+                        stage = %stage_id,
+                        checkpoint = result.stage_progress,
+                        processed = entities.map(|entities| entities.processed),
+                        total = entities.and_then(|entities| entities.total),
+                        "Stage committed progress"
+                    );
                 }
             }
+            PipelineEvent::Progress { stage_id, progress } => {
+                info!(
+                    target: "reth::cli",
+                    stage = %stage_id,
+                    checkpoint = progress.checkpoint,
+                    to = progress.target,
+                    blocks_per_second = progress.blocks_per_second,
+                    entities_per_second = progress.entities_per_second,
+                    eta_secs = progress.eta.map(|eta| eta.as_secs()),
+                    "Stage progress"
+                );
+            }
             _ => (),
         }
     }
@@ -38,7 +38,9 @@ use reth_network::{
 };
 use reth_network_api::NetworkInfo;
 use reth_primitives::{BlockHashOrNumber, ChainSpec, Head, Header, SealedHeader, TxHash, H256};
-use reth_provider::{BlockProvider, HeaderProvider, ShareableDatabase};
+use reth_provider::{
+    static_files::StaticFileProvider, BlockProvider, HeaderProvider, ShareableDatabase,
+};
 use reth_revm_inspectors::stack::Hook;
 use reth_rpc_engine_api::{EngineApi, EngineApiHandle};
 use reth_staged_sync::{
@@ -51,7 +53,11 @@ use reth_staged_sync::{
 };
 use reth_stages::{
     prelude::*,
-    stages::{ExecutionStage, HeaderSyncMode, SenderRecoveryStage, TotalDifficultyStage, FINISH},
+    stages::{
+        ExecutionStage, HeaderSyncMode, IndexAccountHistoryStage, IndexStorageHistoryStage,
+        SenderRecoveryStage, TotalDifficultyStage, TransactionLookupStage, FINISH,
+    },
+    Pruner, StaticFileProducer,
 };
 use reth_tasks::TaskExecutor;
 use reth_transaction_pool::{EthTransactionValidator, TransactionPool};
@@ -173,7 +179,9 @@ impl Command {
 
         info!(target: "reth::cli", path = %self.db, "Opening database");
         let db = Arc::new(init_db(&self.db)?);
-        let shareable_db = ShareableDatabase::new(Arc::clone(&db), Arc::clone(&self.chain));
+        let static_files = StaticFileProvider::open(self.db.as_ref().join("static_files"))?;
+        let shareable_db = ShareableDatabase::new(Arc::clone(&db), Arc::clone(&self.chain))
+            .with_static_files(static_files.clone());
         info!(target: "reth::cli", "Database opened");
 
         self.start_metrics_endpoint(Arc::clone(&db)).await?;
@@ -194,8 +202,12 @@ impl Command {
         info!(target: "reth::cli", "Test transaction pool initialized");
 
         info!(target: "reth::cli", "Connecting to P2P network");
-        let network_config =
-            self.load_network_config(&config, Arc::clone(&db), ctx.task_executor.clone());
+        let network_config = self.load_network_config(
+            &config,
+            Arc::clone(&db),
+            shareable_db.clone(),
+            ctx.task_executor.clone(),
+        );
         let network = self
             .start_network(network_config, &ctx.task_executor, transaction_pool.clone())
             .await?;
@@ -218,7 +230,7 @@ impl Command {
 
         // TODO: This will be fixed with the sync controller (https://github.com/paradigmxyz/reth/pull/1662)
         let (tx, _rx) = watch::channel(ForkchoiceState::default());
-        let engine_api_handle = self.init_engine_api(Arc::clone(&db), tx, &ctx.task_executor);
+        let engine_api_handle = self.init_engine_api(shareable_db.clone(), tx, &ctx.task_executor);
         info!(target: "reth::cli", "Engine API handler initialized");
 
         let _auth_server = self
@@ -255,6 +267,35 @@ impl Command {
 
         ctx.task_executor.spawn(events::handle_events(Some(network.clone()), events));
 
+        if !config.prune.segments.is_empty() {
+            let pruner =
+                Pruner::new(db.clone(), config.prune.segments, config.prune.block_interval);
+            let pruner_events = pipeline.events();
+            info!(target: "reth::cli", segments = ?config.prune.segments, "Starting pruner");
+            ctx.task_executor.spawn_critical_blocking("pruner task", async move {
+                if let Err(err) = pruner.run_with_events(pruner_events).await {
+                    error!(target: "reth::cli", ?err, "Pruner failed");
+                }
+            });
+        }
+
+        if config.static_files.enabled {
+            let producer = StaticFileProducer::new(
+                db.clone(),
+                static_files,
+                config.static_files.distance,
+                config.static_files.block_interval,
+            )
+            .with_prune_modes(config.prune.segments);
+            let producer_events = pipeline.events();
+            info!(target: "reth::cli", path = %self.db, distance = config.static_files.distance, "Starting static file producer");
+            ctx.task_executor.spawn_critical_blocking("static file producer task", async move {
+                if let Err(err) = producer.run_with_events(producer_events).await {
+                    error!(target: "reth::cli", ?err, "Static file producer failed");
+                }
+            });
+        }
+
         // Run pipeline
         let (rx, tx) = tokio::sync::oneshot::channel();
         info!(target: "reth::cli", "Starting sync pipeline");
@@ -351,17 +392,13 @@ impl Command {
 
     fn init_engine_api(
         &self,
-        db: Arc<Env<WriteMap>>,
+        shareable_db: ShareableDatabase<Arc<Env<WriteMap>>>,
         forkchoice_state_tx: watch::Sender<ForkchoiceState>,
         task_executor: &TaskExecutor,
     ) -> EngineApiHandle {
         let (message_tx, message_rx) = unbounded_channel();
-        let engine_api = EngineApi::new(
-            ShareableDatabase::new(db, self.chain.clone()),
-            self.chain.clone(),
-            message_rx,
-            forkchoice_state_tx,
-        );
+        let engine_api =
+            EngineApi::new(shareable_db, self.chain.clone(), message_rx, forkchoice_state_tx);
         task_executor.spawn_critical("engine API task", engine_api);
         message_tx
     }
@@ -473,9 +510,10 @@ impl Command {
         &self,
         config: &Config,
         db: Arc<Env<WriteMap>>,
+        shareable_db: ShareableDatabase<Arc<Env<WriteMap>>>,
         executor: TaskExecutor,
     ) -> NetworkConfig<ShareableDatabase<Arc<Env<WriteMap>>>> {
-        let head = self.lookup_head(Arc::clone(&db)).expect("the head block is missing");
+        let head = self.lookup_head(db).expect("the head block is missing");
 
         self.network
             .network_config(config, self.chain.clone())
@@ -485,7 +523,7 @@ impl Command {
                 Ipv4Addr::UNSPECIFIED,
                 self.network.discovery.port.unwrap_or(DEFAULT_DISCOVERY_PORT),
             )))
-            .build(ShareableDatabase::new(db, self.chain.clone()))
+            .build(shareable_db)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -505,6 +543,7 @@ impl Command {
         U: SyncStateUpdater + StatusUpdater + Clone + 'static,
     {
         let stage_conf = &config.stages;
+        let prune_modes = config.prune.segments;
 
         let mut builder = Pipeline::builder();
 
@@ -528,9 +567,11 @@ impl Command {
             } else {
                 Hook::None
             },
+            ..Default::default()
         };
 
-        let factory = factory.with_stack_config(stack_config);
+        // pre-Byzantium receipts are verified along with the state roots they contain
+        let factory = factory.with_stack_config(stack_config).with_receipt_state_roots();
 
         let header_mode =
             if continuous { HeaderSyncMode::Continuous } else { HeaderSyncMode::Tip(tip_rx) };
@@ -553,7 +594,14 @@ impl Command {
                 .set(SenderRecoveryStage {
                     commit_threshold: stage_conf.sender_recovery.commit_threshold,
                 })
-                .set(ExecutionStage::new(factory, stage_conf.execution.commit_threshold)),
+                .set(
+                    ExecutionStage::new(factory, stage_conf.execution.commit_threshold)
+                        .with_prune_modes(prune_modes)
+                        .with_prefetch_threads(stage_conf.execution.prefetch_threads),
+                )
+                .set(TransactionLookupStage::default().with_prune_modes(prune_modes))
+                .set(IndexStorageHistoryStage { prune_modes, ..Default::default() })
+                .set(IndexAccountHistoryStage { prune_modes, ..Default::default() }),
             )
             .build();
 
@@ -0,0 +1,142 @@
+//! Main `profile` command
+//!
+//! Profiles where gas and time are spent when executing a range of blocks.
+use crate::dirs::{DbPath, PlatformPath};
+use clap::{Parser, ValueEnum};
+use eyre::eyre;
+use reth_executor::executor::Executor;
+use reth_primitives::ChainSpec;
+use reth_provider::{
+    static_files::StaticFileProvider, BlockProvider, HeaderProvider, ShareableDatabase,
+    StateProviderFactory,
+};
+use reth_revm::database::{State, SubState};
+use reth_revm_inspectors::{
+    profiler::{GasProfile, GasProfiler},
+    stack::{Hook, InspectorRegistry, InspectorStack, InspectorStackConfig},
+};
+use reth_staged_sync::utils::{chainspec::chain_spec_value_parser, init::init_db};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tracing::*;
+
+/// `reth profile` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the database folder.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/db` or `$HOME/.local/share/reth/db`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/db`
+    /// - macOS: `$HOME/Library/Application Support/reth/db`
+    #[arg(long, value_name = "PATH", verbatim_doc_comment, default_value_t)]
+    db: PlatformPath<DbPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    ///
+    /// Built-in chains:
+    /// - mainnet
+    /// - goerli
+    /// - sepolia
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        verbatim_doc_comment,
+        default_value = "mainnet",
+        value_parser = chain_spec_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// The first block to execute.
+    #[arg(long)]
+    from: u64,
+
+    /// The last block to execute.
+    #[arg(long, short)]
+    to: u64,
+
+    /// The format of the report.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Csv)]
+    format: ReportFormat,
+
+    /// The file to write the report to, defaults to stdout.
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// The format of the profiling report.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    /// Gas, executions and time per opcode, precompile and contract.
+    Csv,
+    /// Gas per call stack, in the folded format used by flamegraph tools.
+    Folded,
+}
+
+impl Command {
+    /// Execute `profile` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        if self.from == 0 || self.from > self.to {
+            return Err(eyre!("Invalid block range {}..={}", self.from, self.to))
+        }
+
+        info!(target: "reth::cli", "Profiling execution of blocks {}..={}", self.from, self.to);
+
+        let db = Arc::new(init_db(&self.db)?);
+        let static_files = StaticFileProvider::open(self.db.as_ref().join("static_files"))?;
+        let provider =
+            ShareableDatabase::new(db, self.chain.clone()).with_static_files(static_files);
+
+        let profile = Arc::new(Mutex::new(GasProfile::default()));
+        let mut registry = InspectorRegistry::default();
+        let sink = profile.clone();
+        registry.register(Hook::All, GasProfiler::default, move |output| {
+            sink.lock().expect("not poisoned").merge(output.inspector.into_profile(&output.result))
+        });
+        let stack = InspectorStack::new(InspectorStackConfig { registry, ..Default::default() });
+
+        for number in self.from..=self.to {
+            let block = provider
+                .block_by_number(number)?
+                .ok_or_else(|| eyre!("Block {number} not found"))?;
+            let total_difficulty = provider
+                .header_td_by_number(number)?
+                .ok_or_else(|| eyre!("Total difficulty of block {number} not found"))?;
+
+            // the state at the end of the parent block
+            let state = provider.history_by_block_number(number - 1)?;
+            let mut executor = Executor::new(self.chain.clone(), SubState::new(State::new(state)))
+                .with_stack(stack.clone());
+            executor.execute_transactions(&block, total_difficulty, None)?;
+
+            debug!(target: "reth::cli", number, txs = block.body.len(), "Executed block");
+        }
+
+        let profile = profile.lock().expect("not poisoned");
+        info!(
+            target: "reth::cli",
+            transactions = profile.transactions.count,
+            gas = profile.transactions.gas,
+            "Profiled execution"
+        );
+
+        let mut writer: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(BufWriter::new(io::stdout())),
+        };
+        match self.format {
+            ReportFormat::Csv => profile.write_csv(&mut writer)?,
+            ReportFormat::Folded => profile.write_folded(&mut writer)?,
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
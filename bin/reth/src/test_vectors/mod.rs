@@ -1,5 +1,6 @@
 //! Command for generating test vectors.
 use clap::{Parser, Subcommand};
+use reth_db::tables::Tables;
 
 mod tables;
 
@@ -16,7 +17,7 @@ pub enum Subcommands {
     /// Generates test vectors for specified tables. If no table is specified, generate for all.
     Tables {
         /// List of table names. Case-sensitive.
-        names: Vec<String>,
+        names: Vec<Tables>,
     },
 }
 
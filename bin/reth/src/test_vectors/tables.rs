@@ -9,7 +9,7 @@ use proptest::{
 };
 use reth_db::{
     table::{DupSort, Table},
-    tables,
+    tables::{self, Tables},
 };
 use tracing::error;
 
@@ -17,7 +17,7 @@ const VECTORS_FOLDER: &str = "testdata/micro/db";
 const PER_TABLE: usize = 1000;
 
 /// Generates test vectors for specified `tables`. If list is empty, then generate for all tables.
-pub(crate) fn generate_vectors(mut tables: Vec<String>) -> Result<()> {
+pub(crate) fn generate_vectors(mut tables: Vec<Tables>) -> Result<()> {
     let mut runner = TestRunner::new(ProptestConfig::default());
     std::fs::create_dir_all(VECTORS_FOLDER)?;
 
@@ -32,23 +32,23 @@ pub(crate) fn generate_vectors(mut tables: Vec<String>) -> Result<()> {
 
     macro_rules! generate {
         ([$(($table_type:ident, $per_table:expr, $table_or_dup:tt)),*]) => {
-            let all_tables = vec![$(stringify!($table_type).to_string(),)*];
+            let all_tables = vec![$(Tables::$table_type,)*];
 
             if tables.is_empty() {
                 tables = all_tables;
             }
 
             for table in tables {
-                match table.as_str() {
+                match table {
                     $(
-                        stringify!($table_type) => {
+                        Tables::$table_type => {
                             println!("Generating test vectors for {} <{}>.", stringify!($table_or_dup), tables::$table_type::NAME);
 
                             generate_vector!($table_type, $per_table, $table_or_dup);
                         },
                     )*
                     _ => {
-                        error!(target: "reth::cli", "Unknown table: {}", table);
+                        error!(target: "reth::cli", "Test vectors are not supported for table: {}", table);
                     }
                 }
             }
@@ -3,7 +3,11 @@
 //! A [`Chain`] contains the state of accounts for the chain after execution of its constituent
 //! blocks, as well as a list of the blocks the chain is composed of.
 use crate::{post_state::PostState, substate::PostStateProvider};
-use reth_interfaces::{consensus::Consensus, executor::Error as ExecError, Error};
+use reth_interfaces::{
+    consensus::{Consensus, ConsensusError},
+    executor::Error as ExecError,
+    Error,
+};
 use reth_primitives::{BlockHash, BlockNumber, SealedBlockWithSenders, SealedHeader, U256};
 use reth_provider::{BlockExecutor, ExecutorFactory, StateProvider};
 use std::collections::BTreeMap;
@@ -188,13 +192,28 @@ impl Chain {
         consensus.pre_validate_header(&block, parent_block)?;
         consensus.pre_validate_block(&block)?;
 
+        let expected_state_root = block.state_root;
         let (unseal, senders) = block.into_components();
         let unseal = unseal.unseal();
 
-        factory
-            .with_sp(state_provider)
-            .execute_and_verify_receipt(&unseal, U256::MAX, Some(senders))
-            .map_err(Into::into)
+        let block_state = factory.with_sp(&state_provider).execute_and_verify_receipt(
+            &unseal,
+            U256::MAX,
+            Some(senders),
+        )?;
+
+        // The state root is computed on top of the state the block was executed against, without
+        // touching the database.
+        let state_root = state_provider.state_root(&block_state)?;
+        if state_root != expected_state_root {
+            return Err(ConsensusError::BodyStateRootDiff {
+                got: state_root,
+                expected: expected_state_root,
+            }
+            .into())
+        }
+
+        Ok(block_state)
     }
 
     /// Validate and execute the given block, and append it to this chain.
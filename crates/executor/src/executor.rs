@@ -1,12 +1,14 @@
-use crate::post_state::PostState;
+use crate::{
+    post_state::{Change, PostState},
+    rewards::block_rewards,
+};
 use reth_interfaces::executor::Error;
 use reth_primitives::{
     Account, Address, Block, Bloom, Bytecode, ChainSpec, Hardfork, Header, Log, Receipt,
     ReceiptWithBloom, TransactionSigned, H256, U256,
 };
-use reth_provider::{BlockExecutor, StateProvider};
+use reth_provider::{trie::HashedStateChanges, BlockExecutor, StateProvider};
 use reth_revm::{
-    config::{WEI_2ETH, WEI_3ETH, WEI_5ETH},
     database::SubState,
     env::{fill_cfg_and_block_env, fill_tx_env},
     into_reth_log, to_reth_acc,
@@ -34,6 +36,13 @@ where
     pub chain_spec: Arc<ChainSpec>,
     evm: EVM<SubState<DB>>,
     stack: InspectorStack,
+    /// Whether the receipts of pre-Byzantium transactions include the state root after the
+    /// transaction.
+    receipt_state_roots: bool,
+    /// The hashed state the state roots of pre-Byzantium receipts are computed with: the changes
+    /// taking the state trie to the state of the provider, followed by the changes of the blocks
+    /// and transactions executed since. It's gathered on the first pre-Byzantium block.
+    receipt_state: Option<HashedStateChanges>,
 }
 
 impl<DB> From<Arc<ChainSpec>> for Executor<DB>
@@ -44,7 +53,13 @@ where
     /// `with_db` to set the database before executing.
     fn from(chain_spec: Arc<ChainSpec>) -> Self {
         let evm = EVM::new();
-        Executor { chain_spec, evm, stack: InspectorStack::new(InspectorStackConfig::default()) }
+        Executor {
+            chain_spec,
+            evm,
+            stack: InspectorStack::new(InspectorStackConfig::default()),
+            receipt_state_roots: false,
+            receipt_state: None,
+        }
     }
 }
 
@@ -57,7 +72,13 @@ where
         let mut evm = EVM::new();
         evm.database(db);
 
-        Executor { chain_spec, evm, stack: InspectorStack::new(InspectorStackConfig::default()) }
+        Executor {
+            chain_spec,
+            evm,
+            stack: InspectorStack::new(InspectorStackConfig::default()),
+            receipt_state_roots: false,
+            receipt_state: None,
+        }
     }
 
     /// Configures the executor with the given inspectors.
@@ -66,6 +87,18 @@ where
         self
     }
 
+    /// Computes the state roots of pre-Byzantium receipts and verifies them along with the receipts
+    /// root of pre-Byzantium blocks.
+    ///
+    /// The changes of the state provider on top of its state trie, see
+    /// [StateProvider::hashed_state], are gathered once. The changes of every executed transaction
+    /// and block are then applied to them, and the state roots are computed with
+    /// [StateProvider::trie_overlay_root].
+    pub fn with_receipt_state_roots(mut self) -> Self {
+        self.receipt_state_roots = true;
+        self
+    }
+
     /// Gives a reference to the database
     pub fn db(&mut self) -> &mut SubState<DB> {
         self.evm.db().expect("db to not be moved")
@@ -217,23 +250,12 @@ where
         let mut balance_increments = HashMap::<Address, U256>::default();
 
         // Collect balance increments for block and uncle rewards.
-        if let Some(reward) = self.get_block_reward(block, td) {
-            // Calculate Uncle reward
-            // OpenEthereum code: https://github.com/openethereum/openethereum/blob/6c2d392d867b058ff867c4373e40850ca3f96969/crates/ethcore/src/ethereum/ethash.rs#L319-L333
-            for ommer in block.ommers.iter() {
-                let ommer_reward =
-                    U256::from(((8 + ommer.number - block.number) as u128 * reward) >> 3);
-                // From yellowpaper Page 15:
-                // If there are collisions of the beneficiary addresses between ommers and the
-                // block (i.e. two ommers with the same beneficiary address
-                // or an ommer with the same beneficiary address as the
-                // present block), additions are applied cumulatively
-                *balance_increments.entry(ommer.beneficiary).or_default() += ommer_reward;
-            }
-
-            // Increment balance for main block reward.
-            let block_reward = U256::from(reward + (reward >> 5) * block.ommers.len() as u128);
-            *balance_increments.entry(block.beneficiary).or_default() += block_reward;
+        // From yellowpaper Page 15:
+        // If there are collisions of the beneficiary addresses between ommers and the block (i.e.
+        // two ommers with the same beneficiary address or an ommer with the same beneficiary
+        // address as the present block), additions are applied cumulatively
+        for reward in block_rewards(&self.chain_spec, block, td) {
+            *balance_increments.entry(reward.beneficiary).or_default() += reward.value;
         }
 
         if self.chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(block.timestamp) {
@@ -248,29 +270,6 @@ where
         Ok(balance_increments)
     }
 
-    /// From yellowpapper Page 15:
-    /// 11.3. Reward Application. The application of rewards to a block involves raising the
-    /// balance of the accounts of the beneficiary address of the block and each ommer by
-    /// a certain amount. We raise the block’s beneficiary account by Rblock; for each
-    /// ommer, we raise the block’s beneficiary by an additional 1/32 of the block reward
-    /// and the beneficiary of the ommer gets rewarded depending on the blocknumber.
-    /// Formally we define the function Ω.
-    ///
-    /// NOTE: Related to Ethereum reward change, for other network this is probably going to be
-    /// moved to config.
-    fn get_block_reward(&self, header: &Header, total_difficulty: U256) -> Option<u128> {
-        if self.chain_spec.fork(Hardfork::Paris).active_at_ttd(total_difficulty, header.difficulty)
-        {
-            None
-        } else if self.chain_spec.fork(Hardfork::Petersburg).active_at_block(header.number) {
-            Some(WEI_2ETH)
-        } else if self.chain_spec.fork(Hardfork::Byzantium).active_at_block(header.number) {
-            Some(WEI_3ETH)
-        } else {
-            Some(WEI_5ETH)
-        }
-    }
-
     /// Irregular state change at Ethereum DAO hardfork
     fn apply_dao_fork_changes(&mut self, post_state: &mut PostState) -> Result<(), Error> {
         let db = self.db();
@@ -349,7 +348,7 @@ where
         fill_tx_env(&mut self.evm.env.tx, transaction, sender);
 
         let hash = transaction.hash();
-        let out = if self.stack.should_inspect(&self.evm.env, hash) {
+        let out = if self.stack.start_transaction(&self.evm.env, hash) {
             // execution with inspector.
             let output = self.evm.inspect(&mut self.stack);
             tracing::trace!(
@@ -357,6 +356,9 @@ where
                 ?hash, ?output, ?transaction, env = ?self.evm.env,
                 "Executed transaction"
             );
+            if let Ok(ResultAndState { result, .. }) = &output {
+                self.stack.finish_transaction(result);
+            }
             output
         } else {
             // main execution.
@@ -385,6 +387,17 @@ where
 
         self.init_env(&block.header, total_difficulty);
 
+        let pre_byzantium =
+            !self.chain_spec.fork(Hardfork::Byzantium).active_at_block(block.number);
+        if pre_byzantium && self.receipt_state_roots && self.receipt_state.is_none() {
+            let provider = self.evm.db.as_ref().expect("db to not be moved").db.state();
+            let state = provider.hashed_state().map_err(|err| Error::ReceiptState {
+                block_number: block.number,
+                inner: err.to_string(),
+            })?;
+            self.receipt_state = Some(state);
+        }
+
         let mut cumulative_gas_used = 0;
         let mut post_state = PostState::with_tx_capacity(block.body.len());
         for (transaction, sender) in block.body.iter().zip(senders.into_iter()) {
@@ -399,6 +412,7 @@ where
             }
             // Execute transaction.
             let ResultAndState { result, state } = self.transact(transaction, sender)?;
+            let first_change = post_state.changes().len();
 
             // commit changes
             self.commit_changes(
@@ -413,6 +427,17 @@ where
             // cast revm logs to reth logs
             let logs: Vec<Log> = result.logs().into_iter().map(into_reth_log).collect();
 
+            // Before `EIP-658: Embedding transaction status code in receipts`, receipts contained
+            // the state root after the transaction.
+            let state_root = if pre_byzantium {
+                self.intermediate_state_root(
+                    transaction.hash,
+                    &post_state.changes()[first_change..],
+                )?
+            } else {
+                None
+            };
+
             // Push transaction changeset and calculate header bloom filter for receipt.
             post_state.add_receipt(Receipt {
                 tx_type: transaction.tx_type(),
@@ -420,6 +445,7 @@ where
                 // receipts`.
                 success: result.is_success(),
                 cumulative_gas_used,
+                state_root,
                 logs,
             });
             post_state.finish_transition();
@@ -427,6 +453,54 @@ where
 
         Ok((post_state, cumulative_gas_used))
     }
+
+    /// Applies the changes of the transaction to the changes executed before, and returns the
+    /// state root with all of them applied.
+    ///
+    /// Returns `None` if the state roots of receipts aren't computed.
+    fn intermediate_state_root(
+        &mut self,
+        hash: H256,
+        changes: &[Change],
+    ) -> Result<Option<H256>, Error> {
+        let Some(receipt_state) = &mut self.receipt_state else { return Ok(None) };
+        receipt_state.extend(&HashedStateChanges::from(changes));
+
+        let provider = self.evm.db.as_ref().expect("db to not be moved").db.state();
+        provider
+            .trie_overlay_root(receipt_state)
+            .map(Some)
+            .map_err(|err| Error::IntermediateStateRoot { hash, inner: err.to_string() })
+    }
+
+    /// Applies the state changes at the end of the block, i.e. the block and ommer rewards, the
+    /// withdrawals and the irregular state change of the DAO fork.
+    ///
+    /// If there are any, the changes are part of an extra transition after the transitions of the
+    /// transactions.
+    pub fn apply_post_block_changes(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        post_state: &mut PostState,
+    ) -> Result<(), Error> {
+        let balance_increments = self.post_block_balance_increments(block, total_difficulty)?;
+        let mut includes_block_transition = !balance_increments.is_empty();
+        for (address, increment) in balance_increments.into_iter() {
+            self.increment_account_balance(address, increment, post_state)?;
+        }
+
+        if self.chain_spec.fork(Hardfork::Dao).transitions_at_block(block.number) {
+            includes_block_transition = true;
+            self.apply_dao_fork_changes(post_state)?;
+        }
+
+        if includes_block_transition {
+            post_state.finish_transition();
+        }
+
+        Ok(())
+    }
 }
 
 impl<DB> BlockExecutor<DB> for Executor<DB>
@@ -447,19 +521,14 @@ where
             return Err(Error::BlockGasUsed { got: cumulative_gas_used, expected: block.gas_used })
         }
 
-        let balance_increments = self.post_block_balance_increments(block, total_difficulty)?;
-        let mut includes_block_transition = !balance_increments.is_empty();
-        for (address, increment) in balance_increments.into_iter() {
-            self.increment_account_balance(address, increment, &mut post_state)?;
-        }
+        let first_change = post_state.changes().len();
+        self.apply_post_block_changes(block, total_difficulty, &mut post_state)?;
 
-        if self.chain_spec.fork(Hardfork::Dao).transitions_at_block(block.number) {
-            includes_block_transition = true;
-            self.apply_dao_fork_changes(&mut post_state)?;
-        }
-
-        if includes_block_transition {
-            post_state.finish_transition();
+        if self.chain_spec.fork(Hardfork::Byzantium).active_at_block(block.number) {
+            // the state roots of later blocks aren't needed anymore
+            self.receipt_state = None;
+        } else if let Some(receipt_state) = &mut self.receipt_state {
+            receipt_state.extend(&HashedStateChanges::from(&post_state.changes()[first_change..]));
         }
 
         Ok(post_state)
@@ -473,7 +542,12 @@ where
     ) -> Result<PostState, Error> {
         let post_state = self.execute(block, total_difficulty, senders)?;
 
-        if self.chain_spec.fork(Hardfork::Byzantium).active_at_block(block.header.number) {
+        // Before Byzantium, receipts contain the state root after every transaction instead of
+        // the status, so they can only be verified if the state roots were computed.
+        // See more about EIP here: https://eips.ethereum.org/EIPS/eip-658
+        if self.receipt_state_roots ||
+            self.chain_spec.fork(Hardfork::Byzantium).active_at_block(block.header.number)
+        {
             verify_receipt(
                 block.header.receipts_root,
                 block.header.logs_bloom,
@@ -481,11 +555,6 @@ where
             )?;
         }
 
-        // TODO Before Byzantium, receipts contained state root that would mean that expensive
-        // operation as hashing that is needed for state root got calculated in every
-        // transaction This was replaced with is_success flag.
-        // See more about EIP here: https://eips.ethereum.org/EIPS/eip-658
-
         Ok(post_state)
     }
 }
@@ -517,17 +586,30 @@ pub fn verify_receipt<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use reth_db::{
+        database::Database, mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut,
+    };
     use reth_primitives::{
-        hex_literal::hex, keccak256, Account, Address, BlockNumber, Bytecode, Bytes,
-        ChainSpecBuilder, ForkCondition, StorageKey, H256, MAINNET, U256,
+        bytes, hex_literal::hex, keccak256, Account, Address, BlockNumber, Bytecode, Bytes,
+        ChainSpecBuilder, ForkCondition, Signature, StageCheckpoint, StorageKey, Transaction,
+        TransactionKind, TxLegacy, H256, MAINNET, U256,
     };
     use reth_provider::{
-        post_state::{Change, Storage},
-        AccountProvider, BlockHashProvider, StateProvider,
+        post_state::Storage,
+        trie::{StateRoot, MERKLE_STAGE_ID},
+        AccountProvider, BlockHashProvider, LatestStateProviderRef, StateProvider,
+    };
+    use reth_revm::{config::WEI_2ETH, database::State};
+    use reth_revm_inspectors::{
+        profiler::GasProfiler,
+        stack::{Hook, InspectorDb, InspectorRegistry},
     };
-    use reth_revm::database::State;
     use reth_rlp::Decodable;
-    use std::{collections::HashMap, str::FromStr};
+    use revm::{
+        interpreter::{opcode, CallInputs, Gas, InstructionResult, Interpreter},
+        Database as _, EVMData, Inspector,
+    };
+    use std::{collections::HashMap, str::FromStr, sync::Mutex};
 
     #[derive(Debug, Default, Clone, Eq, PartialEq)]
     struct StateProviderTest {
@@ -603,6 +685,17 @@ mod tests {
         ) -> reth_interfaces::Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
             todo!()
         }
+
+        fn hashed_state(&self) -> reth_interfaces::Result<HashedStateChanges> {
+            todo!()
+        }
+
+        fn trie_overlay_root(
+            &self,
+            _changes: &HashedStateChanges,
+        ) -> reth_interfaces::Result<H256> {
+            todo!()
+        }
     }
 
     #[test]
@@ -942,6 +1035,269 @@ mod tests {
         );
     }
 
+    #[derive(Default)]
+    struct OpcodeCounter {
+        steps: usize,
+        selfdestructs: usize,
+        caller_balance: Option<U256>,
+    }
+
+    impl Inspector<InspectorDb<'_>> for OpcodeCounter {
+        fn step(
+            &mut self,
+            _interp: &mut Interpreter,
+            _data: &mut EVMData<'_, InspectorDb<'_>>,
+            _is_static: bool,
+        ) -> InstructionResult {
+            self.steps += 1;
+            InstructionResult::Continue
+        }
+
+        fn call(
+            &mut self,
+            data: &mut EVMData<'_, InspectorDb<'_>>,
+            inputs: &mut CallInputs,
+            _is_static: bool,
+        ) -> (InstructionResult, Gas, bytes::Bytes) {
+            // the database holds the state before the transaction
+            let caller = data.db.basic(inputs.context.caller).unwrap();
+            self.caller_balance.get_or_insert(caller.unwrap_or_default().balance);
+            (InstructionResult::Continue, Gas::new(inputs.gas_limit), bytes::Bytes::new())
+        }
+
+        fn selfdestruct(&mut self, _contract: Address, _target: Address) {
+            self.selfdestructs += 1;
+        }
+    }
+
+    #[test]
+    fn registered_inspectors() {
+        // Same block as `test_selfdestruct`, with a single transaction calling a contract that
+        // executes `PUSH20 BALANCE SELFDESTRUCT`.
+        let mut block_rlp = hex!("f9025ff901f7a0c86e8cc0310ae7c531c758678ddbfd16fc51c8cef8cec650b032de9869e8b94fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa050554882fbbda2c2fd93fdc466db9946ea262a67f7a76cc169e714f105ab583da00967f09ef1dfed20c0eacfaa94d5cd4002eda3242ac47eae68972d07b106d192a0e3c8b47fbfc94667ef4cceb17e5cc21e3b1eebd442cebb27f07562b33836290db90100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008302000001830f42408238108203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f862f860800a83061a8094095e7baea6a6c7c4c2dfeb977efac326af552d8780801ba072ed817487b84ba367d15d2f039b5fc5f087d0a8882fbdf73e8cb49357e1ce30a0403d800545b8fc544f92ce8124e2255f8c3c6af93f28243a120585d4c4c6a2a3c0").as_slice();
+        let block = Block::decode(&mut block_rlp).unwrap();
+        let mut db = StateProviderTest::default();
+
+        let address_caller = Address::from_str("a94f5374fce5edbc8e2a8697c15331677e6ebf0b").unwrap();
+        let address_selfdestruct =
+            Address::from_str("095e7baea6a6c7c4c2dfeb977efac326af552d87").unwrap();
+        db.insert_account(
+            address_caller,
+            Account {
+                balance: U256::from(0x0de0b6b3a7640000u64),
+                nonce: 0x00,
+                bytecode_hash: None,
+            },
+            None,
+            HashMap::new(),
+        );
+        db.insert_account(
+            address_selfdestruct,
+            Account::default(),
+            Some(hex!("73095e7baea6a6c7c4c2dfeb977efac326af552d8731ff00").into()),
+            HashMap::new(),
+        );
+
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = InspectorRegistry::default();
+        let sink = outputs.clone();
+        registry.register(Hook::Block(block.number), OpcodeCounter::default, move |output| {
+            sink.lock().unwrap().push(output)
+        });
+        // hooked on another block, never runs
+        registry.register(Hook::Block(block.number + 1), OpcodeCounter::default, |_| {
+            panic!("inspector should not run")
+        });
+
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build());
+        let stack = InspectorStack::new(InspectorStackConfig { registry, ..Default::default() });
+        let mut executor =
+            Executor::new(chain_spec, SubState::new(State::new(db))).with_stack(stack);
+        let out = executor.execute_and_verify_receipt(&block, U256::ZERO, None).unwrap();
+
+        let outputs = outputs.lock().unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].block_number, block.number);
+        assert_eq!(outputs[0].transaction_hash, block.body[0].hash());
+        assert_eq!(outputs[0].result.gas_used(), out.receipts()[0].cumulative_gas_used);
+        assert_eq!(outputs[0].inspector.steps, 3);
+        assert_eq!(outputs[0].inspector.selfdestructs, 1);
+        assert_eq!(outputs[0].inspector.caller_balance, Some(U256::from(0x0de0b6b3a7640000u64)));
+    }
+
+    #[test]
+    fn gas_profiler() {
+        let caller = Address::from_low_u64_be(0xaa);
+        let contract = Address::from_low_u64_be(0xbb);
+        let callee = Address::from_low_u64_be(0xcc);
+        let identity = Address::from_low_u64_be(4);
+
+        // CALL the callee, then STATICCALL the identity precompile, both without data
+        let mut code = hex!("6000600060006000600073").to_vec();
+        code.extend_from_slice(callee.as_bytes());
+        code.extend_from_slice(&hex!("5af150600060006000600060045afa5000"));
+
+        let mut db = StateProviderTest::default();
+        db.insert_account(
+            caller,
+            Account { balance: U256::from(1_000_000), ..Default::default() },
+            None,
+            HashMap::new(),
+        );
+        db.insert_account(contract, Account::default(), Some(code.into()), HashMap::new());
+        // PUSH1 1 PUSH1 2 ADD POP STOP
+        db.insert_account(
+            callee,
+            Account::default(),
+            Some(hex!("60016002015000").into()),
+            HashMap::new(),
+        );
+
+        let transaction = Transaction::Legacy(TxLegacy {
+            chain_id: Some(1),
+            nonce: 0,
+            gas_price: 1,
+            gas_limit: 100_000,
+            to: TransactionKind::Call(contract),
+            value: 0,
+            input: Bytes::default(),
+        });
+        let block = Block {
+            header: Header { number: 1, gas_limit: 1_000_000, ..Default::default() },
+            body: vec![TransactionSigned::from_transaction_and_signature(
+                transaction,
+                Signature::default(),
+            )],
+            ommers: vec![],
+            withdrawals: None,
+        };
+
+        let profiles = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = InspectorRegistry::default();
+        let sink = profiles.clone();
+        registry.register(Hook::All, GasProfiler::default, move |output| {
+            sink.lock().unwrap().push(output.inspector.into_profile(&output.result))
+        });
+
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build());
+        let stack = InspectorStack::new(InspectorStackConfig { registry, ..Default::default() });
+        let mut executor =
+            Executor::new(chain_spec, SubState::new(State::new(db))).with_stack(stack);
+        let (post_state, gas_used) =
+            executor.execute_transactions(&block, U256::ZERO, Some(vec![caller])).unwrap();
+        assert!(post_state.receipts()[0].success);
+
+        let profiles = profiles.lock().unwrap();
+        assert_eq!(profiles.len(), 1);
+        let profile = &profiles[0];
+        assert_eq!(profile.transactions.count, 1);
+        assert_eq!(profile.transactions.gas, gas_used);
+
+        // the call frames are attributed to the executed code
+        assert_eq!(profile.contracts.keys().copied().collect::<Vec<_>>(), vec![contract, callee]);
+        assert_eq!(profile.contracts[&callee].count, 1);
+        assert_eq!(profile.contracts[&callee].gas, 3 + 3 + 3 + 2);
+        assert_eq!(profile.stacks[&format!("{contract:?};{callee:?};ADD")], 3);
+
+        // the gas of the calls isn't counted in the opcodes making them
+        assert_eq!(profile.opcodes[&opcode::ADD].gas, 3);
+        assert_eq!(profile.opcodes[&opcode::CALL].count, 1);
+        assert_eq!(profile.opcodes[&opcode::STATICCALL].count, 1);
+        assert!(profile.opcodes[&opcode::CALL].gas < profile.contracts[&contract].gas);
+
+        // the identity precompile costs 15 gas for an empty input
+        assert_eq!(profile.precompiles.keys().copied().collect::<Vec<_>>(), vec![identity]);
+        assert_eq!(profile.precompiles[&identity].count, 1);
+        assert_eq!(profile.precompiles[&identity].gas, 15);
+    }
+
+    #[test]
+    fn frontier_receipt_state_roots() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+
+        let (sender, recipient, beneficiary) =
+            (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+        let account = Account { balance: U256::from(10u128.pow(18)), ..Default::default() };
+        tx.put::<tables::PlainAccountState>(sender, account).unwrap();
+        tx.put::<tables::HashedAccount>(keccak256(sender), account).unwrap();
+
+        // the state trie is at the genesis block
+        let genesis_root = StateRoot::new(&tx).calculate_root().unwrap();
+        assert_eq!(
+            genesis_root,
+            H256(hex!("176a3fe6cfe66b3061f2f8750206530da568ca5be992641af0d1fe227156159f"))
+        );
+        tx.put::<tables::Headers>(0, Header { state_root: genesis_root, ..Default::default() })
+            .unwrap();
+        tx.put::<tables::BlockTransitionIndex>(0, 0).unwrap();
+        tx.put::<tables::SyncStage>(MERKLE_STAGE_ID.to_string(), StageCheckpoint::new(0)).unwrap();
+
+        let transfer = |nonce, value| {
+            let transaction = Transaction::Legacy(TxLegacy {
+                chain_id: None,
+                nonce,
+                gas_price: 1,
+                gas_limit: 21_000,
+                to: TransactionKind::Call(recipient),
+                value,
+                input: Bytes::default(),
+            });
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+        };
+        let block = |number, body: Vec<TransactionSigned>, receipts_root| Block {
+            header: Header {
+                number,
+                beneficiary,
+                gas_limit: 1_000_000,
+                gas_used: 21_000 * body.len() as u64,
+                receipts_root: H256(receipts_root),
+                ..Default::default()
+            },
+            body,
+            ommers: vec![],
+            withdrawals: None,
+        };
+
+        // the blocks of the mainnet spec are pre-Byzantium, the state of the first block is only
+        // in the executor when executing the second one
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().build());
+        let state = SubState::new(State::new(LatestStateProviderRef::new(&tx)));
+        let mut executor = Executor::new(chain_spec, state).with_receipt_state_roots();
+
+        let first = block(
+            1,
+            vec![transfer(0, 100), transfer(1, 200)],
+            hex!("0e8b6dd000426d3035b32e1c89bc0728714841fa942d99e9a7ad286c57d25dea"),
+        );
+        let post_state =
+            executor.execute_and_verify_receipt(&first, U256::ZERO, Some(vec![sender; 2])).unwrap();
+        assert_eq!(
+            post_state.receipts().iter().map(|receipt| receipt.state_root).collect::<Vec<_>>(),
+            vec![
+                Some(H256(hex!(
+                    "0c7be790a39f5208cf515c658acbd7bcc5be00f364dc5e323b4708e6bfe3eacb"
+                ))),
+                Some(H256(hex!(
+                    "f3f70f0fbd59442725c14bff5fb57f6cbfb80366331a35bf460718bdc471f3cf"
+                ))),
+            ]
+        );
+
+        // the block reward of the first block is part of the state
+        let second = block(
+            2,
+            vec![transfer(2, 300)],
+            hex!("c953a82eab9bf04e3730e24f1a9a1c90df4b106ad90f4b5ed86ee8311e6088a7"),
+        );
+        let post_state =
+            executor.execute_and_verify_receipt(&second, U256::ZERO, Some(vec![sender])).unwrap();
+        assert_eq!(
+            post_state.receipts()[0].state_root,
+            Some(H256(hex!("40bb6c62f00c5ba1e3d63fce558c36a9bd6c5ef6e54cb695234f4b41b14be29a")))
+        );
+    }
+
     // Test vector from https://github.com/ethereum/tests/blob/3156db5389921125bb9e04142d18e0e7b0cf8d64/BlockchainTests/EIPTests/bc4895-withdrawals/twoIdenticalIndexDifferentValidator.json
     #[test]
     fn test_withdrawals() {
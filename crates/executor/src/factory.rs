@@ -2,7 +2,7 @@ use reth_primitives::ChainSpec;
 use reth_provider::{ExecutorFactory, StateProvider};
 use reth_revm::{
     database::{State, SubState},
-    stack::{InspectorStack, InspectorStackConfig},
+    stack::{InspectorRegistry, InspectorStack, InspectorStackConfig},
 };
 
 use crate::executor::Executor;
@@ -13,12 +13,13 @@ use std::sync::Arc;
 pub struct Factory {
     chain_spec: Arc<ChainSpec>,
     stack: Option<InspectorStack>,
+    receipt_state_roots: bool,
 }
 
 impl Factory {
     /// Create new factory
     pub fn new(chain_spec: Arc<ChainSpec>) -> Self {
-        Self { chain_spec, stack: None }
+        Self { chain_spec, stack: None, receipt_state_roots: false }
     }
 
     /// Sets the inspector stack for all generated executors.
@@ -32,6 +33,21 @@ impl Factory {
         self.stack = Some(InspectorStack::new(config));
         self
     }
+
+    /// Sets the inspectors to run in all generated executors, e.g. during the execution stage and
+    /// the execution of blocks in the blockchain tree.
+    pub fn with_inspector_registry(mut self, registry: InspectorRegistry) -> Self {
+        self.stack.get_or_insert_with(InspectorStack::default).registry = registry;
+        self
+    }
+
+    /// Computes the state roots of pre-Byzantium receipts in all generated executors.
+    ///
+    /// See [Executor::with_receipt_state_roots].
+    pub fn with_receipt_state_roots(mut self) -> Self {
+        self.receipt_state_roots = true;
+        self
+    }
 }
 
 impl ExecutorFactory for Factory {
@@ -45,6 +61,9 @@ impl ExecutorFactory for Factory {
         if let Some(ref stack) = self.stack {
             executor = executor.with_stack(stack.clone());
         }
+        if self.receipt_state_roots {
+            executor = executor.with_receipt_state_roots();
+        }
         executor
     }
 
@@ -20,6 +20,7 @@ pub mod executor;
 
 /// ExecutorFactory impl
 pub mod factory;
+pub mod rewards;
 pub use factory::Factory;
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -0,0 +1,118 @@
+//! Block and ommer rewards paid at the end of a block.
+use reth_primitives::{Address, Block, ChainSpec, Hardfork, Header, U256};
+use reth_revm::config::{WEI_2ETH, WEI_3ETH, WEI_5ETH};
+
+/// The kind of a [BlockReward].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockRewardKind {
+    /// The reward of the beneficiary of the block, including the rewards for the included ommers.
+    Block,
+    /// The reward of the beneficiary of an included ommer.
+    Ommer,
+}
+
+/// A reward paid to a beneficiary at the end of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockReward {
+    /// The rewarded account.
+    pub beneficiary: Address,
+    /// The rewarded amount in wei.
+    pub value: U256,
+    /// Whether the reward is for the block or for an ommer.
+    pub kind: BlockRewardKind,
+}
+
+/// Returns the rewards paid at the end of the block, the reward of the block first, followed by the
+/// rewards of the ommers.
+///
+/// There are no rewards after the merge.
+pub fn block_rewards(
+    chain_spec: &ChainSpec,
+    block: &Block,
+    total_difficulty: U256,
+) -> Vec<BlockReward> {
+    let Some(reward) = base_block_reward(chain_spec, block, total_difficulty) else {
+        return Vec::new()
+    };
+
+    // The block beneficiary gets an additional 1/32 of the block reward for every ommer.
+    let block_reward = BlockReward {
+        beneficiary: block.beneficiary,
+        value: U256::from(reward + (reward >> 5) * block.ommers.len() as u128),
+        kind: BlockRewardKind::Block,
+    };
+
+    // Calculate Uncle reward
+    // OpenEthereum code: https://github.com/openethereum/openethereum/blob/6c2d392d867b058ff867c4373e40850ca3f96969/crates/ethcore/src/ethereum/ethash.rs#L319-L333
+    let ommer_rewards = block.ommers.iter().map(|ommer| BlockReward {
+        beneficiary: ommer.beneficiary,
+        value: U256::from(((8 + ommer.number - block.number) as u128 * reward) >> 3),
+        kind: BlockRewardKind::Ommer,
+    });
+
+    std::iter::once(block_reward).chain(ommer_rewards).collect()
+}
+
+/// From yellowpapper Page 15:
+/// 11.3. Reward Application. The application of rewards to a block involves raising the
+/// balance of the accounts of the beneficiary address of the block and each ommer by
+/// a certain amount. We raise the block’s beneficiary account by Rblock; for each
+/// ommer, we raise the block’s beneficiary by an additional 1/32 of the block reward
+/// and the beneficiary of the ommer gets rewarded depending on the blocknumber.
+/// Formally we define the function Ω.
+///
+/// NOTE: Related to Ethereum reward change, for other network this is probably going to be
+/// moved to config.
+pub fn base_block_reward(
+    chain_spec: &ChainSpec,
+    header: &Header,
+    total_difficulty: U256,
+) -> Option<u128> {
+    if chain_spec.fork(Hardfork::Paris).active_at_ttd(total_difficulty, header.difficulty) {
+        None
+    } else if chain_spec.fork(Hardfork::Petersburg).active_at_block(header.number) {
+        Some(WEI_2ETH)
+    } else if chain_spec.fork(Hardfork::Byzantium).active_at_block(header.number) {
+        Some(WEI_3ETH)
+    } else {
+        Some(WEI_5ETH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::MAINNET;
+
+    #[test]
+    fn ommer_rewards() {
+        let ommer =
+            Header { number: 99, beneficiary: Address::from_low_u64_be(2), ..Default::default() };
+        let block = Block {
+            header: Header {
+                number: 100,
+                beneficiary: Address::from_low_u64_be(1),
+                ..Default::default()
+            },
+            ommers: vec![ommer],
+            ..Default::default()
+        };
+
+        let rewards = block_rewards(&MAINNET, &block, U256::ZERO);
+        assert_eq!(
+            rewards,
+            vec![
+                BlockReward {
+                    beneficiary: Address::from_low_u64_be(1),
+                    value: U256::from(WEI_5ETH + WEI_5ETH / 32),
+                    kind: BlockRewardKind::Block,
+                },
+                BlockReward {
+                    beneficiary: Address::from_low_u64_be(2),
+                    value: U256::from(WEI_5ETH * 7 / 8),
+                    kind: BlockRewardKind::Ommer,
+                },
+            ]
+        );
+    }
+}
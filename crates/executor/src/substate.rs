@@ -2,7 +2,10 @@
 
 use reth_interfaces::{provider::ProviderError, Result};
 use reth_primitives::{Account, Address, BlockHash, BlockNumber, Bytecode, Bytes, H256, U256};
-use reth_provider::{post_state::PostState, AccountProvider, BlockHashProvider, StateProvider};
+use reth_provider::{
+    post_state::PostState, trie::HashedStateChanges, AccountProvider, BlockHashProvider,
+    StateProvider,
+};
 use std::collections::BTreeMap;
 
 /// A state provider that either resolves to data in a wrapped [`PostState`], or an underlying state
@@ -96,4 +99,15 @@ impl<'a, SP: StateProvider> StateProvider for PostStateProvider<'a, SP> {
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         Err(ProviderError::HistoryStateRoot.into())
     }
+
+    fn hashed_state(&self) -> Result<HashedStateChanges> {
+        // the changes of the chain are applied on top of the state of the chain it forks from
+        let mut state = self.provider.hashed_state()?;
+        state.extend(&HashedStateChanges::from(self.state));
+        Ok(state)
+    }
+
+    fn trie_overlay_root(&self, changes: &HashedStateChanges) -> Result<H256> {
+        self.provider.trie_overlay_root(changes)
+    }
 }
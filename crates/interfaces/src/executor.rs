@@ -34,6 +34,12 @@ pub enum Error {
     BlockGasUsed { got: u64, expected: u64 },
     #[error("Provider error")]
     ProviderError,
+    #[error("Failed to compute the state root after transaction {hash:?}: {inner}")]
+    IntermediateStateRoot { hash: H256, inner: String },
+    #[error(
+        "Failed to gather the state for the receipt state roots of block #{block_number}: {inner}"
+    )]
+    ReceiptState { block_number: BlockNumber, inner: String },
     #[error("BlockChainId can't be found in BlockchainTree with internal index {chain_id}")]
     BlockChainIdConsistency { chain_id: u64 },
     #[error(
@@ -1,4 +1,6 @@
-use reth_primitives::{Address, BlockHash, BlockNumber, TransitionId, TxNumber, H256};
+use reth_primitives::{
+    Address, BlockHash, BlockNumber, PruneSegment, TransitionId, TxNumber, H256,
+};
 
 /// Bundled errors variants thrown by various providers.
 #[allow(missing_docs)]
@@ -72,8 +74,8 @@ pub enum ProviderError {
     #[error("Missing block hash for block #{block_number:?} in blockchain tree")]
     BlockchainTreeBlockHash { block_number: BlockNumber },
     /// Some error occurred while interacting with the state tree.
-    #[error("Unknown error occurred while interacting with the state trie.")]
-    StateTrie,
+    #[error("Error occurred while interacting with the state trie: {0}")]
+    StateTrie(String),
     #[error("History state root, can't be calculated")]
     HistoryStateRoot,
     /// Thrown when required header related data was not found but was required.
@@ -88,4 +90,13 @@ pub enum ProviderError {
     /// Thrown when the cache service task dropped
     #[error("cache service task stopped")]
     CacheServiceUnavailable,
+    /// The requested data of the block was pruned.
+    #[error("Data of block #{block_number} was pruned from {segment}")]
+    Pruned { segment: PruneSegment, block_number: BlockNumber },
+    /// The requested data of the transaction was pruned.
+    #[error("Data of transaction #{tx_number} was pruned from {segment}")]
+    PrunedTransaction { segment: PruneSegment, tx_number: TxNumber },
+    /// Reading from the static files failed.
+    #[error("{0}")]
+    StaticFile(String),
 }
@@ -2,6 +2,8 @@
 
 use reth_primitives::BlockNumber;
 
+pub use reth_network_api::StageSyncProgress;
+
 /// A type that provides information about whether the node is currently syncing and the network is
 /// currently serving syncing related requests.
 #[auto_impl::auto_impl(&, Arc, Box)]
@@ -26,6 +28,9 @@ pub trait SyncStateProvider: Send + Sync {
 pub trait SyncStateUpdater: SyncStateProvider {
     /// Notifies about an [SyncState] update.
     fn update_sync_state(&self, state: SyncState);
+
+    /// Notifies about the [StageSyncProgress] of a stage.
+    fn update_stage_progress(&self, progress: StageSyncProgress);
 }
 
 /// The state the network is currently in when it comes to synchronization.
@@ -74,4 +79,6 @@ impl SyncStateProvider for NoopSyncStateUpdate {
 
 impl SyncStateUpdater for NoopSyncStateUpdate {
     fn update_sync_state(&self, _state: SyncState) {}
+
+    fn update_stage_progress(&self, _progress: StageSyncProgress) {}
 }
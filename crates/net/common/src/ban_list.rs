@@ -1,6 +1,11 @@
 //! Support for banning peers.
+use crate::subnet::IpSubnet;
 use reth_primitives::PeerId;
-use std::{collections::HashMap, net::IpAddr, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    time::Instant,
+};
 
 /// Determines whether or not the IP is globally routable.
 /// Should be replaced with [`IpAddr::is_global`](std::net::IpAddr::is_global) once it is stable.
@@ -17,12 +22,19 @@ pub fn is_global(ip: &IpAddr) -> bool {
 
 /// Stores peers that should be taken out of circulation either indefinitely or until a certain
 /// timestamp
+///
+/// In addition to single IPs, entire subnets can be banned. If any subnets are explicitly allowed,
+/// all IPs outside of these subnets are considered banned as well.
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct BanList {
     /// A set of IPs whose packets get dropped instantly.
     banned_ips: HashMap<IpAddr, Option<Instant>>,
     /// A set of [`PeerId`] whose packets get dropped instantly.
     banned_peers: HashMap<PeerId, Option<Instant>>,
+    /// Subnets whose packets get dropped instantly.
+    banned_subnets: HashSet<IpSubnet>,
+    /// If not empty, only IPs in these subnets are accepted.
+    allowed_subnets: HashSet<IpSubnet>,
 }
 
 impl BanList {
@@ -42,7 +54,21 @@ impl BanList {
         banned_peers: HashMap<PeerId, Option<Instant>>,
         banned_ips: HashMap<IpAddr, Option<Instant>>,
     ) -> Self {
-        Self { banned_ips, banned_peers }
+        Self { banned_ips, banned_peers, ..Default::default() }
+    }
+
+    /// Bans all given subnets indefinitely.
+    pub fn with_banned_subnets(mut self, subnets: impl IntoIterator<Item = IpSubnet>) -> Self {
+        self.banned_subnets.extend(subnets);
+        self
+    }
+
+    /// Only accepts IPs of the given subnets.
+    ///
+    /// See also [`BanList::allow_subnet`].
+    pub fn with_allowed_subnets(mut self, subnets: impl IntoIterator<Item = IpSubnet>) -> Self {
+        self.allowed_subnets.extend(subnets);
+        self
     }
 
     /// Removes all peers that are no longer banned.
@@ -91,9 +117,61 @@ impl BanList {
     }
 
     /// checks the ban list to see if it contains the given ip
+    ///
+    /// This is also true if the ip is in a banned subnet or not in any of the allowed subnets.
     #[inline]
     pub fn is_banned_ip(&self, ip: &IpAddr) -> bool {
-        self.banned_ips.contains_key(ip)
+        self.banned_ips.contains_key(ip) || self.is_banned_subnet(ip) || !self.is_allowed_ip(ip)
+    }
+
+    /// Returns true if the ip is in any of the banned subnets.
+    pub fn is_banned_subnet(&self, ip: &IpAddr) -> bool {
+        self.banned_subnets.iter().any(|subnet| subnet.contains(ip))
+    }
+
+    /// Returns true if no subnets are explicitly allowed or the ip is in one of them.
+    pub fn is_allowed_ip(&self, ip: &IpAddr) -> bool {
+        self.allowed_subnets.is_empty() ||
+            self.allowed_subnets.iter().any(|subnet| subnet.contains(ip))
+    }
+
+    /// Returns all banned subnets.
+    pub fn banned_subnets(&self) -> impl Iterator<Item = &IpSubnet> + '_ {
+        self.banned_subnets.iter()
+    }
+
+    /// Returns all explicitly allowed subnets.
+    pub fn allowed_subnets(&self) -> impl Iterator<Item = &IpSubnet> + '_ {
+        self.allowed_subnets.iter()
+    }
+
+    /// Bans all IPs of the subnet indefinitely.
+    ///
+    /// Unlike [`BanList::ban_ip`], this also bans non-global IPs.
+    pub fn ban_subnet(&mut self, subnet: IpSubnet) {
+        self.banned_subnets.insert(subnet);
+    }
+
+    /// Unbans the subnet.
+    ///
+    /// This does not unban IPs of the subnet that were banned individually.
+    pub fn unban_subnet(&mut self, subnet: &IpSubnet) {
+        self.banned_subnets.remove(subnet);
+    }
+
+    /// Adds the subnet to the allowed subnets.
+    ///
+    /// Once a subnet is allowed, all IPs outside of the allowed subnets are considered banned.
+    /// Banned IPs and subnets take precedence over allowed subnets.
+    pub fn allow_subnet(&mut self, subnet: IpSubnet) {
+        self.allowed_subnets.insert(subnet);
+    }
+
+    /// Removes the subnet from the allowed subnets.
+    ///
+    /// If this was the last allowed subnet, all IPs are allowed again.
+    pub fn disallow_subnet(&mut self, subnet: &IpSubnet) {
+        self.allowed_subnets.remove(subnet);
     }
 
     /// checks the ban list to see if it contains the given ip
@@ -198,4 +276,32 @@ mod tests {
         banlist.ban_ip(ip);
         assert!(!banlist.is_banned_ip(&ip));
     }
+
+    #[test]
+    fn can_ban_unban_subnet() {
+        let subnet: IpSubnet = "10.0.0.0/8".parse().unwrap();
+        let ip = IpAddr::from([10, 1, 2, 3]);
+        let mut banlist = BanList::default();
+        banlist.ban_subnet(subnet);
+        assert!(banlist.is_banned_ip(&ip));
+        assert!(!banlist.is_banned_ip(&IpAddr::from([11, 1, 2, 3])));
+        banlist.unban_subnet(&subnet);
+        assert!(!banlist.is_banned_ip(&ip));
+    }
+
+    #[test]
+    fn allowed_subnets() {
+        let allowed: IpSubnet = "1.2.0.0/16".parse().unwrap();
+        let mut banlist = BanList::default().with_allowed_subnets([allowed]);
+        assert!(!banlist.is_banned_ip(&IpAddr::from([1, 2, 3, 4])));
+        assert!(banlist.is_banned_ip(&IpAddr::from([1, 3, 3, 4])));
+
+        // bans take precedence
+        banlist.ban_subnet("1.2.3.0/24".parse().unwrap());
+        assert!(banlist.is_banned_ip(&IpAddr::from([1, 2, 3, 4])));
+        assert!(!banlist.is_banned_ip(&IpAddr::from([1, 2, 4, 4])));
+
+        banlist.disallow_subnet(&allowed);
+        assert!(!banlist.is_banned_ip(&IpAddr::from([1, 3, 3, 4])));
+    }
 }
@@ -13,3 +13,4 @@ pub mod bandwidth_meter;
 pub mod stream;
 
 pub mod ratelimit;
+pub mod subnet;
@@ -0,0 +1,203 @@
+//! Support for IP subnets in CIDR notation.
+
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    num::ParseIntError,
+    str::FromStr,
+};
+
+/// A range of IP addresses in CIDR notation, e.g. `10.0.0.0/8` or `2001:db8::/32`.
+///
+/// The address is always stored with all host bits cleared, so two subnets covering the same range
+/// are equal.
+///
+/// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) are treated as the IPv4 address they represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IpSubnet {
+    /// The network address.
+    addr: IpAddr,
+    /// The number of leading bits that make up the network address.
+    prefix_len: u8,
+}
+
+// === impl IpSubnet ===
+
+impl IpSubnet {
+    /// Creates a new subnet from the given address and prefix length.
+    ///
+    /// Host bits of the address are cleared.
+    ///
+    /// Returns an error if the prefix length exceeds the number of bits of the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, IpSubnetError> {
+        let addr = canonical(addr);
+        if prefix_len > max_prefix_len(&addr) {
+            return Err(IpSubnetError::InvalidPrefixLen(prefix_len))
+        }
+        Ok(Self { addr: mask(addr, prefix_len), prefix_len })
+    }
+
+    /// Returns the subnet with the given prefix length the IP belongs to.
+    ///
+    /// The prefix length is capped at the number of bits of the address.
+    pub fn of(ip: IpAddr, prefix_len: u8) -> Self {
+        let ip = canonical(ip);
+        let prefix_len = prefix_len.min(max_prefix_len(&ip));
+        Self { addr: mask(ip, prefix_len), prefix_len }
+    }
+
+    /// Returns the subnet that only contains the given IP.
+    pub fn single(ip: IpAddr) -> Self {
+        let ip = canonical(ip);
+        Self { addr: ip, prefix_len: max_prefix_len(&ip) }
+    }
+
+    /// Returns the network address of the subnet.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the prefix length of the subnet.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns true if the given IP belongs to the subnet.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = canonical(*ip);
+        ip.is_ipv4() == self.addr.is_ipv4() && mask(ip, self.prefix_len) == self.addr
+    }
+}
+
+impl From<IpAddr> for IpSubnet {
+    fn from(ip: IpAddr) -> Self {
+        Self::single(ip)
+    }
+}
+
+impl fmt::Display for IpSubnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl FromStr for IpSubnet {
+    type Err = IpSubnetError;
+
+    /// Parses a subnet in CIDR notation, a plain IP address is parsed as single address subnet.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((addr, prefix_len)) => {
+                let addr =
+                    addr.parse().map_err(|_| IpSubnetError::InvalidAddr(addr.to_string()))?;
+                Self::new(addr, prefix_len.parse()?)
+            }
+            None => {
+                let addr = s.parse().map_err(|_| IpSubnetError::InvalidAddr(s.to_string()))?;
+                Ok(Self::single(addr))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for IpSubnet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for IpSubnet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Possible errors when parsing an [`IpSubnet`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IpSubnetError {
+    /// The address part is not a valid IP address.
+    #[error("invalid ip address: {0}")]
+    InvalidAddr(String),
+    /// The prefix length is not a number.
+    #[error(transparent)]
+    ParsePrefixLen(#[from] ParseIntError),
+    /// The prefix length exceeds the number of bits of the address.
+    #[error("invalid prefix length: {0}")]
+    InvalidPrefixLen(u8),
+}
+
+/// Converts IPv4-mapped IPv6 addresses into IPv4 addresses.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    }
+}
+
+fn max_prefix_len(ip: &IpAddr) -> u8 {
+    match ip {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+/// Clears all but the first `prefix_len` bits of the IP.
+fn mask(ip: IpAddr, prefix_len: u8) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or_default();
+            IpAddr::V4(Ipv4Addr::from(u32::from(ip) & mask))
+        }
+        IpAddr::V6(ip) => {
+            let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or_default();
+            IpAddr::V6(Ipv6Addr::from(u128::from(ip) & mask))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_subnet() {
+        let subnet: IpSubnet = "10.1.2.3/16".parse().unwrap();
+        assert_eq!(subnet.addr(), IpAddr::from([10, 1, 0, 0]));
+        assert_eq!(subnet.prefix_len(), 16);
+        assert_eq!(subnet.to_string(), "10.1.0.0/16");
+
+        let subnet: IpSubnet = "2001:db8::1/32".parse().unwrap();
+        assert_eq!(subnet.to_string(), "2001:db8::/32");
+
+        let subnet: IpSubnet = "1.1.1.1".parse().unwrap();
+        assert_eq!(subnet, IpSubnet::single(IpAddr::from([1, 1, 1, 1])));
+        assert_eq!(subnet.to_string(), "1.1.1.1/32");
+
+        let subnet: IpSubnet = "0.0.0.0/0".parse().unwrap();
+        assert!(subnet.contains(&IpAddr::from([8, 8, 8, 8])));
+
+        assert!("10.0.0.0/33".parse::<IpSubnet>().is_err());
+        assert!("10.0.0/8".parse::<IpSubnet>().is_err());
+        assert!("10.0.0.0/x".parse::<IpSubnet>().is_err());
+    }
+
+    #[test]
+    fn subnet_contains() {
+        let subnet: IpSubnet = "192.168.0.0/16".parse().unwrap();
+        assert!(subnet.contains(&IpAddr::from([192, 168, 10, 1])));
+        assert!(!subnet.contains(&IpAddr::from([192, 169, 0, 1])));
+        assert!(subnet.contains(&"::ffff:192.168.1.1".parse().unwrap()));
+        assert!(!subnet.contains(&"2001:db8::1".parse().unwrap()));
+
+        let subnet: IpSubnet = "2001:db8::/32".parse().unwrap();
+        assert!(subnet.contains(&"2001:db8:1::1".parse().unwrap()));
+        assert!(!subnet.contains(&"2001:db9::1".parse().unwrap()));
+
+        let ip = IpAddr::from([1, 2, 3, 4]);
+        assert_eq!(IpSubnet::of(ip, 24), "1.2.3.0/24".parse().unwrap());
+        assert_eq!(IpSubnet::of(ip, 64), IpSubnet::single(ip));
+    }
+}
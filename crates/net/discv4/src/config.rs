@@ -59,6 +59,10 @@ pub struct Discv4Config {
     /// Whether to automatically lookup peers.
     pub enable_lookup: bool,
     /// Whether to enforce EIP-868 extension
+    ///
+    /// If enabled, nodes that advertise an ENR are only announced via
+    /// [`DiscoveryUpdate::Added`](crate::DiscoveryUpdate::Added) once their ENR (and its `eth`
+    /// fork id) was requested.
     pub enable_eip868: bool,
     /// Whether to respect expiration timestamps in messages
     pub enforce_expiration_timestamps: bool,
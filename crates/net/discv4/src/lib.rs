@@ -30,7 +30,7 @@ use discv5::{
     ConnectionDirection, ConnectionState,
 };
 use enr::{Enr, EnrBuilder};
-use proto::{EnrRequest, EnrResponse, EnrWrapper};
+use proto::{EnrRequest, EnrResponse};
 use reth_primitives::{
     bytes::{Bytes, BytesMut},
     ForkId, PeerId, H256,
@@ -771,12 +771,7 @@ impl Discv4Service {
                 if !old_status.is_connected() {
                     let _ = entry.update(ConnectionState::Connected, Some(old_status.direction));
                     debug!(target : "discv4",  ?record, "added after successful endpoint proof");
-                    self.notify(DiscoveryUpdate::Added(record));
-
-                    if has_enr_seq {
-                        // request the ENR of the node
-                        self.send_enr_request(record);
-                    }
+                    self.on_endpoint_proof(record, has_enr_seq);
                 }
             }
             kbucket::Entry::Pending(mut entry, mut status) => {
@@ -785,18 +780,27 @@ impl Discv4Service {
                     status.state = ConnectionState::Connected;
                     let _ = entry.update(status);
                     debug!(target : "discv4",  ?record, "added after successful endpoint proof");
-                    self.notify(DiscoveryUpdate::Added(record));
-
-                    if has_enr_seq {
-                        // request the ENR of the node
-                        self.send_enr_request(record);
-                    }
+                    self.on_endpoint_proof(record, has_enr_seq);
                 }
             }
             _ => {}
         };
     }
 
+    /// Announces a node for which the endpoint proof was established.
+    ///
+    /// If the node supports EIP-868 this requests its ENR first and defers the
+    /// [`DiscoveryUpdate::Added`] until the ENR response (and with it the node's `eth` fork id)
+    /// arrived, so that listeners can skip nodes of other networks before dialing them. If the
+    /// node doesn't respond in time it is announced without a fork id.
+    fn on_endpoint_proof(&mut self, record: NodeRecord, has_enr_seq: bool) {
+        if has_enr_seq {
+            self.send_enr_request_with(record, true);
+        } else {
+            self.notify(DiscoveryUpdate::Added(record));
+        }
+    }
+
     /// Adds all nodes
     ///
     /// See [Self::add_node]
@@ -991,7 +995,18 @@ impl Discv4Service {
     ///
     /// Returns the echo hash of the ping message.
     pub(crate) fn send_enr_request(&mut self, node: NodeRecord) {
+        self.send_enr_request_with(node, false)
+    }
+
+    /// Sends an enr request message to the node's UDP address.
+    ///
+    /// If `announce` is true, the [`DiscoveryUpdate::Added`] for the node is emitted once the
+    /// request resolved.
+    fn send_enr_request_with(&mut self, node: NodeRecord, mut announce: bool) {
         if !self.config.enable_eip868 {
+            if announce {
+                self.notify(DiscoveryUpdate::Added(node));
+            }
             return
         }
         let remote_addr = node.udp_addr();
@@ -1000,8 +1015,13 @@ impl Discv4Service {
         trace!(target : "discv4",  ?enr_request, "sending enr request");
         let echo_hash = self.send_packet(Message::EnrRequest(enr_request), remote_addr);
 
+        // don't lose a pending announcement if the request is replaced
+        if let Some(prev) = self.pending_enr_requests.get(&node.id) {
+            announce |= prev.announce;
+        }
+
         self.pending_enr_requests
-            .insert(node.id, EnrRequestState { sent_at: Instant::now(), echo_hash });
+            .insert(node.id, EnrRequestState { sent_at: Instant::now(), echo_hash, announce });
     }
 
     /// Message handler for an incoming `Pong`.
@@ -1075,31 +1095,37 @@ impl Discv4Service {
     /// Handler for incoming `EnrResponse` message
     fn on_enr_response(&mut self, msg: EnrResponse, remote_addr: SocketAddr, id: PeerId) {
         trace!(target : "discv4", ?remote_addr, ?msg, "received ENR response");
-        if let Some(resp) = self.pending_enr_requests.remove(&id) {
-            if resp.echo_hash == msg.request_hash {
-                let key = kad_key(id);
-                let fork_id = msg.eth_fork_id();
-                let (record, old_fork_id) = match self.kbuckets.entry(&key) {
-                    kbucket::Entry::Present(mut entry, _) => {
-                        let id = entry.value_mut().update_with_fork_id(fork_id);
-                        (entry.value().record, id)
-                    }
-                    kbucket::Entry::Pending(mut entry, _) => {
-                        let id = entry.value().update_with_fork_id(fork_id);
-                        (entry.value().record, id)
-                    }
-                    _ => return,
-                };
-                match (fork_id, old_fork_id) {
-                    (Some(new), Some(old)) => {
-                        if new != old {
-                            self.notify(DiscoveryUpdate::EnrForkId(record, new))
-                        }
-                    }
-                    (Some(new), None) => self.notify(DiscoveryUpdate::EnrForkId(record, new)),
-                    _ => {}
+        let resp = match self.pending_enr_requests.entry(id) {
+            Entry::Occupied(entry) if entry.get().echo_hash == msg.request_hash => entry.remove(),
+            _ => return,
+        };
+
+        let key = kad_key(id);
+        let fork_id = msg.eth_fork_id();
+        let (record, old_fork_id) = match self.kbuckets.entry(&key) {
+            kbucket::Entry::Present(mut entry, _) => {
+                let id = entry.value_mut().update_with_fork_id(fork_id);
+                (entry.value().record, id)
+            }
+            kbucket::Entry::Pending(mut entry, _) => {
+                let id = entry.value().update_with_fork_id(fork_id);
+                (entry.value().record, id)
+            }
+            _ => return,
+        };
+        match (fork_id, old_fork_id) {
+            (Some(new), Some(old)) => {
+                if new != old {
+                    self.notify(DiscoveryUpdate::EnrForkId(record, new))
                 }
             }
+            (Some(new), None) => self.notify(DiscoveryUpdate::EnrForkId(record, new)),
+            _ => {}
+        }
+
+        if resp.announce {
+            // the fork id (if any) is announced first so that listeners can filter the node
+            self.notify(DiscoveryUpdate::Added(record));
         }
     }
 
@@ -1120,7 +1146,7 @@ impl Discv4Service {
             self.send_packet(
                 Message::EnrResponse(EnrResponse {
                     request_hash,
-                    enr: EnrWrapper::new(self.local_eip_868_enr.clone()),
+                    enr: self.local_eip_868_enr.clone(),
                 }),
                 remote_addr,
             );
@@ -1235,10 +1261,27 @@ impl Discv4Service {
     }
 
     fn evict_expired_requests(&mut self, now: Instant) {
-        self.pending_enr_requests.retain(|_node_id, enr_request| {
-            now.duration_since(enr_request.sent_at) < self.config.ping_expiration
+        let mut unanswered_enr_requests = Vec::new();
+        self.pending_enr_requests.retain(|node_id, enr_request| {
+            if now.duration_since(enr_request.sent_at) < self.config.ping_expiration {
+                return true
+            }
+            if enr_request.announce {
+                unanswered_enr_requests.push(*node_id);
+            }
+            false
         });
 
+        // announce nodes that didn't respond to the ENR request without a fork id
+        for node_id in unanswered_enr_requests {
+            let record = match self.kbuckets.entry(&kad_key(node_id)) {
+                kbucket::Entry::Present(entry, _) => entry.value().record,
+                kbucket::Entry::Pending(mut entry, _) => entry.value().record,
+                _ => continue,
+            };
+            self.notify(DiscoveryUpdate::Added(record));
+        }
+
         let mut failed_pings = Vec::new();
         self.pending_pings.retain(|node_id, ping_request| {
             if now.duration_since(ping_request.sent_at) > self.config.ping_expiration {
@@ -1844,6 +1887,8 @@ struct EnrRequestState {
     sent_at: Instant,
     // Hash sent in the Ping request
     echo_hash: H256,
+    // Whether the node should be announced via `DiscoveryUpdate::Added` once the request resolved.
+    announce: bool,
 }
 
 /// Stored node info.
@@ -2300,6 +2345,54 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_added_after_enr_response() {
+        reth_tracing::init_test_tracing();
+        let fork_id = ForkId { hash: ForkHash(hex!("743f3d89")), next: 16191202 };
+
+        let config = Discv4Config::builder().external_ip_resolver(None).build();
+        let (_discv4, mut service_1) = create_discv4_with_config(config).await;
+        let config = Discv4Config::builder()
+            .external_ip_resolver(None)
+            .add_eip868_pair("eth", fork_id)
+            .build();
+        let (_discv4, mut service_2) = create_discv4_with_config(config).await;
+        let record_2 = service_2.local_node_record;
+
+        let mut updates = service_1.update_stream();
+
+        // send ping from 1 -> 2
+        service_1.add_node(record_2);
+        let event = poll_fn(|cx| service_2.poll(cx)).await;
+        assert_eq!(event, Discv4Event::Ping);
+
+        // endpoint is proven, but the node is only announced once its ENR was received
+        let event = poll_fn(|cx| service_1.poll(cx)).await;
+        assert_eq!(event, Discv4Event::Pong);
+        assert!(service_1.pending_enr_requests[&record_2.id].announce);
+
+        let _handle = service_2.spawn();
+        loop {
+            let event = poll_fn(|cx| service_1.poll(cx)).await;
+            if event == Discv4Event::EnrResponse {
+                break
+            }
+        }
+        assert!(service_1.pending_enr_requests.is_empty());
+
+        match updates.next().await.unwrap() {
+            DiscoveryUpdate::EnrForkId(record, id) => {
+                assert_eq!(record.id, record_2.id);
+                assert_eq!(id, fork_id);
+            }
+            update => unreachable!("{update:?}"),
+        }
+        match updates.next().await.unwrap() {
+            DiscoveryUpdate::Added(record) => assert_eq!(record.id, record_2.id),
+            update => unreachable!("{update:?}"),
+        }
+    }
+
     #[test]
     fn test_insert() {
         let local_node_record = rng_record(&mut rand::thread_rng());
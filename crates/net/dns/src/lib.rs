@@ -6,6 +6,9 @@
 ))]
 
 //! Implementation of [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) Node Discovery via DNS.
+//!
+//! Besides syncing trees, this also supports building and signing a tree for a set of nodes, see
+//! [`DnsTree`].
 
 pub use crate::resolver::{DnsResolver, MapResolver, Resolver};
 use crate::{
@@ -16,6 +19,7 @@ use crate::{
 pub use config::DnsDiscoveryConfig;
 use enr::Enr;
 use error::ParseDnsEntryError;
+pub use publish::DnsTree;
 use reth_primitives::{ForkId, NodeRecord, PeerId};
 use schnellru::{ByLength, LruMap};
 use secp256k1::SecretKey;
@@ -44,6 +48,7 @@ use tracing::{debug, trace, warn};
 
 mod config;
 mod error;
+pub mod publish;
 mod query;
 pub mod resolver;
 mod sync;
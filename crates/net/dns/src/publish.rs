@@ -0,0 +1,305 @@
+//! Support for publishing a node list as an [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) tree.
+//!
+//! This is the inverse of the sync process: a set of ENRs (and optionally links to other trees) is
+//! turned into a signed [`DnsTree`] whose entries can be published as DNS TXT records.
+//!
+//! Every entry is stored at the subdomain `base32(keccak256(entry)[..16])`, the signed root entry
+//! is stored at the domain of the tree itself.
+
+use crate::tree::{BranchEntry, DnsEntry, LinkEntry, NodeEntry, TreeRootEntry};
+use data_encoding::BASE32_NOPAD;
+use enr::{Enr, EnrKey};
+use reth_primitives::{bytes::Bytes, keccak256};
+use secp256k1::SecretKey;
+use std::{collections::BTreeMap, fmt, fmt::Write};
+
+/// The maximum number of children of a branch entry.
+///
+/// This ensures a branch entry fits into a single TXT string: `370 / (26 + 1)`, like geth.
+const MAX_CHILDREN: usize = 13;
+
+/// The maximum length of a single character-string in a TXT record.
+const MAX_TXT_STRING_LEN: usize = 255;
+
+/// A signed tree of node records, ready to be published.
+#[derive(Debug, Clone)]
+pub struct DnsTree {
+    /// The signed root of the tree.
+    root: TreeRootEntry,
+    /// All entries of the tree keyed by their subdomain.
+    entries: BTreeMap<String, DnsEntry<SecretKey>>,
+    /// The public key of the key that signed the root.
+    pubkey: <SecretKey as EnrKey>::PublicKey,
+}
+
+// === impl DnsTree ===
+
+impl DnsTree {
+    /// Builds a new tree from the given records and links and signs its root with the given key.
+    ///
+    /// Records are sorted by node id and links by their text representation, so the same input
+    /// always results in the same tree.
+    pub fn new(
+        records: impl IntoIterator<Item = Enr<SecretKey>>,
+        links: impl IntoIterator<Item = LinkEntry>,
+        sequence_number: u64,
+        key: &SecretKey,
+    ) -> Self {
+        let mut records = records.into_iter().collect::<Vec<_>>();
+        records.sort_by_key(|enr| enr.node_id().raw());
+        records.dedup_by_key(|enr| enr.node_id().raw());
+
+        let mut links = links.into_iter().map(|link| (link.to_string(), link)).collect::<Vec<_>>();
+        links.sort_by(|(a, _), (b, _)| a.cmp(b));
+        links.dedup_by(|(a, _), (b, _)| a == b);
+
+        let mut entries = BTreeMap::new();
+        let enr_root = build_subtree(
+            records.into_iter().map(|enr| DnsEntry::Node(NodeEntry { enr })).collect(),
+            &mut entries,
+        );
+        let link_root = build_subtree(
+            links.into_iter().map(|(_, link)| DnsEntry::Link(link)).collect(),
+            &mut entries,
+        );
+
+        let mut root =
+            TreeRootEntry { enr_root, link_root, sequence_number, signature: Bytes::new() };
+        root.sign_recoverable(key);
+
+        Self { root, entries, pubkey: key.public() }
+    }
+
+    /// Returns the signed root entry of the tree.
+    pub fn root(&self) -> &TreeRootEntry {
+        &self.root
+    }
+
+    /// Returns all entries of the tree, keyed by their subdomain.
+    pub fn entries(&self) -> &BTreeMap<String, DnsEntry<SecretKey>> {
+        &self.entries
+    }
+
+    /// Returns the link to this tree if it's published at the given domain.
+    pub fn link(&self, domain: impl Into<String>) -> LinkEntry {
+        LinkEntry { domain: domain.into(), pubkey: self.pubkey }
+    }
+
+    /// Returns all TXT records of the tree for the given domain as `(name, content)` pairs.
+    ///
+    /// The root entry is stored at the domain itself.
+    pub fn txt_records(&self, domain: &str) -> Vec<(String, String)> {
+        let domain = domain.trim_end_matches('.');
+        std::iter::once((domain.to_string(), self.root.to_string()))
+            .chain(
+                self.entries
+                    .iter()
+                    .map(|(subdomain, entry)| (format!("{subdomain}.{domain}"), entry.to_string())),
+            )
+            .collect()
+    }
+
+    /// Returns a zone file in the RFC 1035 master file format with all TXT records of the tree
+    /// for the given domain.
+    pub fn to_zone_file(&self, domain: &str, ttl: u64) -> String {
+        ZoneFile { tree: self, domain: domain.trim_end_matches('.'), ttl }.to_string()
+    }
+}
+
+/// Helper type to render a [`DnsTree`] as zone file.
+struct ZoneFile<'a> {
+    tree: &'a DnsTree,
+    domain: &'a str,
+    ttl: u64,
+}
+
+impl fmt::Display for ZoneFile<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "$ORIGIN {}.", self.domain)?;
+        writeln!(f, "$TTL {}", self.ttl)?;
+
+        let width = self.tree.entries.keys().map(String::len).max().unwrap_or_default();
+        writeln!(f, "{:width$} IN TXT {}", "@", quote_txt(&self.tree.root.to_string()))?;
+        for (subdomain, entry) in self.tree.entries.iter() {
+            writeln!(f, "{subdomain:width$} IN TXT {}", quote_txt(&entry.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes the content of a TXT record, splitting it into multiple character-strings if it exceeds
+/// the maximum length of a single one.
+fn quote_txt(content: &str) -> String {
+    // entries only consist of ASCII characters, so splitting at arbitrary byte offsets is fine
+    let mut quoted = String::with_capacity(content.len() + 4);
+    for (idx, chunk) in content.as_bytes().chunks(MAX_TXT_STRING_LEN).enumerate() {
+        if idx > 0 {
+            quoted.push(' ');
+        }
+        let _ = write!(quoted, "\"{}\"", String::from_utf8_lossy(chunk));
+    }
+    quoted
+}
+
+/// Returns the subdomain of the entry: `base32(keccak256(entry)[..16])`
+pub fn subdomain(entry: &impl fmt::Display) -> String {
+    let hash = keccak256(entry.to_string().as_bytes());
+    BASE32_NOPAD.encode(&hash.as_bytes()[..16])
+}
+
+/// Builds the subtree for the given entries and returns the subdomain of its root.
+fn build_subtree(
+    entries: Vec<DnsEntry<SecretKey>>,
+    tree: &mut BTreeMap<String, DnsEntry<SecretKey>>,
+) -> String {
+    let root = build(entries, tree);
+    let hash = subdomain(&root);
+    tree.insert(hash.clone(), root);
+    hash
+}
+
+/// Recursively builds the tree for the given entries and returns its root entry.
+///
+/// All entries below the root are inserted into the `tree`.
+fn build(
+    mut entries: Vec<DnsEntry<SecretKey>>,
+    tree: &mut BTreeMap<String, DnsEntry<SecretKey>>,
+) -> DnsEntry<SecretKey> {
+    if entries.len() == 1 {
+        return entries.pop().expect("exists; qed")
+    }
+
+    if entries.len() <= MAX_CHILDREN {
+        let children = entries
+            .into_iter()
+            .map(|entry| {
+                let hash = subdomain(&entry);
+                tree.insert(hash.clone(), entry);
+                hash
+            })
+            .collect();
+        return DnsEntry::Branch(BranchEntry { children })
+    }
+
+    let mut subtrees = Vec::with_capacity(entries.len() / MAX_CHILDREN + 1);
+    while !entries.is_empty() {
+        let rem = entries.split_off(MAX_CHILDREN.min(entries.len()));
+        subtrees.push(build(entries, tree));
+        entries = rem;
+    }
+    build(subtrees, tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DnsDiscoveryEvent, DnsDiscoveryService, MapResolver};
+    use enr::EnrBuilder;
+    use secp256k1::rand::thread_rng;
+    use std::{collections::HashSet, net::Ipv4Addr, sync::Arc};
+    use tokio_stream::StreamExt;
+
+    fn rng_enr() -> Enr<SecretKey> {
+        let key = SecretKey::new(&mut thread_rng());
+        let mut builder = EnrBuilder::new("v4");
+        builder.ip(Ipv4Addr::LOCALHOST.into()).tcp4(30303).udp4(30303);
+        builder.build(&key).unwrap()
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let key = SecretKey::new(&mut thread_rng());
+        let records = std::iter::repeat_with(rng_enr).take(100).collect::<Vec<_>>();
+        let link: LinkEntry =
+            "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.org"
+                .parse()
+                .unwrap();
+
+        let tree = DnsTree::new(records.clone(), vec![link.clone()], 1, &key);
+        assert!(tree.root().verify::<SecretKey>(&key.public()));
+        assert_eq!(tree.root().signature.len(), 65);
+
+        // all entries are stored at their hash
+        for (hash, entry) in tree.entries() {
+            assert_eq!(hash, &subdomain(entry));
+            let s = entry.to_string();
+            assert!(s.len() <= 370 || matches!(entry, DnsEntry::Node(_)), "{s}");
+        }
+
+        // all records are reachable
+        let num_nodes =
+            tree.entries().values().filter(|entry| matches!(entry, DnsEntry::Node(_))).count();
+        assert_eq!(num_nodes, records.len());
+
+        // the link tree consists of the link only
+        match &tree.entries()[&tree.root().link_root] {
+            DnsEntry::Link(entry) => assert_eq!(entry, &link),
+            _ => unreachable!(),
+        }
+
+        // the same input results in the same tree
+        let mut reversed = records;
+        reversed.reverse();
+        let other = DnsTree::new(reversed, vec![link], 1, &key);
+        assert_eq!(tree.root().enr_root, other.root().enr_root);
+        assert_eq!(tree.txt_records("nodes.example.org"), other.txt_records("nodes.example.org"));
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let key = SecretKey::new(&mut thread_rng());
+        let tree = DnsTree::new(vec![], vec![], 1, &key);
+        assert_eq!(tree.entries().len(), 1);
+        let entry = &tree.entries()[&tree.root().enr_root];
+        assert_eq!(entry.to_string(), "enrtree-branch:");
+        assert!(entry.to_string().parse::<BranchEntry>().unwrap().children.is_empty());
+    }
+
+    #[test]
+    fn test_zone_file() {
+        let key = SecretKey::new(&mut thread_rng());
+        let record = rng_enr();
+        let tree = DnsTree::new(vec![record.clone()], vec![], 7, &key);
+
+        let zone = tree.to_zone_file("nodes.example.org.", 3600);
+        let mut lines = zone.lines();
+        assert_eq!(lines.next(), Some("$ORIGIN nodes.example.org."));
+        assert_eq!(lines.next(), Some("$TTL 3600"));
+        assert!(lines.next().unwrap().ends_with(&format!("IN TXT \"{}\"", tree.root())));
+        assert_eq!(lines.count(), tree.entries().len());
+
+        assert_eq!(
+            quote_txt(&"a".repeat(300)),
+            format!("\"{}\" \"{}\"", "a".repeat(255), "a".repeat(45))
+        );
+        assert!(zone.contains(&quote_txt(&record.to_base64())));
+    }
+
+    #[tokio::test]
+    async fn test_sync_published_tree() {
+        reth_tracing::init_test_tracing();
+
+        let key = SecretKey::new(&mut thread_rng());
+        let records = std::iter::repeat_with(rng_enr).take(20).collect::<Vec<_>>();
+        let tree = DnsTree::new(records.clone(), vec![], 1, &key);
+
+        let domain = "nodes.example.org";
+        let resolver = MapResolver::default();
+        for (name, content) in tree.txt_records(domain) {
+            resolver.insert(name, content);
+        }
+
+        let mut service = DnsDiscoveryService::new(Arc::new(resolver), Default::default());
+        service.sync_tree_with_link(tree.link(domain));
+
+        let mut discovered = HashSet::new();
+        while discovered.len() < records.len() {
+            match service.next().await.unwrap() {
+                DnsDiscoveryEvent::Enr(enr) => {
+                    discovered.insert(enr.node_id().raw());
+                }
+            }
+        }
+        assert!(records.iter().all(|record| discovered.contains(&record.node_id().raw())));
+    }
+}
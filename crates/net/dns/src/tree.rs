@@ -25,8 +25,8 @@ use crate::error::{
 };
 use data_encoding::{BASE32_NOPAD, BASE64URL_NOPAD};
 use enr::{Enr, EnrError, EnrKey, EnrKeyUnambiguous, EnrPublicKey};
-use reth_primitives::{bytes::Bytes, hex};
-use secp256k1::SecretKey;
+use reth_primitives::{bytes::Bytes, hex, keccak256};
+use secp256k1::{Message, SecretKey, SECP256K1};
 use std::{
     fmt,
     hash::{Hash, Hasher},
@@ -133,6 +133,20 @@ impl TreeRootEntry {
         Ok(())
     }
 
+    /// Signs the content with the given secp256k1 key.
+    ///
+    /// Unlike [`Self::sign`] this produces the 65-byte `[R || S || V]` signature (including the
+    /// recovery id) other EIP-1459 implementations expect.
+    pub fn sign_recoverable(&mut self, key: &SecretKey) {
+        let msg = Message::from_slice(keccak256(self.content().as_bytes()).as_bytes())
+            .expect("hash is 32 bytes; qed");
+        let (rec_id, sig) = SECP256K1.sign_ecdsa_recoverable(&msg, key).serialize_compact();
+        let mut signature = Vec::with_capacity(65);
+        signature.extend_from_slice(&sig);
+        signature.push(rec_id.to_i32() as u8);
+        self.signature = signature.into();
+    }
+
     /// Verify the signature of the record.
     #[must_use]
     pub fn verify<K: EnrKey>(&self, pubkey: &K::PublicKey) -> bool {
@@ -201,8 +215,14 @@ impl BranchEntry {
             Ok(hash.to_string())
         }
 
+        let input = input.trim();
+        if input.is_empty() {
+            // an empty branch, used for empty trees
+            return Ok(Self { children: Vec::new() })
+        }
+
         let children =
-            input.trim().split(',').map(ensure_valid_hash).collect::<ParseEntryResult<Vec<_>>>()?;
+            input.split(',').map(ensure_valid_hash).collect::<ParseEntryResult<Vec<_>>>()?;
         Ok(Self { children })
     }
 }
@@ -362,7 +382,6 @@ fn ensure_strip_key(input: &mut &str, key: &str, err: &'static str) -> ParseEntr
 #[cfg(test)]
 mod tests {
     use super::*;
-    use secp256k1::SecretKey;
 
     #[test]
     fn parse_root_entry() {
@@ -405,6 +424,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_empty_branch_entry() {
+        let s = "enrtree-branch:";
+        let entry: BranchEntry = s.parse().unwrap();
+        assert!(entry.children.is_empty());
+        assert_eq!(entry.to_string(), s);
+    }
+
+    #[test]
+    fn sign_recoverable_root_entry() {
+        let secret_key = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let s = "enrtree-root:v1 e=QFT4PBCRX4XQCV3VUYJ6BTCEPU l=JGUFMSAGI7KZYB3P7IZW4S5Y3A seq=3 sig=3FmXuVwpa8Y7OstZTx9PIb1mt8FrW7VpDOFv4AaGCsZ2EIHmhraWhe4NxYhQDlw5MjeFXYMbJjsPeKlHzmJREQE";
+        let mut root: TreeRootEntry = s.parse().unwrap();
+        root.sign_recoverable(&secret_key);
+        assert_eq!(root.signature.len(), 65);
+        assert!(root.verify::<SecretKey>(&secret_key.public()));
+
+        let parsed: TreeRootEntry = root.to_string().parse().unwrap();
+        assert_eq!(parsed, root);
+    }
+
     #[test]
     fn parse_invalid_branch_entry() {
         let s = "enrtree-branch:1,2";
@@ -8,7 +8,7 @@ use reth_interfaces::{
         headers::client::{HeadersClient, HeadersFut, HeadersRequest},
         priority::Priority,
     },
-    sync::{SyncState, SyncStateProvider, SyncStateUpdater},
+    sync::{StageSyncProgress, SyncState, SyncStateProvider, SyncStateUpdater},
 };
 use reth_primitives::{
     Block, BlockBody, BlockHash, BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId,
@@ -235,6 +235,8 @@ impl SyncStateUpdater for FileClient {
         let is_syncing = state.is_syncing();
         self.is_syncing.store(is_syncing, Ordering::Relaxed)
     }
+
+    fn update_stage_progress(&self, _progress: StageSyncProgress) {}
 }
 
 #[cfg(test)]
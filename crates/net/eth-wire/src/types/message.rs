@@ -295,7 +295,7 @@ impl Encodable for EthBroadcastMessage {
 
 /// Represents message IDs for eth protocol messages.
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EthMessageID {
     Status = 0x00,
@@ -315,6 +315,13 @@ pub enum EthMessageID {
     Receipts = 0x10,
 }
 
+impl EthMessageID {
+    /// Returns the max value.
+    pub const fn max() -> u8 {
+        EthMessageID::Receipts as u8
+    }
+}
+
 impl Encodable for EthMessageID {
     fn encode(&self, out: &mut dyn BufMut) {
         out.put_u8(*self as u8);
@@ -42,6 +42,7 @@ mod test {
                 tx_type: TxType::EIP1559,
                 success: false,
                 cumulative_gas_used: 0,
+                state_root: None,
                 logs: vec![],
             },
             bloom: Default::default(),
@@ -101,6 +102,7 @@ mod test {
                     ReceiptWithBloom {
                         receipt: Receipt {tx_type: TxType::Legacy,
                         cumulative_gas_used: 0x1u64,
+                        state_root: None,
                         logs: vec![
                             Log {
                                 address: hex!("0000000000000000000000000000000000000011").into(),
@@ -136,6 +138,7 @@ mod test {
                             receipt: Receipt {
                                 tx_type: TxType::Legacy,
                                 cumulative_gas_used: 0x1u64,
+                                state_root: None,
                                 logs: vec![
                                     Log {
                                         address: hex!("0000000000000000000000000000000000000011").into(),
@@ -18,12 +18,18 @@ use std::net::SocketAddr;
 use serde::{Deserialize, Serialize};
 
 pub use error::NetworkError;
+pub use messages::{MessageCount, MessageStats, SessionMessages};
 pub use reputation::{Reputation, ReputationChangeKind};
+pub use sync::StageSyncProgress;
 
 /// Network Error
 pub mod error;
+/// Message accounting of sessions
+pub mod messages;
 /// Reputation score
 pub mod reputation;
+/// Sync progress of the pipeline
+pub mod sync;
 
 #[cfg(feature = "test-utils")]
 /// Implementation of network traits for testing purposes.
@@ -43,6 +49,12 @@ pub trait NetworkInfo: Send + Sync {
 
     /// Returns `true` if the network is undergoing sync.
     fn is_syncing(&self) -> bool;
+
+    /// Returns the sync progress of the pipeline stages, empty if the node is not syncing.
+    fn sync_progress(&self) -> Vec<StageSyncProgress>;
+
+    /// Returns the message statistics of all active sessions.
+    async fn session_messages(&self) -> Result<Vec<SessionMessages>, NetworkError>;
 }
 
 /// Provides general purpose information about Peers in the network.
@@ -0,0 +1,70 @@
+use reth_eth_wire::EthMessageID;
+use reth_primitives::PeerId;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Message statistics of a single active session.
+///
+/// Sizes are payload bytes, the length of the RLP encoding of the messages. They do not include
+/// the message id, compression or the framing of the encrypted transport, so they differ from the
+/// bytes sent over the wire, which are measured by the network's `BandwidthMeter`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SessionMessages {
+    /// The peer of the session.
+    pub peer_id: PeerId,
+    /// Messages received from the peer.
+    pub inbound: MessageStats,
+    /// Messages sent to the peer.
+    pub outbound: MessageStats,
+}
+
+// === impl SessionMessages ===
+
+impl SessionMessages {
+    /// Returns the total number of payload bytes exchanged with the peer.
+    pub fn total_payload_bytes(&self) -> u64 {
+        self.inbound.total_payload_bytes() + self.outbound.total_payload_bytes()
+    }
+}
+
+/// Number and size of the messages exchanged in one direction, per message kind.
+///
+/// The size of a message is the length of its RLP encoding, before compression and framing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MessageStats {
+    /// Statistics of all message kinds that were exchanged at least once.
+    pub messages: BTreeMap<EthMessageID, MessageCount>,
+}
+
+// === impl MessageStats ===
+
+impl MessageStats {
+    /// Returns the statistics for the given message kind.
+    pub fn get(&self, id: EthMessageID) -> MessageCount {
+        self.messages.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Returns the total number of messages.
+    pub fn total_messages(&self) -> u64 {
+        self.messages.values().map(|count| count.messages).sum()
+    }
+
+    /// Returns the total number of payload bytes of all messages.
+    pub fn total_payload_bytes(&self) -> u64 {
+        self.messages.values().map(|count| count.payload_bytes).sum()
+    }
+}
+
+/// Number and accumulated payload size of messages of a single kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MessageCount {
+    /// Number of messages.
+    pub messages: u64,
+    /// Accumulated length of the RLP encoding of the messages.
+    pub payload_bytes: u64,
+}
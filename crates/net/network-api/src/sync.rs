@@ -0,0 +1,27 @@
+use reth_primitives::BlockNumber;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Sync progress of a single stage of the pipeline.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StageSyncProgress {
+    /// The name of the stage.
+    pub stage: String,
+    /// The highest block the stage has fully processed.
+    pub checkpoint: BlockNumber,
+    /// The block the stage is syncing to, if known.
+    pub target: Option<BlockNumber>,
+    /// The number of entities the stage has processed, if it tracks them.
+    pub entities_processed: Option<u64>,
+    /// The total number of entities the stage has to process, if known.
+    pub entities_total: Option<u64>,
+    /// Processed blocks per second.
+    pub blocks_per_second: f64,
+    /// Processed entities per second, if the stage tracks entities.
+    pub entities_per_second: Option<f64>,
+    /// The estimated time until the stage reaches its target.
+    pub eta: Option<Duration>,
+}
@@ -1,6 +1,6 @@
 use crate::{
     EthProtocolInfo, NetworkError, NetworkInfo, NetworkStatus, PeerKind, Peers, PeersInfo,
-    ReputationChangeKind,
+    ReputationChangeKind, SessionMessages, StageSyncProgress,
 };
 use async_trait::async_trait;
 use reth_eth_wire::{DisconnectReason, ProtocolVersion};
@@ -39,6 +39,14 @@ impl NetworkInfo for NoopNetwork {
     fn is_syncing(&self) -> bool {
         false
     }
+
+    fn sync_progress(&self) -> Vec<StageSyncProgress> {
+        Vec::new()
+    }
+
+    async fn session_messages(&self) -> Result<Vec<SessionMessages>, NetworkError> {
+        Ok(Vec::new())
+    }
 }
 
 impl PeersInfo for NoopNetwork {
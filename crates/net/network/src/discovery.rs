@@ -146,7 +146,13 @@ impl Discovery {
                 self.on_node_record_update(record, None);
             }
             DiscoveryUpdate::EnrForkId(node, fork_id) => {
-                self.queued_events.push_back(DiscoveryEvent::EnrForkId(node.id, fork_id))
+                if self.discovered_nodes.contains_key(&node.id) {
+                    self.queued_events.push_back(DiscoveryEvent::EnrForkId(node.id, fork_id))
+                } else {
+                    // discv4 announces the fork id of a new node before the node itself, so the
+                    // node can be checked against our fork filter before it's dialed
+                    self.on_node_record_update(node, Some(fork_id));
+                }
             }
             DiscoveryUpdate::Removed(node) => {
                 self.discovered_nodes.remove(&node);
@@ -226,6 +232,7 @@ pub enum DiscoveryEvent {
 mod tests {
     use super::*;
     use rand::thread_rng;
+    use reth_primitives::ForkHash;
     use secp256k1::SECP256K1;
     use std::net::{Ipv4Addr, SocketAddrV4};
 
@@ -239,4 +246,28 @@ mod tests {
                 .await
                 .unwrap();
     }
+
+    #[test]
+    fn test_enr_fork_id_before_added() {
+        let mut discovery = Discovery::noop();
+        let fork_id = ForkId { hash: ForkHash([1, 2, 3, 4]), next: 0 };
+        let record = NodeRecord {
+            address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            tcp_port: 30303,
+            udp_port: 30303,
+            id: PeerId::random(),
+        };
+
+        discovery.on_discv4_update(DiscoveryUpdate::EnrForkId(record, fork_id));
+        discovery.on_discv4_update(DiscoveryUpdate::Added(record));
+
+        assert_eq!(discovery.queued_events.len(), 1);
+        match discovery.queued_events.pop_front().unwrap() {
+            DiscoveryEvent::Discovered { peer_id, fork_id: id, .. } => {
+                assert_eq!(peer_id, record.id);
+                assert_eq!(id, Some(fork_id));
+            }
+            DiscoveryEvent::EnrForkId(..) => unreachable!(),
+        }
+    }
 }
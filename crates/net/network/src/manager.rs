@@ -548,6 +548,9 @@ where
             NetworkHandleMessage::GetPeerInfoById(peer_id, tx) => {
                 let _ = tx.send(self.swarm.sessions_mut().get_peer_info_by_id(peer_id));
             }
+            NetworkHandleMessage::GetSessionMessages(tx) => {
+                let _ = tx.send(self.swarm.sessions().session_messages());
+            }
         }
     }
 }
@@ -631,6 +634,7 @@ where
                         SwarmEvent::SessionEstablished {
                             peer_id,
                             remote_addr,
+                            client_version,
                             capabilities,
                             version,
                             messages,
@@ -654,13 +658,22 @@ where
                                     .peers_mut()
                                     .on_active_inbound_session(peer_id, remote_addr);
                             }
-                            this.event_listeners.send(NetworkEvent::SessionEstablished {
-                                peer_id,
-                                capabilities,
-                                version,
-                                status,
-                                messages,
-                            });
+                            // peers with a rejected client version are disconnected right away, so
+                            // listeners are not notified about their session
+                            if this
+                                .swarm
+                                .state_mut()
+                                .peers_mut()
+                                .on_session_client_version(peer_id, &client_version)
+                            {
+                                this.event_listeners.send(NetworkEvent::SessionEstablished {
+                                    peer_id,
+                                    capabilities,
+                                    version,
+                                    status,
+                                    messages,
+                                });
+                            }
                         }
                         SwarmEvent::PeerAdded(peer_id) => {
                             trace!(target: "net", ?peer_id, "Peer added");
@@ -735,7 +748,7 @@ where
                                 this.swarm
                                     .state_mut()
                                     .peers_mut()
-                                    .on_incoming_pending_session_gracefully_closed();
+                                    .on_incoming_pending_session_gracefully_closed(remote_addr);
                             }
                             this.metrics.closed_sessions.increment(1);
                             this.metrics
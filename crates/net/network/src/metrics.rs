@@ -33,10 +33,27 @@ pub struct NetworkMetrics {
     pub(crate) invalid_messages_received: Counter,
 }
 
+/// Metrics for the eth messages exchanged over all sessions, labeled by direction and message kind
+#[derive(Metrics)]
+#[metrics(scope = "network")]
+pub struct EthMessageMetrics {
+    /// Total number of eth messages
+    pub(crate) eth_messages: Counter,
+
+    /// Total payload size of eth messages in bytes, before compression and framing
+    pub(crate) eth_message_payload_bytes: Counter,
+}
+
 /// Metrics for the TransactionsManager
 #[derive(Metrics)]
 #[metrics(scope = "network")]
 pub struct TransactionsManagerMetrics {
     /// Total number of propagated transactions
     pub(crate) propagated_transactions: Counter,
+
+    /// Number of inflight requests for announced transactions
+    pub(crate) inflight_transaction_requests: Gauge,
+
+    /// Number of announced transactions that are not fetched yet
+    pub(crate) unknown_announced_transactions: Gauge,
 }
@@ -12,6 +12,7 @@ use reth_interfaces::{
 use reth_net_common::bandwidth_meter::BandwidthMeter;
 use reth_network_api::{
     NetworkError, NetworkInfo, NetworkStatus, PeerKind, Peers, PeersInfo, ReputationChangeKind,
+    SessionMessages, StageSyncProgress,
 };
 use reth_primitives::{Head, NodeRecord, PeerId, TransactionSigned, H256};
 use std::{
@@ -57,6 +58,7 @@ impl NetworkHandle {
             network_mode,
             bandwidth_meter,
             is_syncing: Arc::new(Default::default()),
+            sync_progress: Default::default(),
             chain_id,
         };
         Self { inner: Arc::new(inner) }
@@ -233,6 +235,16 @@ impl NetworkInfo for NetworkHandle {
     fn is_syncing(&self) -> bool {
         SyncStateProvider::is_syncing(self)
     }
+
+    fn sync_progress(&self) -> Vec<StageSyncProgress> {
+        self.inner.sync_progress.lock().clone()
+    }
+
+    async fn session_messages(&self) -> Result<Vec<SessionMessages>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetSessionMessages(tx));
+        rx.await.map_err(Into::into)
+    }
 }
 
 impl StatusUpdater for NetworkHandle {
@@ -251,7 +263,18 @@ impl SyncStateProvider for NetworkHandle {
 impl SyncStateUpdater for NetworkHandle {
     fn update_sync_state(&self, state: SyncState) {
         let is_syncing = state.is_syncing();
-        self.inner.is_syncing.store(is_syncing, Ordering::Relaxed)
+        self.inner.is_syncing.store(is_syncing, Ordering::Relaxed);
+        if !is_syncing {
+            self.inner.sync_progress.lock().clear();
+        }
+    }
+
+    fn update_stage_progress(&self, progress: StageSyncProgress) {
+        let mut sync_progress = self.inner.sync_progress.lock();
+        match sync_progress.iter_mut().find(|stage| stage.stage == progress.stage) {
+            Some(stage) => *stage = progress,
+            None => sync_progress.push(progress),
+        }
     }
 }
 
@@ -273,6 +296,8 @@ struct NetworkInner {
     bandwidth_meter: BandwidthMeter,
     /// Represents if the network is currently syncing.
     is_syncing: Arc<AtomicBool>,
+    /// The latest sync progress of each pipeline stage, in the order the stages reported it.
+    sync_progress: Mutex<Vec<StageSyncProgress>>,
     /// The chain id
     chain_id: Arc<AtomicU64>,
 }
@@ -313,6 +338,8 @@ pub(crate) enum NetworkHandleMessage {
     GetPeerInfo(oneshot::Sender<Vec<PeerInfo>>),
     /// Get PeerInfo for a specific peer
     GetPeerInfoById(PeerId, oneshot::Sender<Option<PeerInfo>>),
+    /// Get the message statistics of all active sessions
+    GetSessionMessages(oneshot::Sender<Vec<SessionMessages>>),
     /// Gracefully shutdown network
     Shutdown(oneshot::Sender<()>),
 }
@@ -0,0 +1,185 @@
+//! Rules to restrict which peers we stay connected to.
+
+use reth_net_common::subnet::IpSubnet;
+use std::{convert::Infallible, fmt, net::IpAddr, str::FromStr};
+
+/// Rules for the client version a peer announces in its `Hello` message.
+///
+/// These are checked once the `Hello` handshake of a new session completed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ClientVersionFilter {
+    /// If not empty, only peers whose client version matches any of these patterns are accepted.
+    pub allowed: Vec<ClientVersionPattern>,
+    /// Peers whose client version matches any of these patterns are rejected.
+    ///
+    /// This takes precedence over `allowed`.
+    pub denied: Vec<ClientVersionPattern>,
+}
+
+// === impl ClientVersionFilter ===
+
+impl ClientVersionFilter {
+    /// Only accepts peers whose client version matches any of the given patterns.
+    pub fn with_allowed(
+        mut self,
+        patterns: impl IntoIterator<Item = ClientVersionPattern>,
+    ) -> Self {
+        self.allowed.extend(patterns);
+        self
+    }
+
+    /// Rejects peers whose client version matches any of the given patterns.
+    pub fn with_denied(mut self, patterns: impl IntoIterator<Item = ClientVersionPattern>) -> Self {
+        self.denied.extend(patterns);
+        self
+    }
+
+    /// Returns true if a peer with the given client version should be accepted.
+    pub fn is_allowed(&self, client_version: &str) -> bool {
+        if self.denied.iter().any(|pattern| pattern.matches(client_version)) {
+            return false
+        }
+        self.allowed.is_empty() ||
+            self.allowed.iter().any(|pattern| pattern.matches(client_version))
+    }
+}
+
+/// A case-insensitive pattern for client versions, e.g. `Geth/v1.10*`.
+///
+/// `*` matches any sequence of characters, all other characters match themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ClientVersionPattern(String);
+
+// === impl ClientVersionPattern ===
+
+impl ClientVersionPattern {
+    /// Creates a new pattern.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self(pattern.into())
+    }
+
+    /// Returns true if the client version matches the pattern.
+    pub fn matches(&self, client_version: &str) -> bool {
+        let pattern = self.0.to_ascii_lowercase();
+        let client_version = client_version.to_ascii_lowercase();
+
+        let mut parts = pattern.split('*').collect::<Vec<_>>();
+        let first = parts.remove(0);
+        let Some(mut rest) = client_version.strip_prefix(first) else { return false };
+        // without any wildcard the pattern must match exactly
+        let Some(last) = parts.pop() else { return rest.is_empty() };
+
+        for part in parts {
+            match rest.find(part) {
+                Some(idx) => rest = &rest[idx + part.len()..],
+                None => return false,
+            }
+        }
+        rest.ends_with(last)
+    }
+}
+
+impl fmt::Display for ClientVersionPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for ClientVersionPattern {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s))
+    }
+}
+
+impl From<&str> for ClientVersionPattern {
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+/// Limits the number of inbound connections from the same subnet.
+///
+/// This makes it harder to occupy all inbound slots with nodes controlled by a single party.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubnetLimit {
+    /// Maximum number of pending and active inbound connections per subnet.
+    pub max_inbound: usize,
+    /// The prefix length of IPv4 subnets.
+    pub ipv4_prefix_len: u8,
+    /// The prefix length of IPv6 subnets.
+    pub ipv6_prefix_len: u8,
+}
+
+// === impl SubnetLimit ===
+
+impl SubnetLimit {
+    /// Creates a new limit for `/24` IPv4 and `/64` IPv6 subnets.
+    pub fn new(max_inbound: usize) -> Self {
+        Self { max_inbound, ipv4_prefix_len: 24, ipv6_prefix_len: 64 }
+    }
+
+    /// Returns the subnet the IP is accounted to.
+    pub fn subnet(&self, ip: IpAddr) -> IpSubnet {
+        match IpSubnet::single(ip).addr() {
+            ip @ IpAddr::V4(_) => IpSubnet::of(ip, self.ipv4_prefix_len),
+            ip @ IpAddr::V6(_) => IpSubnet::of(ip, self.ipv6_prefix_len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_client_version() {
+        let pattern = ClientVersionPattern::new("Geth/*");
+        assert!(pattern.matches("Geth/v1.11.5-stable/linux-amd64/go1.20.2"));
+        assert!(pattern.matches("geth/"));
+        assert!(!pattern.matches("erigon/v2.42.0/linux-amd64/go1.20.2"));
+
+        let pattern = ClientVersionPattern::new("*/v1.10.*/*");
+        assert!(pattern.matches("Geth/v1.10.26-stable/linux-amd64/go1.19.4"));
+        assert!(!pattern.matches("Geth/v1.11.5-stable/linux-amd64/go1.20.2"));
+
+        let pattern = ClientVersionPattern::new("reth");
+        assert!(pattern.matches("Reth"));
+        assert!(!pattern.matches("reth/v0.1.0"));
+
+        let pattern = ClientVersionPattern::new("a*a");
+        assert!(!pattern.matches("a"));
+        assert!(pattern.matches("aa"));
+    }
+
+    #[test]
+    fn filter_client_versions() {
+        let filter = ClientVersionFilter::default();
+        assert!(filter.is_allowed("anything"));
+
+        let filter = ClientVersionFilter::default()
+            .with_allowed(["geth/*".into(), "reth/*".into()])
+            .with_denied(["geth/v1.9*".into()]);
+        assert!(filter.is_allowed("Geth/v1.11.5-stable"));
+        assert!(filter.is_allowed("reth/v0.1.0"));
+        assert!(!filter.is_allowed("Geth/v1.9.25-stable"));
+        assert!(!filter.is_allowed("erigon/v2.42.0"));
+    }
+
+    #[test]
+    fn subnet_of_ip() {
+        let limit = SubnetLimit::new(2);
+        assert_eq!(limit.subnet(IpAddr::from([1, 2, 3, 4])), "1.2.3.0/24".parse().unwrap());
+        assert_eq!(
+            limit.subnet("2001:db8:1:2:3::1".parse().unwrap()),
+            "2001:db8:1:2::/64".parse().unwrap()
+        );
+        assert_eq!(limit.subnet("::ffff:1.2.3.4".parse().unwrap()), "1.2.3.0/24".parse().unwrap());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     error::{BackoffKind, SessionError},
     peers::{
+        filter::{ClientVersionFilter, SubnetLimit},
         reputation::{is_banned_reputation, BACKOFF_REPUTATION_CHANGE, DEFAULT_REPUTATION},
         ReputationChangeWeights, DEFAULT_MAX_PEERS_INBOUND, DEFAULT_MAX_PEERS_OUTBOUND,
     },
@@ -8,7 +9,7 @@ use crate::{
 };
 use futures::StreamExt;
 use reth_eth_wire::{errors::EthStreamError, DisconnectReason};
-use reth_net_common::ban_list::BanList;
+use reth_net_common::{ban_list::BanList, subnet::IpSubnet};
 use reth_network_api::{PeerKind, ReputationChangeKind};
 use reth_primitives::{ForkId, NodeRecord, PeerId};
 use std::{
@@ -72,6 +73,45 @@ impl PeersHandle {
 
         rx.await.unwrap_or_default()
     }
+
+    /// Bans all IPs of the subnet.
+    ///
+    /// Connected peers in the subnet are disconnected.
+    pub fn ban_subnet(&self, subnet: IpSubnet) {
+        self.send(PeerCommand::BanSubnet(subnet));
+    }
+
+    /// Unbans the subnet.
+    pub fn unban_subnet(&self, subnet: IpSubnet) {
+        self.send(PeerCommand::UnbanSubnet(subnet));
+    }
+
+    /// Adds the subnet to the allowed subnets.
+    ///
+    /// Once any subnet is allowed, peers outside of all allowed subnets are disconnected and no
+    /// longer accepted.
+    pub fn allow_subnet(&self, subnet: IpSubnet) {
+        self.send(PeerCommand::AllowSubnet(subnet));
+    }
+
+    /// Removes the subnet from the allowed subnets.
+    pub fn disallow_subnet(&self, subnet: IpSubnet) {
+        self.send(PeerCommand::DisallowSubnet(subnet));
+    }
+
+    /// Replaces the rules for the client versions of peers.
+    ///
+    /// Connected peers that are no longer accepted are disconnected.
+    pub fn set_client_version_filter(&self, filter: ClientVersionFilter) {
+        self.send(PeerCommand::SetClientVersionFilter(filter));
+    }
+
+    /// Sets or removes the limit of inbound connections per subnet.
+    ///
+    /// This only applies to new inbound connections.
+    pub fn set_inbound_subnet_limit(&self, limit: Option<SubnetLimit>) {
+        self.send(PeerCommand::SetInboundSubnetLimit(limit));
+    }
 }
 
 /// Maintains the state of _all_ the peers known to the network.
@@ -106,6 +146,10 @@ pub(crate) struct PeersManager {
     backoff_durations: PeerBackoffDurations,
     /// If non-trusted peers should be connected to
     connect_trusted_nodes_only: bool,
+    /// Rules for the client versions of non-trusted peers.
+    client_version_filter: ClientVersionFilter,
+    /// Tracks inbound connections per subnet.
+    inbound_subnets: InboundSubnets,
 }
 
 impl PeersManager {
@@ -121,8 +165,14 @@ impl PeersManager {
             trusted_nodes,
             connect_trusted_nodes_only,
             basic_nodes,
+            banned_subnets,
+            allowed_subnets,
+            client_version_filter,
+            inbound_subnet_limit,
             ..
         } = config;
+        let ban_list =
+            ban_list.with_banned_subnets(banned_subnets).with_allowed_subnets(allowed_subnets);
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
         let now = Instant::now();
 
@@ -155,6 +205,8 @@ impl PeersManager {
             ban_duration,
             backoff_durations,
             connect_trusted_nodes_only,
+            client_version_filter,
+            inbound_subnets: InboundSubnets::new(inbound_subnet_limit),
         }
     }
 
@@ -177,7 +229,8 @@ impl PeersManager {
     /// Invoked when a new _incoming_ tcp connection is accepted.
     ///
     /// returns an error if the inbound ip address is on the ban list or
-    /// we have reached our limit for max inbound connections
+    /// we have reached our limit for max inbound connections, either in total or for the subnet of
+    /// the ip address.
     pub(crate) fn on_incoming_pending_session(
         &mut self,
         addr: IpAddr,
@@ -188,20 +241,32 @@ impl PeersManager {
         if !self.connection_info.has_in_capacity() {
             return Err(InboundConnectionError::ExceedsLimit(self.connection_info.max_inbound))
         }
+        if let Some(subnet) = self.inbound_subnets.exceeded_subnet(&addr) {
+            return Err(InboundConnectionError::ExceedsSubnetLimit(subnet))
+        }
         // keep track of new connection
         self.connection_info.inc_in();
+        self.inbound_subnets.inc(addr);
         Ok(())
     }
 
     /// Invoked when a previous call to [Self::on_incoming_pending_session] succeeded but it was
     /// rejected.
-    pub(crate) fn on_incoming_pending_session_rejected_internally(&mut self) {
+    pub(crate) fn on_incoming_pending_session_rejected_internally(
+        &mut self,
+        remote_addr: SocketAddr,
+    ) {
         self.connection_info.decr_in();
+        self.inbound_subnets.decr(&remote_addr.ip());
     }
 
     /// Invoked when a pending session was closed.
-    pub(crate) fn on_incoming_pending_session_gracefully_closed(&mut self) {
-        self.connection_info.decr_in()
+    pub(crate) fn on_incoming_pending_session_gracefully_closed(
+        &mut self,
+        remote_addr: SocketAddr,
+    ) {
+        self.connection_info.decr_in();
+        self.inbound_subnets.decr(&remote_addr.ip());
     }
 
     /// Returns the number of currently active inbound connections.
@@ -231,7 +296,8 @@ impl PeersManager {
             }
         }
 
-        self.connection_info.decr_in()
+        self.connection_info.decr_in();
+        self.inbound_subnets.decr(&remote_addr.ip());
     }
 
     /// Called when a new _incoming_ active session was established to the given peer.
@@ -241,6 +307,9 @@ impl PeersManager {
     /// If the reputation of the peer is below the `BANNED_REPUTATION` threshold, a disconnect will
     /// be scheduled.
     pub(crate) fn on_active_inbound_session(&mut self, peer_id: PeerId, addr: SocketAddr) {
+        // the pending connection is now accounted to the session
+        self.inbound_subnets.on_active_session(peer_id, addr.ip());
+
         // we only need to check the peer id here as the ip address will have been checked at
         // on_inbound_pending_session
         if self.ban_list.is_banned_peer(&peer_id) {
@@ -264,6 +333,72 @@ impl PeersManager {
         }
     }
 
+    /// Called with the client version the peer announced in the `Hello` message of a new active
+    /// session.
+    ///
+    /// If the client version is rejected by the [`ClientVersionFilter`], the peer is disconnected
+    /// and banned. Trusted peers are exempt.
+    ///
+    /// Returns `false` if the peer was rejected.
+    pub(crate) fn on_session_client_version(
+        &mut self,
+        peer_id: PeerId,
+        client_version: &str,
+    ) -> bool {
+        let Some(peer) = self.peers.get_mut(&peer_id) else { return true };
+        peer.client_version = Some(client_version.to_string());
+
+        if peer.is_trusted() ||
+            !peer.state.is_connected() ||
+            self.client_version_filter.is_allowed(client_version)
+        {
+            return true
+        }
+
+        trace!(target: "net::peers", ?peer_id, %client_version, "disconnecting peer with rejected client version");
+        peer.state.disconnect();
+        self.queued_actions.push_back(PeerAction::Disconnect {
+            peer_id,
+            reason: Some(DisconnectReason::UselessPeer),
+        });
+        self.ban_peer(peer_id);
+        false
+    }
+
+    /// Disconnects all connected peers that are no longer accepted after the ban list or the
+    /// client version rules changed.
+    fn disconnect_filtered_peers(&mut self) {
+        let mut rejected_client_versions = Vec::new();
+        for (peer_id, peer) in self.peers.iter_mut() {
+            if !peer.state.is_connected() {
+                continue
+            }
+
+            let ip = self.inbound_subnets.remote_ip(peer_id).unwrap_or_else(|| peer.addr.ip());
+            let reason = if self.ban_list.is_banned_ip(&ip) {
+                DisconnectReason::DisconnectRequested
+            } else if !peer.is_trusted() &&
+                peer.client_version
+                    .as_deref()
+                    .map_or(false, |version| !self.client_version_filter.is_allowed(version))
+            {
+                rejected_client_versions.push(*peer_id);
+                DisconnectReason::UselessPeer
+            } else {
+                continue
+            };
+
+            trace!(target: "net::peers", ?peer_id, ?ip, ?reason, "disconnecting filtered peer");
+            peer.state.disconnect();
+            self.queued_actions
+                .push_back(PeerAction::Disconnect { peer_id: *peer_id, reason: Some(reason) });
+        }
+
+        for peer_id in rejected_client_versions {
+            self.ban_peer(peer_id);
+        }
+    }
+
     /// Bans the peer temporarily with the configured ban timeout
     fn ban_peer(&mut self, peer_id: PeerId) {
         self.ban_list.ban_peer_until(peer_id, std::time::Instant::now() + self.ban_duration);
@@ -335,6 +470,8 @@ impl PeersManager {
 
     /// Gracefully disconnected an active session
     pub(crate) fn on_active_session_gracefully_closed(&mut self, peer_id: PeerId) {
+        self.inbound_subnets.on_session_closed(&peer_id);
+
         match self.peers.entry(peer_id) {
             Entry::Occupied(mut entry) => {
                 self.connection_info.decr_state(entry.get().state);
@@ -368,6 +505,7 @@ impl PeersManager {
         peer_id: &PeerId,
         err: &EthStreamError,
     ) {
+        self.inbound_subnets.on_session_closed(peer_id);
         self.on_connection_failure(remote_addr, peer_id, err, ReputationChangeKind::Dropped)
     }
 
@@ -446,9 +584,13 @@ impl PeersManager {
     ///
     /// If the session was an outgoing connection, this means that the peer initiated a connection
     /// to us at the same time and this connection is already established.
-    pub(crate) fn on_already_connected(&mut self, direction: Direction) {
+    pub(crate) fn on_already_connected(&mut self, remote_addr: SocketAddr, direction: Direction) {
         match direction {
-            Direction::Incoming => {}
+            Direction::Incoming => {
+                // need to release the slot of the pending incoming connection
+                self.connection_info.decr_in();
+                self.inbound_subnets.decr(&remote_addr.ip());
+            }
             Direction::Outgoing(_) => {
                 // need to decrement the outgoing counter
                 self.connection_info.decr_out();
@@ -568,6 +710,7 @@ impl PeersManager {
         let mut unconnected = self.peers.iter_mut().filter(|(_, peer)| {
             peer.state.is_unconnected() &&
                 !peer.is_banned() &&
+                !self.ban_list.is_banned_ip(&peer.addr.ip()) &&
                 (!self.connect_trusted_nodes_only || peer.is_trusted())
         });
 
@@ -653,6 +796,26 @@ impl PeersManager {
                     PeerCommand::GetPeers(tx) => {
                         let _ = tx.send(self.iter_peers().collect());
                     }
+                    PeerCommand::BanSubnet(subnet) => {
+                        self.ban_list.ban_subnet(subnet);
+                        self.disconnect_filtered_peers();
+                    }
+                    PeerCommand::UnbanSubnet(subnet) => self.ban_list.unban_subnet(&subnet),
+                    PeerCommand::AllowSubnet(subnet) => {
+                        self.ban_list.allow_subnet(subnet);
+                        self.disconnect_filtered_peers();
+                    }
+                    PeerCommand::DisallowSubnet(subnet) => {
+                        self.ban_list.disallow_subnet(&subnet);
+                        self.disconnect_filtered_peers();
+                    }
+                    PeerCommand::SetClientVersionFilter(filter) => {
+                        self.client_version_filter = filter;
+                        self.disconnect_filtered_peers();
+                    }
+                    PeerCommand::SetInboundSubnetLimit(limit) => {
+                        self.inbound_subnets.limit = limit;
+                    }
                 }
             }
 
@@ -751,6 +914,71 @@ impl Default for ConnectionInfo {
     }
 }
 
+/// Tracks the remote IPs of pending and active inbound connections to enforce the
+/// [`SubnetLimit`].
+#[derive(Debug, Default)]
+struct InboundSubnets {
+    /// The limit to enforce, if any.
+    limit: Option<SubnetLimit>,
+    /// Number of pending and active inbound connections per remote IP.
+    connections: HashMap<IpAddr, usize>,
+    /// Remote IPs of active inbound sessions.
+    active_sessions: HashMap<PeerId, IpAddr>,
+}
+
+// === impl InboundSubnets ===
+
+impl InboundSubnets {
+    fn new(limit: Option<SubnetLimit>) -> Self {
+        Self { limit, ..Default::default() }
+    }
+
+    /// Returns the subnet of the IP if it has no capacity for another inbound connection.
+    ///
+    /// Connections are counted per IP, so changes of the limit apply to existing connections.
+    fn exceeded_subnet(&self, ip: &IpAddr) -> Option<IpSubnet> {
+        let limit = self.limit?;
+        let subnet = limit.subnet(*ip);
+        let num_connections = self
+            .connections
+            .iter()
+            .filter(|(other, _)| subnet.contains(other))
+            .map(|(_, num)| num)
+            .sum::<usize>();
+        (num_connections >= limit.max_inbound).then_some(subnet)
+    }
+
+    fn inc(&mut self, ip: IpAddr) {
+        *self.connections.entry(ip).or_default() += 1;
+    }
+
+    fn decr(&mut self, ip: &IpAddr) {
+        if let Entry::Occupied(mut entry) = self.connections.entry(*ip) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Returns the remote IP of the active inbound session to the peer.
+    fn remote_ip(&self, peer_id: &PeerId) -> Option<IpAddr> {
+        self.active_sessions.get(peer_id).copied()
+    }
+
+    /// Invoked when a pending inbound connection was turned into an active session.
+    fn on_active_session(&mut self, peer_id: PeerId, ip: IpAddr) {
+        self.active_sessions.insert(peer_id, ip);
+    }
+
+    /// Invoked when any active session was closed, releases the connection if it was inbound.
+    fn on_session_closed(&mut self, peer_id: &PeerId) {
+        if let Some(ip) = self.active_sessions.remove(peer_id) {
+            self.decr(&ip);
+        }
+    }
+}
+
 /// Tracks info about a single peer.
 #[derive(Debug, Clone)]
 pub struct Peer {
@@ -768,6 +996,8 @@ pub struct Peer {
     kind: PeerKind,
     /// Counts number of times the peer was backed off   
     backoff_counter: u32,
+    /// The client version the peer announced in its last session.
+    client_version: Option<String>,
 }
 
 // === impl Peer ===
@@ -790,6 +1020,7 @@ impl Peer {
             remove_after_disconnect: false,
             kind: Default::default(),
             backoff_counter: 0,
+            client_version: None,
         }
     }
 
@@ -908,6 +1139,18 @@ pub(crate) enum PeerCommand {
     GetPeer(PeerId, oneshot::Sender<Option<Peer>>),
     /// Get node information on all peers
     GetPeers(oneshot::Sender<Vec<NodeRecord>>),
+    /// Ban all IPs of the subnet.
+    BanSubnet(IpSubnet),
+    /// Unban the subnet.
+    UnbanSubnet(IpSubnet),
+    /// Add the subnet to the allowed subnets.
+    AllowSubnet(IpSubnet),
+    /// Remove the subnet from the allowed subnets.
+    DisallowSubnet(IpSubnet),
+    /// Replace the rules for the client versions of peers.
+    SetClientVersionFilter(ClientVersionFilter),
+    /// Set or remove the limit of inbound connections per subnet.
+    SetInboundSubnetLimit(Option<SubnetLimit>),
 }
 
 /// Actions the peer manager can trigger.
@@ -969,6 +1212,18 @@ pub struct PeersConfig {
     /// How long to backoff peers that are we failed to connect to for non-fatal reasons, such as
     /// [`DisconnectReason::TooManyPeers`].
     pub backoff_durations: PeerBackoffDurations,
+    /// Subnets to never connect to.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub banned_subnets: Vec<IpSubnet>,
+    /// If not empty, only peers in these subnets are connected to.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub allowed_subnets: Vec<IpSubnet>,
+    /// Rules for the client versions of peers, checked after the `Hello` handshake.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_version_filter: ClientVersionFilter,
+    /// Limits the number of inbound connections from the same subnet, if set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inbound_subnet_limit: Option<SubnetLimit>,
 }
 
 impl Default for PeersConfig {
@@ -984,6 +1239,10 @@ impl Default for PeersConfig {
             trusted_nodes: Default::default(),
             connect_trusted_nodes_only: false,
             basic_nodes: Default::default(),
+            banned_subnets: Default::default(),
+            allowed_subnets: Default::default(),
+            client_version_filter: Default::default(),
+            inbound_subnet_limit: None,
         }
     }
 }
@@ -1029,6 +1288,30 @@ impl PeersConfig {
         self
     }
 
+    /// Subnets to never connect to.
+    pub fn with_banned_subnets(mut self, subnets: impl IntoIterator<Item = IpSubnet>) -> Self {
+        self.banned_subnets.extend(subnets);
+        self
+    }
+
+    /// Only connect to peers in these subnets.
+    pub fn with_allowed_subnets(mut self, subnets: impl IntoIterator<Item = IpSubnet>) -> Self {
+        self.allowed_subnets.extend(subnets);
+        self
+    }
+
+    /// Rules for the client versions of peers.
+    pub fn with_client_version_filter(mut self, filter: ClientVersionFilter) -> Self {
+        self.client_version_filter = filter;
+        self
+    }
+
+    /// Limit the number of inbound connections from the same subnet.
+    pub fn with_inbound_subnet_limit(mut self, limit: SubnetLimit) -> Self {
+        self.inbound_subnet_limit = Some(limit);
+        self
+    }
+
     /// Nodes available at launch.
     pub fn with_basic_nodes(mut self, nodes: HashSet<NodeRecord>) -> Self {
         self.basic_nodes = nodes;
@@ -1040,9 +1323,7 @@ impl PeersConfig {
         self,
         optional_file: Option<impl AsRef<Path>>,
     ) -> Result<Self, io::Error> {
-        let Some(file_path) = optional_file else {
-            return Ok(self)
-        };
+        let Some(file_path) = optional_file else { return Ok(self) };
         let reader = match std::fs::File::open(file_path.as_ref()) {
             Ok(file) => std::io::BufReader::new(file),
             Err(e) if e.kind() == ErrorKind::NotFound => return Ok(self),
@@ -1106,6 +1387,7 @@ impl Default for PeerBackoffDurations {
 #[derive(Debug, Error)]
 pub enum InboundConnectionError {
     ExceedsLimit(usize),
+    ExceedsSubnetLimit(IpSubnet),
     IpBanned,
 }
 
@@ -1117,14 +1399,14 @@ impl Display for InboundConnectionError {
 
 #[cfg(test)]
 mod test {
-    use super::PeersManager;
+    use super::{InboundConnectionError, PeersManager};
     use crate::{
         error::BackoffKind,
         peers::{
             manager::{ConnectionInfo, PeerBackoffDurations, PeerConnectionState},
-            PeerAction,
+            ClientVersionFilter, PeerAction, SubnetLimit,
         },
-        session::PendingSessionHandshakeError,
+        session::{Direction, PendingSessionHandshakeError},
         PeersConfig,
     };
     use reth_discv4::NodeRecord;
@@ -1132,7 +1414,7 @@ mod test {
         errors::{EthHandshakeError, EthStreamError, P2PHandshakeError, P2PStreamError},
         DisconnectReason,
     };
-    use reth_net_common::ban_list::BanList;
+    use reth_net_common::{ban_list::BanList, subnet::IpSubnet};
     use reth_network_api::ReputationChangeKind;
     use reth_primitives::{PeerId, H512};
     use std::{
@@ -1522,7 +1804,7 @@ mod test {
 
         assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
         assert_eq!(peers.connection_info.num_inbound, 1);
-        peers.on_incoming_pending_session_rejected_internally();
+        peers.on_incoming_pending_session_rejected_internally(socket_addr);
         assert_eq!(peers.connection_info.num_inbound, 0);
     }
 
@@ -1533,7 +1815,7 @@ mod test {
 
         assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
         assert_eq!(peers.connection_info.num_inbound, 1);
-        peers.on_incoming_pending_session_gracefully_closed();
+        peers.on_incoming_pending_session_gracefully_closed(socket_addr);
         assert_eq!(peers.connection_info.num_inbound, 0);
     }
 
@@ -1571,6 +1853,124 @@ mod test {
         assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
     }
 
+    #[tokio::test]
+    async fn test_inbound_subnet_limit() {
+        let config = PeersConfig::default().with_inbound_subnet_limit(SubnetLimit::new(2));
+        let mut peers = PeersManager::new(config);
+
+        let addr = |last| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, last)), 30303);
+        assert!(peers.on_incoming_pending_session(addr(1).ip()).is_ok());
+        assert!(peers.on_incoming_pending_session(addr(2).ip()).is_ok());
+        match peers.on_incoming_pending_session(addr(3).ip()) {
+            Err(InboundConnectionError::ExceedsSubnetLimit(subnet)) => {
+                assert_eq!(subnet, "1.2.3.0/24".parse().unwrap())
+            }
+            _ => unreachable!(),
+        }
+        // other subnets are not affected
+        assert!(peers.on_incoming_pending_session(IpAddr::V4(Ipv4Addr::new(1, 2, 4, 1))).is_ok());
+
+        // slot of a closed pending session is released
+        peers.on_incoming_pending_session_gracefully_closed(addr(1));
+        assert!(peers.on_incoming_pending_session(addr(3).ip()).is_ok());
+
+        // slot of an active session is released once the session is closed
+        let peer = PeerId::random();
+        peers.on_active_inbound_session(peer, addr(3));
+        assert!(peers.on_incoming_pending_session(addr(4).ip()).is_err());
+        peers.on_active_session_gracefully_closed(peer);
+        assert!(peers.on_incoming_pending_session(addr(4).ip()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_already_connected_incoming() {
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::default();
+
+        assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
+        assert_eq!(peers.connection_info.num_inbound, 1);
+
+        peers.on_already_connected(socket_addr, Direction::Incoming);
+        assert_eq!(peers.connection_info.num_inbound, 0);
+    }
+
+    #[tokio::test]
+    async fn test_reject_client_version() {
+        let filter = ClientVersionFilter::default().with_denied(["bad/*".into()]);
+        let config = PeersConfig::default().with_client_version_filter(filter);
+        let mut peers = PeersManager::new(config);
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+
+        let good = PeerId::random();
+        assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
+        peers.on_active_inbound_session(good, socket_addr);
+        assert!(peers.on_session_client_version(good, "good/v1.0.0"));
+
+        let bad = PeerId::random();
+        assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
+        peers.on_active_inbound_session(bad, socket_addr);
+        assert!(!peers.on_session_client_version(bad, "Bad/v1.0.0"));
+
+        assert!(matches!(event!(peers), PeerAction::PeerAdded(peer_id) if peer_id == good));
+        assert!(matches!(event!(peers), PeerAction::PeerAdded(peer_id) if peer_id == bad));
+        match event!(peers) {
+            PeerAction::Disconnect { peer_id, reason } => {
+                assert_eq!(peer_id, bad);
+                assert_eq!(reason, Some(DisconnectReason::UselessPeer));
+            }
+            _ => unreachable!(),
+        }
+        assert!(matches!(event!(peers), PeerAction::BanPeer { peer_id } if peer_id == bad));
+        assert!(peers.ban_list.is_banned_peer(&bad));
+        assert_eq!(peers.peers[&bad].state, PeerConnectionState::DisconnectingIn);
+
+        // updating the rules disconnects already connected peers
+        let filter = ClientVersionFilter::default().with_allowed(["bad/*".into()]);
+        peers.handle().set_client_version_filter(filter);
+        match event!(peers) {
+            PeerAction::Disconnect { peer_id, reason } => {
+                assert_eq!(peer_id, good);
+                assert_eq!(reason, Some(DisconnectReason::UselessPeer));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ban_subnet() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 8008);
+        let mut peers = PeersManager::default();
+        peers.add_peer(peer, socket_addr, None);
+
+        assert!(matches!(event!(peers), PeerAction::PeerAdded(_)));
+        assert!(matches!(event!(peers), PeerAction::Connect { .. }));
+
+        let subnet: IpSubnet = "1.2.0.0/16".parse().unwrap();
+        peers.handle().ban_subnet(subnet);
+        match event!(peers) {
+            PeerAction::Disconnect { peer_id, .. } => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+        assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_err());
+
+        // new peers outside of the allowed subnets are ignored
+        let allowed: IpSubnet = "5.0.0.0/8".parse().unwrap();
+        peers.handle().allow_subnet(allowed);
+        poll_fn(|cx| {
+            let _ = peers.poll(cx);
+            Poll::Ready(())
+        })
+        .await;
+        let other = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(6, 0, 0, 1)), 8008);
+        peers.add_peer(PeerId::random(), other, None);
+        assert_eq!(peers.peers.len(), 1);
+        assert!(peers.on_incoming_pending_session(other.ip()).is_err());
+        assert!(peers.on_incoming_pending_session(IpAddr::V4(Ipv4Addr::new(5, 1, 1, 1))).is_ok());
+    }
+
     #[tokio::test]
     async fn test_reputation_change_connected() {
         let peer = PeerId::random();
@@ -1725,7 +2125,8 @@ mod test {
             Ok(_) => panic!(),
             Err(err) => match err {
                 super::InboundConnectionError::IpBanned {} => {}
-                super::InboundConnectionError::ExceedsLimit { .. } => {
+                super::InboundConnectionError::ExceedsLimit { .. } |
+                super::InboundConnectionError::ExceedsSubnetLimit { .. } => {
                     panic!()
                 }
             },
@@ -1742,7 +2143,11 @@ mod test {
         let mut peer_manager = PeersManager::new(config);
         peer_manager.on_active_inbound_session(given_peer_id, socket_addr);
 
-        let Some(PeerAction::DisconnectBannedIncoming { peer_id }) = peer_manager.queued_actions.pop_front() else { panic!() };
+        let Some(PeerAction::DisconnectBannedIncoming { peer_id }) =
+            peer_manager.queued_actions.pop_front()
+        else {
+            panic!()
+        };
 
         assert_eq!(peer_id, given_peer_id)
     }
@@ -1,8 +1,11 @@
 //! Peer related implementations
 
+mod filter;
 mod manager;
 mod reputation;
 
+pub use filter::{ClientVersionFilter, ClientVersionPattern, SubnetLimit};
+
 pub(crate) use manager::{InboundConnectionError, PeerAction, PeersManager};
 pub use manager::{Peer, PeersConfig, PeersHandle};
 pub use reputation::ReputationChangeWeights;
@@ -5,6 +5,7 @@ use crate::{
     session::{
         config::INITIAL_REQUEST_TIMEOUT,
         handle::{ActiveSessionMessage, SessionCommand},
+        messages::SessionMessagesMeter,
         SessionId,
     },
 };
@@ -16,12 +17,13 @@ use reth_eth_wire::{
     capability::Capabilities,
     errors::{EthHandshakeError, EthStreamError, P2PStreamError},
     message::{EthBroadcastMessage, RequestPair},
-    DisconnectReason, EthMessage, EthStream, P2PStream,
+    DisconnectReason, EthMessage, EthMessageID, EthStream, P2PStream,
 };
 use reth_interfaces::p2p::error::RequestError;
 use reth_metrics_common::metered_sender::MeteredSender;
 use reth_net_common::bandwidth_meter::MeteredStream;
 use reth_primitives::PeerId;
+use reth_rlp::Encodable;
 use std::{
     collections::VecDeque,
     future::Future,
@@ -94,6 +96,8 @@ pub(crate) struct ActiveSession {
     /// If an [ActiveSession] does not receive a response at all within this duration then it is
     /// considered a protocol violation and the session will initiate a drop.
     pub(crate) protocol_breach_request_timeout: Duration,
+    /// Accounts all messages exchanged with the remote peer.
+    pub(crate) message_stats: SessionMessagesMeter,
 }
 
 impl ActiveSession {
@@ -514,6 +518,7 @@ impl Future for ActiveSession {
             while this.conn.poll_ready_unpin(cx).is_ready() {
                 if let Some(msg) = this.queued_outgoing.pop_front() {
                     progress = true;
+                    this.message_stats.on_outbound(msg.message_id(), msg.length());
                     let res = match msg {
                         OutgoingMessage::Eth(msg) => this.conn.start_send_unpin(msg),
                         OutgoingMessage::Broadcast(msg) => this.conn.start_send_broadcast(msg),
@@ -566,6 +571,7 @@ impl Future for ActiveSession {
                         match res {
                             Ok(msg) => {
                                 trace!(target: "net::session", msg_id=?msg.message_id(), remote_peer_id=?this.remote_peer_id, "received eth message");
+                                this.message_stats.on_inbound(msg.message_id(), msg.length());
                                 // decode and handle message
                                 match this.on_incoming(msg) {
                                     OnIncomingMessageOutcome::Ok => {
@@ -690,6 +696,26 @@ pub(crate) enum OutgoingMessage {
     Broadcast(EthBroadcastMessage),
 }
 
+// === impl OutgoingMessage ===
+
+impl OutgoingMessage {
+    /// Returns the message's ID.
+    fn message_id(&self) -> EthMessageID {
+        match self {
+            OutgoingMessage::Eth(msg) => msg.message_id(),
+            OutgoingMessage::Broadcast(msg) => msg.message_id(),
+        }
+    }
+
+    /// Returns the length of the message's RLP encoding.
+    fn length(&self) -> usize {
+        match self {
+            OutgoingMessage::Eth(msg) => msg.length(),
+            OutgoingMessage::Broadcast(msg) => msg.length(),
+        }
+    }
+}
+
 impl From<EthMessage> for OutgoingMessage {
     fn from(value: EthMessage) -> Self {
         OutgoingMessage::Eth(value)
@@ -838,6 +864,7 @@ mod tests {
                             INITIAL_REQUEST_TIMEOUT.as_millis() as u64,
                         )),
                         protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
+                        message_stats: SessionMessagesMeter::new(Default::default()),
                     }
                 }
                 ev => {
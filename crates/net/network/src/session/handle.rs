@@ -1,7 +1,7 @@
 //! Session handles
 use crate::{
     message::PeerMessage,
-    session::{Direction, SessionId},
+    session::{messages::SessionMessagesMeter, Direction, SessionId},
 };
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
@@ -65,6 +65,8 @@ pub(crate) struct ActiveSessionHandle {
     pub(crate) client_version: String,
     /// The address we're connected to
     pub(crate) remote_addr: SocketAddr,
+    /// Accounts the messages exchanged over the session.
+    pub(crate) message_stats: SessionMessagesMeter,
 }
 
 // === impl ActiveSessionHandle ===
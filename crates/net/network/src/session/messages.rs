@@ -0,0 +1,164 @@
+//! Per session accounting of exchanged messages.
+
+use crate::metrics::EthMessageMetrics;
+use reth_eth_wire::EthMessageID;
+use reth_network_api::{MessageCount, MessageStats, SessionMessages};
+use reth_primitives::PeerId;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Number of slots required to index all message kinds by their id.
+const NUM_MESSAGE_IDS: usize = EthMessageID::max() as usize + 1;
+
+/// Accounts the number and payload size of the messages exchanged over a single session, per
+/// message kind.
+///
+/// The payload size is the length of the RLP encoding of a message. Bytes on the wire, including
+/// compression and framing, are only measured per connection by the [`BandwidthMeter`].
+///
+/// [`BandwidthMeter`]: reth_net_common::bandwidth_meter::BandwidthMeter
+///
+/// This is shared by the [`ActiveSession`](super::active::ActiveSession), which records all
+/// messages, and its [`ActiveSessionHandle`](super::handle::ActiveSessionHandle), so the statistics
+/// can be read by the [`SessionManager`](super::SessionManager) at any time.
+///
+/// All recorded messages are also reported to the [`MessagesMetrics`] shared by all sessions.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionMessagesMeter {
+    inner: Arc<SessionMessagesMeterInner>,
+    metrics: Arc<MessagesMetrics>,
+}
+
+// === impl SessionMessagesMeter ===
+
+impl SessionMessagesMeter {
+    /// Creates a new meter that also reports to the given metrics.
+    pub(crate) fn new(metrics: Arc<MessagesMetrics>) -> Self {
+        Self { inner: Default::default(), metrics }
+    }
+
+    /// Records a message received from the peer.
+    pub(crate) fn on_inbound(&self, id: EthMessageID, size: usize) {
+        self.inner.inbound.record(id, size);
+        self.metrics.inbound[id as usize].record(size);
+    }
+
+    /// Records a message sent to the peer.
+    pub(crate) fn on_outbound(&self, id: EthMessageID, size: usize) {
+        self.inner.outbound.record(id, size);
+        self.metrics.outbound[id as usize].record(size);
+    }
+
+    /// Returns the statistics of all messages recorded so far.
+    ///
+    /// > **Note**: This method is by design subject to race conditions. The returned value should
+    /// > only ever be used for statistics purposes.
+    pub(crate) fn session_messages(&self, peer_id: PeerId) -> SessionMessages {
+        SessionMessages {
+            peer_id,
+            inbound: self.inner.inbound.stats(),
+            outbound: self.inner.outbound.stats(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SessionMessagesMeterInner {
+    /// Messages received from the peer.
+    inbound: DirectionMeter,
+    /// Messages sent to the peer.
+    outbound: DirectionMeter,
+}
+
+/// Counters for messages in a single direction, indexed by message id.
+#[derive(Debug, Default)]
+struct DirectionMeter {
+    messages: [AtomicU64; NUM_MESSAGE_IDS],
+    payload_bytes: [AtomicU64; NUM_MESSAGE_IDS],
+}
+
+// === impl DirectionMeter ===
+
+impl DirectionMeter {
+    fn record(&self, id: EthMessageID, size: usize) {
+        self.messages[id as usize].fetch_add(1, Ordering::Relaxed);
+        self.payload_bytes[id as usize].fetch_add(size as u64, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> MessageStats {
+        let messages = (0..NUM_MESSAGE_IDS)
+            .filter_map(|idx| {
+                let id = EthMessageID::try_from(idx).ok()?;
+                let count = MessageCount {
+                    messages: self.messages[idx].load(Ordering::Relaxed),
+                    payload_bytes: self.payload_bytes[idx].load(Ordering::Relaxed),
+                };
+                (count.messages > 0).then_some((id, count))
+            })
+            .collect();
+        MessageStats { messages }
+    }
+}
+
+/// Prometheus metrics of the messages exchanged over all sessions, indexed by message id.
+#[derive(Debug)]
+pub(crate) struct MessagesMetrics {
+    inbound: Vec<EthMessageMetrics>,
+    outbound: Vec<EthMessageMetrics>,
+}
+
+impl Default for MessagesMetrics {
+    fn default() -> Self {
+        let metrics = |direction: &'static str| {
+            (0..NUM_MESSAGE_IDS)
+                .map(|idx| {
+                    // ids without a message kind are never recorded
+                    let message = EthMessageID::try_from(idx)
+                        .map(|id| format!("{id:?}"))
+                        .unwrap_or_else(|_| format!("{idx:#04x}"));
+                    EthMessageMetrics::new_with_labels(&[
+                        ("direction", direction.to_string()),
+                        ("message", message),
+                    ])
+                })
+                .collect()
+        };
+        Self { inbound: metrics("inbound"), outbound: metrics("outbound") }
+    }
+}
+
+impl EthMessageMetrics {
+    fn record(&self, size: usize) {
+        self.eth_messages.increment(1);
+        self.eth_message_payload_bytes.increment(size as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_messages() {
+        let meter = SessionMessagesMeter::new(Default::default());
+        meter.on_inbound(EthMessageID::PooledTransactions, 100);
+        meter.on_inbound(EthMessageID::PooledTransactions, 50);
+        meter.on_inbound(EthMessageID::NewPooledTransactionHashes, 10);
+        meter.clone().on_outbound(EthMessageID::GetPooledTransactions, 33);
+
+        let peer_id = PeerId::random();
+        let stats = meter.session_messages(peer_id);
+        assert_eq!(stats.peer_id, peer_id);
+        assert_eq!(
+            stats.inbound.get(EthMessageID::PooledTransactions),
+            MessageCount { messages: 2, payload_bytes: 150 }
+        );
+        assert_eq!(stats.inbound.messages.len(), 2);
+        assert_eq!(stats.inbound.total_messages(), 3);
+        assert_eq!(stats.inbound.total_payload_bytes(), 160);
+        assert_eq!(stats.outbound.total_payload_bytes(), 33);
+        assert_eq!(stats.total_payload_bytes(), 193);
+    }
+}
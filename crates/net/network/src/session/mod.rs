@@ -8,6 +8,7 @@ use crate::{
             ActiveSessionHandle, ActiveSessionMessage, PendingSessionEvent, PendingSessionHandle,
             SessionCommand,
         },
+        messages::{MessagesMetrics, SessionMessagesMeter},
     },
 };
 pub use crate::{message::PeerRequestSender, session::handle::PeerInfo};
@@ -24,6 +25,7 @@ use reth_net_common::{
     bandwidth_meter::{BandwidthMeter, MeteredStream},
     stream::HasRemoteAddr,
 };
+use reth_network_api::SessionMessages;
 use reth_primitives::{ForkFilter, ForkId, ForkTransition, Head, PeerId};
 use reth_tasks::TaskSpawner;
 use secp256k1::SecretKey;
@@ -46,6 +48,7 @@ use tracing::{instrument, trace};
 mod active;
 mod config;
 mod handle;
+mod messages;
 pub use config::SessionsConfig;
 
 /// Internal identifier for active sessions.
@@ -101,6 +104,8 @@ pub(crate) struct SessionManager {
     active_session_rx: ReceiverStream<ActiveSessionMessage>,
     /// Used to measure inbound & outbound bandwidth across all managed streams
     bandwidth_meter: BandwidthMeter,
+    /// Metrics for the messages exchanged over all active sessions.
+    message_metrics: Arc<MessagesMetrics>,
 }
 
 // === impl SessionManager ===
@@ -137,6 +142,7 @@ impl SessionManager {
             active_session_tx: MeteredSender::new(active_session_tx, "network_active_session"),
             active_session_rx: ReceiverStream::new(active_session_rx),
             bandwidth_meter,
+            message_metrics: Default::default(),
         }
     }
 
@@ -431,6 +437,8 @@ impl SessionManager {
                 // negotiated version
                 let version = conn.version();
 
+                let session_messages = SessionMessagesMeter::new(Arc::clone(&self.message_metrics));
+
                 let session = ActiveSession {
                     next_id: 0,
                     remote_peer_id: peer_id,
@@ -450,6 +458,7 @@ impl SessionManager {
                     ),
                     internal_request_timeout: Arc::clone(&timeout),
                     protocol_breach_request_timeout: self.protocol_breach_request_timeout,
+                    message_stats: session_messages.clone(),
                 };
 
                 self.spawn(session);
@@ -462,8 +471,9 @@ impl SessionManager {
                     established: Instant::now(),
                     capabilities: Arc::clone(&capabilities),
                     commands_to_session,
-                    client_version: client_id,
+                    client_version: client_id.clone(),
                     remote_addr,
+                    message_stats: session_messages,
                 };
 
                 self.active_sessions.insert(peer_id, handle);
@@ -478,6 +488,7 @@ impl SessionManager {
                     messages,
                     direction,
                     timeout,
+                    client_version: client_id,
                 })
             }
             PendingSessionEvent::Disconnected { remote_addr, session_id, direction, error } => {
@@ -565,6 +576,14 @@ impl SessionManager {
             .collect()
     }
 
+    /// Returns the [`SessionMessages`] of all active sessions.
+    pub(crate) fn session_messages(&self) -> Vec<SessionMessages> {
+        self.active_sessions
+            .values()
+            .map(|session| session.message_stats.session_messages(session.remote_id))
+            .collect()
+    }
+
     /// Returns [`PeerInfo`] for a given peer.
     ///
     /// Returns `None` if there's no active session to the peer.
@@ -595,6 +614,8 @@ pub(crate) enum SessionEvent {
         messages: PeerRequestSender,
         direction: Direction,
         timeout: Arc<AtomicU64>,
+        /// The client version the peer announced in the `Hello` message.
+        client_version: String,
     },
     AlreadyConnected {
         peer_id: PeerId,
@@ -131,6 +131,7 @@ where
                 messages,
                 direction,
                 timeout,
+                client_version,
             } => {
                 self.state.on_session_activated(
                     peer_id,
@@ -142,6 +143,7 @@ where
                 Some(SwarmEvent::SessionEstablished {
                     peer_id,
                     remote_addr,
+                    client_version,
                     capabilities,
                     version,
                     messages,
@@ -151,7 +153,7 @@ where
             }
             SessionEvent::AlreadyConnected { peer_id, remote_addr, direction } => {
                 trace!( target: "net", ?peer_id, ?remote_addr, ?direction, "already connected");
-                self.state.peers_mut().on_already_connected(direction);
+                self.state.peers_mut().on_already_connected(remote_addr, direction);
                 None
             }
             SessionEvent::ValidMessage { peer_id, message } => {
@@ -213,6 +215,13 @@ where
                                 DisconnectReason::TooManyPeers,
                             );
                         }
+                        InboundConnectionError::ExceedsSubnetLimit(subnet) => {
+                            trace!(target: "net", %subnet, ?remote_addr, "Exceeded incoming connection limit of subnet; disconnecting");
+                            self.sessions.disconnect_incoming_connection(
+                                stream,
+                                DisconnectReason::TooManyPeers,
+                            );
+                        }
                     }
                     return None
                 }
@@ -225,7 +234,7 @@ where
                         warn!(target: "net", ?err, "Incoming connection rejected");
                         self.state_mut()
                             .peers_mut()
-                            .on_incoming_pending_session_rejected_internally();
+                            .on_incoming_pending_session_rejected_internally(remote_addr);
                     }
                 }
             }
@@ -390,6 +399,8 @@ pub(crate) enum SwarmEvent {
     SessionEstablished {
         peer_id: PeerId,
         remote_addr: SocketAddr,
+        /// The client version the peer announced in the `Hello` message.
+        client_version: String,
         capabilities: Arc<Capabilities>,
         /// negotiated eth version
         version: EthVersion,
@@ -0,0 +1,574 @@
+//! Fetching of announced transactions.
+//!
+//! Peers announce new transactions via `NewPooledTransactionHashes` and it's up to us to request
+//! the full transaction objects for all hashes we don't know yet.
+//!
+//! The [`TransactionFetcher`] keeps track of all announced but unknown hashes and the peers that
+//! announced them. Hashes are requested in batches from one of their announcers: there's at most
+//! one request in flight per peer and every hash is only requested from a single peer at a time.
+//! If a request fails, or the peer doesn't deliver all requested transactions, the missing hashes
+//! are requested from another peer that announced them.
+
+use super::Peer;
+use crate::message::PeerRequest;
+use futures::{stream::FuturesUnordered, Future, StreamExt};
+use reth_eth_wire::{GetPooledTransactions, NewPooledTransactionHashes, PooledTransactions};
+use reth_interfaces::p2p::error::{RequestError, RequestResult};
+use reth_primitives::{
+    PeerId, TransactionSigned, TxHash, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
+};
+use reth_rlp::{length_of_length, Encodable};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::oneshot;
+use tracing::trace;
+
+/// Maximum number of hashes requested in a single `GetPooledTransactions` request.
+const GET_POOLED_TRANSACTIONS_SOFT_LIMIT_NUM_HASHES: usize = 256;
+
+/// Soft limit for the accumulated announced size of the transactions requested in a single
+/// `GetPooledTransactions` request.
+///
+/// Only eth/68 announcements carry the size of the transaction.
+const POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT_BYTE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Maximum number of announcers tracked per hash, which are the peers we can fall back to.
+const MAX_ANNOUNCERS_PER_TX: usize = 8;
+
+/// How often a hash is requested before it's dropped.
+const MAX_REQUEST_RETRIES_PER_TX_HASH: u8 = 3;
+
+/// Maximum number of unknown hashes tracked at once, new announcements are ignored beyond that.
+const MAX_UNKNOWN_HASHES: usize = 32 * 1024;
+
+/// The future for a `GetPooledTransactions` request to a peer.
+type GetPooledTxRequestFut = Pin<Box<dyn Future<Output = GetPooledTxResponse> + Send + 'static>>;
+
+/// Keeps track of announced transactions and fetches them from the announcing peers.
+#[derive(Default)]
+pub(super) struct TransactionFetcher {
+    /// All currently active `GetPooledTransactions` requests.
+    inflight_requests: FuturesUnordered<GetPooledTxRequestFut>,
+    /// Peers with an active request.
+    active_peers: HashSet<PeerId>,
+    /// All announced hashes that are unknown to the pool and neither fetched nor dropped yet.
+    unknown_hashes: HashMap<TxHash, UnknownTransaction>,
+    /// Hashes that are waiting to be requested, in order of their announcement.
+    ///
+    /// Hashes that are no longer in `unknown_hashes` are skipped.
+    buffered_hashes: VecDeque<TxHash>,
+    /// Events that are ready to be returned.
+    queued_events: VecDeque<FetchEvent>,
+}
+
+// === impl TransactionFetcher ===
+
+impl TransactionFetcher {
+    /// Returns the number of `GetPooledTransactions` requests in flight.
+    pub(super) fn num_inflight_requests(&self) -> usize {
+        self.inflight_requests.len()
+    }
+
+    /// Returns the number of announced hashes that haven't been fetched yet.
+    pub(super) fn num_unknown_hashes(&self) -> usize {
+        self.unknown_hashes.len()
+    }
+
+    /// Returns `true` if the hash is currently tracked.
+    #[cfg(test)]
+    fn is_tracked(&self, hash: &TxHash) -> bool {
+        self.unknown_hashes.contains_key(hash)
+    }
+
+    /// Registers the hashes the peer announced and doesn't know yet.
+    ///
+    /// Hashes that are already tracked only record the peer as an additional announcer, so they
+    /// are not requested multiple times. eth/68 announcements of unsupported transaction types
+    /// are ignored.
+    ///
+    /// Note: this does not send any requests, see [`Self::schedule_requests`].
+    pub(super) fn on_announced_hashes(
+        &mut self,
+        peer_id: PeerId,
+        announced: impl IntoIterator<Item = (TxHash, Option<AnnouncedTxMeta>)>,
+    ) {
+        for (hash, meta) in announced {
+            if meta.map_or(false, |meta| !meta.is_supported_type()) {
+                trace!(target: "net::tx", ?peer_id, ?hash, ?meta, "Ignoring announced transaction of unsupported type");
+                continue
+            }
+
+            if let Some(tx) = self.unknown_hashes.get_mut(&hash) {
+                if tx.announcers.len() < MAX_ANNOUNCERS_PER_TX &&
+                    tx.announcers.iter().all(|(id, _)| *id != peer_id)
+                {
+                    tx.announcers.push((peer_id, meta));
+                }
+                continue
+            }
+
+            if self.unknown_hashes.len() >= MAX_UNKNOWN_HASHES {
+                trace!(target: "net::tx", ?peer_id, ?hash, "Too many unknown transactions, ignoring announcement");
+                continue
+            }
+
+            self.unknown_hashes.insert(
+                hash,
+                UnknownTransaction {
+                    announcers: vec![(peer_id, meta)],
+                    requested_from: None,
+                    retries: 0,
+                },
+            );
+            self.buffered_hashes.push_back(hash);
+        }
+    }
+
+    /// Stops tracking the hash if it's not requested yet, because the transaction was received
+    /// otherwise, e.g. via broadcast.
+    pub(super) fn on_received_transaction(&mut self, hash: &TxHash) {
+        if self.unknown_hashes.get(hash).map_or(false, |tx| tx.requested_from.is_none()) {
+            self.unknown_hashes.remove(hash);
+        }
+    }
+
+    /// Removes the peer from all announcer lists.
+    ///
+    /// Buffered hashes that were only announced by this peer are dropped. The peer's inflight
+    /// request, if any, resolves with an error once the session is gone.
+    pub(super) fn on_session_closed(&mut self, peer_id: &PeerId) {
+        self.unknown_hashes.retain(|_, tx| {
+            tx.announcers.retain(|(id, _)| id != peer_id);
+            tx.requested_from.is_some() || !tx.announcers.is_empty()
+        });
+    }
+
+    /// Sends requests for buffered hashes to idle peers that announced them.
+    ///
+    /// Hashes are grouped per peer, respecting the soft limits for the number of hashes and the
+    /// (announced) size of the response. Hashes without an idle announcer remain buffered.
+    pub(super) fn schedule_requests(&mut self, peers: &HashMap<PeerId, Peer>) {
+        if self.buffered_hashes.is_empty() {
+            return
+        }
+
+        let mut batches = HashMap::<PeerId, RequestBatch>::new();
+        let mut remaining = VecDeque::new();
+        let mut seen = HashSet::new();
+
+        while let Some(hash) = self.buffered_hashes.pop_front() {
+            let tx = match self.unknown_hashes.get_mut(&hash) {
+                Some(tx) if tx.requested_from.is_none() && seen.insert(hash) => tx,
+                _ => continue,
+            };
+
+            // pick the first idle announcer that still has capacity
+            let active_peers = &self.active_peers;
+            let idle_announcer = tx
+                .announcers
+                .iter()
+                .find(|(peer_id, meta)| {
+                    !active_peers.contains(peer_id) &&
+                        peers.contains_key(peer_id) &&
+                        batches.get(peer_id).map_or(true, |batch| batch.has_capacity(*meta))
+                })
+                .copied();
+
+            match idle_announcer {
+                Some((peer_id, meta)) => {
+                    batches.entry(peer_id).or_default().push(hash, meta);
+                    tx.requested_from = Some(peer_id);
+                }
+                None => remaining.push_back(hash),
+            }
+        }
+
+        self.buffered_hashes = remaining;
+
+        for (peer_id, batch) in batches {
+            let peer = peers.get(&peer_id).expect("batches only contain connected peers; qed");
+            self.request_transactions(peer_id, peer, batch.hashes);
+        }
+    }
+
+    /// Sends a `GetPooledTransactions` request for the given hashes to the peer.
+    fn request_transactions(&mut self, peer_id: PeerId, peer: &Peer, hashes: Vec<TxHash>) {
+        trace!(target: "net::tx", ?peer_id, num_hashes = hashes.len(), "Requesting pooled transactions");
+
+        let (response, rx) = oneshot::channel();
+        let req = PeerRequest::GetPooledTransactions {
+            request: GetPooledTransactions(hashes.clone()),
+            response,
+        };
+
+        if peer.request_tx.try_send(req).is_err() {
+            // the session is busy or closed, try again later
+            for hash in hashes {
+                if let Some(tx) = self.unknown_hashes.get_mut(&hash) {
+                    tx.requested_from = None;
+                    self.buffered_hashes.push_back(hash);
+                }
+            }
+            return
+        }
+
+        self.active_peers.insert(peer_id);
+        self.inflight_requests.push(Box::pin(async move {
+            let result = match rx.await {
+                Ok(result) => result,
+                Err(err) => Err(err.into()),
+            };
+            GetPooledTxResponse { peer_id, requested_hashes: hashes, result }
+        }));
+    }
+
+    /// Handles the response of a `GetPooledTransactions` request.
+    fn on_response(&mut self, response: GetPooledTxResponse) {
+        let GetPooledTxResponse { peer_id, requested_hashes, result } = response;
+        self.active_peers.remove(&peer_id);
+
+        let transactions = match result {
+            Ok(PooledTransactions(transactions)) => transactions,
+            Err(error) => {
+                trace!(target: "net::tx", ?peer_id, ?error, "Failed to fetch pooled transactions");
+                for hash in requested_hashes {
+                    self.on_missing_transaction(peer_id, hash);
+                }
+                self.queued_events.push_back(FetchEvent::FetchError { peer_id, error });
+                return
+            }
+        };
+
+        let mut requested = requested_hashes.into_iter().collect::<HashSet<_>>();
+        let mut fetched = Vec::with_capacity(transactions.len());
+        let mut bad_announcers = HashSet::new();
+        let mut has_unsolicited = false;
+
+        for transaction in transactions {
+            if !requested.remove(&transaction.hash) {
+                // not requested or delivered twice
+                has_unsolicited = true;
+                continue
+            }
+
+            if let Some(tx) = self.unknown_hashes.remove(&transaction.hash) {
+                // the hash commits to the transaction, so any mismatch is the announcer's fault
+                bad_announcers.extend(tx.announcers.into_iter().filter_map(|(id, meta)| {
+                    meta.filter(|meta| !meta.matches(&transaction)).map(|_| id)
+                }));
+            }
+
+            fetched.push(transaction);
+        }
+
+        // the peer is allowed to omit transactions, e.g. if they're no longer in its pool
+        for hash in requested {
+            self.on_missing_transaction(peer_id, hash);
+        }
+
+        if !fetched.is_empty() {
+            self.queued_events
+                .push_back(FetchEvent::TransactionsFetched { peer_id, transactions: fetched });
+        }
+        if has_unsolicited {
+            self.queued_events.push_back(FetchEvent::UnsolicitedTransactions { peer_id });
+        }
+        self.queued_events.extend(
+            bad_announcers.into_iter().map(|peer_id| FetchEvent::BadAnnouncement { peer_id }),
+        );
+    }
+
+    /// Invoked when the peer didn't deliver the requested transaction.
+    ///
+    /// The peer is no longer considered an announcer of the hash and the hash is buffered again if
+    /// there's another announcer and the retry limit isn't reached yet.
+    fn on_missing_transaction(&mut self, peer_id: PeerId, hash: TxHash) {
+        let tx = match self.unknown_hashes.get_mut(&hash) {
+            Some(tx) => tx,
+            None => return,
+        };
+
+        tx.requested_from = None;
+        tx.retries += 1;
+        tx.announcers.retain(|(id, _)| *id != peer_id);
+
+        if tx.announcers.is_empty() || tx.retries >= MAX_REQUEST_RETRIES_PER_TX_HASH {
+            trace!(target: "net::tx", ?hash, retries = tx.retries, "Dropping unfetched transaction");
+            self.unknown_hashes.remove(&hash);
+        } else {
+            self.buffered_hashes.push_front(hash);
+        }
+    }
+
+    /// Advances all inflight requests and returns the next event.
+    pub(super) fn poll(&mut self, cx: &mut Context<'_>) -> Poll<FetchEvent> {
+        loop {
+            if let Some(event) = self.queued_events.pop_front() {
+                return Poll::Ready(event)
+            }
+
+            match self.inflight_requests.poll_next_unpin(cx) {
+                Poll::Ready(Some(response)) => self.on_response(response),
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Returns the announced hashes with their eth/68 metadata, if any.
+///
+/// Returns `None` if the eth/68 message is malformed.
+pub(super) fn announced_transactions(
+    msg: NewPooledTransactionHashes,
+) -> Option<Vec<(TxHash, Option<AnnouncedTxMeta>)>> {
+    match msg {
+        NewPooledTransactionHashes::Eth66(msg) => {
+            Some(msg.0.into_iter().map(|hash| (hash, None)).collect())
+        }
+        NewPooledTransactionHashes::Eth68(msg) => {
+            if msg.hashes.len() != msg.types.len() || msg.hashes.len() != msg.sizes.len() {
+                return None
+            }
+            Some(
+                msg.hashes
+                    .into_iter()
+                    .zip(msg.types.into_iter().zip(msg.sizes))
+                    .map(|(hash, (tx_type, size))| (hash, Some(AnnouncedTxMeta { tx_type, size })))
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// The type and size of a transaction as announced via eth/68.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct AnnouncedTxMeta {
+    /// The EIP-2718 transaction type.
+    pub(super) tx_type: u8,
+    /// The announced encoded length of the transaction.
+    pub(super) size: usize,
+}
+
+// === impl AnnouncedTxMeta ===
+
+impl AnnouncedTxMeta {
+    /// Whether we can handle transactions of the announced type.
+    fn is_supported_type(&self) -> bool {
+        matches!(self.tx_type, LEGACY_TX_TYPE_ID | EIP2930_TX_TYPE_ID | EIP1559_TX_TYPE_ID)
+    }
+
+    /// Whether the delivered transaction matches the announced type and size.
+    ///
+    /// The size is accepted as either the length of the RLP encoding of the transaction or, for
+    /// typed transactions, the length of its EIP-2718 envelope without the RLP string header.
+    fn matches(&self, transaction: &TransactionSigned) -> bool {
+        let tx_type = u8::from(transaction.tx_type());
+        if self.tx_type != tx_type {
+            return false
+        }
+        let length = transaction.length();
+        self.size == length ||
+            (tx_type != LEGACY_TX_TYPE_ID && self.size + length_of_length(self.size) == length)
+    }
+}
+
+/// An announced transaction that's unknown to the pool.
+#[derive(Debug)]
+struct UnknownTransaction {
+    /// Peers that announced the hash and didn't fail to deliver it yet, in order of announcement.
+    announcers: Vec<(PeerId, Option<AnnouncedTxMeta>)>,
+    /// The peer the hash is currently requested from.
+    requested_from: Option<PeerId>,
+    /// How often the hash was requested without success.
+    retries: u8,
+}
+
+/// Hashes that are about to be requested from a single peer.
+#[derive(Debug, Default)]
+struct RequestBatch {
+    hashes: Vec<TxHash>,
+    /// The accumulated announced size of the transactions.
+    size: usize,
+}
+
+// === impl RequestBatch ===
+
+impl RequestBatch {
+    /// Whether the transaction still fits into the batch.
+    ///
+    /// A single transaction is always accepted, even if it exceeds the size limit.
+    fn has_capacity(&self, meta: Option<AnnouncedTxMeta>) -> bool {
+        let size = meta.map(|meta| meta.size).unwrap_or_default();
+        self.hashes.len() < GET_POOLED_TRANSACTIONS_SOFT_LIMIT_NUM_HASHES &&
+            (self.hashes.is_empty() ||
+                self.size + size <= POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT_BYTE_SIZE)
+    }
+
+    fn push(&mut self, hash: TxHash, meta: Option<AnnouncedTxMeta>) {
+        self.hashes.push(hash);
+        self.size += meta.map(|meta| meta.size).unwrap_or_default();
+    }
+}
+
+/// The response to a `GetPooledTransactions` request.
+struct GetPooledTxResponse {
+    peer_id: PeerId,
+    requested_hashes: Vec<TxHash>,
+    result: RequestResult<PooledTransactions>,
+}
+
+/// Events emitted by the [`TransactionFetcher`].
+#[derive(Debug)]
+pub(super) enum FetchEvent {
+    /// Requested transactions delivered by the peer.
+    TransactionsFetched { peer_id: PeerId, transactions: Vec<TransactionSigned> },
+    /// The peer responded with transactions that were not requested.
+    UnsolicitedTransactions { peer_id: PeerId },
+    /// The peer announced a type or size that doesn't match the delivered transaction.
+    BadAnnouncement { peer_id: PeerId },
+    /// The request to the peer failed.
+    FetchError { peer_id: PeerId, error: RequestError },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cache::LruCache, message::PeerRequestSender};
+    use futures::future::poll_fn;
+    use reth_eth_wire::EthVersion;
+    use reth_primitives::H256;
+    use std::num::NonZeroUsize;
+    use tokio::sync::mpsc;
+
+    fn new_peer(peer_id: PeerId) -> (Peer, mpsc::Receiver<PeerRequest>) {
+        let (tx, rx) = mpsc::channel(8);
+        let peer = Peer {
+            transactions: LruCache::new(NonZeroUsize::new(16).unwrap()),
+            request_tx: PeerRequestSender::new(peer_id, tx),
+            version: EthVersion::Eth68,
+        };
+        (peer, rx)
+    }
+
+    fn next_request(
+        rx: &mut mpsc::Receiver<PeerRequest>,
+    ) -> (Vec<TxHash>, oneshot::Sender<RequestResult<PooledTransactions>>) {
+        match rx.try_recv().expect("request sent") {
+            PeerRequest::GetPooledTransactions { request, response } => (request.0, response),
+            req => unreachable!("unexpected request {req:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_other_announcer_on_timeout() {
+        let peer_a = PeerId::random();
+        let peer_b = PeerId::random();
+        let (a, mut rx_a) = new_peer(peer_a);
+        let (b, mut rx_b) = new_peer(peer_b);
+        let peers = HashMap::from([(peer_a, a), (peer_b, b)]);
+
+        let (h1, h2) = (H256::random(), H256::random());
+        let mut fetcher = TransactionFetcher::default();
+        fetcher.on_announced_hashes(peer_a, vec![(h1, None), (h2, None)]);
+        fetcher.on_announced_hashes(peer_b, vec![(h1, None)]);
+        fetcher.schedule_requests(&peers);
+
+        // every hash is only requested once
+        let (hashes, response) = next_request(&mut rx_a);
+        assert_eq!(hashes, vec![h1, h2]);
+        assert!(rx_b.try_recv().is_err());
+        assert_eq!(fetcher.num_inflight_requests(), 1);
+
+        response.send(Err(RequestError::Timeout)).unwrap();
+        match poll_fn(|cx| fetcher.poll(cx)).await {
+            FetchEvent::FetchError { peer_id, error } => {
+                assert_eq!(peer_id, peer_a);
+                assert_eq!(error, RequestError::Timeout);
+            }
+            ev => unreachable!("unexpected event {ev:?}"),
+        }
+
+        // `h2` has no other announcer
+        assert!(!fetcher.is_tracked(&h2));
+
+        fetcher.schedule_requests(&peers);
+        let (hashes, _response) = next_request(&mut rx_b);
+        assert_eq!(hashes, vec![h1]);
+        assert!(rx_a.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_response() {
+        let peer_a = PeerId::random();
+        let peer_b = PeerId::random();
+        let (a, mut rx_a) = new_peer(peer_a);
+        let (b, _rx_b) = new_peer(peer_b);
+        let peers = HashMap::from([(peer_a, a), (peer_b, b)]);
+
+        let tx = TransactionSigned::default();
+        let valid = AnnouncedTxMeta { tx_type: LEGACY_TX_TYPE_ID, size: tx.length() };
+        let invalid = AnnouncedTxMeta { tx_type: EIP1559_TX_TYPE_ID, size: tx.length() };
+        assert!(valid.matches(&tx));
+        assert!(!invalid.matches(&tx));
+
+        let mut fetcher = TransactionFetcher::default();
+        fetcher.on_announced_hashes(peer_a, vec![(tx.hash, Some(valid))]);
+        fetcher.on_announced_hashes(peer_b, vec![(tx.hash, Some(invalid))]);
+        fetcher.schedule_requests(&peers);
+
+        let (hashes, response) = next_request(&mut rx_a);
+        assert_eq!(hashes, vec![tx.hash]);
+
+        // respond with the requested transaction and an unsolicited one
+        let mut unsolicited = TransactionSigned::default();
+        unsolicited.hash = H256::random();
+        response.send(Ok(PooledTransactions(vec![tx.clone(), unsolicited]))).unwrap();
+
+        match poll_fn(|cx| fetcher.poll(cx)).await {
+            FetchEvent::TransactionsFetched { peer_id, transactions } => {
+                assert_eq!(peer_id, peer_a);
+                assert_eq!(transactions, vec![tx.clone()]);
+            }
+            ev => unreachable!("unexpected event {ev:?}"),
+        }
+        assert!(matches!(
+            poll_fn(|cx| fetcher.poll(cx)).await,
+            FetchEvent::UnsolicitedTransactions { peer_id } if peer_id == peer_a
+        ));
+        assert!(matches!(
+            poll_fn(|cx| fetcher.poll(cx)).await,
+            FetchEvent::BadAnnouncement { peer_id } if peer_id == peer_b
+        ));
+        assert!(!fetcher.is_tracked(&tx.hash));
+    }
+
+    #[test]
+    fn test_batch_size_limit() {
+        let peer_a = PeerId::random();
+        let (a, mut rx_a) = new_peer(peer_a);
+        let peers = HashMap::from([(peer_a, a)]);
+
+        let size = POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT_BYTE_SIZE / 2;
+        let meta = AnnouncedTxMeta { tx_type: EIP1559_TX_TYPE_ID, size };
+        let hashes = std::iter::repeat_with(H256::random).take(3).collect::<Vec<_>>();
+
+        let mut fetcher = TransactionFetcher::default();
+        fetcher.on_announced_hashes(
+            peer_a,
+            hashes
+                .iter()
+                .map(|hash| (*hash, Some(meta)))
+                .chain([(H256::random(), Some(AnnouncedTxMeta { tx_type: 0x7f, size: 1 }))]),
+        );
+        // unsupported types are ignored
+        assert_eq!(fetcher.num_unknown_hashes(), 3);
+
+        fetcher.schedule_requests(&peers);
+        let (requested, _response) = next_request(&mut rx_a);
+        assert_eq!(requested, hashes[..2]);
+        assert!(fetcher.is_tracked(&hashes[2]));
+    }
+}
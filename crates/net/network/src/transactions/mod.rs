@@ -1,18 +1,19 @@
 //! Transactions management for the p2p network.
 
 use crate::{
-    cache::LruCache,
-    manager::NetworkEvent,
-    message::{PeerRequest, PeerRequestSender},
-    metrics::TransactionsManagerMetrics,
-    NetworkHandle,
+    cache::LruCache, manager::NetworkEvent, message::PeerRequestSender,
+    metrics::TransactionsManagerMetrics, NetworkHandle,
 };
-use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
+use fetcher::{FetchEvent, TransactionFetcher};
+use futures::{stream::FuturesUnordered, StreamExt};
 use reth_eth_wire::{
     EthVersion, GetPooledTransactions, NewPooledTransactionHashes, NewPooledTransactionHashes66,
     NewPooledTransactionHashes68, PooledTransactions, Transactions,
 };
-use reth_interfaces::{p2p::error::RequestResult, sync::SyncStateProvider};
+use reth_interfaces::{
+    p2p::error::{RequestError, RequestResult},
+    sync::SyncStateProvider,
+};
 use reth_network_api::{Peers, ReputationChangeKind};
 use reth_primitives::{
     FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, TransactionSigned, TxHash, H256,
@@ -23,7 +24,7 @@ use reth_transaction_pool::{
     ValidPoolTransaction,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     future::Future,
     num::NonZeroUsize,
     pin::Pin,
@@ -34,6 +35,8 @@ use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
 use tracing::trace;
 
+mod fetcher;
+
 /// Cache limit of transactions to keep track of for a single peer.
 const PEER_TRANSACTION_CACHE_LIMIT: usize = 1024 * 10;
 
@@ -88,8 +91,8 @@ pub struct TransactionsManager<Pool> {
     ///
     /// From which we get all new incoming transaction related messages.
     network_events: UnboundedReceiverStream<NetworkEvent>,
-    /// All currently active requests for pooled transactions.
-    inflight_requests: Vec<GetPooledTxRequest>,
+    /// Fetches announced transactions from peers.
+    transaction_fetcher: TransactionFetcher,
     /// All currently pending transactions grouped by peers.
     ///
     /// This way we can track incoming transactions and prevent multiple pool imports for the same
@@ -130,7 +133,7 @@ impl<Pool: TransactionPool> TransactionsManager<Pool> {
             pool,
             network,
             network_events,
-            inflight_requests: Default::default(),
+            transaction_fetcher: Default::default(),
             transactions_by_peers: Default::default(),
             pool_imports: Default::default(),
             peers: Default::default(),
@@ -284,30 +287,31 @@ where
         let mut num_already_seen = 0;
 
         if let Some(peer) = self.peers.get_mut(&peer_id) {
-            let mut hashes = msg.into_hashes();
             // keep track of the transactions the peer knows
-            for tx in hashes.iter().copied() {
+            for tx in msg.iter_hashes().copied() {
                 if !peer.transactions.insert(tx) {
                     num_already_seen += 1;
                 }
             }
 
-            self.pool.retain_unknown(&mut hashes);
-
-            if hashes.is_empty() {
-                // nothing to request
-                return
-            }
-
-            // request the missing transactions
-            let (response, rx) = oneshot::channel();
-            let req = PeerRequest::GetPooledTransactions {
-                request: GetPooledTransactions(hashes),
-                response,
+            let mut announced = match fetcher::announced_transactions(msg) {
+                Some(announced) => announced,
+                None => {
+                    // mismatching number of types, sizes and hashes
+                    self.report_bad_message(peer_id);
+                    return
+                }
             };
 
-            if peer.request_tx.try_send(req).is_ok() {
-                self.inflight_requests.push(GetPooledTxRequest { peer_id, response: rx })
+            let mut hashes = announced.iter().map(|(hash, _)| *hash).collect::<Vec<_>>();
+            self.pool.retain_unknown(&mut hashes);
+            let unknown = hashes.into_iter().collect::<HashSet<_>>();
+            announced.retain(|(hash, _)| unknown.contains(hash));
+
+            if !announced.is_empty() {
+                // request the missing transactions
+                self.transaction_fetcher.on_announced_hashes(peer_id, announced);
+                self.transaction_fetcher.schedule_requests(&self.peers);
             }
         }
 
@@ -316,6 +320,30 @@ where
         }
     }
 
+    /// Handles an event of the [`TransactionFetcher`].
+    fn on_fetch_event(&mut self, event: FetchEvent) {
+        match event {
+            FetchEvent::TransactionsFetched { peer_id, transactions } => {
+                self.import_transactions(peer_id, transactions, TransactionSource::Response);
+            }
+            FetchEvent::UnsolicitedTransactions { peer_id } |
+            FetchEvent::BadAnnouncement { peer_id } => {
+                self.report_bad_message(peer_id);
+            }
+            FetchEvent::FetchError { peer_id, error } => match error {
+                RequestError::Timeout => {
+                    self.network.reputation_change(peer_id, ReputationChangeKind::Timeout);
+                }
+                // handled when the session is closed
+                RequestError::ChannelClosed | RequestError::ConnectionDropped => {}
+                _ => self.report_bad_message(peer_id),
+            },
+        }
+
+        // the peer is idle again and missing transactions can be requested from other peers
+        self.transaction_fetcher.schedule_requests(&self.peers);
+    }
+
     /// Handles dedicated transaction events related tot the `eth` protocol.
     fn on_network_tx_event(&mut self, event: NetworkTransactionEvent) {
         match event {
@@ -346,6 +374,7 @@ where
             NetworkEvent::SessionClosed { peer_id, .. } => {
                 // remove the peer
                 self.peers.remove(&peer_id);
+                self.transaction_fetcher.on_session_closed(&peer_id);
             }
             NetworkEvent::SessionEstablished { peer_id, messages, version, .. } => {
                 // insert a new peer into the peerset
@@ -417,8 +446,12 @@ where
                 // If we received the transactions as the response to our GetPooledTransactions
                 // requests (based on received `NewPooledTransactionHashes`) then we already
                 // recorded the hashes in [`Self::on_new_pooled_transaction_hashes`]
-                if source.is_broadcast() && !peer.transactions.insert(tx.hash) {
-                    num_already_seen += 1;
+                if source.is_broadcast() {
+                    if !peer.transactions.insert(tx.hash) {
+                        num_already_seen += 1;
+                    }
+                    // no need to fetch it anymore if it was announced by other peers
+                    self.transaction_fetcher.on_received_transaction(&tx.hash);
                 }
 
                 match self.transactions_by_peers.entry(tx.hash) {
@@ -494,25 +527,16 @@ where
             this.on_network_tx_event(event);
         }
 
-        // Advance all requests.
-        // We remove each request one by one and add them back.
-        for idx in (0..this.inflight_requests.len()).rev() {
-            let mut req = this.inflight_requests.swap_remove(idx);
-            match req.response.poll_unpin(cx) {
-                Poll::Pending => {
-                    this.inflight_requests.push(req);
-                }
-                Poll::Ready(Ok(Ok(txs))) => {
-                    this.import_transactions(req.peer_id, txs.0, TransactionSource::Response);
-                }
-                Poll::Ready(Ok(Err(_))) => {
-                    this.report_bad_message(req.peer_id);
-                }
-                Poll::Ready(Err(_)) => {
-                    this.report_bad_message(req.peer_id);
-                }
-            }
+        // Advance all requests for announced transactions.
+        while let Poll::Ready(event) = this.transaction_fetcher.poll(cx) {
+            this.on_fetch_event(event);
         }
+        this.metrics
+            .inflight_transaction_requests
+            .set(this.transaction_fetcher.num_inflight_requests() as f64);
+        this.metrics
+            .unknown_announced_transactions
+            .set(this.transaction_fetcher.num_unknown_hashes() as f64);
 
         // Advance all imports
         while let Poll::Ready(Some(import_res)) = this.pool_imports.poll_next_unpin(cx) {
@@ -615,7 +639,8 @@ impl PooledTransactionsHashesBuilder {
 enum TransactionSource {
     /// Transactions were broadcast to us via [`Transactions`] message.
     Broadcast,
-    /// Transactions were sent as the response of [`GetPooledTxRequest`] issued by us.
+    /// Transactions were sent as the response of a [`GetPooledTransactions`] request issued by
+    /// us.
     Response,
 }
 
@@ -628,13 +653,6 @@ impl TransactionSource {
     }
 }
 
-/// An inflight request for `PooledTransactions` from a peer
-#[allow(missing_docs)]
-struct GetPooledTxRequest {
-    peer_id: PeerId,
-    response: oneshot::Receiver<RequestResult<PooledTransactions>>,
-}
-
 /// Tracks a single peer
 struct Peer {
     /// Keeps track of transactions that we know the peer has seen.
@@ -1,6 +1,7 @@
 //! Tests for eth related requests
 
 use rand::Rng;
+use reth_eth_wire::EthMessageID;
 use reth_interfaces::p2p::{
     bodies::client::BodiesClient,
     headers::client::{HeadersClient, HeadersRequest},
@@ -73,6 +74,14 @@ async fn test_get_body() {
             BlockBody { transactions: block.body, ommers: block.ommers, withdrawals: None };
         assert_eq!(blocks[0], expected);
     }
+
+    // all requests and responses are accounted for
+    let stats = handle0.session_messages().await.unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].peer_id, *handle1.peer_id());
+    assert_eq!(stats[0].outbound.get(EthMessageID::GetBlockBodies).messages, 100);
+    assert_eq!(stats[0].inbound.get(EthMessageID::BlockBodies).messages, 100);
+    assert!(stats[0].inbound.get(EthMessageID::BlockBodies).payload_bytes > 0);
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -1,15 +1,17 @@
-use crate::{Address, H256};
-use reth_codecs::{main_codec, Compact};
+use crate::{Address, BlockNumber, H256};
+use bytes::Buf;
+use reth_codecs::{derive_arbitrary, main_codec, Compact};
+use serde::{Deserialize, Serialize};
 
 /// Saves the progress of MerkleStage
 #[main_codec]
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ProofCheckpoint {
     /// The next hashed account to insert into the trie.
     pub hashed_address: Option<H256>,
     /// The next storage entry to insert into the trie.
     pub storage_key: Option<H256>,
-    /// Current intermediate root for `AccountsTrie`.
+    /// Current intermediate root of the state trie.
     pub account_root: Option<H256>,
     /// Current intermediate storage root from an account.
     pub storage_root: Option<H256>,
@@ -17,7 +19,7 @@ pub struct ProofCheckpoint {
 
 /// Saves the progress of AccountHashing
 #[main_codec]
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub struct AccountHashingCheckpoint {
     /// The next account to start hashing from
     pub address: Option<Address>,
@@ -25,11 +27,13 @@ pub struct AccountHashingCheckpoint {
     pub from: u64,
     /// Last transition id
     pub to: u64,
+    /// Progress measured in hashed entities.
+    pub progress: EntitiesCheckpoint,
 }
 
 /// Saves the progress of StorageHashing
 #[main_codec]
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub struct StorageHashingCheckpoint {
     /// The next account to start hashing from
     pub address: Option<Address>,
@@ -39,4 +43,203 @@ pub struct StorageHashingCheckpoint {
     pub from: u64,
     /// Last transition id
     pub to: u64,
+    /// Progress measured in hashed entities.
+    pub progress: EntitiesCheckpoint,
+}
+
+/// Saves the progress of a stage that processes a countable number of entities, like accounts or
+/// storage slots.
+#[main_codec]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EntitiesCheckpoint {
+    /// Number of entities already processed.
+    pub processed: u64,
+    /// Total number of entities to process, if known.
+    pub total: Option<u64>,
+}
+
+impl EntitiesCheckpoint {
+    /// Returns the progress as a fraction of the total, if the total is known and not zero.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total.filter(|total| *total > 0).map(|total| self.processed as f64 / total as f64)
+    }
+}
+
+/// Saves the progress of the execution stage, measured in gas.
+#[main_codec]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExecutionCheckpoint {
+    /// The block the stage was executing to when the progress was measured.
+    pub target: BlockNumber,
+    /// The gas used by the executed blocks and by all blocks up to `target`.
+    pub progress: EntitiesCheckpoint,
+}
+
+/// Stage-specific progress within the block range a stage is executing.
+#[derive_arbitrary(compact)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StageUnitCheckpoint {
+    /// Saves the progress of AccountHashing.
+    Account(AccountHashingCheckpoint),
+    /// Saves the progress of StorageHashing.
+    Storage(StorageHashingCheckpoint),
+    /// Saves the progress of MerkleStage.
+    Merkle(ProofCheckpoint),
+    /// Saves the progress of a stage in processed entities.
+    Entities(EntitiesCheckpoint),
+    /// Saves the progress of the execution stage.
+    Execution(ExecutionCheckpoint),
+}
+
+impl StageUnitCheckpoint {
+    /// Returns the progress in processed entities, if the stage tracks it.
+    pub fn entities(&self) -> Option<EntitiesCheckpoint> {
+        match self {
+            StageUnitCheckpoint::Account(checkpoint) => Some(checkpoint.progress),
+            StageUnitCheckpoint::Storage(checkpoint) => Some(checkpoint.progress),
+            StageUnitCheckpoint::Merkle(_) => None,
+            StageUnitCheckpoint::Entities(checkpoint) => Some(*checkpoint),
+            StageUnitCheckpoint::Execution(checkpoint) => Some(checkpoint.progress),
+        }
+    }
+}
+
+// NOTE: Implemented manually, since the derived implementation of enums returns the variant as
+// the length, which can't be nested in an `Option`.
+impl Compact for StageUnitCheckpoint {
+    fn to_compact(self, buf: &mut impl bytes::BufMut) -> usize {
+        let mut inner = Vec::new();
+        let variant: u8 = match self {
+            StageUnitCheckpoint::Account(data) => {
+                data.to_compact(&mut inner);
+                0
+            }
+            StageUnitCheckpoint::Storage(data) => {
+                data.to_compact(&mut inner);
+                1
+            }
+            StageUnitCheckpoint::Merkle(data) => {
+                data.to_compact(&mut inner);
+                2
+            }
+            StageUnitCheckpoint::Entities(data) => {
+                data.to_compact(&mut inner);
+                3
+            }
+            StageUnitCheckpoint::Execution(data) => {
+                data.to_compact(&mut inner);
+                4
+            }
+        };
+        buf.put_u8(variant);
+        buf.put_slice(&inner);
+        inner.len() + 1
+    }
+
+    fn from_compact(mut buf: &[u8], len: usize) -> (Self, &[u8])
+    where
+        Self: Sized,
+    {
+        let variant = buf.get_u8();
+        let len = len - 1;
+        match variant {
+            0 => {
+                let (data, buf) = AccountHashingCheckpoint::from_compact(buf, len);
+                (Self::Account(data), buf)
+            }
+            1 => {
+                let (data, buf) = StorageHashingCheckpoint::from_compact(buf, len);
+                (Self::Storage(data), buf)
+            }
+            2 => {
+                let (data, buf) = ProofCheckpoint::from_compact(buf, len);
+                (Self::Merkle(data), buf)
+            }
+            3 => {
+                let (data, buf) = EntitiesCheckpoint::from_compact(buf, len);
+                (Self::Entities(data), buf)
+            }
+            4 => {
+                let (data, buf) = ExecutionCheckpoint::from_compact(buf, len);
+                (Self::Execution(data), buf)
+            }
+            _ => unreachable!("Junk data in database: unknown StageUnitCheckpoint variant"),
+        }
+    }
+}
+
+/// Saves the progress of a stage.
+#[main_codec]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StageCheckpoint {
+    /// The highest block the stage has fully processed.
+    pub block_number: BlockNumber,
+    /// Stage-specific progress beyond `block_number`, if the stage tracks it.
+    pub stage_checkpoint: Option<StageUnitCheckpoint>,
+}
+
+impl StageCheckpoint {
+    /// Creates a checkpoint at the block, without stage-specific progress.
+    pub fn new(block_number: BlockNumber) -> Self {
+        Self { block_number, stage_checkpoint: None }
+    }
+
+    /// Sets the stage-specific progress of the checkpoint.
+    pub fn with_stage_checkpoint(mut self, stage_checkpoint: StageUnitCheckpoint) -> Self {
+        self.stage_checkpoint = Some(stage_checkpoint);
+        self
+    }
+
+    /// Returns the progress in processed entities, if the stage tracks it.
+    pub fn entities(&self) -> Option<EntitiesCheckpoint> {
+        self.stage_checkpoint.as_ref().and_then(StageUnitCheckpoint::entities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_checkpoint_roundtrip() {
+        let checkpoints = [
+            StageCheckpoint::new(10),
+            StageCheckpoint::new(20).with_stage_checkpoint(StageUnitCheckpoint::Account(
+                AccountHashingCheckpoint {
+                    address: Some(Address::random()),
+                    from: 1,
+                    to: 2,
+                    progress: EntitiesCheckpoint { processed: 3, total: Some(4) },
+                },
+            )),
+            StageCheckpoint::new(30).with_stage_checkpoint(StageUnitCheckpoint::Storage(
+                StorageHashingCheckpoint {
+                    address: Some(Address::random()),
+                    storage: Some(H256::random()),
+                    from: 1,
+                    to: 2,
+                    progress: EntitiesCheckpoint { processed: 3, total: None },
+                },
+            )),
+            StageCheckpoint::new(40).with_stage_checkpoint(StageUnitCheckpoint::Merkle(
+                ProofCheckpoint { hashed_address: Some(H256::random()), ..Default::default() },
+            )),
+            StageCheckpoint::new(50).with_stage_checkpoint(StageUnitCheckpoint::Entities(
+                EntitiesCheckpoint { processed: 0, total: Some(0) },
+            )),
+            StageCheckpoint::new(60).with_stage_checkpoint(StageUnitCheckpoint::Execution(
+                ExecutionCheckpoint {
+                    target: 70,
+                    progress: EntitiesCheckpoint { processed: 21_000, total: Some(42_000) },
+                },
+            )),
+        ];
+
+        for checkpoint in checkpoints {
+            let mut buf = Vec::new();
+            checkpoint.to_compact(&mut buf);
+            let (decoded, _) = StageCheckpoint::from_compact(&buf, buf.len());
+            assert_eq!(decoded, checkpoint);
+        }
+    }
 }
@@ -28,9 +28,11 @@ mod integer_list;
 mod log;
 mod net;
 mod peer;
+mod prune;
 mod receipt;
 mod storage;
 mod transaction;
+mod trie;
 mod withdrawal;
 
 /// Helper function for calculating Merkle proofs and hashes
@@ -47,7 +49,10 @@ pub use chain::{
     AllGenesisFormats, Chain, ChainInfo, ChainSpec, ChainSpecBuilder, ForkCondition, GOERLI,
     MAINNET, SEPOLIA,
 };
-pub use checkpoints::{AccountHashingCheckpoint, ProofCheckpoint, StorageHashingCheckpoint};
+pub use checkpoints::{
+    AccountHashingCheckpoint, EntitiesCheckpoint, ExecutionCheckpoint, ProofCheckpoint,
+    StageCheckpoint, StageUnitCheckpoint, StorageHashingCheckpoint,
+};
 pub use constants::{
     EMPTY_OMMER_ROOT, GOERLI_GENESIS, KECCAK_EMPTY, MAINNET_GENESIS, SEPOLIA_GENESIS,
 };
@@ -63,10 +68,11 @@ pub use net::{
     SEPOLIA_BOOTNODES,
 };
 pub use peer::{PeerId, WithPeerId};
+pub use prune::{PruneCheckpoint, PruneMode, PruneModes, PruneSegment, MINIMUM_PRUNING_DISTANCE};
 pub use receipt::{Receipt, ReceiptWithBloom};
 pub use revm_primitives::JumpMap;
 pub use serde_helper::JsonU256;
-pub use storage::{StorageEntry, StorageTrieEntry};
+pub use storage::StorageEntry;
 pub use transaction::{
     util::secp256k1::sign_message, AccessList, AccessListItem, AccessListWithGasUsed,
     FromRecoveredTransaction, IntoRecoveredTransaction, InvalidTransactionError, Signature,
@@ -74,6 +80,7 @@ pub use transaction::{
     TxEip1559, TxEip2930, TxLegacy, TxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID,
     LEGACY_TX_TYPE_ID,
 };
+pub use trie::{StorageBranchEntry, StoredNibbles, StoredNibblesSubKey, MAX_TRIE_PATH_LEN};
 pub use withdrawal::Withdrawal;
 
 /// A block hash.
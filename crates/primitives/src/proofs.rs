@@ -128,6 +128,7 @@ mod tests {
                 tx_type: TxType::EIP2930,
                 success: true,
                 cumulative_gas_used: 102068,
+                state_root: None,
                 logs,
             },
             bloom,
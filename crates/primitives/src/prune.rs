@@ -0,0 +1,221 @@
+use crate::{BlockNumber, TxNumber};
+use reth_codecs::{main_codec, Compact};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Minimum distance from the tip that history segments can be pruned to.
+///
+/// Changesets of this many recent blocks are kept so that reorgs can still be unwound.
+pub const MINIMUM_PRUNING_DISTANCE: u64 = 128;
+
+/// A segment of the database that can be pruned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneSegment {
+    /// Receipts of executed transactions.
+    Receipts,
+    /// Transaction hash to transaction number lookups.
+    TransactionLookup,
+    /// Account changesets and the account history index.
+    AccountHistory,
+    /// Storage changesets and the storage history index.
+    StorageHistory,
+}
+
+impl PruneSegment {
+    /// All prunable segments.
+    pub const ALL: [PruneSegment; 4] = [
+        PruneSegment::Receipts,
+        PruneSegment::TransactionLookup,
+        PruneSegment::AccountHistory,
+        PruneSegment::StorageHistory,
+    ];
+
+    /// Returns the minimum distance from the tip the segment can be pruned to.
+    pub fn min_distance(&self) -> u64 {
+        match self {
+            PruneSegment::Receipts | PruneSegment::TransactionLookup => 0,
+            PruneSegment::AccountHistory | PruneSegment::StorageHistory => MINIMUM_PRUNING_DISTANCE,
+        }
+    }
+}
+
+impl From<PruneSegment> for u8 {
+    fn from(segment: PruneSegment) -> Self {
+        segment as u8
+    }
+}
+
+impl TryFrom<u8> for PruneSegment {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        PruneSegment::ALL.get(value as usize).copied().ok_or(value)
+    }
+}
+
+impl fmt::Display for PruneSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PruneSegment::Receipts => "receipts",
+            PruneSegment::TransactionLookup => "transaction lookup",
+            PruneSegment::AccountHistory => "account history",
+            PruneSegment::StorageHistory => "storage history",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Defines which data of a segment is pruned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneMode {
+    /// Prune all data up to the tip.
+    Full,
+    /// Keep the data of the last `N` blocks before the tip.
+    Distance(u64),
+    /// Keep the data of all blocks starting at the given block number.
+    Before(BlockNumber),
+}
+
+impl PruneMode {
+    /// Returns the highest block whose data can be pruned with the given chain tip, if any.
+    pub fn prune_target_block(&self, tip: BlockNumber) -> Option<BlockNumber> {
+        match self {
+            PruneMode::Full => Some(tip),
+            PruneMode::Distance(distance) => tip.checked_sub(*distance),
+            PruneMode::Before(block) => block.checked_sub(1).map(|block| block.min(tip)),
+        }
+    }
+
+    /// Returns true if the data of the block should be pruned with the given chain tip.
+    pub fn should_prune(&self, block: BlockNumber, tip: BlockNumber) -> bool {
+        self.prune_target_block(tip).map_or(false, |target| block <= target)
+    }
+}
+
+/// Prune modes of all segments, segments without a mode are never pruned.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PruneModes {
+    /// Receipts pruning.
+    pub receipts: Option<PruneMode>,
+    /// Transaction lookup pruning.
+    pub transaction_lookup: Option<PruneMode>,
+    /// Account history pruning.
+    pub account_history: Option<PruneMode>,
+    /// Storage history pruning.
+    pub storage_history: Option<PruneMode>,
+}
+
+impl PruneModes {
+    /// Prune modes that keep all data.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no segment is pruned.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the prune mode of the segment.
+    pub fn get(&self, segment: PruneSegment) -> Option<PruneMode> {
+        match segment {
+            PruneSegment::Receipts => self.receipts,
+            PruneSegment::TransactionLookup => self.transaction_lookup,
+            PruneSegment::AccountHistory => self.account_history,
+            PruneSegment::StorageHistory => self.storage_history,
+        }
+    }
+
+    /// Returns the highest block of the segment that can be pruned with the given chain tip.
+    ///
+    /// This respects the [minimum distance](PruneSegment::min_distance) of the segment.
+    pub fn prune_target_block(
+        &self,
+        segment: PruneSegment,
+        tip: BlockNumber,
+    ) -> Option<BlockNumber> {
+        let target = self.get(segment)?.prune_target_block(tip)?;
+        let max_target = tip.checked_sub(segment.min_distance())?;
+        Some(target.min(max_target))
+    }
+
+    /// Iterates over all segments that are pruned and their modes.
+    pub fn iter(&self) -> impl Iterator<Item = (PruneSegment, PruneMode)> + '_ {
+        PruneSegment::ALL
+            .into_iter()
+            .filter_map(|segment| self.get(segment).map(|mode| (segment, mode)))
+    }
+}
+
+/// Saves the pruning progress of a segment.
+#[main_codec]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PruneCheckpoint {
+    /// Highest pruned block number.
+    pub block_number: BlockNumber,
+    /// Highest pruned transaction number, if the segment is keyed by transactions.
+    pub tx_number: Option<TxNumber>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_target_block() {
+        assert_eq!(PruneMode::Full.prune_target_block(100), Some(100));
+        assert_eq!(PruneMode::Distance(10).prune_target_block(100), Some(90));
+        assert_eq!(PruneMode::Distance(200).prune_target_block(100), None);
+        assert_eq!(PruneMode::Before(50).prune_target_block(100), Some(49));
+        assert_eq!(PruneMode::Before(500).prune_target_block(100), Some(100));
+        assert_eq!(PruneMode::Before(0).prune_target_block(100), None);
+
+        assert!(PruneMode::Before(50).should_prune(49, 100));
+        assert!(!PruneMode::Before(50).should_prune(50, 100));
+    }
+
+    #[test]
+    fn segment_min_distance() {
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Full),
+            account_history: Some(PruneMode::Full),
+            storage_history: Some(PruneMode::Before(1_000)),
+            ..Default::default()
+        };
+        assert_eq!(modes.prune_target_block(PruneSegment::Receipts, 1_000), Some(1_000));
+        assert_eq!(modes.prune_target_block(PruneSegment::TransactionLookup, 1_000), None);
+        assert_eq!(
+            modes.prune_target_block(PruneSegment::AccountHistory, 1_000),
+            Some(1_000 - MINIMUM_PRUNING_DISTANCE)
+        );
+        assert_eq!(
+            modes.prune_target_block(PruneSegment::StorageHistory, 1_000),
+            Some(1_000 - MINIMUM_PRUNING_DISTANCE)
+        );
+        assert_eq!(modes.prune_target_block(PruneSegment::AccountHistory, 100), None);
+        assert_eq!(modes.iter().count(), 3);
+    }
+
+    #[test]
+    fn deserialize_prune_modes() {
+        let modes: PruneModes = serde_json::from_str(
+            r#"{"receipts":"full","account_history":{"distance":100000},"storage_history":{"before":15537394}}"#,
+        )
+        .unwrap();
+        assert_eq!(modes.receipts, Some(PruneMode::Full));
+        assert_eq!(modes.transaction_lookup, None);
+        assert_eq!(modes.account_history, Some(PruneMode::Distance(100_000)));
+        assert_eq!(modes.storage_history, Some(PruneMode::Before(15_537_394)));
+    }
+
+    #[test]
+    fn segment_u8_roundtrip() {
+        for segment in PruneSegment::ALL {
+            assert_eq!(PruneSegment::try_from(u8::from(segment)), Ok(segment));
+        }
+        assert_eq!(PruneSegment::try_from(4), Err(4));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{bloom::logs_bloom, Bloom, Log, TxType};
+use crate::{bloom::logs_bloom, Bloom, Log, TxType, H256};
 use bytes::{Buf, BufMut, BytesMut};
 use reth_codecs::{main_codec, Compact};
 use reth_rlp::{length_of_length, Decodable, Encodable};
@@ -16,6 +16,11 @@ pub struct Receipt {
     pub success: bool,
     /// Gas used
     pub cumulative_gas_used: u64,
+    /// The state root after the transaction.
+    ///
+    /// Receipts of transactions before Byzantium contain the state root instead of the status,
+    /// see <https://eips.ethereum.org/EIPS/eip-658>.
+    pub state_root: Option<H256>,
     /// Log send from contracts.
     pub logs: Vec<Log>,
 }
@@ -67,7 +72,10 @@ impl ReceiptWithBloom {
     fn receipt_rlp_header(&self) -> reth_rlp::Header {
         let mut rlp_head = reth_rlp::Header { list: true, payload_length: 0 };
 
-        rlp_head.payload_length += self.receipt.success.length();
+        rlp_head.payload_length += match self.receipt.state_root {
+            Some(state_root) => state_root.length(),
+            None => self.receipt.success.length(),
+        };
         rlp_head.payload_length += self.receipt.cumulative_gas_used.length();
         rlp_head.payload_length += self.bloom.length();
         rlp_head.payload_length += self.receipt.logs.length();
@@ -78,7 +86,10 @@ impl ReceiptWithBloom {
     /// Encodes the receipt data.
     fn encode_fields(&self, out: &mut dyn BufMut) {
         self.receipt_rlp_header().encode(out);
-        self.receipt.success.encode(out);
+        match self.receipt.state_root {
+            Some(state_root) => state_root.encode(out),
+            None => self.receipt.success.encode(out),
+        }
         self.receipt.cumulative_gas_used.encode(out);
         self.bloom.encode(out);
         self.receipt.logs.encode(out);
@@ -127,12 +138,18 @@ impl ReceiptWithBloom {
         }
         let started_len = b.len();
 
-        let success = reth_rlp::Decodable::decode(b)?;
+        // the status is a single byte, while a pre-Byzantium state root is a 32 bytes string
+        let (success, state_root) = if b.first() == Some(&(reth_rlp::EMPTY_STRING_CODE + 32)) {
+            (false, Some(reth_rlp::Decodable::decode(b)?))
+        } else {
+            (reth_rlp::Decodable::decode(b)?, None)
+        };
         let cumulative_gas_used = reth_rlp::Decodable::decode(b)?;
         let bloom = reth_rlp::Decodable::decode(b)?;
         let logs = reth_rlp::Decodable::decode(b)?;
 
-        let this = Self { receipt: Receipt { tx_type, success, cumulative_gas_used, logs }, bloom };
+        let receipt = Receipt { tx_type, success, cumulative_gas_used, state_root, logs };
+        let this = Self { receipt, bloom };
         let consumed = started_len - b.len();
         if consumed != rlp_head.payload_length {
             return Err(reth_rlp::DecodeError::ListLengthMismatch {
@@ -214,6 +231,7 @@ mod tests {
             receipt: Receipt {
                 tx_type: TxType::Legacy,
                 cumulative_gas_used: 0x1u64,
+                state_root: None,
                 logs: vec![Log {
                     address: Address::from_str("0000000000000000000000000000000000000011").unwrap(),
                     topics: vec![
@@ -250,6 +268,7 @@ mod tests {
             receipt: Receipt {
                 tx_type: TxType::Legacy,
                 cumulative_gas_used: 0x1u64,
+                state_root: None,
                 logs: vec![Log {
                     address: Address::from_str("0000000000000000000000000000000000000011").unwrap(),
                     topics: vec![
@@ -272,4 +291,25 @@ mod tests {
         let receipt = ReceiptWithBloom::decode(&mut &data[..]).unwrap();
         assert_eq!(receipt, expected);
     }
+
+    #[test]
+    fn encode_decode_receipt_with_state_root() {
+        let receipt = ReceiptWithBloom {
+            receipt: Receipt {
+                tx_type: TxType::Legacy,
+                success: false,
+                cumulative_gas_used: 21_000,
+                state_root: Some(H256::from_low_u64_be(0x1234)),
+                logs: vec![],
+            },
+            bloom: Bloom::zero(),
+        };
+
+        let mut data = vec![];
+        receipt.encode(&mut data);
+        assert_eq!(receipt.length(), data.len());
+        // the state root takes the place of the status
+        assert_eq!(data[3], reth_rlp::EMPTY_STRING_CODE + 32);
+        assert_eq!(ReceiptWithBloom::decode(&mut &data[..]).unwrap(), receipt);
+    }
 }
@@ -217,6 +217,15 @@ impl Transaction {
         }
     }
 
+    /// Gets the transaction's access list, or `None` for legacy transactions.
+    pub fn access_list(&self) -> Option<&AccessList> {
+        match self {
+            Transaction::Legacy(_) => None,
+            Transaction::Eip2930(TxEip2930 { access_list, .. }) |
+            Transaction::Eip1559(TxEip1559 { access_list, .. }) => Some(access_list),
+        }
+    }
+
     /// Get transaction type
     pub fn tx_type(&self) -> TxType {
         match self {
@@ -274,6 +283,25 @@ impl Transaction {
         }
     }
 
+    /// The price per gas paid by the transaction in a block with the given base fee.
+    ///
+    /// For eip1559 transactions this is the base fee plus the priority fee, capped by the max fee
+    /// per gas. For legacy and eip2930 transactions this is the gas price.
+    pub fn effective_gas_price(&self, base_fee: Option<u64>) -> u128 {
+        match self {
+            Transaction::Legacy(TxLegacy { gas_price, .. }) |
+            Transaction::Eip2930(TxEip2930 { gas_price, .. }) => *gas_price,
+            Transaction::Eip1559(TxEip1559 {
+                max_fee_per_gas, max_priority_fee_per_gas, ..
+            }) => match base_fee {
+                Some(base_fee) => {
+                    (*max_fee_per_gas).min(base_fee as u128 + *max_priority_fee_per_gas)
+                }
+                None => *max_fee_per_gas,
+            },
+        }
+    }
+
     /// Get the transaction's input field.
     pub fn input(&self) -> &Bytes {
         match self {
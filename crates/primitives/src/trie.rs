@@ -0,0 +1,115 @@
+use reth_codecs::Compact;
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of nibbles of a trie path, the length of an unpacked hashed key.
+pub const MAX_TRIE_PATH_LEN: usize = 64;
+
+/// The path of a trie node, stored with one nibble per byte.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StoredNibbles {
+    /// The nibbles of the path.
+    pub inner: Vec<u8>,
+}
+
+impl From<Vec<u8>> for StoredNibbles {
+    fn from(inner: Vec<u8>) -> Self {
+        Self { inner }
+    }
+}
+
+/// The path of a storage trie node, used as the subkey of a `DUPSORT` table.
+///
+/// Subkeys are compared as a prefix of the value, so the path is padded to
+/// [MAX_TRIE_PATH_LEN] nibbles and followed by its length. This keeps the order of the paths:
+/// a path comes right before the paths it is a prefix of.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StoredNibblesSubKey(pub StoredNibbles);
+
+impl StoredNibblesSubKey {
+    /// The length of an encoded subkey.
+    pub const ENCODED_LEN: usize = MAX_TRIE_PATH_LEN + 1;
+
+    /// Encodes the padded path followed by its length.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let nibbles = &self.0.inner;
+        assert!(nibbles.len() <= MAX_TRIE_PATH_LEN, "trie path is longer than a hashed key");
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[..nibbles.len()].copy_from_slice(nibbles);
+        out[MAX_TRIE_PATH_LEN] = nibbles.len() as u8;
+        out
+    }
+
+    /// Decodes a subkey encoded by [StoredNibblesSubKey::to_bytes], returning `None` if the
+    /// encoding is invalid.
+    pub fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let len = *buf.get(MAX_TRIE_PATH_LEN)? as usize;
+        (buf.len() == Self::ENCODED_LEN && len <= MAX_TRIE_PATH_LEN)
+            .then(|| Self(buf[..len].to_vec().into()))
+    }
+}
+
+impl From<Vec<u8>> for StoredNibblesSubKey {
+    fn from(inner: Vec<u8>) -> Self {
+        Self(inner.into())
+    }
+}
+
+/// Account storage trie branch node, keyed by its path in the storage trie.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageBranchEntry {
+    /// The path of the branch node.
+    pub nibbles: StoredNibblesSubKey,
+    /// Reference to the branch node: the RLP of the node if it is shorter than 32 bytes, its
+    /// RLP-encoded hash otherwise.
+    pub node: Vec<u8>,
+}
+
+// NOTE: Manually encode the subkey so it stays a prefix of the value, see `StorageEntry`.
+impl Compact for StorageBranchEntry {
+    fn to_compact(self, buf: &mut impl bytes::BufMut) -> usize {
+        buf.put_slice(&self.nibbles.to_bytes());
+        buf.put_slice(&self.node[..]);
+        self.node.len() + StoredNibblesSubKey::ENCODED_LEN
+    }
+
+    fn from_compact(buf: &[u8], len: usize) -> (Self, &[u8])
+    where
+        Self: Sized,
+    {
+        let nibbles = StoredNibblesSubKey::from_bytes(&buf[..StoredNibblesSubKey::ENCODED_LEN])
+            .expect("valid subkey");
+        let node = Vec::from(&buf[StoredNibblesSubKey::ENCODED_LEN..len]);
+        (Self { nibbles, node }, &buf[len..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subkey_order() {
+        let paths: Vec<Vec<u8>> =
+            vec![vec![], vec![0], vec![0, 0], vec![0, 0, 15], vec![0, 1], vec![1], vec![15; 63]];
+        let encoded = paths
+            .iter()
+            .map(|path| StoredNibblesSubKey::from(path.clone()).to_bytes())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+
+        for (path, encoded) in paths.into_iter().zip(encoded) {
+            assert_eq!(StoredNibblesSubKey::from_bytes(&encoded), Some(path.into()));
+        }
+    }
+
+    #[test]
+    fn storage_branch_entry_compact() {
+        let entry =
+            StorageBranchEntry { nibbles: vec![1, 2, 3].into(), node: vec![0xc2, 0x80, 0x80] };
+        let mut buf = vec![];
+        let len = entry.clone().to_compact(&mut buf);
+        assert_eq!(StorageBranchEntry::from_compact(&buf, len), (entry, &[][..]));
+    }
+}
@@ -0,0 +1,368 @@
+use reth_primitives::{bytes::Bytes, Address};
+use revm::{
+    interpreter::{opcode, CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
+    precompile::{Precompiles, SpecId as PrecompilesSpecId},
+    primitives::{ExecutionResult, SpecId},
+    Database, EVMData, Inspector,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+    ops::AddAssign,
+    time::{Duration, Instant},
+};
+
+/// Gas and wall time spent, aggregated over a number of executions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GasStats {
+    /// The number of executions.
+    pub count: u64,
+    /// The total gas spent.
+    pub gas: u64,
+    /// The total wall time spent.
+    pub time: Duration,
+}
+
+impl AddAssign for GasStats {
+    fn add_assign(&mut self, other: Self) {
+        self.count += other.count;
+        self.gas += other.gas;
+        self.time += other.time;
+    }
+}
+
+/// Where gas and time were spent during the execution of one or more transactions.
+///
+/// The stats of opcodes and contracts exclude the calls they make, so that gas and time are not
+/// counted twice.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasProfile {
+    /// The executed transactions, with the gas they used.
+    pub transactions: GasStats,
+    /// The executed opcodes.
+    pub opcodes: BTreeMap<u8, GasStats>,
+    /// The calls to precompiles, by address.
+    pub precompiles: BTreeMap<Address, GasStats>,
+    /// The executed contract code, by code address.
+    pub contracts: BTreeMap<Address, GasStats>,
+    /// The gas spent per call stack, with the executed opcode as the last frame.
+    pub stacks: BTreeMap<String, u64>,
+}
+
+impl GasProfile {
+    /// Adds the stats of the other profile to this one.
+    pub fn merge(&mut self, other: GasProfile) {
+        self.transactions += other.transactions;
+        for (opcode, stats) in other.opcodes {
+            *self.opcodes.entry(opcode).or_default() += stats;
+        }
+        for (address, stats) in other.precompiles {
+            *self.precompiles.entry(address).or_default() += stats;
+        }
+        for (address, stats) in other.contracts {
+            *self.contracts.entry(address).or_default() += stats;
+        }
+        for (stack, gas) in other.stacks {
+            *self.stacks.entry(stack).or_default() += gas;
+        }
+    }
+
+    /// Writes the stats as CSV, with a `kind,name,count,gas,time_ns` header.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "kind,name,count,gas,time_ns")?;
+        let mut write_row = |kind: &str, name: String, stats: &GasStats| {
+            writeln!(
+                writer,
+                "{kind},{name},{},{},{}",
+                stats.count,
+                stats.gas,
+                stats.time.as_nanos()
+            )
+        };
+
+        write_row("transactions", "all".to_string(), &self.transactions)?;
+        for (opcode, stats) in &self.opcodes {
+            write_row("opcode", opcode_name(*opcode), stats)?;
+        }
+        for (address, stats) in &self.precompiles {
+            write_row("precompile", format!("{address:?}"), stats)?;
+        }
+        for (address, stats) in &self.contracts {
+            write_row("contract", format!("{address:?}"), stats)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the gas per call stack in the folded format used by flamegraph tools, e.g.
+    /// `0x..;0x..;SSTORE 20000`.
+    pub fn write_folded<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for (stack, gas) in &self.stacks {
+            writeln!(writer, "{stack} {gas}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An [Inspector] profiling the gas and wall time spent per opcode, per precompile and per
+/// contract during the execution of a single transaction.
+///
+/// Once the transaction was executed, the profile is returned by [GasProfiler::into_profile].
+///
+/// Measuring the time of every step slows down the execution significantly, so the times are
+/// mostly useful relative to each other.
+#[derive(Debug, Default)]
+pub struct GasProfiler {
+    profile: GasProfile,
+    /// The call frames being executed.
+    frames: Vec<Frame>,
+    /// The time spent in the outermost frames.
+    time: Duration,
+    /// The precompiles of the spec the transaction is executed with.
+    precompiles: Option<&'static Precompiles>,
+}
+
+/// A call frame, with the stats of the code it executed.
+#[derive(Debug)]
+struct Frame {
+    /// The code address, `None` for a contract creation.
+    address: Option<Address>,
+    started: Instant,
+    opcodes: HashMap<u8, GasStats>,
+    /// The opcode being executed, with the remaining gas and time before it started.
+    step: Option<(u8, u64, Instant)>,
+    /// The gas and time spent in the calls of the step being executed.
+    calls_gas: u64,
+    calls_time: Duration,
+}
+
+impl Frame {
+    fn new(address: Option<Address>) -> Self {
+        Self {
+            address,
+            started: Instant::now(),
+            opcodes: HashMap::new(),
+            step: None,
+            calls_gas: 0,
+            calls_time: Duration::ZERO,
+        }
+    }
+}
+
+impl GasProfiler {
+    /// Records the stats of the frame that ended, spending the given gas.
+    fn end_frame(&mut self, address: Option<Address>, gas: u64) {
+        let Some(mut frame) = self.frames.pop() else { return };
+        frame.address = frame.address.or(address);
+        let time = frame.started.elapsed();
+
+        if let Some(parent) = self.frames.last_mut() {
+            parent.calls_gas += gas;
+            parent.calls_time += time;
+        } else {
+            self.time += time;
+        }
+
+        let Some(address) = frame.address else { return };
+        let is_precompile = self.precompiles.map_or(false, |p| p.contains(&address));
+        if frame.opcodes.is_empty() && is_precompile {
+            let stats = self.profile.precompiles.entry(address).or_default();
+            *stats += GasStats { count: 1, gas, time };
+            return
+        }
+
+        let path = self
+            .frames
+            .iter()
+            .map(|frame| frame.address.map_or_else(|| "CREATE".to_string(), |a| format!("{a:?}")))
+            .chain(std::iter::once(format!("{address:?}")))
+            .collect::<Vec<_>>()
+            .join(";");
+        let mut contract = GasStats { count: 1, ..Default::default() };
+        for (opcode, stats) in frame.opcodes {
+            contract.gas += stats.gas;
+            contract.time += stats.time;
+            *self.profile.opcodes.entry(opcode).or_default() += stats;
+            *self.profile.stacks.entry(format!("{path};{}", opcode_name(opcode))).or_default() +=
+                stats.gas;
+        }
+        *self.profile.contracts.entry(address).or_default() += contract;
+    }
+
+    /// Consumes the profiler once the transaction was executed, returning its profile.
+    pub fn into_profile(mut self, result: &ExecutionResult) -> GasProfile {
+        // frames of a halted execution may not have ended
+        while !self.frames.is_empty() {
+            self.end_frame(None, 0);
+        }
+        self.profile.transactions += GasStats { count: 1, gas: result.gas_used(), time: self.time };
+        self.profile
+    }
+}
+
+impl<DB> Inspector<DB> for GasProfiler
+where
+    DB: Database,
+{
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.step = Some((interp.current_opcode(), interp.gas.remaining(), Instant::now()));
+            frame.calls_gas = 0;
+            frame.calls_time = Duration::ZERO;
+        }
+        InstructionResult::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+        _eval: InstructionResult,
+    ) -> InstructionResult {
+        if let Some(frame) = self.frames.last_mut() {
+            if let Some((opcode, gas_before, started)) = frame.step.take() {
+                let gas = gas_before.saturating_sub(interp.gas.remaining());
+                let stats = GasStats {
+                    count: 1,
+                    gas: gas.saturating_sub(frame.calls_gas),
+                    time: started.elapsed().saturating_sub(frame.calls_time),
+                };
+                *frame.opcodes.entry(opcode).or_default() += stats;
+            }
+        }
+        InstructionResult::Continue
+    }
+
+    fn call(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, Bytes) {
+        self.precompiles.get_or_insert_with(|| precompiles(data.env.cfg.spec_id));
+        self.frames.push(Frame::new(Some(inputs.context.code_address)));
+        (InstructionResult::Continue, Gas::new(inputs.gas_limit), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: InstructionResult,
+        out: Bytes,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, Bytes) {
+        self.end_frame(None, remaining_gas.spend());
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.precompiles.get_or_insert_with(|| precompiles(data.env.cfg.spec_id));
+        self.frames.push(Frame::new(None));
+        (InstructionResult::Continue, None, Gas::new(inputs.gas_limit), Bytes::new())
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        out: Bytes,
+    ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.end_frame(address, remaining_gas.spend());
+        (ret, address, remaining_gas, out)
+    }
+}
+
+/// Returns the precompiles the EVM executes with the given spec.
+fn precompiles(spec_id: SpecId) -> &'static Precompiles {
+    let spec = match spec_id {
+        SpecId::FRONTIER |
+        SpecId::FRONTIER_THAWING |
+        SpecId::HOMESTEAD |
+        SpecId::DAO_FORK |
+        SpecId::TANGERINE |
+        SpecId::SPURIOUS_DRAGON => PrecompilesSpecId::HOMESTEAD,
+        SpecId::BYZANTIUM | SpecId::CONSTANTINOPLE | SpecId::PETERSBURG => {
+            PrecompilesSpecId::BYZANTIUM
+        }
+        SpecId::ISTANBUL | SpecId::MUIR_GLACIER => PrecompilesSpecId::ISTANBUL,
+        SpecId::BERLIN |
+        SpecId::LONDON |
+        SpecId::ARROW_GLACIER |
+        SpecId::GRAY_GLACIER |
+        SpecId::MERGE |
+        SpecId::SHANGHAI |
+        SpecId::CANCUN => PrecompilesSpecId::BERLIN,
+        SpecId::LATEST => PrecompilesSpecId::LATEST,
+    };
+    Precompiles::new(spec)
+}
+
+/// Returns the name of the opcode, or its hex value if it's unknown.
+fn opcode_name(opcode: u8) -> String {
+    opcode::OPCODE_JUMPMAP[opcode as usize]
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("0x{opcode:02x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_and_export() {
+        let address = Address::from_low_u64_be(0x1234);
+        let stats = GasStats { count: 1, gas: 3, time: Duration::from_nanos(10) };
+        let profile = GasProfile {
+            transactions: GasStats { count: 1, gas: 21_003, time: Duration::from_nanos(50) },
+            opcodes: BTreeMap::from([(opcode::ADD, stats)]),
+            precompiles: BTreeMap::new(),
+            contracts: BTreeMap::from([(address, stats)]),
+            stacks: BTreeMap::from([(format!("{address:?};ADD"), 3)]),
+        };
+
+        let mut merged = GasProfile::default();
+        merged.merge(profile.clone());
+        merged.merge(profile);
+        assert_eq!(merged.transactions.count, 2);
+        assert_eq!(
+            merged.opcodes[&opcode::ADD],
+            GasStats { count: 2, gas: 6, time: Duration::from_nanos(20) }
+        );
+
+        let mut csv = Vec::new();
+        merged.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!(
+                "kind,name,count,gas,time_ns\ntransactions,all,2,42006,100\nopcode,ADD,2,6,20\ncontract,{address:?},2,6,20\n"
+            )
+        );
+
+        let mut folded = Vec::new();
+        merged.write_folded(&mut folded).unwrap();
+        assert_eq!(String::from_utf8(folded).unwrap(), format!("{address:?};ADD 6\n"));
+    }
+
+    #[test]
+    fn precompiles_of_spec() {
+        // modexp was added in Byzantium
+        let modexp = Address::from_low_u64_be(5);
+        assert!(!precompiles(SpecId::HOMESTEAD).contains(&modexp));
+        assert!(precompiles(SpecId::BYZANTIUM).contains(&modexp));
+        assert!(precompiles(SpecId::FRONTIER).contains(&Address::from_low_u64_be(4)));
+    }
+}
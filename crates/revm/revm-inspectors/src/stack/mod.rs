@@ -1,10 +1,11 @@
 use std::fmt::Debug;
 
+use reth_interfaces::Error;
 use reth_primitives::{bytes::Bytes, Address, TxHash, H256};
 use revm::{
     inspectors::CustomPrintTracer,
     interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
-    primitives::Env,
+    primitives::{Env, ExecutionResult},
     Database, EVMData, Inspector,
 };
 
@@ -12,6 +13,11 @@ use revm::{
 mod maybe_owned;
 pub use maybe_owned::MaybeOwnedInspector;
 
+/// Inspectors registered to run during block execution
+mod registry;
+use registry::ActiveInspectors;
+pub use registry::{InspectorDb, InspectorRegistry, RegisteredInspector, TransactionOutput};
+
 /// One can hook on inspector execution in 3 ways:
 /// - Block: Hook on block execution
 /// - BlockWithIndex: Hook on block execution transaction index
@@ -29,7 +35,20 @@ pub enum Hook {
     All,
 }
 
-#[derive(Default, Clone)]
+impl Hook {
+    /// Returns `true` if the transaction with the given hash, executed in the given environment,
+    /// matches the hook.
+    pub fn matches(&self, env: &Env, tx_hash: TxHash) -> bool {
+        match self {
+            Hook::None => false,
+            Hook::Block(block) => env.block.number.to::<u64>() == *block,
+            Hook::Transaction(hash) => *hash == tx_hash,
+            Hook::All => true,
+        }
+    }
+}
+
+#[derive(Default)]
 /// An inspector that calls multiple inspectors in sequence.
 ///
 /// If a call to an inspector returns a value other than [InstructionResult::Continue] (or
@@ -39,6 +58,24 @@ pub struct InspectorStack {
     pub custom_print_tracer: Option<CustomPrintTracer>,
     /// The provided hook
     pub hook: Hook,
+    /// The inspectors registered to run during execution.
+    pub registry: InspectorRegistry,
+    /// Whether the printer inspects the current transaction.
+    print: bool,
+    /// The registered inspectors inspecting the current transaction.
+    active: ActiveInspectors,
+}
+
+impl Clone for InspectorStack {
+    fn clone(&self) -> Self {
+        Self {
+            custom_print_tracer: self.custom_print_tracer.clone(),
+            hook: self.hook.clone(),
+            registry: self.registry.clone(),
+            print: false,
+            active: Default::default(),
+        }
+    }
 }
 
 impl Debug for InspectorStack {
@@ -46,6 +83,7 @@ impl Debug for InspectorStack {
         f.debug_struct("InspectorStack")
             .field("custom_print_tracer", &self.custom_print_tracer.is_some())
             .field("hook", &self.hook)
+            .field("registry", &self.registry)
             .finish()
     }
 }
@@ -53,7 +91,8 @@ impl Debug for InspectorStack {
 impl InspectorStack {
     /// Create a new inspector stack.
     pub fn new(config: InspectorStackConfig) -> Self {
-        let mut stack = InspectorStack { hook: config.hook, ..Default::default() };
+        let mut stack =
+            InspectorStack { hook: config.hook, registry: config.registry, ..Default::default() };
 
         if config.use_printer_tracer {
             stack.custom_print_tracer = Some(CustomPrintTracer::default());
@@ -64,12 +103,36 @@ impl InspectorStack {
 
     /// Check if the inspector should be used.
     pub fn should_inspect(&self, env: &Env, tx_hash: TxHash) -> bool {
-        match self.hook {
-            Hook::None => false,
-            Hook::Block(block) => env.block.number.to::<u64>() == block,
-            Hook::Transaction(hash) => hash == tx_hash,
-            Hook::All => true,
-        }
+        self.hook.matches(env, tx_hash)
+    }
+
+    /// Prepares the stack for the execution of the given transaction, instantiating the registered
+    /// inspectors whose hook matches it.
+    ///
+    /// Returns `true` if the transaction should be executed with the stack as inspector.
+    pub fn start_transaction(&mut self, env: &Env, tx_hash: TxHash) -> bool {
+        self.print = self.should_inspect(env, tx_hash);
+        self.active = self.registry.activate(env, tx_hash);
+        self.print || !self.active.is_empty()
+    }
+
+    /// Delivers the outputs of the registered inspectors for the executed transaction.
+    ///
+    /// Must be called after the transaction started with [InspectorStack::start_transaction] was
+    /// executed successfully.
+    pub fn finish_transaction(&mut self, result: &ExecutionResult) {
+        self.print = false;
+        self.active.finish(result);
+    }
+
+    /// Returns the printer if it inspects the current transaction.
+    fn printer(&mut self) -> Option<&mut CustomPrintTracer> {
+        self.custom_print_tracer.as_mut().filter(|_| self.print)
+    }
+
+    /// Returns the registered inspectors if any of them inspects the current transaction.
+    fn registered(&mut self) -> Option<&mut ActiveInspectors> {
+        Some(&mut self.active).filter(|active| !active.is_empty())
     }
 }
 
@@ -82,6 +145,9 @@ pub struct InspectorStackConfig {
 
     /// Hook on a specific block or transaction.
     pub hook: Hook,
+
+    /// The inspectors to run during execution, each with its own hook.
+    pub registry: InspectorRegistry,
 }
 
 /// Helper macro to call the same method on multiple inspectors without resorting to dynamic
@@ -99,7 +165,7 @@ macro_rules! call_inspectors {
 
 impl<DB> Inspector<DB> for InspectorStack
 where
-    DB: Database,
+    DB: Database<Error = Error>,
 {
     fn initialize_interp(
         &mut self,
@@ -107,7 +173,7 @@ where
         data: &mut EVMData<'_, DB>,
         is_static: bool,
     ) -> InstructionResult {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let status = inspector.initialize_interp(interpreter, data, is_static);
 
             // Allow inspectors to exit early
@@ -125,7 +191,7 @@ where
         data: &mut EVMData<'_, DB>,
         is_static: bool,
     ) -> InstructionResult {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let status = inspector.step(interpreter, data, is_static);
 
             // Allow inspectors to exit early
@@ -144,7 +210,7 @@ where
         topics: &[H256],
         data: &Bytes,
     ) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             inspector.log(evm_data, address, topics, data);
         });
     }
@@ -156,7 +222,7 @@ where
         is_static: bool,
         eval: InstructionResult,
     ) -> InstructionResult {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let status = inspector.step_end(interpreter, data, is_static, eval);
 
             // Allow inspectors to exit early
@@ -174,7 +240,7 @@ where
         inputs: &mut CallInputs,
         is_static: bool,
     ) -> (InstructionResult, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let (status, gas, retdata) = inspector.call(data, inputs, is_static);
 
             // Allow inspectors to exit early
@@ -195,7 +261,7 @@ where
         out: Bytes,
         is_static: bool,
     ) -> (InstructionResult, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let (new_ret, new_gas, new_out) =
                 inspector.call_end(data, inputs, remaining_gas, ret, out.clone(), is_static);
 
@@ -214,7 +280,7 @@ where
         data: &mut EVMData<'_, DB>,
         inputs: &mut CreateInputs,
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let (status, addr, gas, retdata) = inspector.create(data, inputs);
 
             // Allow inspectors to exit early
@@ -235,7 +301,7 @@ where
         remaining_gas: Gas,
         out: Bytes,
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             let (new_ret, new_address, new_gas, new_retdata) =
                 inspector.create_end(data, inputs, ret, address, remaining_gas, out.clone());
 
@@ -248,7 +314,7 @@ where
     }
 
     fn selfdestruct(&mut self, contract: Address, target: Address) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [self.printer(), self.registered()], {
             Inspector::<DB>::selfdestruct(inspector, contract, target);
         });
     }
@@ -0,0 +1,356 @@
+use super::Hook;
+use reth_interfaces::Error;
+use reth_primitives::{bytes::Bytes, Address, TxHash, H160, H256, U256};
+use revm::{
+    interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
+    primitives::{AccountInfo, Bytecode, Env, ExecutionResult},
+    Database, EVMData, Inspector, JournaledState,
+};
+use std::{fmt::Debug, sync::Arc};
+
+/// The database the inspectors registered in an [InspectorRegistry] are executed with.
+///
+/// The executor runs with different databases (e.g. the execution stage and the blockchain tree),
+/// so registered inspectors can't depend on its type. Instead, they implement
+/// [Inspector](revm::Inspector) for this type-erased database, which gives them the same access to
+/// the state as the EVM.
+pub struct InspectorDb<'a> {
+    db: &'a mut dyn Database<Error = Error>,
+}
+
+impl Debug for InspectorDb<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InspectorDb").finish_non_exhaustive()
+    }
+}
+
+impl Database for InspectorDb<'_> {
+    type Error = Error;
+
+    fn basic(&mut self, address: H160) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: H256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: H160, index: U256) -> Result<U256, Self::Error> {
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<H256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+/// An [Inspector](revm::Inspector) that can be registered in an [InspectorRegistry].
+pub type RegisteredInspector = dyn for<'db> Inspector<InspectorDb<'db>> + Send;
+
+/// The inspector registered in an [InspectorRegistry] after it inspected a transaction.
+#[derive(Debug, Clone)]
+pub struct TransactionOutput<I> {
+    /// The number of the block the transaction is in.
+    pub block_number: u64,
+    /// The hash of the transaction.
+    pub transaction_hash: TxHash,
+    /// The result of the transaction.
+    pub result: ExecutionResult,
+    /// The inspector that inspected the transaction.
+    pub inspector: I,
+}
+
+/// A registry of [Inspector](revm::Inspector)s run by the
+/// [InspectorStack](crate::stack::InspectorStack) for the transactions selected by their [Hook].
+///
+/// A new instance of a registered inspector is created for every matching transaction, and handed
+/// to the callback given on registration once the transaction was executed, so its output can be
+/// collected or forwarded to a channel.
+///
+/// Cloning the registry is cheap: the registrations are shared.
+#[derive(Default, Clone)]
+pub struct InspectorRegistry {
+    inspectors: Vec<Arc<dyn Registration>>,
+}
+
+impl Debug for InspectorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InspectorRegistry").field("inspectors", &self.inspectors.len()).finish()
+    }
+}
+
+impl InspectorRegistry {
+    /// Registers an inspector to run for the transactions matching the hook.
+    ///
+    /// `new_inspector` is called for every matching transaction, and `on_output` with the
+    /// inspector once the transaction was executed.
+    pub fn register<I, F, C>(&mut self, hook: Hook, new_inspector: F, on_output: C)
+    where
+        I: for<'db> Inspector<InspectorDb<'db>> + Send + 'static,
+        F: Fn() -> I + Send + Sync + 'static,
+        C: Fn(TransactionOutput<I>) + Send + Sync + 'static,
+    {
+        self.inspectors.push(Arc::new(InspectorRegistration {
+            hook,
+            new_inspector,
+            on_output: Arc::new(on_output),
+        }));
+    }
+
+    /// Returns `true` if no inspector is registered.
+    pub fn is_empty(&self) -> bool {
+        self.inspectors.is_empty()
+    }
+
+    /// Returns new instances of the inspectors whose hook matches the transaction.
+    pub(crate) fn activate(&self, env: &Env, tx_hash: TxHash) -> ActiveInspectors {
+        ActiveInspectors(
+            self.inspectors
+                .iter()
+                .filter(|registration| registration.hook().matches(env, tx_hash))
+                .map(|registration| registration.activate(env, tx_hash))
+                .collect(),
+        )
+    }
+}
+
+/// A type-erased [InspectorRegistration].
+trait Registration: Send + Sync {
+    fn hook(&self) -> &Hook;
+
+    fn activate(&self, env: &Env, tx_hash: TxHash) -> Box<dyn ActiveInspector>;
+}
+
+struct InspectorRegistration<F, C> {
+    hook: Hook,
+    new_inspector: F,
+    on_output: Arc<C>,
+}
+
+impl<I, F, C> Registration for InspectorRegistration<F, C>
+where
+    I: for<'db> Inspector<InspectorDb<'db>> + Send + 'static,
+    F: Fn() -> I + Send + Sync + 'static,
+    C: Fn(TransactionOutput<I>) + Send + Sync + 'static,
+{
+    fn hook(&self) -> &Hook {
+        &self.hook
+    }
+
+    fn activate(&self, env: &Env, tx_hash: TxHash) -> Box<dyn ActiveInspector> {
+        Box::new(Active {
+            inspector: (self.new_inspector)(),
+            on_output: Arc::clone(&self.on_output),
+            block_number: env.block.number.to::<u64>(),
+            tx_hash,
+        })
+    }
+}
+
+/// A registered inspector inspecting a transaction, along with the callback for its output.
+trait ActiveInspector: Send {
+    /// Returns the wrapped inspector.
+    fn inspector(&mut self) -> &mut RegisteredInspector;
+
+    /// Delivers the inspector to the callback.
+    fn finish(self: Box<Self>, result: &ExecutionResult);
+}
+
+struct Active<I, C> {
+    inspector: I,
+    on_output: Arc<C>,
+    block_number: u64,
+    tx_hash: TxHash,
+}
+
+impl<I, C> ActiveInspector for Active<I, C>
+where
+    I: for<'db> Inspector<InspectorDb<'db>> + Send + 'static,
+    C: Fn(TransactionOutput<I>) + Send + Sync + 'static,
+{
+    fn inspector(&mut self) -> &mut RegisteredInspector {
+        &mut self.inspector
+    }
+
+    fn finish(self: Box<Self>, result: &ExecutionResult) {
+        let Active { inspector, on_output, block_number, tx_hash } = *self;
+        on_output(TransactionOutput {
+            block_number,
+            transaction_hash: tx_hash,
+            result: result.clone(),
+            inspector,
+        });
+    }
+}
+
+/// The registered inspectors inspecting the current transaction.
+///
+/// The inspectors are called in sequence with the same semantics as the
+/// [InspectorStack](crate::stack::InspectorStack).
+#[derive(Default)]
+pub(crate) struct ActiveInspectors(Vec<Box<dyn ActiveInspector>>);
+
+impl ActiveInspectors {
+    /// Returns `true` if no registered inspector inspects the current transaction.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Delivers the inspectors to their callbacks.
+    pub(crate) fn finish(&mut self, result: &ExecutionResult) {
+        for inspector in self.0.drain(..) {
+            inspector.finish(result);
+        }
+    }
+
+    /// Calls `f` with every inspector and the EVM data with the type-erased database, until `f`
+    /// returns `Some`.
+    fn inspect<DB, R>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        mut f: impl FnMut(&mut RegisteredInspector, &mut EVMData<'_, InspectorDb<'_>>) -> Option<R>,
+    ) -> Option<R>
+    where
+        DB: Database<Error = Error>,
+    {
+        let mut db = InspectorDb { db: &mut *data.db };
+        let mut inspector_data = EVMData {
+            env: &mut *data.env,
+            journaled_state: std::mem::replace(&mut data.journaled_state, JournaledState::new(0)),
+            db: &mut db,
+            error: data.error.take(),
+        };
+
+        let out = self.0.iter_mut().find_map(|active| f(active.inspector(), &mut inspector_data));
+
+        data.journaled_state = inspector_data.journaled_state;
+        data.error = inspector_data.error;
+        out
+    }
+}
+
+impl<DB> Inspector<DB> for ActiveInspectors
+where
+    DB: Database<Error = Error>,
+{
+    fn initialize_interp(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> InstructionResult {
+        self.inspect(data, |inspector, data| {
+            let status = inspector.initialize_interp(interpreter, data, is_static);
+            (status != InstructionResult::Continue).then_some(status)
+        })
+        .unwrap_or(InstructionResult::Continue)
+    }
+
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> InstructionResult {
+        self.inspect(data, |inspector, data| {
+            let status = inspector.step(interpreter, data, is_static);
+            (status != InstructionResult::Continue).then_some(status)
+        })
+        .unwrap_or(InstructionResult::Continue)
+    }
+
+    fn log(
+        &mut self,
+        evm_data: &mut EVMData<'_, DB>,
+        address: &Address,
+        topics: &[H256],
+        data: &Bytes,
+    ) {
+        self.inspect(evm_data, |inspector, evm_data| {
+            inspector.log(evm_data, address, topics, data);
+            None::<()>
+        });
+    }
+
+    fn step_end(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+        eval: InstructionResult,
+    ) -> InstructionResult {
+        self.inspect(data, |inspector, data| {
+            let status = inspector.step_end(interpreter, data, is_static, eval);
+            (status != InstructionResult::Continue).then_some(status)
+        })
+        .unwrap_or(InstructionResult::Continue)
+    }
+
+    fn call(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        is_static: bool,
+    ) -> (InstructionResult, Gas, Bytes) {
+        self.inspect(data, |inspector, data| {
+            let (status, gas, retdata) = inspector.call(data, inputs, is_static);
+            (status != InstructionResult::Continue).then_some((status, gas, retdata))
+        })
+        .unwrap_or_else(|| (InstructionResult::Continue, Gas::new(inputs.gas_limit), Bytes::new()))
+    }
+
+    fn call_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: InstructionResult,
+        out: Bytes,
+        is_static: bool,
+    ) -> (InstructionResult, Gas, Bytes) {
+        self.inspect(data, |inspector, data| {
+            let (new_ret, new_gas, new_out) =
+                inspector.call_end(data, inputs, remaining_gas, ret, out.clone(), is_static);
+            (new_ret != ret || (new_ret == InstructionResult::Revert && new_out != out))
+                .then_some((new_ret, new_gas, new_out))
+        })
+        .unwrap_or((ret, remaining_gas, out))
+    }
+
+    fn create(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.inspect(data, |inspector, data| {
+            let (status, addr, gas, retdata) = inspector.create(data, inputs);
+            (status != InstructionResult::Continue).then_some((status, addr, gas, retdata))
+        })
+        .unwrap_or_else(|| {
+            (InstructionResult::Continue, None, Gas::new(inputs.gas_limit), Bytes::new())
+        })
+    }
+
+    fn create_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        out: Bytes,
+    ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.inspect(data, |inspector, data| {
+            let (new_ret, new_address, new_gas, new_retdata) =
+                inspector.create_end(data, inputs, ret, address, remaining_gas, out.clone());
+            (new_ret != ret).then_some((new_ret, new_address, new_gas, new_retdata))
+        })
+        .unwrap_or((ret, address, remaining_gas, out))
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address) {
+        for active in &mut self.0 {
+            active.inspector().selfdestruct(contract, target);
+        }
+    }
+}
@@ -448,6 +448,89 @@ impl Decodable for smol_str::SmolStr {
     }
 }
 
+#[cfg(feature = "enr")]
+mod enr_support {
+    use super::*;
+    use enr::{Enr, EnrKey};
+
+    /// The maximum size of an encoded ENR, see <https://eips.ethereum.org/EIPS/eip-778>
+    const MAX_ENR_SIZE: usize = 300;
+
+    /// Advances the buffer past the next string item and returns its payload.
+    fn decode_string<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+        let h = Header::decode(buf)?;
+        if h.list {
+            return Err(DecodeError::UnexpectedList)
+        }
+        if buf.len() < h.payload_length {
+            return Err(DecodeError::InputTooShort)
+        }
+        let (payload, rest) = buf.split_at(h.payload_length);
+        *buf = rest;
+        Ok(payload)
+    }
+
+    /// Advances the buffer past the next item (string or list) and returns its raw encoding,
+    /// including the header.
+    fn decode_raw<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+        let started = *buf;
+        let h = Header::decode(buf)?;
+        if buf.len() < h.payload_length {
+            return Err(DecodeError::InputTooShort)
+        }
+        buf.advance(h.payload_length);
+        Ok(&started[..started.len() - buf.len()])
+    }
+
+    impl<K: EnrKey> Decodable for Enr<K> {
+        fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+            let b = &mut &**buf;
+            let rlp_head = Header::decode(b)?;
+            if !rlp_head.list {
+                return Err(DecodeError::UnexpectedString)
+            }
+            if b.len() < rlp_head.payload_length {
+                return Err(DecodeError::InputTooShort)
+            }
+
+            let encoded_len = buf.len() - b.len() + rlp_head.payload_length;
+            if encoded_len > MAX_ENR_SIZE {
+                return Err(DecodeError::Custom("enr exceeds max size"))
+            }
+
+            // validate the record structure: `[signature, seq, k, v, ...]` with unique and sorted
+            // keys
+            let payload = &mut &b[..rlp_head.payload_length];
+            decode_string(payload)?;
+            u64::decode(payload)?;
+            let mut prev_key: Option<&[u8]> = None;
+            while !payload.is_empty() {
+                let key = decode_string(payload)?;
+                if payload.is_empty() {
+                    return Err(DecodeError::Custom("enr content is not a multiple of 2"))
+                }
+                decode_raw(payload)?;
+                if prev_key.map_or(false, |prev| prev >= key) {
+                    return Err(DecodeError::Custom("enr keys are unsorted"))
+                }
+                prev_key = Some(key);
+            }
+
+            // The fields of `Enr` are private, the only way to construct a record is through the
+            // key-scheme specific `rlp::Decodable` implementation which also verifies the
+            // signature.
+            let enr = <Self as rlp::Decodable>::decode(&rlp::Rlp::new(&buf[..encoded_len]))
+                .map_err(|err| match err {
+                    rlp::DecoderError::Custom(s) => DecodeError::Custom(s),
+                    _ => DecodeError::Custom("invalid enr"),
+                })?;
+
+            buf.advance(encoded_len);
+            Ok(enr)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate alloc;
@@ -666,4 +749,75 @@ mod tests {
             (Err(DecodeError::UnexpectedList), &hex!("C0")[..]),
         ])
     }
+
+    // test vector from the enr library rlp encoding tests
+    // <https://github.com/sigp/enr/blob/e59dcb45ea07e423a7091d2a6ede4ad6d8ef2840/src/lib.rs#L1019>
+    #[cfg(feature = "enr")]
+    #[test]
+    fn rlp_enr() {
+        use enr::{secp256k1::SecretKey, Enr, EnrPublicKey};
+        use std::net::Ipv4Addr;
+
+        let valid_record = hex!("f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f");
+        let signature = hex!("7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c");
+        let expected_pubkey =
+            hex!("03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138");
+
+        let mut input = &valid_record[..];
+        let enr = Enr::<SecretKey>::decode(&mut input).unwrap();
+        assert!(input.is_empty());
+
+        assert_eq!(enr.ip4(), Some(Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(enr.id(), Some(String::from("v4")));
+        assert_eq!(enr.udp4(), Some(30303));
+        assert_eq!(enr.tcp4(), None);
+        assert_eq!(enr.signature(), &signature[..]);
+        assert_eq!(enr.public_key().encode().to_vec(), expected_pubkey);
+        assert!(enr.verify());
+
+        let mut encoded = BytesMut::new();
+        enr.encode(&mut encoded);
+        assert_eq!(&encoded[..], &valid_record[..]);
+        assert_eq!(enr.length(), valid_record.len());
+
+        // trailing data is not consumed
+        let mut with_trailing = valid_record.to_vec();
+        with_trailing.extend_from_slice(&hex!("c0"));
+        let mut input = &with_trailing[..];
+        Enr::<SecretKey>::decode(&mut input).unwrap();
+        assert_eq!(input, &hex!("c0")[..]);
+
+        // truncated record
+        assert!(Enr::<SecretKey>::decode(&mut &valid_record[..valid_record.len() - 1]).is_err());
+        // not a list
+        assert_eq!(
+            Enr::<SecretKey>::decode(&mut &hex!("80")[..]),
+            Err(DecodeError::UnexpectedString)
+        );
+    }
+
+    #[cfg(feature = "enr")]
+    #[test]
+    fn rlp_enr_roundtrip() {
+        use enr::{secp256k1::SecretKey, Enr, EnrBuilder};
+        use std::net::Ipv4Addr;
+
+        let key = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let enr = {
+            let mut builder = EnrBuilder::new("v4");
+            builder.ip(Ipv4Addr::new(127, 0, 0, 1).into());
+            builder.tcp4(30303);
+            builder.udp4(30303);
+            builder.build(&key).unwrap()
+        };
+
+        let mut encoded = BytesMut::new();
+        enr.encode(&mut encoded);
+        assert_eq!(encoded.len(), enr.length());
+
+        let mut input = &encoded[..];
+        let decoded = Enr::<SecretKey>::decode(&mut input).unwrap();
+        assert!(input.is_empty());
+        assert_eq!(decoded, enr);
+    }
 }
@@ -200,6 +200,41 @@ impl Encodable for std::net::IpAddr {
     }
 }
 
+#[cfg(feature = "enr")]
+mod enr_support {
+    use super::*;
+    use enr::{Enr, EnrKey};
+
+    /// Returns the length of the list payload of the ENR: `[signature, seq, k, v, ...]`
+    fn enr_payload_length<K: EnrKey>(enr: &Enr<K>) -> usize {
+        enr.signature().length() +
+            enr.seq().length() +
+            enr.iter().fold(0, |acc, (k, v)| acc + k.as_slice().length() + v.len())
+    }
+
+    impl<K: EnrKey> Encodable for Enr<K> {
+        fn encode(&self, out: &mut dyn BufMut) {
+            let payload_length = enr_payload_length(self);
+            Header { list: true, payload_length }.encode(out);
+
+            self.signature().encode(out);
+            self.seq().encode(out);
+
+            for (k, v) in self.iter() {
+                // Keys are byte data
+                k.as_slice().encode(out);
+                // Values are raw RLP encoded data
+                out.put_slice(v);
+            }
+        }
+
+        fn length(&self) -> usize {
+            let payload_length = enr_payload_length(self);
+            payload_length + length_of_length(payload_length)
+        }
+    }
+}
+
 #[cfg(feature = "ethnum")]
 mod ethnum_support {
     use super::*;
@@ -3,7 +3,8 @@ use jsonrpsee::{core::Error as RpcError, server::ServerHandle, RpcModule};
 use reth_network_api::{NetworkInfo, Peers};
 use reth_provider::{BlockProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory};
 use reth_rpc::{
-    eth::cache::EthStateCache, AuthLayer, EngineApi, EthApi, JwtAuthValidator, JwtSecret,
+    eth::{cache::EthStateCache, gas_oracle::GasPriceOracle},
+    AuthLayer, EngineApi, EthApi, JwtAuthValidator, JwtSecret,
 };
 use reth_rpc_api::servers::*;
 use reth_rpc_engine_api::EngineApiHandle;
@@ -35,8 +36,9 @@ where
 {
     // spawn a new cache task
     let eth_cache = EthStateCache::spawn_with(client.clone(), Default::default(), executor);
-    launch_with_eth_api(EthApi::new(client, pool, network, eth_cache), handle, socket_addr, secret)
-        .await
+    let gas_oracle = GasPriceOracle::new(client.clone(), Default::default(), eth_cache.clone());
+    let eth_api = EthApi::new(client, pool, network, eth_cache, gas_oracle);
+    launch_with_eth_api(eth_api, handle, socket_addr, secret).await
 }
 
 /// Configure and launch an auth server with existing EthApi implementation.
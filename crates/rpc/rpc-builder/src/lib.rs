@@ -25,13 +25,13 @@
 //!
 //! ```
 //! use reth_network_api::{NetworkInfo, Peers};
-//! use reth_provider::{BlockProvider, StateProviderFactory, EvmEnvProvider};
+//! use reth_provider::{BlockProvider, ChainSpecProvider, StateProviderFactory, EvmEnvProvider};
 //! use reth_rpc_builder::{RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig};
 //! use reth_tasks::TokioTaskExecutor;
 //! use reth_transaction_pool::TransactionPool;
 //! pub async fn launch<Client, Pool, Network>(client: Client, pool: Pool, network: Network)
 //! where
-//!     Client: BlockProvider + StateProviderFactory + EvmEnvProvider + Clone + Unpin + 'static,
+//!     Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + Clone + Unpin + 'static,
 //!     Pool: TransactionPool + Clone + 'static,
 //!     Network: NetworkInfo + Peers + Clone + 'static,
 //! {
@@ -58,7 +58,7 @@ use jsonrpsee::{
 };
 use reth_ipc::server::IpcServer;
 use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, EvmEnvProvider, StateProviderFactory};
+use reth_provider::{BlockProvider, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
 use reth_rpc::{
     AdminApi, DebugApi, EthApi, EthFilter, EthSubscriptionIdProvider, NetApi, TraceApi, Web3Api,
 };
@@ -88,7 +88,7 @@ mod eth;
 pub mod constants;
 pub use crate::eth::{EthConfig, EthHandlers};
 use constants::*;
-use reth_rpc::eth::cache::EthStateCache;
+use reth_rpc::eth::{cache::EthStateCache, gas_oracle::GasPriceOracle};
 use reth_tasks::TaskSpawner;
 
 /// Cors utilities.
@@ -104,7 +104,13 @@ pub async fn launch<Client, Pool, Network, Tasks>(
     executor: Tasks,
 ) -> Result<RpcServerHandle, RpcError>
 where
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + Clone + Unpin + 'static,
+    Client: BlockProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + Clone
+        + Unpin
+        + 'static,
     Pool: TransactionPool + Clone + 'static,
     Network: NetworkInfo + Peers + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
@@ -143,7 +149,7 @@ impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks
     /// Configure the client instance.
     pub fn with_client<C>(self, client: C) -> RpcModuleBuilder<C, Pool, Network, Tasks>
     where
-        C: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+        C: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     {
         let Self { pool, network, executor, .. } = self;
         RpcModuleBuilder { client, network, pool, executor }
@@ -179,7 +185,13 @@ impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks
 
 impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks>
 where
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + Clone + Unpin + 'static,
+    Client: BlockProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + Clone
+        + Unpin
+        + 'static,
     Pool: TransactionPool + Clone + 'static,
     Network: NetworkInfo + Peers + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
@@ -328,7 +340,13 @@ impl RpcModuleSelection {
         config: RpcModuleConfig,
     ) -> RpcModule<()>
     where
-        Client: BlockProvider + StateProviderFactory + EvmEnvProvider + Clone + Unpin + 'static,
+        Client: BlockProvider
+            + StateProviderFactory
+            + EvmEnvProvider
+            + ChainSpecProvider
+            + Clone
+            + Unpin
+            + 'static,
         Pool: TransactionPool + Clone + 'static,
         Network: NetworkInfo + Peers + Clone + 'static,
         Tasks: TaskSpawner + Clone + 'static,
@@ -476,7 +494,13 @@ where
 
 impl<Client, Pool, Network, Tasks> RethModuleRegistry<Client, Pool, Network, Tasks>
 where
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + Clone + Unpin + 'static,
+    Client: BlockProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + Clone
+        + Unpin
+        + 'static,
     Pool: TransactionPool + Clone + 'static,
     Network: NetworkInfo + Peers + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
@@ -582,11 +606,17 @@ where
                 self.config.eth.cache.clone(),
                 self.executor.clone(),
             );
+            let gas_oracle = GasPriceOracle::new(
+                self.client.clone(),
+                self.config.eth.gas_oracle.clone(),
+                eth_cache.clone(),
+            );
             let api = EthApi::new(
                 self.client.clone(),
                 self.pool.clone(),
                 self.network.clone(),
                 eth_cache.clone(),
+                gas_oracle,
             );
             let filter = EthFilter::new(self.client.clone(), self.pool.clone());
 
@@ -45,7 +45,4 @@ pub struct FeeHistoryCacheItem {
     pub base_fee_per_gas: U256,
     /// Block gas used ratio. Calculated as the ratio of `gasUsed` and `gasLimit`.
     pub gas_used_ratio: f64,
-    /// An (optional) array of effective priority fee per gas data points for a
-    /// block. All zeroes are returned if the block is empty.
-    pub reward: Option<Vec<U256>>,
 }
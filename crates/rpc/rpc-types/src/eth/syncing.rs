@@ -16,6 +16,31 @@ pub struct SyncInfo {
     pub warp_chunks_amount: Option<U256>,
     /// Warp sync snapshot chunks processed.
     pub warp_chunks_processed: Option<U256>,
+    /// Progress of the individual sync stages.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageSyncInfo>,
+}
+
+/// Sync progress of a single stage of the sync pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StageSyncInfo {
+    /// Name of the stage
+    pub name: String,
+    /// Highest block the stage has processed
+    pub current_block: U64,
+    /// Block the stage is syncing to
+    pub highest_block: Option<U64>,
+    /// Entities the stage has processed, e.g. hashed accounts or executed gas
+    pub processed_entities: Option<U64>,
+    /// Entities the stage has to process
+    pub total_entities: Option<U64>,
+    /// Blocks processed per second
+    pub blocks_per_second: U64,
+    /// Entities processed per second
+    pub entities_per_second: Option<U64>,
+    /// Estimated seconds until the stage reaches the highest block
+    pub eta_seconds: Option<U64>,
 }
 
 /// Peers info
@@ -0,0 +1,178 @@
+//! Contains RPC handler implementations for fee related requests.
+
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    EthApi,
+};
+use reth_primitives::{BlockNumber, Receipt, TransactionSigned, U256};
+use reth_provider::{BlockProvider, EvmEnvProvider, StateProviderFactory};
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+{
+    /// Returns a suggestion for the gas price of legacy transactions, the suggested priority fee
+    /// plus the base fee of the latest block.
+    pub(crate) async fn gas_price(&self) -> EthResult<U256> {
+        let tip = self.suggested_priority_fee().await?;
+        let head = self.client().chain_info()?;
+        let base_fee = self
+            .client()
+            .header_by_number(head.best_number)?
+            .and_then(|header| header.base_fee_per_gas)
+            .unwrap_or_default();
+        Ok(tip + U256::from(base_fee))
+    }
+
+    /// Returns a suggestion for the priority fee of EIP-1559 transactions.
+    pub(crate) async fn suggested_priority_fee(&self) -> EthResult<U256> {
+        self.gas_oracle().suggest_tip_cap().await
+    }
+
+    /// Returns the effective priority fees per gas paid in the block at the given percentiles of
+    /// the gas used by its transactions.
+    ///
+    /// The percentiles are expected to be validated with [validate_reward_percentiles].
+    pub(crate) fn block_reward_percentiles(
+        &self,
+        number: BlockNumber,
+        percentiles: &[f64],
+    ) -> EthResult<Vec<U256>> {
+        let block = self.client().block(number.into())?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let receipts = self
+            .client()
+            .receipts_by_block(number.into())?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+
+        Ok(calculate_reward_percentiles(
+            percentiles,
+            block.gas_used,
+            block.base_fee_per_gas,
+            &block.body,
+            &receipts,
+        ))
+    }
+}
+
+/// Checks that the reward percentiles are within `0..=100` and in ascending order.
+pub(crate) fn validate_reward_percentiles(percentiles: &[f64]) -> EthResult<()> {
+    let mut previous = 0.;
+    for percentile in percentiles.iter().copied() {
+        if !(previous..=100.).contains(&percentile) {
+            return Err(EthApiError::InvalidRewardPercentiles)
+        }
+        previous = percentile;
+    }
+    Ok(())
+}
+
+/// Calculates the rewards of a block at the given percentiles, the same way geth does.
+///
+/// The transactions are sorted by their effective priority fee per gas, and the reward at a
+/// percentile is the fee of the transaction at which the gas used by the sorted transactions
+/// reaches that percentile of the gas used by the block.
+///
+/// The rewards are all zero if the block is empty.
+pub(crate) fn calculate_reward_percentiles(
+    percentiles: &[f64],
+    block_gas_used: u64,
+    base_fee: Option<u64>,
+    transactions: &[TransactionSigned],
+    receipts: &[Receipt],
+) -> Vec<U256> {
+    // the receipts only store the gas used by the block so far
+    let mut prev_cumulative_gas_used = 0;
+    let mut transactions: Vec<(u128, u64)> = transactions
+        .iter()
+        .zip(receipts)
+        .map(|(tx, receipt)| {
+            let gas_used = receipt.cumulative_gas_used - prev_cumulative_gas_used;
+            prev_cumulative_gas_used = receipt.cumulative_gas_used;
+            let reward = tx
+                .effective_gas_price(base_fee)
+                .saturating_sub(base_fee.unwrap_or_default() as u128);
+            (reward, gas_used)
+        })
+        .collect();
+
+    if transactions.is_empty() {
+        return vec![U256::ZERO; percentiles.len()]
+    }
+
+    transactions.sort_unstable_by_key(|(reward, _)| *reward);
+
+    let mut index = 0;
+    let mut cumulative_gas_used = transactions[0].1;
+    percentiles
+        .iter()
+        .map(|percentile| {
+            let threshold = (block_gas_used as f64 * percentile / 100.) as u64;
+            while cumulative_gas_used < threshold && index < transactions.len() - 1 {
+                index += 1;
+                cumulative_gas_used += transactions[index].1;
+            }
+            U256::from(transactions[index].0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Transaction, TxEip1559, TxLegacy, TxType};
+
+    fn receipt(cumulative_gas_used: u64) -> Receipt {
+        Receipt {
+            tx_type: TxType::Legacy,
+            success: true,
+            cumulative_gas_used,
+            state_root: None,
+            logs: vec![],
+        }
+    }
+
+    #[test]
+    fn reward_percentiles() {
+        let transactions = [
+            Transaction::Legacy(TxLegacy { gas_price: 30, ..Default::default() }),
+            Transaction::Eip1559(TxEip1559 {
+                max_fee_per_gas: 100,
+                max_priority_fee_per_gas: 5,
+                ..Default::default()
+            }),
+            Transaction::Eip1559(TxEip1559 {
+                max_fee_per_gas: 12,
+                max_priority_fee_per_gas: 5,
+                ..Default::default()
+            }),
+        ]
+        .into_iter()
+        .map(|tx| TransactionSigned::from_transaction_and_signature(tx, Default::default()))
+        .collect::<Vec<_>>();
+        // gas used: 50_000, 30_000, 20_000
+        let receipts = [receipt(50_000), receipt(80_000), receipt(100_000)];
+
+        // sorted rewards: 2 (20_000 gas), 5 (30_000 gas), 20 (50_000 gas)
+        let rewards = calculate_reward_percentiles(
+            &[0., 20., 21., 50., 51., 100.],
+            100_000,
+            Some(10),
+            &transactions,
+            &receipts,
+        );
+        assert_eq!(rewards, [2, 2, 5, 5, 20, 20].into_iter().map(U256::from).collect::<Vec<_>>());
+
+        assert_eq!(
+            calculate_reward_percentiles(&[10., 90.], 0, Some(10), &[], &[]),
+            vec![U256::ZERO; 2]
+        );
+    }
+
+    #[test]
+    fn invalid_reward_percentiles() {
+        assert!(validate_reward_percentiles(&[0., 50., 50., 100.]).is_ok());
+        assert!(validate_reward_percentiles(&[50., 10.]).is_err());
+        assert!(validate_reward_percentiles(&[-1.]).is_err());
+        assert!(validate_reward_percentiles(&[101.]).is_err());
+    }
+}
@@ -3,18 +3,19 @@
 //! The entire implementation of the namespace is quite large, hence it is divided across several
 //! files.
 
-use crate::eth::{cache::EthStateCache, signer::EthSigner};
+use crate::eth::{cache::EthStateCache, gas_oracle::GasPriceOracle, signer::EthSigner};
 use async_trait::async_trait;
 use reth_interfaces::Result;
 use reth_network_api::NetworkInfo;
 use reth_primitives::{Address, BlockId, BlockNumberOrTag, ChainInfo, H256, U256, U64};
 use reth_provider::{providers::ChainState, BlockProvider, EvmEnvProvider, StateProviderFactory};
-use reth_rpc_types::{FeeHistoryCache, SyncInfo, SyncStatus};
+use reth_rpc_types::{FeeHistoryCache, StageSyncInfo, SyncInfo, SyncStatus};
 use reth_transaction_pool::TransactionPool;
 use std::{num::NonZeroUsize, sync::Arc};
 
 mod block;
 mod call;
+mod fees;
 mod server;
 mod sign;
 mod state;
@@ -66,8 +67,21 @@ pub struct EthApi<Client, Pool, Network> {
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
     /// Creates a new, shareable instance.
-    pub fn new(client: Client, pool: Pool, network: Network, eth_cache: EthStateCache) -> Self {
-        let inner = EthApiInner { client, pool, network, signers: Default::default(), eth_cache };
+    pub fn new(
+        client: Client,
+        pool: Pool,
+        network: Network,
+        eth_cache: EthStateCache,
+        gas_oracle: GasPriceOracle<Client>,
+    ) -> Self {
+        let inner = EthApiInner {
+            client,
+            pool,
+            network,
+            signers: Default::default(),
+            eth_cache,
+            gas_oracle,
+        };
         Self {
             inner: Arc::new(inner),
             fee_history_cache: FeeHistoryCache::new(
@@ -81,6 +95,11 @@ impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
         &self.inner.eth_cache
     }
 
+    /// Returns the gas price oracle
+    pub(crate) fn gas_oracle(&self) -> &GasPriceOracle<Client> {
+        &self.inner.gas_oracle
+    }
+
     /// Returns the inner `Client`
     pub(crate) fn client(&self) -> &Client {
         &self.inner.client
@@ -207,15 +226,34 @@ where
     /// Returns the [SyncStatus] of the network
     fn sync_status(&self) -> Result<SyncStatus> {
         let status = if self.is_syncing() {
-            let current_block = U256::from(
-                self.client().chain_info().map(|info| info.best_number).unwrap_or_default(),
-            );
+            let current_block =
+                self.client().chain_info().map(|info| info.best_number).unwrap_or_default();
+            let stages = self.network().sync_progress();
+            let highest_block = stages
+                .iter()
+                .filter_map(|stage| stage.target)
+                .fold(current_block, |highest, target| highest.max(target));
             SyncStatus::Info(SyncInfo {
                 starting_block: U256::from(0),
-                current_block,
-                highest_block: current_block,
+                current_block: U256::from(current_block),
+                highest_block: U256::from(highest_block),
                 warp_chunks_amount: None,
                 warp_chunks_processed: None,
+                stages: stages
+                    .into_iter()
+                    .map(|stage| StageSyncInfo {
+                        name: stage.stage,
+                        current_block: U64::from(stage.checkpoint),
+                        highest_block: stage.target.map(U64::from),
+                        processed_entities: stage.entities_processed.map(U64::from),
+                        total_entities: stage.entities_total.map(U64::from),
+                        blocks_per_second: U64::from(stage.blocks_per_second.round() as u64),
+                        entities_per_second: stage
+                            .entities_per_second
+                            .map(|rate| U64::from(rate.round() as u64)),
+                        eta_seconds: stage.eta.map(|eta| U64::from(eta.as_secs())),
+                    })
+                    .collect(),
             })
         } else {
             SyncStatus::None
@@ -236,4 +274,6 @@ struct EthApiInner<Client, Pool, Network> {
     signers: Vec<Box<dyn EthSigner>>,
     /// The async cache frontend for eth related data
     eth_cache: EthStateCache,
+    /// The gas price oracle for suggesting priority fees
+    gas_oracle: GasPriceOracle<Client>,
 }
@@ -4,8 +4,8 @@
 use super::EthApiSpec;
 use crate::{
     eth::{
-        api::{EthApi, EthTransactions},
-        error::{ensure_success, EthApiError},
+        api::{fees::validate_reward_percentiles, EthApi, EthTransactions},
+        error::{ensure_success, EthApiError, EthResult},
     },
     result::{internal_rpc_err, ToRpcResult},
 };
@@ -160,8 +160,9 @@ where
     }
 
     /// Handler for: `eth_getTransactionReceipt`
-    async fn transaction_receipt(&self, _hash: H256) -> Result<Option<TransactionReceipt>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        trace!(target: "rpc::eth", ?hash, "Serving eth_getTransactionReceipt");
+        Ok(EthApi::transaction_receipt(self, hash).await?)
     }
 
     /// Handler for: `eth_getBalance`
@@ -247,7 +248,8 @@ where
 
     /// Handler for: `eth_gasPrice`
     async fn gas_price(&self) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        trace!(target: "rpc::eth", "Serving eth_gasPrice");
+        Ok(EthApi::gas_price(self).await?)
     }
 
     // FeeHistory is calculated based on lazy evaluation of fees for historical blocks, and further
@@ -263,15 +265,20 @@ where
         &self,
         block_count: U64,
         newest_block: BlockId,
-        _reward_percentiles: Option<Vec<f64>>,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> Result<FeeHistory> {
-        trace!(target: "rpc::eth", ?block_count, ?newest_block, ?_reward_percentiles, "Serving eth_feeHistory");
+        trace!(target: "rpc::eth", ?block_count, ?newest_block, ?reward_percentiles, "Serving eth_feeHistory");
         let block_count = block_count.as_u64();
 
         if block_count == 0 {
             return Ok(FeeHistory::default())
         }
 
+        let reward_percentiles = reward_percentiles.filter(|percentiles| !percentiles.is_empty());
+        if let Some(percentiles) = &reward_percentiles {
+            validate_reward_percentiles(percentiles)?;
+        }
+
         let Some(end_block) = self.inner.client.block_number_for_id(newest_block).to_rpc_result()? else { return Err(EthApiError::UnknownBlockNumber.into())};
 
         if end_block < block_count {
@@ -321,12 +328,8 @@ where
                         try_into().unwrap(); // u64 -> U256 won't fail
                 let gas_used_ratio = header.gas_used as f64 / header.gas_limit as f64;
 
-                let fee_history_cache_item = FeeHistoryCacheItem {
-                    hash: None,
-                    base_fee_per_gas,
-                    gas_used_ratio,
-                    reward: None, // TODO: calculate rewards per transaction
-                };
+                let fee_history_cache_item =
+                    FeeHistoryCacheItem { hash: None, base_fee_per_gas, gas_used_ratio };
 
                 // Insert missing cache entries in the map for further response composition from it
                 fee_history_cache_items.insert(header.number, fee_history_cache_item.clone());
@@ -340,6 +343,16 @@ where
         fee_history_cache_items.get_mut(&start_block).unwrap().hash = Some(oldest_block_hash);
         fee_history_cache.get_mut(&start_block).unwrap().hash = Some(oldest_block_hash);
 
+        // The rewards depend on the requested percentiles, so they're not cached
+        let reward = reward_percentiles
+            .map(|percentiles| {
+                fee_history_cache_items
+                    .keys()
+                    .map(|number| self.block_reward_percentiles(*number, &percentiles))
+                    .collect::<EthResult<Vec<_>>>()
+            })
+            .transpose()?;
+
         // `fee_history_cache_items` now contains full requested block range (populated from both
         // cache and database), so we can iterate over it in order and populate the response fields
         Ok(FeeHistory {
@@ -352,13 +365,14 @@ where
                 .map(|item| item.gas_used_ratio)
                 .collect(),
             oldest_block: U256::from_be_bytes(oldest_block_hash.0),
-            reward: None,
+            reward,
         })
     }
 
     /// Handler for: `eth_maxPriorityFeePerGas`
     async fn max_priority_fee_per_gas(&self) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        trace!(target: "rpc::eth", "Serving eth_maxPriorityFeePerGas");
+        Ok(EthApi::suggested_priority_fee(self).await?)
     }
 
     /// Handler for: `eth_mining`
@@ -435,7 +449,10 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{eth::cache::EthStateCache, EthApi};
+    use crate::{
+        eth::{cache::EthStateCache, gas_oracle::GasPriceOracle},
+        EthApi,
+    };
     use jsonrpsee::{
         core::{error::Error as RpcError, RpcResult},
         types::error::{CallError, INVALID_PARAMS_CODE},
@@ -450,11 +467,13 @@ mod tests {
     #[tokio::test]
     /// Handler for: `eth_test_fee_history`
     async fn test_fee_history() {
+        let cache = EthStateCache::spawn(NoopProvider::default(), Default::default());
         let eth_api = EthApi::new(
             NoopProvider::default(),
             testing_pool(),
             NoopNetwork::default(),
-            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+            cache.clone(),
+            GasPriceOracle::new(NoopProvider::default(), Default::default(), cache),
         );
 
         let response = eth_api.fee_history(1.into(), BlockNumberOrTag::Latest.into(), None).await;
@@ -494,11 +513,13 @@ mod tests {
                 .push(base_fee_per_gas.map(|fee| U256::try_from(fee).unwrap()).unwrap_or_default());
         }
 
+        let cache = EthStateCache::spawn(NoopProvider::default(), Default::default());
         let eth_api = EthApi::new(
-            mock_provider,
+            mock_provider.clone(),
             testing_pool(),
             NoopNetwork::default(),
-            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+            cache.clone(),
+            GasPriceOracle::new(mock_provider, Default::default(), cache),
         );
 
         let response =
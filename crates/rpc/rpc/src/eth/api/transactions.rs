@@ -7,13 +7,17 @@ use crate::{
     EthApi,
 };
 use async_trait::async_trait;
+use ethers_core::utils::get_contract_address;
 use reth_primitives::{
-    BlockId, BlockNumberOrTag, Bytes, FromRecoveredTransaction, IntoRecoveredTransaction,
-    TransactionSignedEcRecovered, H256, U256,
+    BlockId, BlockNumberOrTag, Bytes, FromRecoveredTransaction, IntoRecoveredTransaction, Receipt,
+    TransactionKind, TransactionMeta, TransactionSigned, TransactionSignedEcRecovered, H256, U128,
+    U256, U64,
 };
 use reth_provider::{providers::ChainState, BlockProvider, EvmEnvProvider, StateProviderFactory};
 
-use reth_rpc_types::{Index, Transaction, TransactionInfo, TransactionRequest};
+use reth_rpc_types::{
+    Index, Log, Transaction, TransactionInfo, TransactionReceipt, TransactionRequest,
+};
 use reth_transaction_pool::{TransactionOrigin, TransactionPool};
 use revm::primitives::{BlockEnv, CfgEnv};
 
@@ -183,6 +187,25 @@ where
         Ok(None)
     }
 
+    /// Returns the receipt of the mined transaction with the given hash.
+    ///
+    /// Returns `Ok(None)` if the transaction is not mined.
+    pub(crate) async fn transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> EthResult<Option<TransactionReceipt>> {
+        let Some((tx, meta)) = self.client().transaction_by_hash_with_meta(hash)? else {
+            return Ok(None)
+        };
+        let Some(receipts) = self.client().receipts_by_block(meta.block_hash.into())? else {
+            return Ok(None)
+        };
+        let header =
+            self.client().header(&meta.block_hash)?.ok_or(EthApiError::UnknownBlockNumber)?;
+
+        build_transaction_receipt(tx, meta, header.base_fee_per_gas, &receipts).map(Some)
+    }
+
     /// Decodes and recovers the transaction and submits it to the pool.
     ///
     /// Returns the hash of the transaction.
@@ -255,6 +278,74 @@ impl TransactionSource {
     }
 }
 
+/// Builds the rpc receipt of a mined transaction from the receipts of its block.
+fn build_transaction_receipt(
+    tx: TransactionSigned,
+    meta: TransactionMeta,
+    base_fee: Option<u64>,
+    receipts: &[Receipt],
+) -> EthResult<TransactionReceipt> {
+    let index = meta.index as usize;
+    let receipt = receipts.get(index).ok_or(EthApiError::TransactionNotFound)?;
+    let tx = tx.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
+    let from = tx.signer();
+
+    // the receipts only store the gas used by the block so far
+    let prev_cumulative_gas_used =
+        index.checked_sub(1).map(|prev| receipts[prev].cumulative_gas_used).unwrap_or_default();
+    let gas_used = receipt.cumulative_gas_used - prev_cumulative_gas_used;
+
+    let (to, contract_address) = match tx.kind() {
+        TransactionKind::Create => (None, Some(get_contract_address(from, tx.nonce()).into())),
+        TransactionKind::Call(to) => (Some(*to), None),
+    };
+
+    // the index of the first log of the transaction in the block
+    let first_log_index: usize = receipts[..index].iter().map(|receipt| receipt.logs.len()).sum();
+    let logs = receipt
+        .logs
+        .iter()
+        .enumerate()
+        .map(|(tx_log_index, log)| Log {
+            address: log.address,
+            topics: log.topics.clone(),
+            data: log.data.clone(),
+            block_hash: Some(meta.block_hash),
+            block_number: Some(U256::from(meta.block_number)),
+            transaction_hash: Some(meta.tx_hash),
+            transaction_index: Some(U256::from(meta.index)),
+            log_index: Some(U256::from(first_log_index + tx_log_index)),
+            transaction_log_index: Some(U256::from(tx_log_index)),
+            removed: false,
+        })
+        .collect();
+
+    // Receipts of transactions before `EIP-658: Embedding transaction status code in receipts`
+    // contain the state root after the transaction instead of the status.
+    let status_code = match receipt.state_root {
+        Some(_) => None,
+        None => Some(U64::from(receipt.success as u64)),
+    };
+
+    Ok(TransactionReceipt {
+        transaction_hash: Some(meta.tx_hash),
+        transaction_index: Some(U256::from(meta.index)),
+        block_hash: Some(meta.block_hash),
+        block_number: Some(U256::from(meta.block_number)),
+        from,
+        to,
+        cumulative_gas_used: U256::from(receipt.cumulative_gas_used),
+        gas_used: Some(U256::from(gas_used)),
+        contract_address,
+        logs,
+        state_root: receipt.state_root,
+        logs_bloom: receipt.bloom_slow(),
+        status_code,
+        effective_gas_price: U128::from(tx.effective_gas_price(base_fee)),
+        transaction_type: U256::from(u8::from(tx.tx_type())),
+    })
+}
+
 impl From<TransactionSource> for TransactionSignedEcRecovered {
     fn from(value: TransactionSource) -> Self {
         match value {
@@ -282,8 +373,12 @@ impl From<TransactionSource> for Transaction {
 
 #[cfg(test)]
 mod tests {
-    use crate::eth::cache::EthStateCache;
-    use reth_primitives::{hex_literal::hex, Bytes};
+    use super::build_transaction_receipt;
+    use crate::eth::{cache::EthStateCache, gas_oracle::GasPriceOracle};
+    use reth_primitives::{
+        hex_literal::hex, Address, Bytes, Receipt, TransactionMeta, TransactionSigned, TxType,
+        H256, U128, U256,
+    };
     use reth_provider::test_utils::NoopProvider;
     use reth_transaction_pool::{test_utils::testing_pool, TransactionPool};
 
@@ -295,11 +390,13 @@ mod tests {
 
         let pool = testing_pool();
 
+        let cache = EthStateCache::spawn(NoopProvider::default(), Default::default());
         let eth_api = EthApi::new(
             noop_provider,
             pool.clone(),
             (),
-            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+            cache.clone(),
+            GasPriceOracle::new(noop_provider, Default::default(), cache),
         );
 
         // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
@@ -327,4 +424,50 @@ mod tests {
         assert!(pool.get(&tx_1_result).is_some(), "tx1 not found in the pool");
         assert!(pool.get(&tx_2_result).is_some(), "tx2 not found in the pool");
     }
+
+    #[test]
+    fn receipt_with_state_root() {
+        // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
+        let tx = TransactionSigned::decode_enveloped(Bytes::from(hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3"))).unwrap();
+        let meta = TransactionMeta {
+            tx_hash: tx.hash(),
+            index: 1,
+            block_hash: H256::from_low_u64_be(1),
+            block_number: 2,
+        };
+        let log = reth_primitives::Log {
+            address: Address::from_low_u64_be(3),
+            topics: vec![H256::from_low_u64_be(4)],
+            data: Bytes::from(vec![5]),
+        };
+        let state_root = H256::from_low_u64_be(6);
+        let receipts = vec![
+            Receipt {
+                tx_type: TxType::Legacy,
+                success: false,
+                cumulative_gas_used: 21_000,
+                state_root: Some(H256::from_low_u64_be(7)),
+                logs: vec![log.clone()],
+            },
+            Receipt {
+                tx_type: TxType::EIP1559,
+                success: false,
+                cumulative_gas_used: 42_000,
+                state_root: Some(state_root),
+                logs: vec![log],
+            },
+        ];
+
+        let receipt = build_transaction_receipt(tx, meta, Some(10), &receipts).unwrap();
+        assert_eq!(receipt.gas_used, Some(U256::from(21_000)));
+        assert_eq!(receipt.effective_gas_price, U128::from(10));
+        assert_eq!(receipt.logs[0].log_index, Some(U256::from(1)));
+        assert_eq!(receipt.logs[0].transaction_log_index, Some(U256::from(0)));
+        assert_eq!(receipt.state_root, Some(state_root));
+        assert_eq!(receipt.status_code, None);
+
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert!(json.get("root").is_some());
+        assert!(json.get("status").is_none());
+    }
 }
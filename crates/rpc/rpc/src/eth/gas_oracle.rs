@@ -0,0 +1,311 @@
+//! An implementation of the eth gas price oracle, suggesting priority fees based on the tips paid
+//! in recent blocks.
+
+use crate::eth::{
+    cache::EthStateCache,
+    error::{EthApiError, EthResult},
+};
+use reth_primitives::{constants::GWEI_TO_WEI, Block, H256, U256};
+use reth_provider::BlockProvider;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// The number of transactions sampled per block, the ones with the lowest tips.
+pub const SAMPLE_NUMBER: usize = 3;
+
+/// The default maximum suggested priority fee, 500 gwei.
+pub const DEFAULT_MAX_PRICE: U256 = U256::from_limbs([500 * GWEI_TO_WEI, 0, 0, 0]);
+
+/// The default tip below which transactions are not sampled, 2 wei.
+pub const DEFAULT_IGNORE_PRICE: U256 = U256::from_limbs([2, 0, 0, 0]);
+
+/// Settings for the [GasPriceOracle]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GasPriceOracleConfig {
+    /// The number of recent blocks to sample.
+    ///
+    /// Default is 20
+    pub blocks: u32,
+    /// The percentile of the sampled tips to suggest.
+    ///
+    /// Default is 60
+    pub percentile: u32,
+    /// The maximum suggested priority fee.
+    ///
+    /// Default is 500 gwei
+    pub max_price: Option<U256>,
+    /// Transactions paying a lower tip than this are not sampled.
+    ///
+    /// Default is 2 wei
+    pub ignore_price: Option<U256>,
+}
+
+impl Default for GasPriceOracleConfig {
+    fn default() -> Self {
+        Self {
+            blocks: 20,
+            percentile: 60,
+            max_price: Some(DEFAULT_MAX_PRICE),
+            ignore_price: Some(DEFAULT_IGNORE_PRICE),
+        }
+    }
+}
+
+/// The suggestion of the [GasPriceOracle] for a head block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct GasPriceOracleResult {
+    /// The hash of the head block the price was suggested at.
+    block_hash: H256,
+    /// The suggested priority fee.
+    price: U256,
+}
+
+/// Suggests priority fees from the tips paid in recent blocks, similar to geth's `gasprice`
+/// package.
+///
+/// The lowest tips of every sampled block are collected, ignoring the transactions sent by the
+/// miner of the block, and the configured percentile of them is suggested. The suggestion is
+/// cached until the head block changes.
+#[derive(Debug)]
+pub struct GasPriceOracle<Client> {
+    /// The type used to lookup the head block
+    client: Client,
+    /// The async cache frontend for the sampled blocks
+    cache: EthStateCache,
+    /// The config for the oracle
+    config: GasPriceOracleConfig,
+    /// The latest suggestion
+    last_price: Mutex<GasPriceOracleResult>,
+}
+
+impl<Client> GasPriceOracle<Client>
+where
+    Client: BlockProvider + 'static,
+{
+    /// Creates and returns the [GasPriceOracle].
+    pub fn new(client: Client, mut config: GasPriceOracleConfig, cache: EthStateCache) -> Self {
+        if config.percentile > 100 {
+            warn!(target: "rpc::eth", percentile = config.percentile, "Invalid gas price oracle percentile, using 100");
+            config.percentile = 100;
+        }
+        if config.blocks == 0 {
+            warn!(target: "rpc::eth", "Invalid number of gas price oracle blocks, using 1");
+            config.blocks = 1;
+        }
+
+        Self { client, cache, config, last_price: Default::default() }
+    }
+
+    /// Returns the configuration of the oracle.
+    pub fn config(&self) -> &GasPriceOracleConfig {
+        &self.config
+    }
+
+    /// Suggests a priority fee based on the tips paid in the recent blocks.
+    pub async fn suggest_tip_cap(&self) -> EthResult<U256> {
+        let head = self.client.chain_info()?;
+
+        let mut last_price = self.last_price.lock().await;
+        if last_price.block_hash == head.best_hash {
+            return Ok(last_price.price)
+        }
+
+        let mut prices = Vec::new();
+        let mut block_hash = head.best_hash;
+        let mut number = head.best_number;
+        for _ in 0..self.config.blocks {
+            // the genesis block has no transactions
+            if number == 0 {
+                break
+            }
+
+            let block =
+                self.cache.get_block(block_hash).await?.ok_or(EthApiError::UnknownBlockNumber)?;
+            let block_prices = self.block_prices(&block);
+            if block_prices.is_empty() {
+                // The block is empty or only contains transactions of the miner, in which case the
+                // last suggestion is sampled instead.
+                prices.push(last_price.price);
+            } else {
+                prices.extend(block_prices);
+            }
+
+            block_hash = block.parent_hash;
+            number = block.number.saturating_sub(1);
+        }
+
+        let mut price = last_price.price;
+        if !prices.is_empty() {
+            prices.sort_unstable();
+            price = prices[(prices.len() - 1) * self.config.percentile as usize / 100];
+        }
+        if let Some(max_price) = self.config.max_price {
+            price = price.min(max_price);
+        }
+
+        *last_price = GasPriceOracleResult { block_hash: head.best_hash, price };
+        Ok(price)
+    }
+
+    /// Returns the lowest tips paid in the block, ignoring the transactions of the miner and the
+    /// ones paying less than the ignored price.
+    fn block_prices(&self, block: &Block) -> Vec<U256> {
+        let base_fee = block.base_fee_per_gas.unwrap_or_default() as u128;
+
+        let mut tips = block
+            .body
+            .iter()
+            .map(|tx| {
+                let tip = tx.effective_gas_price(block.base_fee_per_gas).saturating_sub(base_fee);
+                (U256::from(tip), tx)
+            })
+            .filter(|(tip, _)| self.config.ignore_price.map_or(true, |ignore| *tip >= ignore))
+            .collect::<Vec<_>>();
+        tips.sort_unstable_by_key(|(tip, _)| *tip);
+
+        tips.into_iter()
+            .filter(|(_, tx)| tx.recover_signer() != Some(block.beneficiary))
+            .map(|(tip, _)| tip)
+            .take(SAMPLE_NUMBER)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, Transaction, TransactionSigned, TxLegacy};
+    use reth_provider::test_utils::{MockEthProvider, NoopProvider};
+
+    /// Adds a block with legacy transactions paying the gas prices, on top of the block before.
+    fn add_block(provider: &MockEthProvider, number: u64, gas_prices: &[u128]) {
+        let body = gas_prices
+            .iter()
+            .map(|&gas_price| {
+                TransactionSigned::from_transaction_and_signature(
+                    Transaction::Legacy(TxLegacy { gas_price, ..Default::default() }),
+                    Default::default(),
+                )
+            })
+            .collect();
+        let header = Header {
+            number,
+            parent_hash: H256::from_low_u64_be(number.saturating_sub(1)),
+            ..Default::default()
+        };
+        provider
+            .add_block(H256::from_low_u64_be(number), Block { header, body, ..Default::default() });
+    }
+
+    fn new_oracle(
+        provider: &MockEthProvider,
+        config: GasPriceOracleConfig,
+    ) -> GasPriceOracle<MockEthProvider> {
+        let cache = EthStateCache::spawn(provider.clone(), Default::default());
+        GasPriceOracle::new(provider.clone(), config, cache)
+    }
+
+    #[test]
+    fn deserialize_partial_config() {
+        let config: GasPriceOracleConfig = serde_json::from_str(r#"{"percentile":80}"#).unwrap();
+        assert_eq!(config, GasPriceOracleConfig { percentile: 80, ..Default::default() });
+    }
+
+    #[tokio::test]
+    async fn block_prices() {
+        let oracle = GasPriceOracle::new(
+            NoopProvider::default(),
+            GasPriceOracleConfig::default(),
+            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+        );
+
+        let body = [1, 50, 10, 30, 20]
+            .into_iter()
+            .map(|gas_price| {
+                TransactionSigned::from_transaction_and_signature(
+                    Transaction::Legacy(TxLegacy { gas_price, ..Default::default() }),
+                    Default::default(),
+                )
+            })
+            .collect();
+        let block = Block { body, ..Default::default() };
+
+        // the tip below the ignored price is skipped
+        assert_eq!(
+            oracle.block_prices(&block),
+            [10, 20, 30].into_iter().map(U256::from).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn suggest_tip_cap_percentile() {
+        let provider = MockEthProvider::default();
+        add_block(&provider, 0, &[]);
+        add_block(&provider, 1, &[70, 10, 30, 20]);
+        add_block(&provider, 2, &[40, 50, 60]);
+
+        // the three lowest tips of each block are sampled: 10, 20, 30, 40, 50, 60
+        let oracle = new_oracle(&provider, GasPriceOracleConfig::default());
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(40));
+
+        let config = GasPriceOracleConfig { percentile: 0, ..Default::default() };
+        assert_eq!(new_oracle(&provider, config).suggest_tip_cap().await.unwrap(), U256::from(10));
+
+        let config = GasPriceOracleConfig { percentile: 100, ..Default::default() };
+        assert_eq!(new_oracle(&provider, config).suggest_tip_cap().await.unwrap(), U256::from(60));
+
+        // only the head block is sampled
+        let config = GasPriceOracleConfig { blocks: 1, ..Default::default() };
+        assert_eq!(new_oracle(&provider, config).suggest_tip_cap().await.unwrap(), U256::from(50));
+    }
+
+    #[tokio::test]
+    async fn suggest_tip_cap_max_price() {
+        let provider = MockEthProvider::default();
+        add_block(&provider, 0, &[]);
+        add_block(&provider, 1, &[10, 20, 30]);
+
+        let config = GasPriceOracleConfig { max_price: Some(U256::from(15)), ..Default::default() };
+        assert_eq!(new_oracle(&provider, config).suggest_tip_cap().await.unwrap(), U256::from(15));
+
+        add_block(&provider, 2, &[600 * GWEI_TO_WEI as u128; 3]);
+        let config = GasPriceOracleConfig { blocks: 1, ..Default::default() };
+        assert_eq!(
+            new_oracle(&provider, config).suggest_tip_cap().await.unwrap(),
+            DEFAULT_MAX_PRICE
+        );
+    }
+
+    #[tokio::test]
+    async fn suggest_tip_cap_empty_block() {
+        let provider = MockEthProvider::default();
+        add_block(&provider, 0, &[]);
+        add_block(&provider, 1, &[10, 20, 30]);
+        // the only tip is below the ignored price
+        add_block(&provider, 2, &[1]);
+
+        // the head block samples the last suggestion instead, which is zero without one
+        let oracle = new_oracle(&provider, GasPriceOracleConfig::default());
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(10));
+    }
+
+    #[tokio::test]
+    async fn suggest_tip_cap_cached_per_head() {
+        let provider = MockEthProvider::default();
+        add_block(&provider, 0, &[]);
+        add_block(&provider, 1, &[10, 20, 30]);
+
+        let oracle = new_oracle(&provider, GasPriceOracleConfig::default());
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(20));
+        assert_eq!(oracle.last_price.lock().await.block_hash, H256::from_low_u64_be(1));
+
+        // the suggestion isn't computed again until the head changes
+        oracle.last_price.lock().await.price = U256::from(1);
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(1));
+
+        add_block(&provider, 2, &[40, 50, 60]);
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(40));
+    }
+}
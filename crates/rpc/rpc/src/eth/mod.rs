@@ -4,6 +4,7 @@ mod api;
 pub mod cache;
 pub mod error;
 mod filter;
+pub mod gas_oracle;
 mod id_provider;
 mod logs_utils;
 mod pubsub;
@@ -1,14 +1,18 @@
 use crate::{
     eth::{
-        cache::EthStateCache, error::EthResult, revm_utils::inspect,
-        utils::recover_raw_transaction, EthTransactions,
+        cache::EthStateCache,
+        error::{EthApiError, EthResult},
+        revm_utils::inspect,
+        utils::recover_raw_transaction,
+        EthTransactions,
     },
     result::internal_rpc_err,
 };
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult as Result;
-use reth_primitives::{BlockId, BlockNumberOrTag, Bytes, H256};
-use reth_provider::{BlockProvider, EvmEnvProvider, StateProviderFactory};
+use reth_executor::rewards::{block_rewards, BlockReward, BlockRewardKind};
+use reth_primitives::{BlockId, BlockNumber, BlockNumberOrTag, Bytes, H256};
+use reth_provider::{BlockProvider, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
 use reth_revm::{
     database::{State, SubState},
     env::tx_env_with_recovered,
@@ -18,9 +22,12 @@ use reth_revm::{
 use reth_rpc_api::TraceApiServer;
 use reth_rpc_types::{
     trace::{filter::TraceFilter, parity::*},
-    CallRequest, Index,
+    CallRequest, Index, TransactionInfo,
+};
+use revm::{
+    primitives::{Env, ExecutionResult, ResultAndState},
+    DatabaseCommit,
 };
-use revm::primitives::{Env, ExecutionResult, ResultAndState};
 use std::collections::HashSet;
 
 /// `trace` API implementation.
@@ -49,7 +56,7 @@ impl<Client, Eth> TraceApi<Client, Eth> {
 
 impl<Client, Eth> TraceApi<Client, Eth>
 where
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Executes the transaction at the given [BlockId] with a tracer configured by the config.
@@ -156,12 +163,69 @@ where
             Ok(Some(traces))
         })
     }
+
+    /// Returns the traces of all transactions in the block, followed by the traces of the block
+    /// and ommer rewards.
+    pub async fn trace_block(
+        &self,
+        block_id: BlockId,
+    ) -> EthResult<Option<Vec<LocalizedTransactionTrace>>> {
+        let Some(block) = self.client.block(block_id)? else { return Ok(None) };
+        let block_hash = block.hash_slow();
+        let total_difficulty =
+            self.client.header_td(&block_hash)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let (cfg, block_env, _) = self.eth_api.evm_env_at(block_hash.into()).await?;
+
+        // execute the transactions on top of each other, starting with the state of the parent
+        let mut traces =
+            self.eth_api.with_state_at(BlockId::Hash(block.parent_hash.into()), |state| {
+                let mut evm = revm::EVM::new();
+                evm.database(SubState::new(State::new(state)));
+
+                let mut traces = Vec::new();
+                for (index, tx) in block.body.iter().enumerate() {
+                    let tx = tx
+                        .clone()
+                        .into_ecrecovered()
+                        .ok_or(EthApiError::InvalidTransactionSignature)?;
+                    let tx_info = TransactionInfo {
+                        hash: Some(tx.hash()),
+                        index: Some(index as u64),
+                        block_hash: Some(block_hash),
+                        block_number: Some(block.number),
+                    };
+
+                    evm.env = Env {
+                        cfg: cfg.clone(),
+                        block: block_env.clone(),
+                        tx: tx_env_with_recovered(&tx),
+                    };
+                    let mut inspector =
+                        TracingInspector::new(TracingInspectorConfig::default_parity());
+                    let ResultAndState { state, .. } = evm.inspect(&mut inspector)?;
+                    evm.db.as_mut().expect("db is set").commit(state);
+
+                    traces.extend(
+                        inspector.into_parity_builder().into_localized_transaction_traces(tx_info),
+                    );
+                }
+                Ok(traces)
+            })?;
+
+        traces.extend(
+            block_rewards(&self.client.chain_spec(), &block, total_difficulty)
+                .into_iter()
+                .map(|reward| reward_trace(block_hash, block.number, reward)),
+        );
+
+        Ok(Some(traces))
+    }
 }
 
 #[async_trait]
 impl<Client, Eth> TraceApiServer for TraceApi<Client, Eth>
 where
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Executes the given call and returns a number of possible traces for it.
@@ -216,9 +280,9 @@ where
     /// Handler for `trace_block`
     async fn trace_block(
         &self,
-        _block_id: BlockId,
+        block_id: BlockId,
     ) -> Result<Option<Vec<LocalizedTransactionTrace>>> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(TraceApi::trace_block(self, block_id).await?)
     }
 
     /// Handler for `trace_filter`
@@ -251,6 +315,34 @@ impl<Client, Eth> std::fmt::Debug for TraceApi<Client, Eth> {
     }
 }
 
+/// Returns the parity style trace of a block or ommer reward.
+fn reward_trace(
+    block_hash: H256,
+    block_number: BlockNumber,
+    reward: BlockReward,
+) -> LocalizedTransactionTrace {
+    let reward_type = match reward.kind {
+        BlockRewardKind::Block => RewardType::Block,
+        BlockRewardKind::Ommer => RewardType::Uncle,
+    };
+    LocalizedTransactionTrace {
+        trace: TransactionTrace {
+            trace_address: vec![],
+            subtraces: 0,
+            action: Action::Reward(RewardAction {
+                author: reward.beneficiary,
+                value: reward.value,
+                reward_type,
+            }),
+            result: None,
+        },
+        transaction_position: None,
+        transaction_hash: None,
+        block_number: Some(block_number),
+        block_hash: Some(block_hash),
+    }
+}
+
 /// Returns the [TracingInspectorConfig] depending on the enabled [TraceType]s
 fn tracing_config(trace_types: &HashSet<TraceType>) -> TracingInspectorConfig {
     TracingInspectorConfig::default_parity()
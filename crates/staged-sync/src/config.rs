@@ -5,6 +5,7 @@ use reth_downloaders::{
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
 use reth_network::{config::rng_secret_key, NetworkConfigBuilder, PeersConfig};
+use reth_primitives::PruneModes;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -17,6 +18,10 @@ pub struct Config {
     pub stages: StageConfig,
     /// Configuration for the discovery service.
     pub peers: PeersConfig,
+    /// Configuration for pruning of historical data.
+    pub prune: PruneConfig,
+    /// Configuration for moving finalized data into static files.
+    pub static_files: StaticFilesConfig,
 }
 
 impl Config {
@@ -145,20 +150,59 @@ impl Default for SenderRecoveryConfig {
 
 /// Execution stage configuration.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct ExecutionConfig {
     /// The maximum number of blocks to execution before committing progress to the database.
     pub commit_threshold: u64,
+    /// The number of threads reading the state of upcoming transactions ahead of their
+    /// execution, `0` disables prefetching.
+    pub prefetch_threads: usize,
 }
 
 impl Default for ExecutionConfig {
     fn default() -> Self {
-        Self { commit_threshold: 5_000 }
+        Self { commit_threshold: 5_000, prefetch_threads: 4 }
+    }
+}
+
+/// Pruning configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PruneConfig {
+    /// Minimum number of blocks between two runs of the pruner.
+    pub block_interval: u64,
+    /// Prune modes of the prunable segments, all data is kept by default.
+    pub segments: PruneModes,
+}
+
+impl Default for PruneConfig {
+    fn default() -> Self {
+        Self { block_interval: 5, segments: PruneModes::none() }
+    }
+}
+
+/// Static files configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct StaticFilesConfig {
+    /// Whether finalized headers, transactions and receipts are moved into static files.
+    pub enabled: bool,
+    /// Minimum number of blocks between two runs of the static file producer.
+    pub block_interval: u64,
+    /// Number of most recent blocks whose data is kept in the database.
+    pub distance: u64,
+}
+
+impl Default for StaticFilesConfig {
+    fn default() -> Self {
+        Self { enabled: false, block_interval: 1_000, distance: 10_000 }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Config;
+    use reth_primitives::PruneMode;
 
     const EXTENSION: &str = "toml";
 
@@ -189,4 +233,50 @@ mod tests {
             assert_eq!(config, loaded_config);
         })
     }
+
+    #[test]
+    fn test_load_prune_config() {
+        with_tempdir("config-prune-test", |config_path| {
+            std::fs::write(
+                config_path,
+                r#"
+[prune]
+block_interval = 10
+
+[prune.segments]
+receipts = { distance = 100000 }
+transaction_lookup = "full"
+account_history = { before = 15537394 }
+"#,
+            )
+            .unwrap();
+
+            let config: Config = confy::load_path(config_path).unwrap();
+            assert_eq!(config.prune.block_interval, 10);
+            assert_eq!(config.prune.segments.receipts, Some(PruneMode::Distance(100_000)));
+            assert_eq!(config.prune.segments.transaction_lookup, Some(PruneMode::Full));
+            assert_eq!(config.prune.segments.account_history, Some(PruneMode::Before(15_537_394)));
+            assert_eq!(config.prune.segments.storage_history, None);
+        })
+    }
+
+    #[test]
+    fn test_load_static_files_config() {
+        with_tempdir("config-static-files-test", |config_path| {
+            std::fs::write(
+                config_path,
+                r#"
+[static_files]
+enabled = true
+distance = 50000
+"#,
+            )
+            .unwrap();
+
+            let config: Config = confy::load_path(config_path).unwrap();
+            assert!(config.static_files.enabled);
+            assert_eq!(config.static_files.block_interval, 1_000);
+            assert_eq!(config.static_files.distance, 50_000);
+        })
+    }
 }
@@ -4,20 +4,48 @@ use reth_db::{
     mdbx::{Env, WriteMap},
     tables,
     transaction::{DbTx, DbTxMut},
+    version::{check_db_version_file, create_db_version_file, DatabaseVersionError},
 };
-use reth_primitives::{keccak256, Account, Bytecode, ChainSpec, StorageEntry, H256};
-use std::{path::Path, sync::Arc};
+use reth_primitives::{
+    keccak256, Account, Bytecode, ChainSpec, StageCheckpoint, StorageEntry, H256, U256,
+};
+use reth_provider::trie::{StateRoot, TrieError, MERKLE_STAGE_ID};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 use tracing::debug;
 
 /// Opens up an existing database or creates a new one at the specified path.
+///
+/// Fails if an existing database was created with a different
+/// [database version](reth_db::version::DB_VERSION).
 pub fn init_db<P: AsRef<Path>>(path: P) -> eyre::Result<Env<WriteMap>> {
-    std::fs::create_dir_all(path.as_ref())?;
-    let db = Env::<WriteMap>::open(path.as_ref(), reth_db::mdbx::EnvKind::RW)?;
+    let path = path.as_ref();
+    if is_database_empty(path) {
+        std::fs::create_dir_all(path)?;
+        create_db_version_file(path)?;
+    } else {
+        match check_db_version_file(path) {
+            Ok(_) => (),
+            // Databases created before the version file was introduced predate version 1
+            Err(DatabaseVersionError::MissingFile) => {
+                return Err(DatabaseVersionError::VersionMismatch { version: 0 }.into())
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    let db = Env::<WriteMap>::open(path, reth_db::mdbx::EnvKind::RW)?;
     db.create_tables()?;
 
     Ok(db)
 }
 
+/// Returns true if there is no database at the specified path.
+fn is_database_empty(path: &Path) -> bool {
+    match path.read_dir() {
+        Ok(mut dir) => dir.next().is_none(),
+        Err(_) => true,
+    }
+}
+
 /// Database initialization error type.
 #[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
 pub enum InitDatabaseError {
@@ -30,9 +58,22 @@ pub enum InitDatabaseError {
         actual: H256,
     },
 
+    /// The state root of the genesis state trie doesn't match the one of the genesis header.
+    #[error("Genesis state root mismatch: expected {expected}, got {actual}")]
+    StateRootMismatch {
+        /// Expected state root.
+        expected: H256,
+        /// Actual state root.
+        actual: H256,
+    },
+
     /// Low-level database error.
     #[error(transparent)]
     DBError(#[from] reth_db::Error),
+
+    /// Error while computing the genesis state trie.
+    #[error(transparent)]
+    TrieError(#[from] TrieError),
 }
 
 /// Write the genesis block if it has not already been written
@@ -60,6 +101,7 @@ pub fn init_genesis<DB: Database>(
     debug!("Writing genesis block.");
     let tx = db.tx_mut()?;
     insert_genesis_state::<DB>(&tx, genesis)?;
+    insert_genesis_trie::<DB>(&tx, genesis, header.state_root)?;
 
     // Insert header
     tx.put::<tables::CanonicalHeaders>(0, hash)?;
@@ -111,16 +153,65 @@ pub fn insert_genesis_state<DB: Database>(
     Ok(())
 }
 
+/// Inserts the hashed genesis state and its state trie into the database.
+///
+/// The merkle stage checkpoint is set to the genesis block, so the state roots of the blocks
+/// executed before the merkle stage ran can be computed on top of the genesis trie.
+pub fn insert_genesis_trie<DB: Database>(
+    tx: &<DB as DatabaseGAT<'_>>::TXMut,
+    genesis: &reth_primitives::Genesis,
+    state_root: H256,
+) -> Result<(), InitDatabaseError> {
+    let mut account_cursor = tx.cursor_write::<tables::HashedAccount>()?;
+    let mut storage_cursor = tx.cursor_dup_write::<tables::HashedStorage>()?;
+
+    for (address, account) in &genesis.alloc {
+        let hashed_address = keccak256(address);
+        account_cursor.upsert(
+            hashed_address,
+            Account {
+                nonce: account.nonce.unwrap_or_default(),
+                balance: account.balance,
+                bytecode_hash: account.code.as_ref().map(|code| keccak256(code.as_ref())),
+            },
+        )?;
+        if let Some(storage) = &account.storage {
+            // the slots are sorted by their hash, and zero values aren't part of the trie
+            let storage = storage
+                .iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(key, value)| (keccak256(key), U256::from_be_bytes(value.0)))
+                .collect::<BTreeMap<_, _>>();
+            for (key, value) in storage {
+                storage_cursor.upsert(hashed_address, StorageEntry { key, value })?;
+            }
+        }
+    }
+
+    let root = StateRoot::new(tx).calculate_root()?;
+    if root != state_root {
+        return Err(InitDatabaseError::StateRootMismatch { expected: state_root, actual: root })
+    }
+    tx.put::<tables::SyncStage>(MERKLE_STAGE_ID.to_string(), StageCheckpoint::new(0))?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 
     use std::sync::Arc;
 
-    use super::{init_genesis, InitDatabaseError};
-    use reth_db::mdbx::test_utils::create_test_rw_db;
+    use super::{init_db, init_genesis, InitDatabaseError};
+    use reth_db::{
+        database::Database,
+        mdbx::test_utils::create_test_rw_db,
+        version::{db_version_file_path, DatabaseVersionError},
+    };
     use reth_primitives::{
         GOERLI, GOERLI_GENESIS, MAINNET, MAINNET_GENESIS, SEPOLIA, SEPOLIA_GENESIS,
     };
+    use reth_provider::trie::StateRoot;
 
     #[test]
     fn success_init_genesis_mainnet() {
@@ -149,6 +240,19 @@ mod tests {
         assert_eq!(genesis_hash, SEPOLIA_GENESIS);
     }
 
+    #[test]
+    fn init_genesis_trie() {
+        let db = create_test_rw_db();
+        init_genesis(db.clone(), Arc::new(MAINNET.clone())).unwrap();
+
+        // the state trie is at the genesis block
+        let tx = db.tx().unwrap();
+        let state_root = StateRoot::new(&tx);
+        let genesis_root = MAINNET.genesis_header().state_root;
+        assert_eq!(state_root.trie_root().unwrap(), (genesis_root, 0));
+        assert_eq!(state_root.stored_root().unwrap(), genesis_root);
+    }
+
     #[test]
     fn fail_init_inconsistent_db() {
         let db = create_test_rw_db();
@@ -165,4 +269,23 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn db_version() {
+        let path = tempfile::tempdir().unwrap();
+        let db = init_db(&path).unwrap();
+        drop(db);
+
+        // Reopening a database of the current version succeeds
+        let db = init_db(&path).unwrap();
+        drop(db);
+
+        // Reopening a database of a previous version fails
+        std::fs::write(db_version_file_path(&path), "0").unwrap();
+        let err = init_db(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DatabaseVersionError>(),
+            Some(DatabaseVersionError::VersionMismatch { version: 0 })
+        ));
+    }
 }
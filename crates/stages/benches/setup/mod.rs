@@ -10,7 +10,7 @@ use reth_interfaces::test_utils::generators::{
     random_transition_range,
 };
 use reth_primitives::{Account, Address, SealedBlock, H256};
-use reth_provider::trie::DBTrieLoader;
+use reth_provider::trie::StateRoot;
 use reth_stages::{
     stages::{AccountHashingStage, StorageHashingStage},
     test_utils::TestTransaction,
@@ -70,9 +70,6 @@ pub(crate) fn unwind_hashes<S: Clone + Stage<Env<WriteMap>>>(
         StorageHashingStage::default().unwind(&mut db_tx, unwind).await.unwrap();
         AccountHashingStage::default().unwind(&mut db_tx, unwind).await.unwrap();
 
-        let target_root = db_tx.get_header(unwind.unwind_to).unwrap().state_root;
-        let _ = db_tx.delete::<tables::AccountsTrie>(target_root, None);
-
         // Clear previous run
         stage.unwind(&mut db_tx, unwind).await.unwrap();
 
@@ -124,8 +121,7 @@ pub(crate) fn txs_testdata(num_blocks: u64) -> PathBuf {
         tx.insert_accounts_and_storages(start_state.clone()).unwrap();
 
         // make first block after genesis have valid state root
-        let root =
-            DBTrieLoader::new(tx.inner().deref()).calculate_root().and_then(|e| e.root()).unwrap();
+        let root = StateRoot::new(tx.inner().deref()).calculate_root().unwrap();
         let second_block = blocks.get_mut(1).unwrap();
         let cloned_second = second_block.clone();
         let mut updated_header = cloned_second.header.unseal();
@@ -146,14 +142,13 @@ pub(crate) fn txs_testdata(num_blocks: u64) -> PathBuf {
         // make last block have valid state root
         let root = {
             let mut tx_mut = tx.inner();
-            let root =
-                DBTrieLoader::new(tx_mut.deref()).calculate_root().and_then(|e| e.root()).unwrap();
+            let root = StateRoot::new(tx_mut.deref()).calculate_root().unwrap();
             tx_mut.commit().unwrap();
             root
         };
 
         tx.query(|tx| {
-            assert!(tx.get::<tables::AccountsTrie>(root)?.is_some());
+            assert_eq!(StateRoot::new(tx).stored_root().unwrap(), root);
             Ok(())
         })
         .unwrap();
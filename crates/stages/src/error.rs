@@ -3,7 +3,7 @@ use reth_interfaces::{
     consensus, db::Error as DbError, executor, p2p::error::DownloadError, provider::ProviderError,
 };
 use reth_primitives::BlockNumber;
-use reth_provider::TransactionError;
+use reth_provider::{static_files::StaticFileError, TransactionError};
 use thiserror::Error;
 use tokio::sync::mpsc::error::SendError;
 
@@ -93,3 +93,25 @@ pub enum PipelineError {
     #[error(transparent)]
     Internal(Box<dyn std::error::Error + Send + Sync>),
 }
+
+/// A pruner error.
+#[derive(Error, Debug)]
+pub enum PrunerError {
+    /// The pruner encountered a database error.
+    #[error("A database error occurred.")]
+    Database(#[from] DbError),
+    /// The pruner encountered an error while pruning a segment.
+    #[error(transparent)]
+    Transaction(#[from] TransactionError),
+}
+
+/// A static file producer error.
+#[derive(Error, Debug)]
+pub enum StaticFileProducerError {
+    /// The static file producer encountered a database error.
+    #[error("A database error occurred.")]
+    Database(#[from] DbError),
+    /// The static file producer failed to write the static files.
+    #[error(transparent)]
+    StaticFile(#[from] StaticFileError),
+}
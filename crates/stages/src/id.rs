@@ -4,7 +4,7 @@ use reth_db::{
     transaction::{DbTx, DbTxMut},
     Error as DbError,
 };
-use reth_primitives::BlockNumber;
+use reth_primitives::{BlockNumber, StageCheckpoint, StageUnitCheckpoint};
 use std::fmt::Display;
 
 /// The ID of a stage.
@@ -25,24 +25,59 @@ impl StageId {
         *self == HEADERS || *self == BODIES
     }
 
-    /// Get the last committed progress of this stage.
-    pub fn get_progress<'db>(&self, tx: &impl DbTx<'db>) -> Result<Option<BlockNumber>, DbError> {
+    /// Get the last committed checkpoint of this stage.
+    pub fn get_checkpoint<'db>(
+        &self,
+        tx: &impl DbTx<'db>,
+    ) -> Result<Option<StageCheckpoint>, DbError> {
         tx.get::<SyncStage>(self.0.to_string())
     }
 
-    /// Save the progress of this stage.
-    pub fn save_progress<'db>(
+    /// Save the checkpoint of this stage.
+    pub fn save_checkpoint<'db>(
         &self,
         tx: &impl DbTxMut<'db>,
+        checkpoint: StageCheckpoint,
+    ) -> Result<(), DbError> {
+        tx.put::<SyncStage>(self.0.to_string(), checkpoint)
+    }
+
+    /// Get the last committed progress of this stage.
+    pub fn get_progress<'db>(&self, tx: &impl DbTx<'db>) -> Result<Option<BlockNumber>, DbError> {
+        Ok(self.get_checkpoint(tx)?.map(|checkpoint| checkpoint.block_number))
+    }
+
+    /// Save the progress of this stage, keeping its stage-specific checkpoint.
+    pub fn save_progress<'db, TX: DbTx<'db> + DbTxMut<'db>>(
+        &self,
+        tx: &TX,
         block: BlockNumber,
     ) -> Result<(), DbError> {
-        tx.put::<SyncStage>(self.0.to_string(), block)
+        let checkpoint = self.get_checkpoint(tx)?.unwrap_or_default();
+        self.save_checkpoint(tx, StageCheckpoint { block_number: block, ..checkpoint })
+    }
+
+    /// Save the stage-specific checkpoint of this stage, keeping its progress.
+    ///
+    /// The stage-specific checkpoint is cleared if `None`.
+    pub fn save_stage_checkpoint<'db, TX: DbTx<'db> + DbTxMut<'db>>(
+        &self,
+        tx: &TX,
+        stage_checkpoint: Option<StageUnitCheckpoint>,
+    ) -> Result<(), DbError> {
+        let checkpoint = self.get_checkpoint(tx)?.unwrap_or_default();
+        self.save_checkpoint(tx, StageCheckpoint { stage_checkpoint, ..checkpoint })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+    };
+    use reth_primitives::EntitiesCheckpoint;
 
     #[test]
     fn stage_id_display() {
@@ -50,6 +85,27 @@ mod tests {
         assert_eq!(StageId("bar").to_string(), "bar");
     }
 
+    #[test]
+    fn save_progress_keeps_stage_checkpoint() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let tx = db.tx_mut().unwrap();
+        let stage = StageId("foo");
+        assert_eq!(stage.get_checkpoint(&tx), Ok(None));
+
+        let stage_checkpoint =
+            StageUnitCheckpoint::Entities(EntitiesCheckpoint { processed: 1, total: Some(2) });
+        stage.save_stage_checkpoint(&tx, Some(stage_checkpoint)).unwrap();
+        stage.save_progress(&tx, 10).unwrap();
+        assert_eq!(
+            stage.get_checkpoint(&tx),
+            Ok(Some(StageCheckpoint::new(10).with_stage_checkpoint(stage_checkpoint)))
+        );
+        assert_eq!(stage.get_progress(&tx), Ok(Some(10)));
+
+        stage.save_stage_checkpoint(&tx, None).unwrap();
+        assert_eq!(stage.get_checkpoint(&tx), Ok(Some(StageCheckpoint::new(10))));
+    }
+
     #[test]
     fn is_downloading_stage() {
         assert!(HEADERS.is_downloading_stage());
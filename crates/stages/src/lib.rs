@@ -58,7 +58,9 @@
 mod error;
 mod id;
 mod pipeline;
+mod pruner;
 mod stage;
+mod static_file_producer;
 mod util;
 
 #[allow(missing_docs)]
@@ -76,7 +78,9 @@ pub mod sets;
 pub use error::*;
 pub use id::*;
 pub use pipeline::*;
+pub use pruner::*;
 pub use stage::*;
+pub use static_file_producer::*;
 
 // NOTE: Needed so the link in the module-level rustdoc works.
 #[allow(unused_extern_crates)]
@@ -2,6 +2,7 @@ use crate::{Pipeline, Stage, StageSet};
 use reth_db::database::Database;
 use reth_interfaces::sync::{NoopSyncStateUpdate, SyncStateUpdater};
 use reth_primitives::{BlockNumber, H256};
+use std::{sync::Arc, time::Duration};
 use tokio::sync::watch;
 
 /// Builds a [`Pipeline`].
@@ -63,9 +64,17 @@ where
         self
     }
 
+    /// Set the minimum time between two progress reports of a stage.
+    ///
+    /// Defaults to [DEFAULT_PROGRESS_INTERVAL][crate::DEFAULT_PROGRESS_INTERVAL].
+    pub fn with_progress_interval(mut self, interval: Duration) -> Self {
+        self.pipeline.progress_interval = interval;
+        self
+    }
+
     /// Set a [SyncStateUpdater].
     pub fn with_sync_state_updater(mut self, updater: U) -> Self {
-        self.pipeline.sync_state_updater = Some(updater);
+        self.pipeline.sync_state_updater = Some(Arc::new(updater));
         self
     }
 
@@ -2,7 +2,8 @@ use crate::{
     id::StageId,
     stage::{ExecOutput, UnwindInput, UnwindOutput},
 };
-use reth_primitives::BlockNumber;
+use reth_interfaces::sync::StageSyncProgress;
+use reth_primitives::StageCheckpoint;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
@@ -13,14 +14,14 @@ use tokio_stream::wrappers::UnboundedReceiverStream;
 ///
 /// - Other stages may ask the pipeline to unwind
 /// - The pipeline will loop indefinitely unless a target block is set
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum PipelineEvent {
     /// Emitted when a stage is about to be run.
     Running {
         /// The stage that is about to be run.
         stage_id: StageId,
         /// The previous checkpoint of the stage.
-        stage_progress: Option<BlockNumber>,
+        checkpoint: Option<StageCheckpoint>,
     },
     /// Emitted when a stage has run a single time.
     Ran {
@@ -28,6 +29,16 @@ pub enum PipelineEvent {
         stage_id: StageId,
         /// The result of executing the stage.
         result: ExecOutput,
+        /// The new checkpoint of the stage, including its stage-specific progress.
+        checkpoint: StageCheckpoint,
+    },
+    /// Emitted periodically while a stage is running, with its throughput and estimated time
+    /// until it reaches its target.
+    Progress {
+        /// The stage that is running.
+        stage_id: StageId,
+        /// The progress of the stage.
+        progress: StageSyncProgress,
     },
     /// Emitted when a stage is about to be unwound.
     Unwinding {
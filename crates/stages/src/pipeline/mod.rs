@@ -1,14 +1,19 @@
 use crate::{error::*, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput};
+use futures_util::future::join_all;
 use reth_db::database::Database;
 use reth_interfaces::sync::{SyncState, SyncStateUpdater};
-use reth_primitives::{BlockNumber, H256};
+use reth_primitives::{BlockNumber, StageCheckpoint, H256};
 use reth_provider::Transaction;
 use std::{
     fmt::{Debug, Formatter},
-    ops::Deref,
+    ops::{Deref, Range},
     sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{watch, Mutex},
 };
-use tokio::sync::watch;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
 
@@ -18,6 +23,7 @@ mod event;
 mod progress;
 mod set;
 mod sync_metrics;
+mod throughput;
 
 pub use builder::*;
 use ctrl::*;
@@ -25,6 +31,7 @@ pub use event::*;
 use progress::*;
 pub use set::*;
 use sync_metrics::*;
+use throughput::*;
 
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// A staged sync pipeline.
@@ -34,6 +41,11 @@ use sync_metrics::*;
 /// and the external chain tip. When a stage is executed, it will run until it reaches the chain
 /// tip.
 ///
+/// Consecutive stages that do not depend on each other (see [Stage::dependencies]) are executed
+/// concurrently, each on its own blocking task. Each of them uses its own write transactions,
+/// which are only opened once the stage uses them: the database allows a single writer, so the
+/// stages overlap until they write, and their commits are ordered.
+///
 /// After the entire pipeline has been run, it will run again unless asked to stop (see
 /// [Pipeline::set_max_block]).
 ///
@@ -80,12 +92,17 @@ pub struct Pipeline<DB: Database, U: SyncStateUpdater> {
     stages: Vec<BoxedStage<DB>>,
     max_block: Option<BlockNumber>,
     listeners: PipelineEventListeners,
-    sync_state_updater: Option<U>,
+    sync_state_updater: Option<Arc<U>>,
     progress: PipelineProgress,
     tip_tx: Option<watch::Sender<H256>>,
     metrics: Metrics,
+    /// The minimum time between two progress reports of a stage.
+    progress_interval: Duration,
 }
 
+/// The default minimum time between two progress reports of a stage.
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
+
 impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
     fn default() -> Self {
         Self {
@@ -96,6 +113,7 @@ impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
             progress: PipelineProgress::default(),
             tip_tx: None,
             metrics: Metrics::default(),
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
         }
     }
 }
@@ -140,11 +158,15 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
 
     /// Run the pipeline in an infinite loop. Will terminate early if the user has specified
     /// a `max_block` in the pipeline.
-    pub async fn run(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
+    pub async fn run(&mut self, db: Arc<DB>) -> Result<(), PipelineError>
+    where
+        DB: 'static,
+        U: 'static,
+    {
         self.register_metrics(db.clone());
 
         loop {
-            let next_action = self.run_loop(db.as_ref()).await?;
+            let next_action = self.run_loop(&db).await?;
 
             // Terminate the loop early if it's reached the maximum user
             // configured block.
@@ -169,51 +191,166 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
     /// Performs one pass of the pipeline across all stages. After successful
     /// execution of each stage, it proceeds to commit it to the database.
     ///
+    /// Stages that do not depend on each other are executed concurrently, see
+    /// [`Pipeline::execution_groups`].
+    ///
     /// If any stage is unsuccessful at execution, we proceed to
     /// unwind. This will undo the progress across the entire pipeline
     /// up to the block that caused the error.
-    async fn run_loop(&mut self, db: &DB) -> Result<ControlFlow, PipelineError> {
-        let mut previous_stage = None;
-        for stage_index in 0..self.stages.len() {
-            let stage = &self.stages[stage_index];
-            let stage_id = stage.id();
+    async fn run_loop(&mut self, db: &Arc<DB>) -> Result<ControlFlow, PipelineError>
+    where
+        DB: 'static,
+        U: 'static,
+    {
+        for group in self.execution_groups() {
+            let mut previous_stages = Vec::with_capacity(group.len());
+            for stage_index in group.clone() {
+                let stage_id = self.stages[stage_index].id();
+
+                // Update sync state
+                if let Some(ref updater) = self.sync_state_updater {
+                    let state = self.progress.current_sync_state(stage_id.is_downloading_stage());
+                    updater.update_sync_state(state);
+                }
 
-            // Update sync state
-            if let Some(ref updater) = self.sync_state_updater {
-                let state = self.progress.current_sync_state(stage_id.is_downloading_stage());
-                updater.update_sync_state(state);
+                previous_stages.push(self.previous_stage(db, stage_index)?);
             }
 
-            trace!(target: "sync::pipeline", stage = %stage_id, "Executing stage");
-            let next = self
-                .execute_stage_to_completion(db, previous_stage, stage_index)
-                .instrument(info_span!("execute", stage = %stage_id))
-                .await?;
-
-            match next {
-                ControlFlow::NoProgress { stage_progress } => {
-                    if let Some(progress) = stage_progress {
-                        self.progress.update(progress);
-                    }
+            // The commits of concurrent stages are handed out in turn
+            let write_permit = Arc::new(Mutex::new(()));
+            let results = if group.len() == 1 {
+                let executor = self.executor(db.clone(), write_permit);
+                let stage = &mut self.stages[group.start];
+                let stage_id = stage.id();
+                trace!(target: "sync::pipeline", stage = %stage_id, "Executing stage");
+                vec![
+                    executor
+                        .execute_to_completion(stage, previous_stages[0])
+                        .instrument(info_span!("execute", stage = %stage_id))
+                        .await,
+                ]
+            } else {
+                // The stages are moved to their own blocking tasks and put back once they are done
+                let stages =
+                    self.stages.splice(group.clone(), std::iter::empty()).collect::<Vec<_>>();
+                let executions =
+                    stages.into_iter().zip(previous_stages).map(|(mut stage, previous_stage)| {
+                        let stage_id = stage.id();
+                        trace!(target: "sync::pipeline", stage = %stage_id, "Executing stage");
+                        let executor = self.executor(db.clone(), write_permit.clone());
+                        let span = info_span!("execute", stage = %stage_id);
+                        let handle = Handle::current();
+                        tokio::task::spawn_blocking(move || {
+                            let result = handle.block_on(
+                                executor
+                                    .execute_to_completion(&mut stage, previous_stage)
+                                    .instrument(span),
+                            );
+                            (stage, result)
+                        })
+                    });
+
+                let mut stages = Vec::with_capacity(group.len());
+                let mut results = Vec::with_capacity(group.len());
+                for execution in join_all(executions).await {
+                    let (stage, result) =
+                        execution.unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()));
+                    stages.push(stage);
+                    results.push(result);
                 }
-                ControlFlow::Continue { progress } => self.progress.update(progress),
-                ControlFlow::Unwind { target, bad_block } => {
-                    // reset the sync state
-                    if let Some(ref updater) = self.sync_state_updater {
-                        updater.update_sync_state(SyncState::Downloading { target_block: target });
+                self.stages.splice(group.start..group.start, stages);
+                results
+            };
+
+            for next in results {
+                match next? {
+                    ControlFlow::NoProgress { stage_progress } => {
+                        if let Some(progress) = stage_progress {
+                            self.progress.update(progress);
+                        }
+                    }
+                    ControlFlow::Continue { progress } => self.progress.update(progress),
+                    ControlFlow::Unwind { target, bad_block } => {
+                        // reset the sync state
+                        if let Some(ref updater) = self.sync_state_updater {
+                            updater
+                                .update_sync_state(SyncState::Downloading { target_block: target });
+                        }
+                        self.unwind(db.as_ref(), target, bad_block).await?;
+                        return Ok(ControlFlow::Unwind { target, bad_block })
                     }
-                    self.unwind(db, target, bad_block).await?;
-                    return Ok(ControlFlow::Unwind { target, bad_block })
                 }
             }
-
-            previous_stage =
-                Some((stage_id, db.view(|tx| stage_id.get_progress(tx))??.unwrap_or_default()));
         }
 
         Ok(self.progress.next_ctrl())
     }
 
+    /// Creates an executor for a stage of the current execution group.
+    fn executor(&self, db: Arc<DB>, write_permit: Arc<Mutex<()>>) -> StageExecutor<DB, U> {
+        StageExecutor {
+            db,
+            max_block: self.max_block,
+            listeners: self.listeners.clone(),
+            metrics: self.metrics.clone(),
+            sync_state_updater: self.sync_state_updater.clone(),
+            progress_interval: self.progress_interval,
+            write_permit,
+        }
+    }
+
+    /// The dependencies of the stage at the given index.
+    ///
+    /// Stages that do not declare their dependencies depend on the stage preceding them.
+    fn dependencies(&self, stage_index: usize) -> Vec<StageId> {
+        match self.stages[stage_index].dependencies() {
+            Some(dependencies) => dependencies.to_vec(),
+            None => stage_index
+                .checked_sub(1)
+                .map(|index| self.stages[index].id())
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Splits the stages into groups of consecutive stages that can be executed concurrently.
+    ///
+    /// A stage starts a new group if it depends on a stage of the current group.
+    fn execution_groups(&self) -> Vec<Range<usize>> {
+        let mut groups = Vec::new();
+        let mut group_start = 0;
+        for stage_index in 1..self.stages.len() {
+            let depends_on_group = self.dependencies(stage_index).into_iter().any(|dependency| {
+                self.stages[group_start..stage_index].iter().any(|stage| stage.id() == dependency)
+            });
+            if depends_on_group {
+                groups.push(group_start..stage_index);
+                group_start = stage_index;
+            }
+        }
+        if !self.stages.is_empty() {
+            groups.push(group_start..self.stages.len());
+        }
+        groups
+    }
+
+    /// The dependency of the stage at the given index with the lowest progress.
+    fn previous_stage(
+        &self,
+        db: &DB,
+        stage_index: usize,
+    ) -> Result<Option<(StageId, BlockNumber)>, PipelineError> {
+        let tx = db.tx()?;
+        let mut previous_stage: Option<(StageId, BlockNumber)> = None;
+        for dependency in self.dependencies(stage_index) {
+            let progress = dependency.get_progress(&tx)?.unwrap_or_default();
+            if previous_stage.map_or(true, |(_, lowest)| progress < lowest) {
+                previous_stage = Some((dependency, progress));
+            }
+        }
+        Ok(previous_stage)
+    }
+
     /// Unwind the stages to the target block.
     ///
     /// If the unwind is due to a bad block the number of that block should be specified.
@@ -245,12 +382,22 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
                 let input = UnwindInput { stage_progress, unwind_to: to, bad_block };
                 self.listeners.notify(PipelineEvent::Unwinding { stage_id, input });
 
+                let prev_checkpoint = stage_id.get_checkpoint(tx.deref())?;
                 let output = stage.unwind(&mut tx, input).await;
                 match output {
                     Ok(unwind_output) => {
                         stage_progress = unwind_output.stage_progress;
                         self.metrics.stage_checkpoint(stage_id, stage_progress);
-                        stage_id.save_progress(tx.deref(), stage_progress)?;
+                        // the stage-specific checkpoint refers to blocks that were unwound, unless
+                        // the stage updated it during the unwind
+                        let checkpoint = stage_id.get_checkpoint(tx.deref())?;
+                        let stage_checkpoint = checkpoint
+                            .filter(|_| checkpoint != prev_checkpoint)
+                            .and_then(|checkpoint| checkpoint.stage_checkpoint);
+                        stage_id.save_checkpoint(
+                            tx.deref(),
+                            StageCheckpoint { block_number: stage_progress, stage_checkpoint },
+                        )?;
 
                         self.listeners
                             .notify(PipelineEvent::Unwound { stage_id, result: unwind_output });
@@ -266,20 +413,38 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         tx.commit()?;
         Ok(())
     }
+}
 
-    async fn execute_stage_to_completion(
-        &mut self,
-        db: &DB,
+/// Executes a single stage to completion.
+///
+/// Stages of the same execution group each get their own executor and share its write permit.
+struct StageExecutor<DB: Database, U: SyncStateUpdater> {
+    db: Arc<DB>,
+    max_block: Option<BlockNumber>,
+    listeners: PipelineEventListeners,
+    metrics: Metrics,
+    sync_state_updater: Option<Arc<U>>,
+    progress_interval: Duration,
+    /// Guards the commits of the stages.
+    write_permit: Arc<Mutex<()>>,
+}
+
+impl<DB: Database, U: SyncStateUpdater> StageExecutor<DB, U> {
+    async fn execute_to_completion(
+        mut self,
+        stage: &mut BoxedStage<DB>,
         previous_stage: Option<(StageId, BlockNumber)>,
-        stage_index: usize,
     ) -> Result<ControlFlow, PipelineError> {
-        let stage = &mut self.stages[stage_index];
         let stage_id = stage.id();
         let mut made_progress = false;
+        let mut throughput = None;
         loop {
-            let mut tx = Transaction::new(db)?;
+            // The write transaction is opened once the stage writes, until then it runs
+            // concurrently with the writes of the other stages
+            let mut tx = Transaction::new_lazy(self.db.as_ref());
 
-            let prev_progress = stage_id.get_progress(tx.deref())?;
+            let prev_checkpoint = self.db.view(|tx| stage_id.get_checkpoint(tx))??;
+            let prev_progress = prev_checkpoint.map(|checkpoint| checkpoint.block_number);
 
             let stage_reached_max_block = prev_progress
                 .zip(self.max_block)
@@ -296,8 +461,10 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
                 return Ok(ControlFlow::NoProgress { stage_progress: prev_progress })
             }
 
-            self.listeners
-                .notify(PipelineEvent::Running { stage_id, stage_progress: prev_progress });
+            self.listeners.notify(PipelineEvent::Running { stage_id, checkpoint: prev_checkpoint });
+            let throughput = throughput.get_or_insert_with(|| {
+                StageThroughput::new(self.progress_interval, prev_checkpoint)
+            });
 
             match stage
                 .execute(&mut tx, ExecInput { previous_stage, stage_progress: prev_progress })
@@ -313,12 +480,33 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
                         "Stage made progress"
                     );
                     self.metrics.stage_checkpoint(stage_id, stage_progress);
-                    stage_id.save_progress(tx.deref(), stage_progress)?;
-
-                    self.listeners.notify(PipelineEvent::Ran { stage_id, result: out.clone() });
+                    // the stage may have saved its stage-specific checkpoint during execution.
+                    // Reading it opens the write transaction before the permit is taken, the
+                    // database allows a single writer which must not wait for the permit.
+                    let checkpoint = StageCheckpoint {
+                        block_number: stage_progress,
+                        ..stage_id.get_checkpoint(tx.deref())?.unwrap_or_default()
+                    };
+                    {
+                        let _permit = self.write_permit.lock().await;
+                        stage_id.save_checkpoint(tx.deref(), checkpoint)?;
+                        // TODO: Make the commit interval configurable
+                        tx.commit()?;
+                    }
 
-                    // TODO: Make the commit interval configurable
-                    tx.commit()?;
+                    self.listeners.notify(PipelineEvent::Ran {
+                        stage_id,
+                        result: out.clone(),
+                        checkpoint,
+                    });
+                    let target = previous_stage.map(|(_, block)| block);
+                    if let Some(progress) = throughput.report(stage_id, checkpoint, target) {
+                        self.metrics.stage_progress(stage_id, &progress);
+                        if let Some(updater) = &self.sync_state_updater {
+                            updater.update_stage_progress(progress.clone());
+                        }
+                        self.listeners.notify(PipelineEvent::Progress { stage_id, progress });
+                    }
 
                     if done {
                         return Ok(if made_progress {
@@ -449,20 +637,99 @@ mod tests {
         assert_eq!(
             events.collect::<Vec<PipelineEvent>>().await,
             vec![
-                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("A"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("A"),
                     result: ExecOutput { stage_progress: 20, done: true },
+                    checkpoint: StageCheckpoint::new(20),
                 },
-                PipelineEvent::Running { stage_id: StageId("B"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("B"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("B"),
                     result: ExecOutput { stage_progress: 10, done: true },
+                    checkpoint: StageCheckpoint::new(10),
                 },
             ]
         );
     }
 
+    #[test]
+    fn execution_groups() {
+        let pipeline: Pipeline<mdbx::Env<mdbx::WriteMap>, NoopSyncStateUpdate> =
+            Pipeline::builder()
+                .add_stage(TestStage::new(StageId("A")))
+                .add_stage(TestStage::new(StageId("B")).with_dependencies(&[StageId("A")]))
+                .add_stage(TestStage::new(StageId("C")).with_dependencies(&[StageId("A")]))
+                .add_stage(TestStage::new(StageId("D")))
+                .add_stage(TestStage::new(StageId("E")).with_dependencies(&[StageId("B")]))
+                .build();
+
+        // D depends on C by default, E on B which is in an earlier group
+        assert_eq!(pipeline.execution_groups(), vec![0..1, 1..3, 3..5]);
+    }
+
+    /// Runs a pipeline with stages that are executed concurrently.
+    ///
+    /// B and C wait for each other in their first execution, so the pipeline only finishes if
+    /// their executions overlap.
+    #[tokio::test]
+    async fn run_independent_stages() {
+        let db = test_utils::create_test_db::<mdbx::WriteMap>(EnvKind::RW);
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+
+        let mut pipeline: Pipeline<_, NoopSyncStateUpdate> = Pipeline::builder()
+            .add_stage(
+                TestStage::new(StageId("A"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 20, done: true })),
+            )
+            .add_stage(
+                TestStage::new(StageId("B"))
+                    .with_dependencies(&[StageId("A")])
+                    .with_barrier(barrier.clone())
+                    .add_exec(Ok(ExecOutput { stage_progress: 10, done: false }))
+                    .add_exec(Ok(ExecOutput { stage_progress: 20, done: true })),
+            )
+            .add_stage(
+                TestStage::new(StageId("C"))
+                    .with_dependencies(&[StageId("A")])
+                    .with_barrier(barrier)
+                    .add_exec(Ok(ExecOutput { stage_progress: 20, done: true })),
+            )
+            .with_max_block(20)
+            .build();
+        let events = pipeline.events();
+
+        tokio::spawn({
+            let db = db.clone();
+            async move {
+                pipeline.run(db).await.unwrap();
+            }
+        });
+        let events = events.collect::<Vec<PipelineEvent>>().await;
+
+        // A runs first, the runs of B and C may interleave
+        assert_eq!(events.len(), 8);
+        assert_eq!(
+            events[1],
+            PipelineEvent::Ran {
+                stage_id: StageId("A"),
+                result: ExecOutput { stage_progress: 20, done: true },
+                checkpoint: StageCheckpoint::new(20),
+            }
+        );
+        for stage_id in [StageId("B"), StageId("C")] {
+            assert!(events.contains(&PipelineEvent::Ran {
+                stage_id,
+                result: ExecOutput { stage_progress: 20, done: true },
+                checkpoint: StageCheckpoint::new(20),
+            }));
+        }
+
+        let tx = db.tx().unwrap();
+        assert_eq!(StageId("B").get_progress(&tx).unwrap(), Some(20));
+        assert_eq!(StageId("C").get_progress(&tx).unwrap(), Some(20));
+    }
+
     /// Unwinds a simple pipeline.
     #[tokio::test]
     async fn unwind_pipeline() {
@@ -502,20 +769,23 @@ mod tests {
             events.collect::<Vec<PipelineEvent>>().await,
             vec![
                 // Executing
-                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("A"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("A"),
                     result: ExecOutput { stage_progress: 100, done: true },
+                    checkpoint: StageCheckpoint::new(100),
                 },
-                PipelineEvent::Running { stage_id: StageId("B"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("B"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("B"),
                     result: ExecOutput { stage_progress: 10, done: true },
+                    checkpoint: StageCheckpoint::new(10),
                 },
-                PipelineEvent::Running { stage_id: StageId("C"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("C"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("C"),
                     result: ExecOutput { stage_progress: 20, done: true },
+                    checkpoint: StageCheckpoint::new(20),
                 },
                 // Unwinding
                 PipelineEvent::Unwinding {
@@ -591,12 +861,13 @@ mod tests {
         assert_eq!(
             events.collect::<Vec<PipelineEvent>>().await,
             vec![
-                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("A"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("A"),
                     result: ExecOutput { stage_progress: 10, done: true },
+                    checkpoint: StageCheckpoint::new(10),
                 },
-                PipelineEvent::Running { stage_id: StageId("B"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("B"), checkpoint: None },
                 PipelineEvent::Error { stage_id: StageId("B") },
                 PipelineEvent::Unwinding {
                     stage_id: StageId("A"),
@@ -606,15 +877,20 @@ mod tests {
                     stage_id: StageId("A"),
                     result: UnwindOutput { stage_progress: 0 },
                 },
-                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: Some(0) },
+                PipelineEvent::Running {
+                    stage_id: StageId("A"),
+                    checkpoint: Some(StageCheckpoint::new(0))
+                },
                 PipelineEvent::Ran {
                     stage_id: StageId("A"),
                     result: ExecOutput { stage_progress: 10, done: true },
+                    checkpoint: StageCheckpoint::new(10),
                 },
-                PipelineEvent::Running { stage_id: StageId("B"), stage_progress: None },
+                PipelineEvent::Running { stage_id: StageId("B"), checkpoint: None },
                 PipelineEvent::Ran {
                     stage_id: StageId("B"),
                     result: ExecOutput { stage_progress: 10, done: true },
+                    checkpoint: StageCheckpoint::new(10),
                 },
             ]
         );
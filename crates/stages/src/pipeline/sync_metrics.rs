@@ -1,26 +1,46 @@
 use crate::StageId;
 use metrics::Gauge;
+use reth_interfaces::sync::StageSyncProgress;
 use reth_metrics_derive::Metrics;
 use std::collections::HashMap;
 
-#[derive(Metrics)]
+#[derive(Clone, Metrics)]
 #[metrics(scope = "sync")]
 pub(crate) struct StageMetrics {
     /// The block number of the last commit for a stage.
     checkpoint: Gauge,
+    /// The number of blocks processed per second.
+    blocks_per_second: Gauge,
+    /// The number of entities processed per second, for stages that track entities.
+    entities_per_second: Gauge,
+    /// The estimated number of seconds until the stage reaches its target.
+    eta_seconds: Gauge,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(crate) struct Metrics {
-    checkpoints: HashMap<StageId, StageMetrics>,
+    stages: HashMap<StageId, StageMetrics>,
 }
 
 impl Metrics {
     pub(crate) fn stage_checkpoint(&mut self, stage_id: StageId, progress: u64) {
-        self.checkpoints
+        self.stage_metrics(stage_id).checkpoint.set(progress as f64);
+    }
+
+    pub(crate) fn stage_progress(&mut self, stage_id: StageId, progress: &StageSyncProgress) {
+        let metrics = self.stage_metrics(stage_id);
+        metrics.blocks_per_second.set(progress.blocks_per_second);
+        if let Some(entities_per_second) = progress.entities_per_second {
+            metrics.entities_per_second.set(entities_per_second);
+        }
+        if let Some(eta) = progress.eta {
+            metrics.eta_seconds.set(eta.as_secs_f64());
+        }
+    }
+
+    fn stage_metrics(&mut self, stage_id: StageId) -> &StageMetrics {
+        self.stages
             .entry(stage_id)
             .or_insert_with(|| StageMetrics::new_with_labels(&[("stage", stage_id.to_string())]))
-            .checkpoint
-            .set(progress as f64);
     }
 }
@@ -0,0 +1,149 @@
+use crate::StageId;
+use reth_interfaces::sync::StageSyncProgress;
+use reth_primitives::{BlockNumber, StageCheckpoint};
+use std::time::{Duration, Instant};
+
+/// Measures the throughput of a stage between two of its progress reports.
+#[derive(Debug)]
+pub(crate) struct StageThroughput {
+    /// The minimum time between two progress reports.
+    interval: Duration,
+    /// When the current measurement started.
+    started_at: Instant,
+    /// The checkpoint of the stage when the current measurement started.
+    checkpoint: StageCheckpoint,
+}
+
+impl StageThroughput {
+    /// Starts measuring the throughput of a stage at the given checkpoint.
+    pub(crate) fn new(interval: Duration, checkpoint: Option<StageCheckpoint>) -> Self {
+        Self { interval, started_at: Instant::now(), checkpoint: checkpoint.unwrap_or_default() }
+    }
+
+    /// Returns the progress of the stage once the interval has passed since the last report, and
+    /// starts a new measurement.
+    pub(crate) fn report(
+        &mut self,
+        stage_id: StageId,
+        checkpoint: StageCheckpoint,
+        target: Option<BlockNumber>,
+    ) -> Option<StageSyncProgress> {
+        let elapsed = self.started_at.elapsed();
+        if elapsed.is_zero() || elapsed < self.interval {
+            return None
+        }
+
+        let progress = measure(stage_id, self.checkpoint, checkpoint, target, elapsed);
+        self.started_at = Instant::now();
+        self.checkpoint = checkpoint;
+        Some(progress)
+    }
+}
+
+/// Computes the throughput of a stage between two checkpoints and the estimated time until it
+/// reaches its target.
+fn measure(
+    stage_id: StageId,
+    start: StageCheckpoint,
+    end: StageCheckpoint,
+    target: Option<BlockNumber>,
+    elapsed: Duration,
+) -> StageSyncProgress {
+    let seconds = elapsed.as_secs_f64();
+    let blocks_per_second = end.block_number.saturating_sub(start.block_number) as f64 / seconds;
+    let entities = end.entities();
+    let entities_per_second = start
+        .entities()
+        .zip(entities)
+        .map(|(start, end)| end.processed.saturating_sub(start.processed) as f64 / seconds);
+
+    // entities advance within the block range of a stage, so they give the better estimate
+    let remaining_entities =
+        entities.and_then(|entities| Some(entities.total?.saturating_sub(entities.processed)));
+    let eta = match remaining_entities.zip(entities_per_second) {
+        Some((remaining, rate)) => remaining_time(remaining, rate),
+        None => target.and_then(|target| {
+            remaining_time(target.saturating_sub(end.block_number), blocks_per_second)
+        }),
+    };
+
+    StageSyncProgress {
+        stage: stage_id.to_string(),
+        checkpoint: end.block_number,
+        target,
+        entities_processed: entities.map(|entities| entities.processed),
+        entities_total: entities.and_then(|entities| entities.total),
+        blocks_per_second,
+        entities_per_second,
+        eta,
+    }
+}
+
+/// The time it takes to process the remaining units at the given rate, if there is progress.
+fn remaining_time(remaining: u64, rate: f64) -> Option<Duration> {
+    if remaining == 0 {
+        return Some(Duration::ZERO)
+    }
+    (rate > 0.0).then(|| Duration::from_secs_f64(remaining as f64 / rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{EntitiesCheckpoint, StageUnitCheckpoint};
+
+    #[test]
+    fn measure_blocks() {
+        let progress = measure(
+            StageId("A"),
+            StageCheckpoint::new(100),
+            StageCheckpoint::new(300),
+            Some(1_300),
+            Duration::from_secs(10),
+        );
+        assert_eq!(progress.blocks_per_second, 20.0);
+        assert_eq!(progress.entities_per_second, None);
+        assert_eq!(progress.eta, Some(Duration::from_secs(50)));
+
+        // no target, no estimate
+        let progress = measure(
+            StageId("A"),
+            StageCheckpoint::new(100),
+            StageCheckpoint::new(300),
+            None,
+            Duration::from_secs(10),
+        );
+        assert_eq!(progress.eta, None);
+    }
+
+    #[test]
+    fn measure_entities() {
+        let checkpoint = |processed| {
+            StageCheckpoint::new(0).with_stage_checkpoint(StageUnitCheckpoint::Entities(
+                EntitiesCheckpoint { processed, total: Some(1_000) },
+            ))
+        };
+        let progress = measure(
+            StageId("A"),
+            checkpoint(100),
+            checkpoint(200),
+            Some(10),
+            Duration::from_secs(5),
+        );
+        assert_eq!(progress.blocks_per_second, 0.0);
+        assert_eq!(progress.entities_per_second, Some(20.0));
+        assert_eq!(progress.entities_processed, Some(200));
+        assert_eq!(progress.entities_total, Some(1_000));
+        assert_eq!(progress.eta, Some(Duration::from_secs(40)));
+
+        // without progress there is no estimate
+        let progress = measure(
+            StageId("A"),
+            checkpoint(200),
+            checkpoint(200),
+            Some(10),
+            Duration::from_secs(5),
+        );
+        assert_eq!(progress.eta, None);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{stages::FINISH, PipelineEvent, PrunerError};
+use futures_util::{Stream, StreamExt};
+use reth_db::database::Database;
+use reth_primitives::{BlockNumber, PruneModes};
+use reth_provider::Transaction;
+use std::sync::Arc;
+use tracing::*;
+
+/// Prunes historical data of the database according to the configured [PruneModes].
+///
+/// The stages only prune the data of the blocks they process, the pruner additionally removes data
+/// that was written before pruning was enabled or that became prunable because the tip advanced.
+#[derive(Debug)]
+pub struct Pruner<DB> {
+    db: Arc<DB>,
+    /// Prune modes of all segments.
+    modes: PruneModes,
+    /// Minimum number of blocks between two pruner runs.
+    block_interval: u64,
+    /// The tip of the last pruner run.
+    last_pruned_block: Option<BlockNumber>,
+}
+
+impl<DB: Database> Pruner<DB> {
+    /// Creates a new pruner.
+    pub fn new(db: Arc<DB>, modes: PruneModes, block_interval: u64) -> Self {
+        Self { db, modes, block_interval, last_pruned_block: None }
+    }
+
+    /// Returns true if the pruner should run for the given tip.
+    pub fn is_pruning_needed(&self, tip: BlockNumber) -> bool {
+        !self.modes.is_empty() &&
+            self.last_pruned_block.map_or(true, |last_pruned_block| {
+                tip.saturating_sub(last_pruned_block) >= self.block_interval
+            })
+    }
+
+    /// Prunes all segments up to their prune target for the given tip.
+    ///
+    /// Returns the number of deleted entries.
+    pub fn run(&mut self, tip: BlockNumber) -> Result<usize, PrunerError> {
+        let mut tx = Transaction::new(self.db.as_ref())?;
+
+        let mut deleted = 0;
+        for (segment, mode) in self.modes.iter() {
+            let Some(prune_target) = self.modes.prune_target_block(segment, tip) else {
+                trace!(target: "pruner", %segment, ?mode, tip, "Nothing to prune");
+                continue
+            };
+            let segment_deleted = tx.prune_segment(segment, prune_target)?;
+            debug!(target: "pruner", %segment, ?mode, prune_target, deleted = segment_deleted, "Pruned segment");
+            deleted += segment_deleted;
+        }
+
+        tx.commit()?;
+        self.last_pruned_block = Some(tip);
+        info!(target: "pruner", tip, deleted, "Pruner finished");
+        Ok(deleted)
+    }
+
+    /// Runs the pruner every time the pipeline finished a sync to a new tip, until the stream of
+    /// pipeline events ends.
+    ///
+    /// This blocks on database writes and should be spawned on a blocking task.
+    pub async fn run_with_events<S>(mut self, mut events: S) -> Result<(), PrunerError>
+    where
+        S: Stream<Item = PipelineEvent> + Unpin,
+    {
+        while let Some(event) = events.next().await {
+            if let PipelineEvent::Ran { stage_id, result, .. } = event {
+                if stage_id == FINISH && self.is_pruning_needed(result.stage_progress) {
+                    self.run(result.stage_progress)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::DbTx,
+    };
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_primitives::{PruneCheckpoint, PruneMode, PruneSegment, H256};
+    use reth_provider::insert_canonical_block;
+
+    #[test]
+    fn is_pruning_needed() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let pruner = Pruner::new(db.clone(), PruneModes::none(), 5);
+        assert!(!pruner.is_pruning_needed(100));
+
+        let modes = PruneModes { receipts: Some(PruneMode::Full), ..Default::default() };
+        let mut pruner = Pruner::new(db, modes, 5);
+        assert!(pruner.is_pruning_needed(100));
+        pruner.last_pruned_block = Some(100);
+        assert!(!pruner.is_pruning_needed(104));
+        assert!(pruner.is_pruning_needed(105));
+    }
+
+    #[test]
+    fn prune_transaction_lookup() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = random_block_range(0..11, H256::zero(), 2..3);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        for block in blocks.iter() {
+            insert_canonical_block(&*tx, block.clone(), None, false).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let modes =
+            PruneModes { transaction_lookup: Some(PruneMode::Distance(5)), ..Default::default() };
+        let mut pruner = Pruner::new(db.clone(), modes, 5);
+        let deleted = pruner.run(10).unwrap();
+        assert_eq!(deleted, blocks[..=5].iter().map(|block| block.body.len()).sum::<usize>());
+
+        let tx = db.tx().unwrap();
+        for block in blocks.iter() {
+            for transaction in block.body.iter() {
+                let tx_number = tx.get::<tables::TxHashNumber>(transaction.hash()).unwrap();
+                assert_eq!(tx_number.is_none(), block.number <= 5);
+            }
+        }
+        let last_tx_number = blocks[..=5].iter().map(|block| block.body.len() as u64).sum::<u64>();
+        assert_eq!(
+            tx.get::<tables::PruneCheckpoints>(PruneSegment::TransactionLookup).unwrap(),
+            Some(PruneCheckpoint { block_number: 5, tx_number: Some(last_tx_number - 1) })
+        );
+    }
+}
@@ -100,7 +100,8 @@ pub enum ExecAction {
 /// Stages must have a unique [ID][StageId] and implement a way to "roll forwards"
 /// ([Stage::execute]) and a way to "roll back" ([Stage::unwind]).
 ///
-/// Stages are executed as part of a pipeline where they are executed serially.
+/// Stages are executed as part of a pipeline in the order they were added. Stages that do not
+/// depend on each other (see [Stage::dependencies]) are executed concurrently.
 ///
 /// Stages receive [`Transaction`] which manages the lifecycle of a transaction,
 /// such as when to commit / reopen a new one etc.
@@ -111,6 +112,14 @@ pub trait Stage<DB: Database>: Send + Sync {
     /// Stage IDs must be unique.
     fn id(&self) -> StageId;
 
+    /// The stages whose output this stage reads.
+    ///
+    /// The pipeline passes the dependency with the lowest progress as the previous stage of
+    /// [ExecInput]. By default a stage depends on the stage that precedes it in the pipeline.
+    fn dependencies(&self) -> Option<&[StageId]> {
+        None
+    }
+
     /// Execute the stage.
     async fn execute(
         &mut self,
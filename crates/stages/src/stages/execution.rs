@@ -12,10 +12,15 @@ use reth_db::{
 };
 use reth_interfaces::provider::ProviderError;
 use reth_metrics_derive::Metrics;
-use reth_primitives::{Address, Block, U256};
+use reth_primitives::{
+    Address, Block, BlockNumber, EntitiesCheckpoint, ExecutionCheckpoint, PruneModes, PruneSegment,
+    StageUnitCheckpoint, TransactionKind, U256,
+};
 use reth_provider::{
-    post_state::PostState, BlockExecutor, ExecutorFactory, LatestStateProviderRef, Transaction,
+    post_state::PostState, BlockExecutor, ExecutorFactory, LatestStateProviderRef, PrefetchTarget,
+    PrefetchedStateProvider, StatePrefetcher, StateProvider, Transaction,
 };
+use std::{collections::HashSet, ops::RangeBounds};
 use tracing::*;
 
 /// The [`StageId`] of the execution stage.
@@ -57,6 +62,9 @@ pub struct ExecutionStageMetrics {
 /// - [tables::AccountHistory] to remove change set and apply old values to
 /// - [tables::PlainAccountState] [tables::StorageHistory] to remove change set and apply old values
 /// to [tables::PlainStorageState]
+///
+/// If receipts are pruned, [tables::Receipts] of all blocks up to the prune target are removed
+/// after each batch.
 // false positive, we cannot derive it if !DB: Debug.
 #[allow(missing_debug_implementations)]
 pub struct ExecutionStage<EF: ExecutorFactory> {
@@ -65,12 +73,22 @@ pub struct ExecutionStage<EF: ExecutorFactory> {
     executor_factory: EF,
     /// Commit threshold
     commit_threshold: u64,
+    /// Pruning configuration, only [PruneSegment::Receipts] is enforced by this stage.
+    prune_modes: PruneModes,
+    /// The number of threads prefetching state ahead of execution, `0` if disabled.
+    prefetch_threads: usize,
 }
 
 impl<EF: ExecutorFactory> ExecutionStage<EF> {
     /// Create new execution stage with specified config.
     pub fn new(executor_factory: EF, commit_threshold: u64) -> Self {
-        Self { metrics: ExecutionStageMetrics::default(), executor_factory, commit_threshold }
+        Self {
+            metrics: ExecutionStageMetrics::default(),
+            executor_factory,
+            commit_threshold,
+            prune_modes: PruneModes::none(),
+            prefetch_threads: 0,
+        }
     }
 
     /// Create an execution stage with the provided  executor factory.
@@ -81,9 +99,27 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
             metrics: ExecutionStageMetrics::default(),
             executor_factory,
             commit_threshold: 10_000,
+            prune_modes: PruneModes::none(),
+            prefetch_threads: 0,
         }
     }
 
+    /// Set the pruning configuration.
+    pub fn with_prune_modes(mut self, prune_modes: PruneModes) -> Self {
+        self.prune_modes = prune_modes;
+        self
+    }
+
+    /// Set the number of threads prefetching the state read by the transactions ahead of their
+    /// execution, `0` disables prefetching.
+    ///
+    /// The threads read from their own transactions, so prefetching must only be enabled if the
+    /// plain state isn't modified by the transaction the stage is executed in before execution.
+    pub fn with_prefetch_threads(mut self, prefetch_threads: usize) -> Self {
+        self.prefetch_threads = prefetch_threads;
+        self
+    }
+
     /// Execute the stage.
     pub fn execute_inner<DB: Database>(
         &self,
@@ -93,6 +129,8 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         let ((start_block, end_block), capped) =
             exec_or_return!(input, self.commit_threshold, "sync::stages::execution");
         let last_block = input.stage_progress.unwrap_or_default();
+        let target = input.previous_stage_progress();
+        let gas_progress = self.gas_progress(tx, last_block, target)?;
 
         // Get header with canonical hashes.
         let mut headers_cursor = tx.cursor_read::<tables::Headers>()?;
@@ -108,7 +146,7 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         let mut tx_cursor = tx.cursor_read::<tables::Transactions>()?;
         // Skip sender recovery and load signer from database.
         let mut tx_sender = tx.cursor_read::<tables::TxSenders>()?;
-        // Get block headers and bodies
+        // Get blocks with their transactions and signers
         let block_batch = headers_cursor
             .walk_range(start_block..=end_block)?
             .map(|entry| -> Result<_, StageError> {
@@ -121,56 +159,114 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
                 let (_, stored_ommers) = ommers_cursor.seek_exact(number)?.unwrap_or_default();
                 let withdrawals =
                     withdrawals_cursor.seek_exact(number)?.map(|(_, w)| w.withdrawals);
-                Ok((header, td.into(), body, stored_ommers.ommers, withdrawals))
+
+                // iterate over all transactions
+                let mut tx_walker = tx_cursor.walk(Some(body.start_tx_id))?;
+                let mut transactions = Vec::with_capacity(body.tx_count as usize);
+                // get next N transactions.
+                for index in body.tx_id_range() {
+                    let (tx_index, tx) =
+                        tx_walker.next().ok_or(ProviderError::EndOfTransactionTable)??;
+                    if tx_index != index {
+                        error!(target: "sync::stages::execution", block = number, expected = index, found = tx_index, ?body, "Transaction gap");
+                        return Err(ProviderError::TransactionsGap { missing: tx_index }.into())
+                    }
+                    transactions.push(tx);
+                }
+
+                // take signers
+                let mut tx_sender_walker = tx_sender.walk(Some(body.start_tx_id))?;
+                let mut signers = Vec::with_capacity(body.tx_count as usize);
+                for index in body.tx_id_range() {
+                    let (tx_index, tx) = tx_sender_walker
+                        .next()
+                        .ok_or(ProviderError::EndOfTransactionSenderTable)??;
+                    if tx_index != index {
+                        error!(target: "sync::stages::execution", block = number, expected = index, found = tx_index, ?body, "Signer gap");
+                        return Err(ProviderError::TransactionsSignerGap { missing: tx_index }.into())
+                    }
+                    signers.push(tx);
+                }
+
+                let block =
+                    Block { header, body: transactions, ommers: stored_ommers.ommers, withdrawals };
+                Ok((block, td.into(), signers))
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Create state provider with cached state
+        let batch_gas: u64 = block_batch.iter().map(|(block, ..)| block.gas_used).sum();
+        let state = self.execute_batch(tx, block_batch)?;
 
-        let mut executor = self.executor_factory.with_sp(LatestStateProviderRef::new(&**tx));
+        // put execution results to database
+        let (first_tx_number, first_transition_id) = tx.get_next_block_ids(start_block)?;
+        state.write_to_db(&**tx, first_transition_id, first_tx_number)?;
+
+        if let Some(prune_target) = self
+            .prune_modes
+            .prune_target_block(PruneSegment::Receipts, input.previous_stage_progress())
+        {
+            let deleted = tx.prune_segment(PruneSegment::Receipts, prune_target.min(end_block))?;
+            trace!(target: "sync::stages::execution", prune_target, deleted, "Pruned receipts");
+        }
 
-        // Fetch transactions, execute them and generate results
-        let mut state = PostState::default();
-        for (header, td, body, ommers, withdrawals) in block_batch.into_iter() {
-            let block_number = header.number;
-            tracing::trace!(target: "sync::stages::execution", ?block_number, "Execute block.");
-
-            // iterate over all transactions
-            let mut tx_walker = tx_cursor.walk(Some(body.start_tx_id))?;
-            let mut transactions = Vec::with_capacity(body.tx_count as usize);
-            // get next N transactions.
-            for index in body.tx_id_range() {
-                let (tx_index, tx) =
-                    tx_walker.next().ok_or(ProviderError::EndOfTransactionTable)??;
-                if tx_index != index {
-                    error!(target: "sync::stages::execution", block = block_number, expected = index, found = tx_index, ?body, "Transaction gap");
-                    return Err(ProviderError::TransactionsGap { missing: tx_index }.into())
-                }
-                transactions.push(tx);
-            }
+        EXECUTION.save_stage_checkpoint(
+            &**tx,
+            Some(StageUnitCheckpoint::Execution(ExecutionCheckpoint {
+                target,
+                progress: EntitiesCheckpoint {
+                    processed: gas_progress.processed + batch_gas,
+                    ..gas_progress
+                },
+            })),
+        )?;
 
-            // take signers
-            let mut tx_sender_walker = tx_sender.walk(Some(body.start_tx_id))?;
-            let mut signers = Vec::with_capacity(body.tx_count as usize);
-            for index in body.tx_id_range() {
-                let (tx_index, tx) =
-                    tx_sender_walker.next().ok_or(ProviderError::EndOfTransactionSenderTable)??;
-                if tx_index != index {
-                    error!(target: "sync::stages::execution", block = block_number, expected = index, found = tx_index, ?body, "Signer gap");
-                    return Err(ProviderError::TransactionsSignerGap { missing: tx_index }.into())
-                }
-                signers.push(tx);
-            }
+        let done = !capped;
+        info!(target: "sync::stages::execution", stage_progress = end_block, done, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: end_block, done })
+    }
+
+    /// Executes the blocks on top of the latest state, warming up the reads of the transactions on
+    /// background threads if enabled.
+    fn execute_batch<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        block_batch: Vec<(Block, U256, Vec<Address>)>,
+    ) -> Result<PostState, StageError> {
+        if self.prefetch_threads == 0 {
+            return self.execute_blocks(LatestStateProviderRef::new(&**tx), block_batch, |_| ())
+        }
+
+        let db = tx.inner();
+        let (targets, block_ends) = prefetch_targets(&block_batch);
+        std::thread::scope(|scope| {
+            let prefetcher = StatePrefetcher::spawn(scope, db, targets, self.prefetch_threads);
+            let provider = PrefetchedStateProvider::new(
+                LatestStateProviderRef::new(&**tx),
+                prefetcher.cache(),
+            );
+            self.execute_blocks(provider, block_batch, |index| {
+                prefetcher.advance(block_ends[index])
+            })
+        })
+    }
 
-            trace!(target: "sync::stages::execution", number = block_number, txs = transactions.len(), "Executing block");
+    /// Executes the blocks on top of the state of the provider, calling `on_block` with the index
+    /// of every executed block.
+    fn execute_blocks<SP: StateProvider>(
+        &self,
+        provider: SP,
+        blocks: Vec<(Block, U256, Vec<Address>)>,
+        mut on_block: impl FnMut(usize),
+    ) -> Result<PostState, StageError> {
+        let mut executor = self.executor_factory.with_sp(provider);
+
+        let mut state = PostState::default();
+        for (index, (block, td, signers)) in blocks.into_iter().enumerate() {
+            let block_number = block.number;
+            trace!(target: "sync::stages::execution", number = block_number, txs = block.body.len(), "Executing block");
 
-            // Configure the executor to use the current state.
             let block_state = executor
-                .execute_and_verify_receipt(
-                    &Block { header, body: transactions, ommers, withdrawals },
-                    td,
-                    Some(signers),
-                )
+                .execute_and_verify_receipt(&block, td, Some(signers))
                 .map_err(|error| StageError::ExecutionError { block: block_number, error })?;
             if let Some(last_receipt) = block_state.receipts().last() {
                 self.metrics
@@ -178,18 +274,62 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
                     .increment(last_receipt.cumulative_gas_used / 1_000_000);
             }
             state.extend(block_state);
+            on_block(index);
         }
+        Ok(state)
+    }
 
-        // put execution results to database
-        let first_transition_id = tx.get_block_transition(last_block)?;
-        state.write_to_db(&**tx, first_transition_id)?;
+    /// Returns the gas used by the executed blocks and by all blocks up to the target.
+    ///
+    /// The running totals are kept in the stage checkpoint, only the gas of the headers between the
+    /// previous and the new target is added. The headers are summed up from genesis if the stage
+    /// has no checkpoint yet, in which case none of them has been moved to static files.
+    fn gas_progress<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        stage_progress: BlockNumber,
+        target: BlockNumber,
+    ) -> Result<EntitiesCheckpoint, StageError> {
+        let Some(checkpoint) = execution_checkpoint(tx)? else {
+            return Ok(EntitiesCheckpoint {
+                processed: gas_used(tx, ..=stage_progress)?,
+                total: Some(gas_used(tx, ..=target)?),
+            })
+        };
 
-        let done = !capped;
-        info!(target: "sync::stages::execution", stage_progress = end_block, done, "Sync iteration finished");
-        Ok(ExecOutput { stage_progress: end_block, done })
+        let total = checkpoint.progress.total.unwrap_or_default();
+        let total = if target >= checkpoint.target {
+            total + gas_used(tx, checkpoint.target + 1..=target)?
+        } else {
+            total.saturating_sub(gas_used(tx, target + 1..=checkpoint.target)?)
+        };
+        Ok(EntitiesCheckpoint { total: Some(total), ..checkpoint.progress })
+    }
+}
+
+/// Returns the checkpoint of the execution stage, if it has any.
+fn execution_checkpoint<DB: Database>(
+    tx: &Transaction<'_, DB>,
+) -> Result<Option<ExecutionCheckpoint>, StageError> {
+    match EXECUTION.get_checkpoint(&**tx)?.and_then(|checkpoint| checkpoint.stage_checkpoint) {
+        Some(StageUnitCheckpoint::Execution(checkpoint)) => Ok(Some(checkpoint)),
+        _ => Ok(None),
     }
 }
 
+/// Returns the gas used by the blocks in the range.
+fn gas_used<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: impl RangeBounds<BlockNumber>,
+) -> Result<u64, StageError> {
+    let mut gas_used = 0;
+    for entry in tx.cursor_read::<tables::Headers>()?.walk_range(range)? {
+        let (_, header) = entry?;
+        gas_used += header.gas_used;
+    }
+    Ok(gas_used)
+}
+
 #[async_trait::async_trait]
 impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
     /// Return the id of the stage
@@ -228,6 +368,24 @@ impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
     ) -> Result<UnwindOutput, StageError> {
         info!(target: "sync::stages::execution", to_block = input.unwind_to, "Unwinding");
 
+        // Remove the gas of the unwound blocks and of the headers above the unwind block, which
+        // are going to be unwound, from the running totals
+        if let Some(checkpoint) = execution_checkpoint(tx)? {
+            let unwound_gas = gas_used(tx, input.unwind_to + 1..=input.stage_progress)?;
+            let removed_gas = gas_used(tx, input.unwind_to + 1..=checkpoint.target)?;
+            let progress = EntitiesCheckpoint {
+                processed: checkpoint.progress.processed.saturating_sub(unwound_gas),
+                total: checkpoint.progress.total.map(|total| total.saturating_sub(removed_gas)),
+            };
+            EXECUTION.save_stage_checkpoint(
+                &**tx,
+                Some(StageUnitCheckpoint::Execution(ExecutionCheckpoint {
+                    target: input.unwind_to,
+                    progress,
+                })),
+            )?;
+        }
+
         // Acquire changeset cursors
         let mut account_changeset = tx.cursor_dup_write::<tables::AccountChangeSet>()?;
         let mut storage_changeset = tx.cursor_dup_write::<tables::StorageChangeSet>()?;
@@ -284,6 +442,17 @@ impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
             }
         }
 
+        // Discard receipts of unwinded transactions
+        let (first_unwinded_tx, _) = tx.get_next_block_ids(input.unwind_to + 1)?;
+        let mut receipts_cursor = tx.cursor_write::<tables::Receipts>()?;
+        let mut rev_receipts_walker = receipts_cursor.walk_back(None)?;
+        while let Some((tx_number, _)) = rev_receipts_walker.next().transpose()? {
+            if tx_number < first_unwinded_tx {
+                break
+            }
+            tx.delete::<tables::Receipts>(tx_number, None)?;
+        }
+
         // Discard unwinded changesets
         let mut rev_acc_changeset_walker = account_changeset.walk_back(None)?;
         while let Some((transition_id, _)) = rev_acc_changeset_walker.next().transpose()? {
@@ -307,6 +476,39 @@ impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
     }
 }
 
+/// Returns the state read by the transactions of the blocks ahead of their execution, in
+/// execution order, along with the number of targets up to the end of every block.
+///
+/// The targets are the beneficiaries, the senders and recipients of the transactions and the
+/// entries of their access lists.
+fn prefetch_targets(blocks: &[(Block, U256, Vec<Address>)]) -> (Vec<PrefetchTarget>, Vec<usize>) {
+    let mut seen = HashSet::new();
+    let mut targets = Vec::new();
+    let mut block_ends = Vec::with_capacity(blocks.len());
+    for (block, _, signers) in blocks {
+        let mut push = |target: PrefetchTarget| {
+            if seen.insert(target) {
+                targets.push(target);
+            }
+        };
+        push(PrefetchTarget::Account(block.beneficiary));
+        for (transaction, signer) in block.body.iter().zip(signers) {
+            push(PrefetchTarget::Account(*signer));
+            if let TransactionKind::Call(to) = transaction.kind() {
+                push(PrefetchTarget::Account(*to));
+            }
+            for item in transaction.access_list().into_iter().flat_map(|list| &list.0) {
+                push(PrefetchTarget::Account(item.address));
+                for key in &item.storage_keys {
+                    push(PrefetchTarget::Storage(item.address, *key));
+                }
+            }
+        }
+        block_ends.push(targets.len());
+    }
+    (targets, block_ends)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,7 +519,7 @@ mod tests {
     };
     use reth_executor::Factory;
     use reth_primitives::{
-        hex_literal::hex, keccak256, Account, Bytecode, ChainSpecBuilder, SealedBlock,
+        hex_literal::hex, keccak256, Account, Bytecode, ChainSpecBuilder, Header, SealedBlock,
         StorageEntry, H160, H256, U256,
     };
     use reth_provider::insert_canonical_block;
@@ -378,6 +580,17 @@ mod tests {
         let output = execution_stage.execute(&mut tx, input).await.unwrap();
         tx.commit().unwrap();
         assert_eq!(output, ExecOutput { stage_progress: 1, done: true });
+        // the gas of the executed block is recorded as progress
+        assert_eq!(
+            EXECUTION
+                .get_checkpoint(tx.deref())
+                .unwrap()
+                .and_then(|checkpoint| checkpoint.entities()),
+            Some(EntitiesCheckpoint {
+                processed: block.header.gas_used,
+                total: Some(block.header.gas_used)
+            })
+        );
         let tx = tx.deref_mut();
         // check post state
         let account1 = H160(hex!("1000000000000000000000000000000000000000"));
@@ -421,6 +634,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prefetching_keeps_execution_results() {
+        let state_db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(state_db.as_ref()).unwrap();
+        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
+        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let block = SealedBlock::decode(&mut block_rlp).unwrap();
+        insert_canonical_block(tx.deref_mut(), genesis, None, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), block.clone(), None, true).unwrap();
+
+        // pre state
+        let acc1 = H160(hex!("1000000000000000000000000000000000000000"));
+        let acc2 = H160(hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b"));
+        let code = hex!("5a465a905090036002900360015500");
+        let code_hash = keccak256(code);
+        let balance = U256::from(0x3635c9adc5dea00000u128);
+        tx.put::<tables::PlainAccountState>(
+            acc1,
+            Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(code_hash) },
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(
+            acc2,
+            Account { nonce: 0, balance, bytecode_hash: None },
+        )
+        .unwrap();
+        tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.to_vec().into())).unwrap();
+        // the prefetching threads read the committed state
+        tx.commit().unwrap();
+
+        let batch = vec![(block.unseal(), U256::ZERO, vec![acc2])];
+        let state = stage().execute_batch(&tx, batch.clone()).unwrap();
+        let prefetched = stage().with_prefetch_threads(4).execute_batch(&tx, batch).unwrap();
+
+        assert!(!state.receipts().is_empty());
+        assert_eq!(prefetched.receipts(), state.receipts());
+        assert_eq!(prefetched, state);
+    }
+
     #[tokio::test]
     async fn sanity_execute_unwind() {
         // TODO cleanup the setup after https://github.com/paradigmxyz/reth/issues/332
@@ -625,4 +878,37 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn gas_progress_adds_headers_above_previous_target() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        // the headers up to the previous target are not in the database, like after they were
+        // moved to static files
+        for number in 2..=4 {
+            tx.put::<tables::Headers>(
+                number,
+                Header { number, gas_used: number * 10, ..Default::default() },
+            )
+            .unwrap();
+        }
+        EXECUTION
+            .save_stage_checkpoint(
+                tx.deref(),
+                Some(StageUnitCheckpoint::Execution(ExecutionCheckpoint {
+                    target: 1,
+                    progress: EntitiesCheckpoint { processed: 5, total: Some(7) },
+                })),
+            )
+            .unwrap();
+
+        assert_eq!(
+            stage().gas_progress(&tx, 1, 1).unwrap(),
+            EntitiesCheckpoint { processed: 5, total: Some(7) }
+        );
+        assert_eq!(
+            stage().gas_progress(&tx, 1, 4).unwrap(),
+            EntitiesCheckpoint { processed: 5, total: Some(7 + 20 + 30 + 40) }
+        );
+    }
 }
@@ -1,14 +1,19 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
-use reth_codecs::Compact;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::Database,
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_primitives::{keccak256, AccountHashingCheckpoint};
+use reth_primitives::{
+    keccak256, AccountHashingCheckpoint, EntitiesCheckpoint, StageUnitCheckpoint,
+};
 use reth_provider::Transaction;
-use std::{collections::BTreeMap, fmt::Debug, ops::Range};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    ops::{Deref, Range},
+};
 use tracing::*;
 
 /// The [`StageId`] of the account hashing stage.
@@ -40,10 +45,8 @@ impl AccountHashingStage {
     ) -> Result<(), StageError> {
         debug!(target: "sync::stages::account_hashing::exec", checkpoint = ?checkpoint, "Saving inner account hashing checkpoint");
 
-        let mut buf = vec![];
-        checkpoint.to_compact(&mut buf);
-
-        Ok(tx.put::<tables::SyncStageProgress>(ACCOUNT_HASHING.0.into(), buf)?)
+        Ok(ACCOUNT_HASHING
+            .save_stage_checkpoint(tx.deref(), Some(StageUnitCheckpoint::Account(checkpoint)))?)
     }
 
     /// Gets the hashing progress
@@ -51,14 +54,13 @@ impl AccountHashingStage {
         &self,
         tx: &Transaction<'_, DB>,
     ) -> Result<AccountHashingCheckpoint, StageError> {
-        let buf =
-            tx.get::<tables::SyncStageProgress>(ACCOUNT_HASHING.0.into())?.unwrap_or_default();
-
-        if buf.is_empty() {
-            return Ok(AccountHashingCheckpoint::default())
-        }
-
-        let (checkpoint, _) = AccountHashingCheckpoint::from_compact(&buf, buf.len());
+        let checkpoint = match ACCOUNT_HASHING
+            .get_checkpoint(tx.deref())?
+            .and_then(|checkpoint| checkpoint.stage_checkpoint)
+        {
+            Some(StageUnitCheckpoint::Account(checkpoint)) => checkpoint,
+            _ => return Ok(AccountHashingCheckpoint::default()),
+        };
 
         if checkpoint.address.is_some() {
             debug!(target: "sync::stages::account_hashing::exec", checkpoint = ?checkpoint, "Continuing inner account hashing checkpoint");
@@ -186,7 +188,13 @@ impl<DB: Database> Stage<DB> for AccountHashingStage {
                 // clear table, load all accounts and hash it
                 tx.clear::<tables::HashedAccount>()?;
 
-                checkpoint = AccountHashingCheckpoint::default();
+                checkpoint = AccountHashingCheckpoint {
+                    progress: EntitiesCheckpoint {
+                        processed: 0,
+                        total: Some(tx.entries::<tables::PlainAccountState>()? as u64),
+                    },
+                    ..Default::default()
+                };
                 self.save_checkpoint(tx, checkpoint)?;
             }
 
@@ -199,6 +207,7 @@ impl<DB: Database> Stage<DB> for AccountHashingStage {
                     .take(self.commit_threshold as usize)
                     .map(|res| res.map(|(address, account)| (keccak256(address), account)))
                     .collect::<Result<BTreeMap<_, _>, _>>()?;
+                checkpoint.progress.processed += hashed_batch.len() as u64;
 
                 let mut hashed_account_cursor = tx.cursor_write::<tables::HashedAccount>()?;
 
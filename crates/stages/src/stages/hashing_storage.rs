@@ -1,6 +1,5 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
 use num_traits::Zero;
-use reth_codecs::Compact;
 use reth_db::{
     cursor::DbDupCursorRO,
     database::Database,
@@ -8,9 +7,12 @@ use reth_db::{
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_primitives::{keccak256, Address, StorageEntry, StorageHashingCheckpoint};
+use reth_primitives::{
+    keccak256, Address, EntitiesCheckpoint, StageUnitCheckpoint, StorageEntry,
+    StorageHashingCheckpoint,
+};
 use reth_provider::Transaction;
-use std::{collections::BTreeMap, fmt::Debug};
+use std::{collections::BTreeMap, fmt::Debug, ops::Deref};
 use tracing::*;
 
 /// The [`StageId`] of the storage hashing stage.
@@ -42,10 +44,8 @@ impl StorageHashingStage {
     ) -> Result<(), StageError> {
         debug!(target: "sync::stages::storage_hashing::exec", checkpoint = ?checkpoint, "Saving inner storage hashing checkpoint");
 
-        let mut buf = vec![];
-        checkpoint.to_compact(&mut buf);
-
-        Ok(tx.put::<tables::SyncStageProgress>(STORAGE_HASHING.0.into(), buf)?)
+        Ok(STORAGE_HASHING
+            .save_stage_checkpoint(tx.deref(), Some(StageUnitCheckpoint::Storage(checkpoint)))?)
     }
 
     /// Gets the hashing progress
@@ -53,14 +53,13 @@ impl StorageHashingStage {
         &self,
         tx: &Transaction<'_, DB>,
     ) -> Result<StorageHashingCheckpoint, StageError> {
-        let buf =
-            tx.get::<tables::SyncStageProgress>(STORAGE_HASHING.0.into())?.unwrap_or_default();
-
-        if buf.is_empty() {
-            return Ok(StorageHashingCheckpoint::default())
-        }
-
-        let (checkpoint, _) = StorageHashingCheckpoint::from_compact(&buf, buf.len());
+        let checkpoint = match STORAGE_HASHING
+            .get_checkpoint(tx.deref())?
+            .and_then(|checkpoint| checkpoint.stage_checkpoint)
+        {
+            Some(StageUnitCheckpoint::Storage(checkpoint)) => checkpoint,
+            _ => return Ok(StorageHashingCheckpoint::default()),
+        };
 
         if checkpoint.address.is_some() {
             debug!(target: "sync::stages::storage_hashing::exec", checkpoint = ?checkpoint, "Continuing inner storage hashing checkpoint");
@@ -105,7 +104,13 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
             {
                 tx.clear::<tables::HashedStorage>()?;
 
-                checkpoint = StorageHashingCheckpoint::default();
+                checkpoint = StorageHashingCheckpoint {
+                    progress: EntitiesCheckpoint {
+                        processed: 0,
+                        total: Some(tx.entries::<tables::PlainStorageState>()? as u64),
+                    },
+                    ..Default::default()
+                };
                 self.save_checkpoint(tx, checkpoint)?;
             }
 
@@ -166,6 +171,7 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
             }
 
             // iterate and put presorted hashed slots
+            checkpoint.progress.processed += hashed_batch.len() as u64;
             hashed_batch.into_iter().try_for_each(|((addr, key), value)| {
                 tx.put::<tables::HashedStorage>(addr, StorageEntry { key, value })
             })?;
@@ -1,5 +1,8 @@
-use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
+use crate::{
+    stages::EXECUTION, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput,
+};
 use reth_db::database::Database;
+use reth_primitives::{PruneModes, PruneSegment};
 use reth_provider::Transaction;
 use std::fmt::Debug;
 use tracing::*;
@@ -10,16 +13,21 @@ pub const INDEX_ACCOUNT_HISTORY: StageId = StageId("IndexAccountHistory");
 /// Stage is indexing history the account changesets generated in
 /// [`ExecutionStage`][crate::stages::ExecutionStage]. For more information
 /// on index sharding take a look at [`reth_db::tables::AccountHistory`]
+///
+/// If account history is pruned, the changesets and history indices of all blocks up to the prune
+/// target are removed after each batch.
 #[derive(Debug)]
 pub struct IndexAccountHistoryStage {
     /// Number of blocks after which the control
     /// flow will be returned to the pipeline for commit.
     pub commit_threshold: u64,
+    /// Pruning configuration, only [PruneSegment::AccountHistory] is enforced by this stage.
+    pub prune_modes: PruneModes,
 }
 
 impl Default for IndexAccountHistoryStage {
     fn default() -> Self {
-        Self { commit_threshold: 100_000 }
+        Self { commit_threshold: 100_000, prune_modes: PruneModes::none() }
     }
 }
 
@@ -30,6 +38,11 @@ impl<DB: Database> Stage<DB> for IndexAccountHistoryStage {
         INDEX_ACCOUNT_HISTORY
     }
 
+    /// Account history is indexed from the changesets written during execution.
+    fn dependencies(&self) -> Option<&[StageId]> {
+        Some(&[EXECUTION])
+    }
+
     /// Execute the stage.
     async fn execute(
         &mut self,
@@ -52,6 +65,15 @@ impl<DB: Database> Stage<DB> for IndexAccountHistoryStage {
         // Insert changeset to history index
         tx.insert_account_history_index(indices)?;
 
+        if let Some(prune_target) = self
+            .prune_modes
+            .prune_target_block(PruneSegment::AccountHistory, previous_stage_progress)
+        {
+            let deleted =
+                tx.prune_segment(PruneSegment::AccountHistory, prune_target.min(to_block))?;
+            trace!(target: "sync::stages::index_account_history", prune_target, deleted, "Pruned account history");
+        }
+
         info!(target: "sync::stages::index_account_history", "Stage finished");
         Ok(ExecOutput { stage_progress: to_block, done: true })
     }
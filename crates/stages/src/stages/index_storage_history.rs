@@ -1,6 +1,8 @@
-use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
+use crate::{
+    stages::EXECUTION, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput,
+};
 use reth_db::{database::Database, models::TransitionIdAddress};
-use reth_primitives::Address;
+use reth_primitives::{Address, PruneModes, PruneSegment};
 use reth_provider::Transaction;
 use std::fmt::Debug;
 use tracing::*;
@@ -11,16 +13,21 @@ pub const INDEX_STORAGE_HISTORY: StageId = StageId("IndexStorageHistory");
 /// Stage is indexing history the account changesets generated in
 /// [`ExecutionStage`][crate::stages::ExecutionStage]. For more information
 /// on index sharding take a look at [`reth_db::tables::StorageHistory`].
+///
+/// If storage history is pruned, the changesets and history indices of all blocks up to the prune
+/// target are removed after each batch.
 #[derive(Debug)]
 pub struct IndexStorageHistoryStage {
     /// Number of blocks after which the control
     /// flow will be returned to the pipeline for commit.
     pub commit_threshold: u64,
+    /// Pruning configuration, only [PruneSegment::StorageHistory] is enforced by this stage.
+    pub prune_modes: PruneModes,
 }
 
 impl Default for IndexStorageHistoryStage {
     fn default() -> Self {
-        Self { commit_threshold: 100_000 }
+        Self { commit_threshold: 100_000, prune_modes: PruneModes::none() }
     }
 }
 
@@ -31,6 +38,11 @@ impl<DB: Database> Stage<DB> for IndexStorageHistoryStage {
         INDEX_STORAGE_HISTORY
     }
 
+    /// Storage history is indexed from the changesets written during execution.
+    fn dependencies(&self) -> Option<&[StageId]> {
+        Some(&[EXECUTION])
+    }
+
     /// Execute the stage.
     async fn execute(
         &mut self,
@@ -53,6 +65,15 @@ impl<DB: Database> Stage<DB> for IndexStorageHistoryStage {
             tx.get_storage_transition_ids_from_changeset(from_transition, to_transition)?;
         tx.insert_storage_history_index(indices)?;
 
+        if let Some(prune_target) = self
+            .prune_modes
+            .prune_target_block(PruneSegment::StorageHistory, previous_stage_progress)
+        {
+            let deleted =
+                tx.prune_segment(PruneSegment::StorageHistory, prune_target.min(to_block))?;
+            trace!(target: "sync::stages::index_storage_history", prune_target, deleted, "Pruned storage history");
+        }
+
         info!(target: "sync::stages::index_storage_history", "Stage finished");
         Ok(ExecOutput { stage_progress: to_block, done: true })
     }
@@ -1,15 +1,15 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
-use reth_db::{database::Database, tables, transaction::DbTx};
+use reth_db::database::Database;
 use reth_interfaces::consensus;
 use reth_provider::{
-    trie::{DBTrieLoader, TrieProgress},
+    trie::{StateRoot, MERKLE_STAGE_ID},
     Transaction,
 };
-use std::{fmt::Debug, ops::DerefMut};
+use std::{fmt::Debug, ops::Deref};
 use tracing::*;
 
 /// The [`StageId`] of the merkle hashing execution stage.
-pub const MERKLE_EXECUTION: StageId = StageId("MerkleExecute");
+pub const MERKLE_EXECUTION: StageId = StageId(MERKLE_STAGE_ID);
 
 /// The [`StageId`] of the merkle hashing unwind stage.
 pub const MERKLE_UNWIND: StageId = StageId("MerkleUnwind");
@@ -109,27 +109,22 @@ impl<DB: Database> Stage<DB> for MerkleStage {
         let trie_root = if from_transition == to_transition {
             block_root
         } else {
-            let res = if to_transition - from_transition > threshold || stage_progress == 0 {
+            // the storage tries are computed in parallel, each worker with its own read-only
+            // transaction which sees the hashed state committed by the previous stages
+            let db = tx.inner();
+            let state_root = StateRoot::new(tx.deref());
+            if to_transition - from_transition > threshold || stage_progress == 0 {
                 debug!(target: "sync::stages::merkle::exec", current = ?stage_progress, target = ?previous_stage_progress, "Rebuilding trie");
                 // if there are more blocks than threshold it is faster to rebuild the trie
-                let mut loader = DBTrieLoader::new(tx.deref_mut());
-                loader.calculate_root().map_err(|e| StageError::Fatal(Box::new(e)))?
+                state_root.calculate_root_parallel(db)
             } else {
                 debug!(target: "sync::stages::merkle::exec", current = ?stage_progress, target = ?previous_stage_progress, "Updating trie");
                 // Iterate over changeset (similar to Hashing stages) and take new values
-                let current_root = tx.get_header(stage_progress)?.state_root;
-                let mut loader = DBTrieLoader::new(tx.deref_mut());
-                loader
-                    .update_root(current_root, from_transition..to_transition)
-                    .map_err(|e| StageError::Fatal(Box::new(e)))?
-            };
-
-            match res {
-                TrieProgress::Complete(root) => root,
-                TrieProgress::InProgress(_) => {
-                    return Ok(ExecOutput { stage_progress, done: false })
-                }
+                state_root
+                    .gather_changes(from_transition..to_transition)
+                    .and_then(|changes| state_root.update_root_parallel(db, &changes))
             }
+            .map_err(|e| StageError::Fatal(Box::new(e)))?
         };
 
         if block_root != trie_root {
@@ -162,34 +157,18 @@ impl<DB: Database> Stage<DB> for MerkleStage {
 
         // If the merkle stage fails to execute, the trie changes weren't commited
         // and the root stayed the same
-        if tx.get::<tables::AccountsTrie>(target_root)?.is_some() {
+        let state_root = StateRoot::new(tx.deref());
+        if state_root.stored_root().map_err(|e| StageError::Fatal(Box::new(e)))? == target_root {
             info!(target: "sync::stages::merkle::unwind", "Stage skipped");
             return Ok(UnwindOutput { stage_progress: input.unwind_to })
         }
 
-        let current_root = tx.get_header(input.stage_progress)?.state_root;
         let from_transition = tx.get_block_transition(input.unwind_to)?;
         let to_transition = tx.get_block_transition(input.stage_progress)?;
 
-        let mut loader = DBTrieLoader::new(tx.deref_mut());
-        let block_root = loop {
-            match loader
-                .update_root(current_root, from_transition..to_transition)
-                .map_err(|e| StageError::Fatal(Box::new(e)))?
-            {
-                TrieProgress::Complete(root) => break root,
-                TrieProgress::InProgress(_) => {
-                    // Save the loader's progress & drop it to allow committing to the database,
-                    // otherwise we're hitting the borrow checker
-                    let progress = loader.current;
-                    let _ = loader;
-                    tx.commit()?;
-                    // Reinstantiate the loader from where it was left off.
-                    loader = DBTrieLoader::new(tx.deref_mut());
-                    loader.current = progress;
-                }
-            }
-        };
+        let block_root = state_root
+            .update_root_for_range(from_transition..to_transition)
+            .map_err(|e| StageError::Fatal(Box::new(e)))?;
 
         if block_root != target_root {
             let unwind_to = input.unwind_to;
@@ -288,10 +267,10 @@ mod tests {
         assert!(runner.validate_execution(input, result.ok()).is_ok(), "execution validation");
     }
 
-    fn create_trie_loader<'tx, 'db>(
+    fn create_state_root<'tx, 'db>(
         tx: &'tx Transaction<'db, Env<WriteMap>>,
-    ) -> DBTrieLoader<'tx, <Env<WriteMap> as DatabaseGAT<'db>>::TXMut> {
-        DBTrieLoader::new(tx.deref())
+    ) -> StateRoot<'tx, <Env<WriteMap> as DatabaseGAT<'db>>::TXMut> {
+        StateRoot::new(tx.deref())
     }
 
     struct MerkleTestRunner {
@@ -474,10 +453,7 @@ mod tests {
 
     impl MerkleTestRunner {
         fn state_root(&self) -> Result<H256, TestRunnerError> {
-            Ok(create_trie_loader(&self.tx.inner())
-                .calculate_root()
-                .and_then(|e| e.root())
-                .unwrap())
+            Ok(create_state_root(&self.tx.inner()).calculate_root().unwrap())
         }
 
         pub(crate) fn generate_initial_trie(
@@ -489,10 +465,8 @@ mod tests {
             )?;
 
             let mut tx = self.tx.inner();
-            let root = create_trie_loader(&tx)
-                .calculate_root()
-                .and_then(|e| e.root())
-                .expect("couldn't create initial trie");
+            let root =
+                create_state_root(&tx).calculate_root().expect("couldn't create initial trie");
 
             tx.commit()?;
 
@@ -503,10 +477,7 @@ mod tests {
             if previous_stage_progress != 0 {
                 let block_root =
                     self.tx.inner().get_header(previous_stage_progress).unwrap().state_root;
-                let root = create_trie_loader(&self.tx().inner())
-                    .calculate_root()
-                    .and_then(|e| e.root())
-                    .unwrap();
+                let root = create_state_root(&self.tx().inner()).calculate_root().unwrap();
                 assert_eq!(block_root, root);
             }
             Ok(())
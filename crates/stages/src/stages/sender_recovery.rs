@@ -1,16 +1,19 @@
 use crate::{
-    exec_or_return, stages::stream::SequentialPairStream, ExecAction, ExecInput, ExecOutput, Stage,
-    StageError, StageId, UnwindInput, UnwindOutput,
+    exec_or_return,
+    stages::{stream::SequentialPairStream, BODIES},
+    ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput,
 };
 use futures_util::StreamExt;
 use itertools::Itertools;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::Database,
+    models::StoredBlockBody,
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_primitives::TxNumber;
+use reth_interfaces::provider::ProviderError;
+use reth_primitives::{BlockNumber, TxNumber};
 use reth_provider::Transaction;
 use std::fmt::Debug;
 use thiserror::Error;
@@ -44,6 +47,11 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
         SENDER_RECOVERY
     }
 
+    /// Senders are recovered from the transactions of the block bodies.
+    fn dependencies(&self) -> Option<&[StageId]> {
+        Some(&[BODIES])
+    }
+
     /// Retrieve the range of transactions to iterate over by querying
     /// [`BlockBodies`][reth_db::tables::BlockBodies],
     /// collect transactions within that range,
@@ -58,11 +66,20 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
             exec_or_return!(input, self.commit_threshold, "sync::stages::sender_recovery");
         let done = !capped;
 
+        // The transactions are read through a read-only transaction, so the write transaction is
+        // only opened to insert the recovered senders
+        let reader = tx.inner().tx()?;
+        let block_body = |number: BlockNumber| -> Result<StoredBlockBody, StageError> {
+            Ok(reader
+                .get::<tables::BlockBodies>(number)?
+                .ok_or(ProviderError::BlockBody { number })?)
+        };
+
         // Look up the start index for the transaction range
-        let start_tx_index = tx.get_block_body(start_block)?.start_tx_id;
+        let start_tx_index = block_body(start_block)?.start_tx_id;
 
         // Look up the end index for transaction range (inclusive)
-        let end_tx_index = tx.get_block_body(end_block)?.last_tx_index();
+        let end_tx_index = block_body(end_block)?.last_tx_index();
 
         // No transactions to walk over
         if start_tx_index > end_tx_index {
@@ -70,11 +87,8 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
             return Ok(ExecOutput { stage_progress: end_block, done })
         }
 
-        // Acquire the cursor for inserting elements
-        let mut senders_cursor = tx.cursor_write::<tables::TxSenders>()?;
-
         // Acquire the cursor over the transactions
-        let mut tx_cursor = tx.cursor_read::<tables::Transactions>()?;
+        let mut tx_cursor = reader.cursor_read::<tables::Transactions>()?;
         // Walk the transactions from start to end index (inclusive)
         let tx_walker = tx_cursor.walk_range(start_tx_index..=end_tx_index)?;
 
@@ -82,7 +96,7 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
         info!(target: "sync::stages::sender_recovery", start_tx_index, end_tx_index, "Recovering senders");
 
         // An _unordered_ channel to receive results from a rayon job
-        let (tx, rx) = mpsc::unbounded_channel();
+        let (result_tx, result_rx) = mpsc::unbounded_channel();
 
         // Spawn recovery jobs onto the default rayon threadpool and send the result through the
         // channel.
@@ -93,7 +107,7 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
         for chunk in
             &tx_walker.chunks(self.commit_threshold as usize / rayon::current_num_threads())
         {
-            let tx = tx.clone();
+            let result_tx = result_tx.clone();
             // Note: Unfortunate side-effect of how chunk is designed in itertools (it is not Send)
             let mut chunk: Vec<_> = chunk.collect();
 
@@ -111,16 +125,19 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
                         Ok((tx_id, sender))
                     })
                     .for_each(|result: Result<_, StageError>| {
-                        let _ = tx.send(result);
+                        let _ = result_tx.send(result);
                     });
             });
         }
-        drop(tx);
+        drop(result_tx);
 
         // We need sorted results, so we wrap the _unordered_ receiver stream into a sequential
         // stream, which yields the results by ascending transaction ID.
         let mut recovered_senders =
-            SequentialPairStream::new(start_tx_index, UnboundedReceiverStream::new(rx));
+            SequentialPairStream::new(start_tx_index, UnboundedReceiverStream::new(result_rx));
+
+        // Acquire the cursor for inserting elements
+        let mut senders_cursor = tx.cursor_write::<tables::TxSenders>()?;
 
         while let Some(recovered) = recovered_senders.next().await {
             let (id, sender) = recovered?;
@@ -264,11 +281,10 @@ mod tests {
 
         /// # Panics
         ///
-        /// 1. If there are any entries in the [tables::TxSenders] table above
-        ///    a given block number.
+        /// 1. If there are any entries in the [tables::TxSenders] table above a given block number.
         ///
-        /// 2. If the is no requested block entry in the bodies table,
-        ///    but [tables::TxSenders] is not empty.
+        /// 2. If the is no requested block entry in the bodies table, but [tables::TxSenders] is
+        ///    not empty.
         fn ensure_no_senders_by_block(&self, block: BlockNumber) -> Result<(), TestRunnerError> {
             let body_result = self.tx.inner().get_block_body(block);
             match body_result {
@@ -1,6 +1,6 @@
 use crate::{
-    exec_or_return, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
-    UnwindOutput,
+    exec_or_return, stages::BODIES, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId,
+    UnwindInput, UnwindOutput,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
@@ -8,6 +8,7 @@ use reth_db::{
     tables,
     transaction::{DbTx, DbTxMut},
 };
+use reth_primitives::{PruneModes, PruneSegment};
 use reth_provider::Transaction;
 use tracing::*;
 
@@ -19,22 +20,33 @@ pub const TRANSACTION_LOOKUP: StageId = StageId("TransactionLookup");
 /// This stage walks over the bodies table, and sets the transaction hash of each transaction in a
 /// block to the corresponding `TransitionId` at each block. This is written to the
 /// [`tables::TxHashNumber`] This is used for looking up changesets via the transaction hash.
+///
+/// If transaction lookups are pruned, the lookups of all blocks up to the prune target are removed
+/// after each batch.
 #[derive(Debug, Clone)]
 pub struct TransactionLookupStage {
     /// The number of table entries to commit at once
     commit_threshold: u64,
+    /// Pruning configuration, only [PruneSegment::TransactionLookup] is enforced by this stage.
+    prune_modes: PruneModes,
 }
 
 impl Default for TransactionLookupStage {
     fn default() -> Self {
-        Self { commit_threshold: 50_000 }
+        Self { commit_threshold: 50_000, prune_modes: PruneModes::none() }
     }
 }
 
 impl TransactionLookupStage {
     /// Create new instance of [TransactionLookupStage].
     pub fn new(commit_threshold: u64) -> Self {
-        Self { commit_threshold }
+        Self { commit_threshold, prune_modes: PruneModes::none() }
+    }
+
+    /// Set the pruning configuration.
+    pub fn with_prune_modes(mut self, prune_modes: PruneModes) -> Self {
+        self.prune_modes = prune_modes;
+        self
     }
 }
 
@@ -45,6 +57,11 @@ impl<DB: Database> Stage<DB> for TransactionLookupStage {
         TRANSACTION_LOOKUP
     }
 
+    /// Transaction hashes are indexed from the transactions of the block bodies.
+    fn dependencies(&self) -> Option<&[StageId]> {
+        Some(&[BODIES])
+    }
+
     /// Write total difficulty entries
     async fn execute(
         &mut self,
@@ -56,8 +73,11 @@ impl<DB: Database> Stage<DB> for TransactionLookupStage {
 
         debug!(target: "sync::stages::transaction_lookup", start_block, end_block, "Commencing sync");
 
-        let mut cursor_bodies = tx.cursor_read::<tables::BlockBodies>()?;
-        let mut tx_cursor = tx.cursor_write::<tables::Transactions>()?;
+        // The transactions are read through a read-only transaction, so the write transaction is
+        // only opened to insert the lookups
+        let reader = tx.inner().tx()?;
+        let mut cursor_bodies = reader.cursor_read::<tables::BlockBodies>()?;
+        let mut tx_cursor = reader.cursor_read::<tables::Transactions>()?;
 
         // Walk over block bodies within a specified range.
         let bodies = cursor_bodies.walk(Some(start_block))?.take_while(|entry| {
@@ -98,6 +118,15 @@ impl<DB: Database> Stage<DB> for TransactionLookupStage {
             }
         }
 
+        if let Some(prune_target) = self
+            .prune_modes
+            .prune_target_block(PruneSegment::TransactionLookup, input.previous_stage_progress())
+        {
+            let deleted =
+                tx.prune_segment(PruneSegment::TransactionLookup, prune_target.min(end_block))?;
+            trace!(target: "sync::stages::transaction_lookup", prune_target, deleted, "Pruned transaction lookups");
+        }
+
         let done = !capped;
         info!(target: "sync::stages::transaction_lookup", stage_progress = end_block, done, "Sync iteration finished");
         Ok(ExecOutput { done, stage_progress: end_block })
@@ -0,0 +1,319 @@
+use crate::{stages::FINISH, PipelineEvent, StaticFileProducerError};
+use futures_util::{Stream, StreamExt};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{BlockNumber, PruneMode, PruneModes, PruneSegment, MINIMUM_PRUNING_DISTANCE};
+use reth_provider::{
+    static_files::{StaticFileProvider, StaticFileTable},
+    Transaction,
+};
+use std::{ops::Range, sync::Arc};
+use tracing::*;
+
+/// Maximum number of rows moved to the static files in one database transaction.
+const BATCH_SIZE: u64 = 10_000;
+
+/// Moves headers, transactions and receipts of finalized blocks from the database into static
+/// files.
+///
+/// Only blocks that are at least `distance` blocks behind the tip are moved, so the data of recent
+/// blocks that may still be unwound stays in the database.
+#[derive(Debug)]
+pub struct StaticFileProducer<DB> {
+    db: Arc<DB>,
+    static_files: StaticFileProvider,
+    /// Prune modes of the node, data that is pruned from the database is not moved.
+    prune_modes: PruneModes,
+    /// Number of most recent blocks whose data is kept in the database.
+    distance: u64,
+    /// Minimum number of blocks between two runs.
+    block_interval: u64,
+    /// The tip of the last run.
+    last_run_block: Option<BlockNumber>,
+}
+
+impl<DB: Database> StaticFileProducer<DB> {
+    /// Creates a new static file producer.
+    ///
+    /// The distance is raised to at least [MINIMUM_PRUNING_DISTANCE].
+    pub fn new(
+        db: Arc<DB>,
+        static_files: StaticFileProvider,
+        distance: u64,
+        block_interval: u64,
+    ) -> Self {
+        Self {
+            db,
+            static_files,
+            prune_modes: PruneModes::none(),
+            distance: distance.max(MINIMUM_PRUNING_DISTANCE),
+            block_interval,
+            last_run_block: None,
+        }
+    }
+
+    /// Sets the prune modes of the node.
+    pub fn with_prune_modes(mut self, prune_modes: PruneModes) -> Self {
+        self.prune_modes = prune_modes;
+        self
+    }
+
+    /// Returns true if the producer should run for the given tip.
+    pub fn is_run_needed(&self, tip: BlockNumber) -> bool {
+        self.last_run_block.map_or(true, |last_run_block| {
+            tip.saturating_sub(last_run_block) >= self.block_interval
+        })
+    }
+
+    /// Moves the data of all blocks up to `tip - distance` into the static files.
+    ///
+    /// Returns the number of moved rows.
+    pub fn run(&mut self, tip: BlockNumber) -> Result<usize, StaticFileProducerError> {
+        self.last_run_block = Some(tip);
+        let Some(target) = tip.checked_sub(self.distance) else {
+            trace!(target: "static_files", tip, "Nothing to move");
+            return Ok(0)
+        };
+
+        let mut tx = Transaction::new(self.db.as_ref())?;
+        let mut moved = self.move_rows::<tables::Headers>(&mut tx, target + 1)?;
+
+        if let Some(body) = tx.get::<tables::BlockBodies>(target)? {
+            let tx_end = body.tx_id_range().end;
+
+            // transaction lookups are pruned by walking the transactions in the database
+            let transactions_end = if self.prune_modes.transaction_lookup.is_some() {
+                tx.get_prune_checkpoint(PruneSegment::TransactionLookup)?
+                    .and_then(|checkpoint| checkpoint.tx_number)
+                    .map(|tx_number| tx_end.min(tx_number + 1))
+            } else {
+                Some(tx_end)
+            };
+            if let Some(end) = transactions_end {
+                moved += self.move_rows::<tables::Transactions>(&mut tx, end)?;
+            }
+
+            // receipts that are pruned later would be kept forever in the static files
+            let receipts_end = match self.prune_modes.receipts {
+                None => Some(tx_end),
+                Some(PruneMode::Before(block)) => tx
+                    .get_prune_checkpoint(PruneSegment::Receipts)?
+                    .filter(|checkpoint| checkpoint.block_number + 1 >= block)
+                    .map(|_| tx_end),
+                Some(_) => None,
+            };
+            if let Some(end) = receipts_end {
+                moved += self.move_rows::<tables::Receipts>(&mut tx, end)?;
+            }
+        }
+
+        info!(target: "static_files", tip, target, moved, "Static file producer finished");
+        Ok(moved)
+    }
+
+    /// Moves all rows of the table below `end` that are not yet in the static files.
+    ///
+    /// Rows are synced to the static files before their deletion from the database is committed,
+    /// so readers always find them in either of the two. If the node stopped in between, the rows
+    /// are in both and the move is completed by deleting them from the database first.
+    fn move_rows<T: StaticFileTable>(
+        &self,
+        tx: &mut Transaction<'_, DB>,
+        end: u64,
+    ) -> Result<usize, StaticFileProducerError> {
+        let mut start = match self.static_files.highest(T::SEGMENT) {
+            Some(highest) => {
+                let deleted = delete_rows::<T, DB>(tx, 0..highest + 1)?;
+                if deleted > 0 {
+                    tx.commit()?;
+                    debug!(target: "static_files", segment = %T::SEGMENT, deleted, "Deleted rows of an interrupted move");
+                }
+                highest + 1
+            }
+            None => match tx.cursor_read::<T>()?.first()? {
+                Some((key, _)) => key,
+                None => return Ok(0),
+            },
+        };
+
+        let mut moved = 0;
+        while start < end {
+            let batch_end = end.min(start + BATCH_SIZE);
+            let rows = tx
+                .cursor_read::<T>()?
+                .walk_range(start..batch_end)?
+                .collect::<Result<Vec<_>, _>>()?;
+            if rows.is_empty() {
+                break
+            }
+            moved += self.static_files.append::<T>(rows)?;
+            delete_rows::<T, DB>(tx, start..batch_end)?;
+            tx.commit()?;
+
+            debug!(target: "static_files", segment = %T::SEGMENT, start, end = batch_end, "Moved rows to static files");
+            start = batch_end;
+        }
+        Ok(moved)
+    }
+
+    /// Runs the producer every time the pipeline finished a sync to a new tip, until the stream of
+    /// pipeline events ends.
+    ///
+    /// This blocks on database writes and should be spawned on a blocking task.
+    pub async fn run_with_events<S>(mut self, mut events: S) -> Result<(), StaticFileProducerError>
+    where
+        S: Stream<Item = PipelineEvent> + Unpin,
+    {
+        while let Some(event) = events.next().await {
+            if let PipelineEvent::Ran { stage_id, result, .. } = event {
+                if stage_id == FINISH && self.is_run_needed(result.stage_progress) {
+                    self.run(result.stage_progress)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deletes the rows of the table in the range from the database, returns the number of deleted
+/// rows.
+fn delete_rows<T: StaticFileTable, DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: Range<u64>,
+) -> Result<usize, StaticFileProducerError> {
+    let mut cursor = tx.cursor_write::<T>()?;
+    let mut walker = cursor.walk_range(range)?;
+    let mut deleted = 0;
+    while walker.next().transpose()?.is_some() {
+        walker.delete_current()?;
+        deleted += 1;
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_primitives::{PruneCheckpoint, Receipt, H256};
+    use reth_provider::{insert_canonical_block, static_files::StaticFileSegment};
+
+    #[test]
+    fn move_finalized_rows() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = random_block_range(0..201, H256::zero(), 1..3);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let mut tx_number = 0;
+        for block in blocks.iter() {
+            insert_canonical_block(&*tx, block.clone(), None, false).unwrap();
+            for _ in block.body.iter() {
+                tx.put::<tables::Receipts>(tx_number, Receipt::default()).unwrap();
+                tx_number += 1;
+            }
+        }
+        tx.commit().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+        let mut producer = StaticFileProducer::new(db.clone(), static_files.clone(), 0, 1);
+        assert_eq!(producer.distance, MINIMUM_PRUNING_DISTANCE);
+        producer.run(200).unwrap();
+
+        let target = 200 - MINIMUM_PRUNING_DISTANCE;
+        let tx_end = blocks[..=target as usize].iter().map(|block| block.body.len() as u64).sum();
+        assert_eq!(static_files.highest(StaticFileSegment::Headers), Some(target));
+        assert_eq!(static_files.highest(StaticFileSegment::Transactions), Some(tx_end - 1));
+        assert_eq!(static_files.highest(StaticFileSegment::Receipts), Some(tx_end - 1));
+
+        let tx = db.tx().unwrap();
+        assert_eq!(
+            tx.cursor_read::<tables::Headers>().unwrap().first().unwrap().unwrap().0,
+            target + 1
+        );
+        assert_eq!(
+            tx.cursor_read::<tables::Transactions>().unwrap().first().unwrap().unwrap().0,
+            tx_end
+        );
+        assert_eq!(
+            static_files.get::<tables::Headers>(target).unwrap().as_ref(),
+            Some(&blocks[target as usize].header.header)
+        );
+    }
+
+    #[test]
+    fn skip_pruned_receipts() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = random_block_range(0..201, H256::zero(), 1..3);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        for block in blocks.iter() {
+            insert_canonical_block(&*tx, block.clone(), None, false).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Distance(10)),
+            transaction_lookup: Some(PruneMode::Full),
+            ..Default::default()
+        };
+        let mut producer =
+            StaticFileProducer::new(db.clone(), static_files.clone(), 0, 1).with_prune_modes(modes);
+
+        // transactions are only moved once their lookups were pruned
+        producer.run(200).unwrap();
+        assert!(static_files.highest(StaticFileSegment::Headers).is_some());
+        assert_eq!(static_files.highest(StaticFileSegment::Transactions), None);
+
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        tx.put::<tables::PruneCheckpoints>(
+            PruneSegment::TransactionLookup,
+            PruneCheckpoint { block_number: 10, tx_number: Some(5) },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+        producer.run(200).unwrap();
+        assert_eq!(static_files.highest(StaticFileSegment::Transactions), Some(5));
+        assert_eq!(static_files.highest(StaticFileSegment::Receipts), None);
+    }
+
+    #[test]
+    fn complete_interrupted_move() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = random_block_range(0..201, H256::zero(), 0..1);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        for block in blocks.iter() {
+            insert_canonical_block(&*tx, block.clone(), None, false).unwrap();
+        }
+        tx.commit().unwrap();
+
+        // the rows were appended to the static files, but their deletion was never committed
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+        let rows = db
+            .tx()
+            .unwrap()
+            .cursor_read::<tables::Headers>()
+            .unwrap()
+            .walk_range(0..10)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        static_files.append::<tables::Headers>(rows).unwrap();
+
+        let mut producer = StaticFileProducer::new(db.clone(), static_files.clone(), 0, 1);
+        producer.run(200).unwrap();
+
+        let target = 200 - MINIMUM_PRUNING_DISTANCE;
+        assert_eq!(static_files.highest(StaticFileSegment::Headers), Some(target));
+        assert_eq!(
+            db.tx().unwrap().cursor_read::<tables::Headers>().unwrap().first().unwrap().unwrap().0,
+            target + 1
+        );
+    }
+}
@@ -1,18 +1,40 @@
 use crate::{ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput};
 use reth_db::database::Database;
 use reth_provider::Transaction;
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Barrier},
+};
 
 #[derive(Debug)]
 pub struct TestStage {
     id: StageId,
+    dependencies: Option<&'static [StageId]>,
     exec_outputs: VecDeque<Result<ExecOutput, StageError>>,
     unwind_outputs: VecDeque<Result<UnwindOutput, StageError>>,
+    barrier: Option<Arc<Barrier>>,
 }
 
 impl TestStage {
     pub fn new(id: StageId) -> Self {
-        Self { id, exec_outputs: VecDeque::new(), unwind_outputs: VecDeque::new() }
+        Self {
+            id,
+            dependencies: None,
+            exec_outputs: VecDeque::new(),
+            unwind_outputs: VecDeque::new(),
+            barrier: None,
+        }
+    }
+
+    pub fn with_dependencies(mut self, dependencies: &'static [StageId]) -> Self {
+        self.dependencies = Some(dependencies);
+        self
+    }
+
+    /// Blocks the first execution until all stages sharing the barrier are executing.
+    pub fn with_barrier(mut self, barrier: Arc<Barrier>) -> Self {
+        self.barrier = Some(barrier);
+        self
     }
 
     pub fn with_exec(mut self, exec_outputs: VecDeque<Result<ExecOutput, StageError>>) -> Self {
@@ -45,11 +67,18 @@ impl<DB: Database> Stage<DB> for TestStage {
         self.id
     }
 
+    fn dependencies(&self) -> Option<&[StageId]> {
+        self.dependencies
+    }
+
     async fn execute(
         &mut self,
         _: &mut Transaction<'_, DB>,
         _input: ExecInput,
     ) -> Result<ExecOutput, StageError> {
+        if let Some(barrier) = self.barrier.take() {
+            barrier.wait();
+        }
         self.exec_outputs
             .pop_front()
             .unwrap_or_else(|| panic!("Test stage {} executed too many times.", self.id))
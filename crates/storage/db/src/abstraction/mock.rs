@@ -74,6 +74,10 @@ impl<'a> DbTx<'a> for TxMock {
     fn cursor_dup_read<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error> {
         todo!()
     }
+
+    fn entries<T: Table>(&self) -> Result<usize, Error> {
+        todo!()
+    }
 }
 
 impl<'a> DbTxMut<'a> for TxMock {
@@ -4,7 +4,7 @@ use crate::{
     Error,
 };
 use reth_primitives::bytes::Bytes;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::Debug,
     marker::{Send, Sync},
@@ -41,9 +41,11 @@ pub trait Decode: Send + Sync + Sized + Debug {
 }
 
 /// Generic trait that enforces the database key to implement [`Encode`] and [`Decode`].
-pub trait Key: Encode + Decode + Ord + Clone {}
+///
+/// Keys are also (de)serializable, so they can be parsed from and printed for users.
+pub trait Key: Encode + Decode + Ord + Clone + Serialize + for<'a> Deserialize<'a> {}
 
-impl<T> Key for T where T: Encode + Decode + Ord + Clone {}
+impl<T> Key for T where T: Encode + Decode + Ord + Clone + Serialize + for<'a> Deserialize<'a> {}
 
 /// Generic trait that enforces the database value to implement [`Compress`] and [`Decompress`].
 pub trait Value: Compress + Decompress + Serialize {}
@@ -79,6 +81,9 @@ pub trait DupSort: Table {
     ///
     /// Upstream docs: <https://libmdbx.dqdkfa.ru/usage.html#autotoc_md48>
     type SubKey: Key;
+
+    /// Returns the subkey of a value of the table.
+    fn subkey(value: &Self::Value) -> Self::SubKey;
 }
 
 /// Allows duplicating tables across databases
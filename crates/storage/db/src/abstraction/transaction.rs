@@ -45,6 +45,8 @@ pub trait DbTx<'tx>: for<'a> DbTxGAT<'a> {
     fn cursor_read<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, Error>;
     /// Iterate over read only values in dup sorted table.
     fn cursor_dup_read<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error>;
+    /// Returns the number of entries in the table.
+    fn entries<T: Table>(&self) -> Result<usize, Error>;
 }
 
 /// Read write transaction that allows writing to database
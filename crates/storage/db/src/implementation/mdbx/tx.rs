@@ -91,6 +91,14 @@ impl<'tx, K: TransactionKind, E: EnvironmentKind> DbTx<'tx> for Tx<'tx, K, E> {
             .map(decode_one::<T>)
             .transpose()
     }
+
+    fn entries<T: Table>(&self) -> Result<usize, Error> {
+        Ok(self
+            .inner
+            .db_stat(&self.inner.open_db(Some(T::NAME)).map_err(|e| Error::Read(e.into()))?)
+            .map_err(|e| Error::Read(e.into()))?
+            .entries())
+    }
 }
 
 impl<E: EnvironmentKind> DbTxMut<'_> for Tx<'_, RW, E> {
@@ -0,0 +1,360 @@
+//! Cursors of the in-memory database.
+
+use super::{lock, MemTables, Row, TransactionKind, KEY_EXIST, KEY_MISMATCH, NOT_FOUND, RW};
+use crate::{
+    common::{PairResult, ValueOnlyResult},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
+        ReverseWalker, Walker,
+    },
+    table::{Compress, DupSort, Encode, Table},
+    tables::utils::*,
+    Error,
+};
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    sync::Mutex,
+};
+
+/// Position of a cursor.
+#[derive(Debug, Clone)]
+enum Position {
+    /// Not positioned, moving forward starts at the first row and moving backward at the last row.
+    Unset,
+    /// At a row.
+    At(Row),
+    /// At a row that was deleted by the cursor, moving continues from where the row was.
+    Deleted(Row),
+}
+
+/// Cursor over a table of the in-memory database.
+#[derive(Debug)]
+pub struct Cursor<'tx, K: TransactionKind, T: Table> {
+    /// Tables of the transaction.
+    tables: &'tx Mutex<MemTables>,
+    /// Current position.
+    position: Position,
+    _dbi: PhantomData<(K, T)>,
+}
+
+impl<'tx, K: TransactionKind, T: Table> Cursor<'tx, K, T> {
+    /// Creates an unpositioned cursor over the table.
+    pub(crate) fn new(tables: &'tx Mutex<MemTables>) -> Self {
+        Self { tables, position: Position::Unset, _dbi: PhantomData }
+    }
+
+    /// Returns the row the cursor moves to when moving forward.
+    fn next_row(&self) -> Option<Row> {
+        let tables = lock(self.tables);
+        let rows = &tables.table(T::NAME).rows;
+        match &self.position {
+            Position::Unset => rows.iter().next(),
+            Position::At(row) | Position::Deleted(row) => {
+                rows.range::<Row, _>((Bound::Excluded(row), Bound::Unbounded)).next()
+            }
+        }
+        .cloned()
+    }
+
+    /// Returns the row the cursor moves to when moving backward.
+    fn prev_row(&self) -> Option<Row> {
+        let tables = lock(self.tables);
+        let rows = &tables.table(T::NAME).rows;
+        match &self.position {
+            Position::Unset => rows.iter().next_back(),
+            Position::At(row) | Position::Deleted(row) => rows.range::<Row, _>(..row).next_back(),
+        }
+        .cloned()
+    }
+
+    /// Returns the first row that is greater or equal than the key and subkey.
+    fn seek_row(&self, key: &[u8], subkey: &[u8]) -> Option<Row> {
+        lock(self.tables)
+            .table(T::NAME)
+            .rows
+            .range::<Row, _>((key.to_vec(), subkey.to_vec())..)
+            .next()
+            .cloned()
+    }
+
+    /// Moves the cursor to the row, keeping the position if there is none.
+    fn move_to(&mut self, row: Option<Row>) -> PairResult<T> {
+        let Some(row) = row else { return Ok(None) };
+        let pair = decode_row::<T>(&row)?;
+        self.position = Position::At(row);
+        Ok(Some(pair))
+    }
+
+    /// Positions the cursor at the row, unpositioning it if there is none.
+    fn set(&mut self, row: Option<Row>) -> PairResult<T> {
+        if row.is_none() {
+            self.position = Position::Unset;
+        }
+        self.move_to(row)
+    }
+}
+
+/// Decodes a `(key, value)` row.
+fn decode_row<T: Table>(row: &Row) -> Result<(T::Key, T::Value), Error> {
+    decoder::<T>((Cow::Borrowed(&row.0), Cow::Borrowed(&row.1)))
+}
+
+/// Encodes a `(key, value)` pair.
+fn encode_row<T: Table>(key: T::Key, value: T::Value) -> Row {
+    (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec())
+}
+
+impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T> {
+    fn first(&mut self) -> PairResult<T> {
+        let row = lock(self.tables).table(T::NAME).rows.iter().next().cloned();
+        self.set(row)
+    }
+
+    fn seek_exact(&mut self, key: <T as Table>::Key) -> PairResult<T> {
+        let key = key.encode();
+        // like MDBX, the cursor is positioned at the next key if the key does not exist
+        let row = self.seek_row(key.as_ref(), &[]);
+        let is_exact = row.as_ref().map_or(false, |(row_key, _)| row_key == key.as_ref());
+        let pair = self.set(row)?;
+        Ok(pair.filter(|_| is_exact))
+    }
+
+    fn seek(&mut self, key: <T as Table>::Key) -> PairResult<T> {
+        let row = self.seek_row(key.encode().as_ref(), &[]);
+        self.set(row)
+    }
+
+    fn next(&mut self) -> PairResult<T> {
+        let row = self.next_row();
+        self.move_to(row)
+    }
+
+    fn prev(&mut self) -> PairResult<T> {
+        let row = self.prev_row();
+        self.move_to(row)
+    }
+
+    fn last(&mut self) -> PairResult<T> {
+        let row = lock(self.tables).table(T::NAME).rows.iter().next_back().cloned();
+        self.set(row)
+    }
+
+    fn current(&mut self) -> PairResult<T> {
+        match &self.position {
+            Position::Unset => Ok(None),
+            Position::At(row) => decode_row::<T>(row).map(Some),
+            // after a deletion, the cursor points at the next row
+            Position::Deleted(_) => self.next_row().as_ref().map(decode_row::<T>).transpose(),
+        }
+    }
+
+    fn walk<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<Walker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => self.seek(start_key),
+            None => self.first(),
+        }
+        .transpose();
+
+        Ok(Walker::new(self, start))
+    }
+
+    fn walk_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<RangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match range.start_bound().cloned() {
+            Bound::Included(key) => {
+                if matches!(range.end_bound().cloned(), Bound::Included(end_key) | Bound::Excluded(end_key) if end_key < key) {
+                    return Err(Error::Read(2))
+                }
+                self.seek(key)
+            }
+            Bound::Excluded(_key) => {
+                unreachable!("Rust doesn't allow for Bound::Excluded in starting bounds");
+            }
+            Bound::Unbounded => self.first(),
+        }
+        .transpose();
+
+        Ok(RangeWalker::new(self, start, range.start_bound().cloned(), range.end_bound().cloned()))
+    }
+
+    fn walk_back<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => self.seek(start_key),
+            None => self.last(),
+        }
+        .transpose();
+
+        Ok(ReverseWalker::new(self, start))
+    }
+}
+
+impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, K, T> {
+    fn next_dup(&mut self) -> PairResult<T> {
+        let row = self.next_row().filter(|(key, _)| match &self.position {
+            Position::Unset => true,
+            Position::At(current) | Position::Deleted(current) => current.0 == *key,
+        });
+        self.move_to(row)
+    }
+
+    fn next_no_dup(&mut self) -> PairResult<T> {
+        let row = match &self.position {
+            Position::Unset => self.next_row(),
+            Position::At(current) | Position::Deleted(current) => lock(self.tables)
+                .table(T::NAME)
+                .rows
+                .range::<Row, _>((Bound::Excluded(current), Bound::Unbounded))
+                .find(|(key, _)| *key != current.0)
+                .cloned(),
+        };
+        self.move_to(row)
+    }
+
+    fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
+        Ok(self.next_dup()?.map(|(_, value)| value))
+    }
+
+    fn seek_by_key_subkey(
+        &mut self,
+        key: <T as Table>::Key,
+        subkey: <T as DupSort>::SubKey,
+    ) -> ValueOnlyResult<T> {
+        let key = key.encode();
+        let row = self
+            .seek_row(key.as_ref(), subkey.encode().as_ref())
+            .filter(|(row_key, _)| row_key == key.as_ref());
+        Ok(self.move_to(row)?.map(|(_, value)| value))
+    }
+
+    /// Depending on its arguments, returns an iterator starting at:
+    /// - Some(key), Some(subkey): a `key` item whose data is >= than `subkey`
+    /// - Some(key), None: first item of a specified `key`
+    /// - None, Some(subkey): like first case, but in the first key
+    /// - None, None: first item in the table
+    /// of a DUPSORT table.
+    fn walk_dup<'cursor>(
+        &'cursor mut self,
+        key: Option<T::Key>,
+        subkey: Option<T::SubKey>,
+    ) -> Result<DupWalker<'cursor, 'tx, T, Self>, Error> {
+        let start = match (key, subkey) {
+            (Some(key), Some(subkey)) => {
+                let key = key.encode();
+                let row = self
+                    .seek_row(key.as_ref(), subkey.encode().as_ref())
+                    .filter(|(row_key, _)| row_key == key.as_ref());
+                self.move_to(row).transpose()
+            }
+            (Some(key), None) => {
+                let key = key.encode();
+                let row = lock(self.tables).table(T::NAME).first_of(key.as_ref()).cloned();
+                self.move_to(row).transpose()
+            }
+            (None, Some(subkey)) => {
+                if let Some((key, _)) = self.first()? {
+                    let key = key.encode();
+                    let row = self
+                        .seek_row(key.as_ref(), subkey.encode().as_ref())
+                        .filter(|(row_key, _)| row_key == key.as_ref());
+                    self.move_to(row).transpose()
+                } else {
+                    Some(Err(Error::Read(NOT_FOUND)))
+                }
+            }
+            (None, None) => self.first().transpose(),
+        };
+
+        Ok(DupWalker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
+    }
+}
+
+impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, RW, T> {
+    /// Database operation that will update an existing row if a specified value already
+    /// exists in a table, and insert a new row if the specified value doesn't already exist
+    fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let row = encode_row::<T>(key, value);
+        lock(self.tables).table_mut(T::NAME).upsert(row.clone());
+        self.position = Position::At(row);
+        Ok(())
+    }
+
+    fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let row = encode_row::<T>(key, value);
+        let mut tables = lock(self.tables);
+        let table = tables.table_mut(T::NAME);
+        if let Some(existing) = table.first_of(&row.0) {
+            self.position = Position::At(existing.clone());
+            return Err(Error::Write(KEY_EXIST))
+        }
+        table.rows.insert(row.clone());
+        self.position = Position::At(row);
+        Ok(())
+    }
+
+    /// Appends the data to the end of the table. Consequently, the append operation
+    /// will fail if the inserted key is less than the last table key
+    fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let row = encode_row::<T>(key, value);
+        let mut tables = lock(self.tables);
+        let table = tables.table_mut(T::NAME);
+        if let Some(last) = table.rows.iter().next_back() {
+            // values of the last key can be appended in any order to `DUPSORT` tables
+            let is_out_of_order = if table.dupsort { row.0 < last.0 } else { row.0 <= last.0 };
+            if is_out_of_order {
+                self.position = Position::At(last.clone());
+                return Err(Error::Write(KEY_MISMATCH))
+            }
+        }
+        table.rows.insert(row.clone());
+        self.position = Position::At(row);
+        Ok(())
+    }
+
+    fn delete_current(&mut self) -> Result<(), Error> {
+        let Position::At(row) = &self.position else { return Err(Error::Delete(NOT_FOUND)) };
+        lock(self.tables).table_mut(T::NAME).rows.remove(row);
+        self.position = Position::Deleted(row.clone());
+        Ok(())
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRW<'tx, T> for Cursor<'tx, RW, T> {
+    fn delete_current_duplicates(&mut self) -> Result<(), Error> {
+        let Position::At(row) = &self.position else { return Err(Error::Delete(NOT_FOUND)) };
+        let last = lock(self.tables).table_mut(T::NAME).remove_key(&row.0);
+        self.position = Position::Deleted(last.unwrap_or_else(|| row.clone()));
+        Ok(())
+    }
+
+    fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let row = encode_row::<T>(key, value);
+        let mut tables = lock(self.tables);
+        let table = tables.table_mut(T::NAME);
+        if let Some(last) = table.rows.iter().next_back().filter(|last| row <= **last) {
+            self.position = Position::At(last.clone());
+            return Err(Error::Write(KEY_MISMATCH))
+        }
+        table.rows.insert(row.clone());
+        self.position = Position::At(row);
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+//! In-memory database backed by ordered maps.
+//!
+//! Rows are ordered by their encoded key and compressed value, like in MDBX, and failures return
+//! the same error codes as MDBX, so both implementations can be used interchangeably.
+
+use crate::{
+    database::{Database, DatabaseGAT},
+    tables::Tables,
+    Error,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Condvar, Mutex, MutexGuard, RwLock},
+};
+
+mod cursor;
+mod tx;
+
+pub use cursor::Cursor;
+pub use tx::Tx;
+
+/// `MDBX_NOTFOUND`: the key or value was not found.
+pub(crate) const NOT_FOUND: i32 = -30798;
+/// `MDBX_KEYEXIST`: the key already exists.
+pub(crate) const KEY_EXIST: i32 = -30799;
+/// `MDBX_EKEYMISMATCH`: the key or value is out of order for an append.
+pub(crate) const KEY_MISMATCH: i32 = -30418;
+
+/// Kind of a transaction, read only or read write.
+pub trait TransactionKind: Send + Sync + 'static {}
+
+/// Marker of read only transactions.
+#[derive(Debug)]
+pub struct RO;
+
+/// Marker of read write transactions.
+#[derive(Debug)]
+pub struct RW;
+
+impl TransactionKind for RO {}
+impl TransactionKind for RW {}
+
+/// A raw `(key, value)` row.
+pub(crate) type Row = (Vec<u8>, Vec<u8>);
+
+/// Rows of a table.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemTable {
+    /// Whether the table allows multiple values per key.
+    pub(crate) dupsort: bool,
+    /// Rows ordered by key and value.
+    pub(crate) rows: BTreeSet<Row>,
+}
+
+impl MemTable {
+    /// Creates an empty table, looking up its type in the [`Tables`] registry.
+    fn new(name: &str) -> Self {
+        let dupsort = name.parse::<Tables>().map_or(false, |table| table.is_dupsort());
+        Self { dupsort, rows: BTreeSet::new() }
+    }
+
+    /// Returns the first row of the key.
+    pub(crate) fn first_of(&self, key: &[u8]) -> Option<&Row> {
+        self.rows.range((key.to_vec(), Vec::new())..).next().filter(|(row_key, _)| row_key == key)
+    }
+
+    /// Inserts the row, replacing the value of the key unless the table is `DUPSORT`.
+    pub(crate) fn upsert(&mut self, row: Row) {
+        if !self.dupsort {
+            self.remove_key(&row.0);
+        }
+        self.rows.insert(row);
+    }
+
+    /// Removes all rows of the key, returning the last removed row.
+    pub(crate) fn remove_key(&mut self, key: &[u8]) -> Option<Row> {
+        let mut removed = self
+            .rows
+            .range((key.to_vec(), Vec::new())..)
+            .take_while(|(row_key, _)| row_key == key)
+            .cloned()
+            .collect::<Vec<_>>();
+        for row in &removed {
+            self.rows.remove(row);
+        }
+        removed.pop()
+    }
+}
+
+/// The tables of a transaction.
+///
+/// Tables are shared with the snapshot the transaction was started from and copied on the first
+/// write.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemTables(HashMap<&'static str, Arc<MemTable>>);
+
+impl MemTables {
+    /// Returns the table, or an empty table if it does not exist.
+    pub(crate) fn table(&self, name: &'static str) -> &MemTable {
+        static EMPTY: MemTable = MemTable { dupsort: false, rows: BTreeSet::new() };
+        self.0.get(name).map_or(&EMPTY, |table| table.as_ref())
+    }
+
+    /// Returns the table for writing, creating it if it does not exist.
+    pub(crate) fn table_mut(&mut self, name: &'static str) -> &mut MemTable {
+        Arc::make_mut(self.0.entry(name).or_insert_with(|| Arc::new(MemTable::new(name))))
+    }
+}
+
+/// An in-memory database, for tests and ephemeral nodes.
+///
+/// Read only transactions see the snapshot of the last commit when they were opened. Like in
+/// MDBX, there is at most one write transaction at a time, opening another one blocks until the
+/// open one is committed or dropped.
+#[derive(Debug)]
+pub struct MemDatabase {
+    /// Tables of the last committed write transaction.
+    snapshot: RwLock<MemTables>,
+    /// Whether a write transaction is open.
+    writer: Mutex<bool>,
+    /// Notified when the write transaction is closed.
+    writer_closed: Condvar,
+}
+
+// === impl MemDatabase ===
+
+impl MemDatabase {
+    /// Creates an empty database with all tables.
+    pub fn new() -> Self {
+        let tables = Tables::ALL
+            .iter()
+            .map(|table| (table.name(), Arc::new(MemTable::new(table.name()))))
+            .collect();
+        Self {
+            snapshot: RwLock::new(MemTables(tables)),
+            writer: Mutex::new(false),
+            writer_closed: Condvar::new(),
+        }
+    }
+
+    /// Returns the tables of the last commit.
+    fn snapshot(&self) -> MemTables {
+        self.snapshot.read().expect("snapshot lock is poisoned").clone()
+    }
+
+    /// Waits until no other write transaction is open and marks a write transaction as open.
+    fn lock_writer(&self) -> WriteLock<'_> {
+        let mut writer = self.writer.lock().expect("writer lock is poisoned");
+        while *writer {
+            writer = self.writer_closed.wait(writer).expect("writer lock is poisoned");
+        }
+        *writer = true;
+        WriteLock { db: self }
+    }
+}
+
+impl Default for MemDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DatabaseGAT<'a> for MemDatabase {
+    type TX = Tx<'a, RO>;
+    type TXMut = Tx<'a, RW>;
+}
+
+impl Database for MemDatabase {
+    fn tx(&self) -> Result<<Self as DatabaseGAT<'_>>::TX, Error> {
+        Ok(Tx::new(self.snapshot(), None))
+    }
+
+    fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
+        let lock = self.lock_writer();
+        Ok(Tx::new(self.snapshot(), Some(lock)))
+    }
+}
+
+/// Marks the write transaction of the database as open until it is dropped.
+#[derive(Debug)]
+pub(crate) struct WriteLock<'db> {
+    db: &'db MemDatabase,
+}
+
+impl WriteLock<'_> {
+    /// Replaces the snapshot of the database with the tables of the write transaction.
+    pub(crate) fn commit(&self, tables: MemTables) {
+        *self.db.snapshot.write().expect("snapshot lock is poisoned") = tables;
+    }
+}
+
+impl Drop for WriteLock<'_> {
+    fn drop(&mut self) {
+        *self.db.writer.lock().expect("writer lock is poisoned") = false;
+        self.db.writer_closed.notify_one();
+    }
+}
+
+/// Locks the tables of a transaction.
+pub(crate) fn lock(tables: &Mutex<MemTables>) -> MutexGuard<'_, MemTables> {
+    tables.lock().expect("transaction lock is poisoned")
+}
+
+/// Collection of in-memory database test utilities
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils {
+    use super::MemDatabase;
+    use std::sync::Arc;
+
+    /// Create in-memory database for testing
+    pub fn create_test_mem_db() -> Arc<MemDatabase> {
+        Arc::new(MemDatabase::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tables::CanonicalHeaders,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::H256;
+
+    db_test_suite!(super::test_utils::create_test_mem_db());
+
+    #[test]
+    fn snapshot_isolation() {
+        let db = MemDatabase::new();
+        db.update(|tx| tx.put::<CanonicalHeaders>(1, H256::zero())).unwrap().unwrap();
+
+        let old = db.tx().unwrap();
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(2, H256::zero()).unwrap();
+        tx.delete::<CanonicalHeaders>(1, None).unwrap();
+        assert_eq!(tx.get::<CanonicalHeaders>(2), Ok(Some(H256::zero())));
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(2), Ok(None));
+        tx.commit().unwrap();
+
+        // open transactions keep reading their snapshot
+        assert_eq!(old.get::<CanonicalHeaders>(1), Ok(Some(H256::zero())));
+        assert_eq!(old.get::<CanonicalHeaders>(2), Ok(None));
+        let new = db.tx().unwrap();
+        assert_eq!(new.get::<CanonicalHeaders>(1), Ok(None));
+        assert_eq!(new.get::<CanonicalHeaders>(2), Ok(Some(H256::zero())));
+
+        // dropped write transactions are discarded
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(3, H256::zero()).unwrap();
+        drop(tx);
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(3), Ok(None));
+    }
+
+    #[test]
+    fn single_writer() {
+        let db = Arc::new(MemDatabase::new());
+        let tx = db.tx_mut().unwrap();
+
+        let handle = std::thread::spawn({
+            let db = db.clone();
+            // blocks until the open write transaction is committed
+            move || db.update(|tx| tx.get::<CanonicalHeaders>(1)).unwrap()
+        });
+
+        tx.put::<CanonicalHeaders>(1, H256::zero()).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(handle.join().unwrap(), Ok(Some(H256::zero())));
+    }
+}
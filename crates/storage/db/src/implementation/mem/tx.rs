@@ -0,0 +1,112 @@
+//! Transactions of the in-memory database.
+
+use super::{cursor::Cursor, lock, MemTables, TransactionKind, WriteLock, RW};
+use crate::{
+    table::{Compress, DupSort, Encode, Table, TableImporter},
+    tables::utils::decode_one,
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    Error,
+};
+use std::{borrow::Cow, marker::PhantomData, sync::Mutex};
+
+/// Transaction of the in-memory database.
+#[derive(Debug)]
+pub struct Tx<'db, K: TransactionKind> {
+    /// Tables of the transaction.
+    tables: Mutex<MemTables>,
+    /// Write lock of the database, only set for write transactions.
+    lock: Option<WriteLock<'db>>,
+    _kind: PhantomData<K>,
+}
+
+impl<'db, K: TransactionKind> Tx<'db, K> {
+    /// Creates a new transaction on the tables.
+    pub(crate) fn new(tables: MemTables, lock: Option<WriteLock<'db>>) -> Self {
+        Self { tables: Mutex::new(tables), lock, _kind: PhantomData }
+    }
+
+    /// Creates a cursor over the table.
+    pub fn new_cursor<T: Table>(&self) -> Result<Cursor<'_, K, T>, Error> {
+        Ok(Cursor::new(&self.tables))
+    }
+}
+
+impl<'a, K: TransactionKind> DbTxGAT<'a> for Tx<'_, K> {
+    type Cursor<T: Table> = Cursor<'a, K, T>;
+    type DupCursor<T: DupSort> = Cursor<'a, K, T>;
+}
+
+impl<'a, K: TransactionKind> DbTxMutGAT<'a> for Tx<'_, K> {
+    type CursorMut<T: Table> = Cursor<'a, RW, T>;
+    type DupCursorMut<T: DupSort> = Cursor<'a, RW, T>;
+}
+
+impl<'a> TableImporter<'a> for Tx<'_, RW> {}
+
+impl<'tx, K: TransactionKind> DbTx<'tx> for Tx<'tx, K> {
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, Error> {
+        lock(&self.tables)
+            .table(T::NAME)
+            .first_of(key.encode().as_ref())
+            .map(|(_, value)| decode_one::<T>(Cow::Borrowed(value)))
+            .transpose()
+    }
+
+    fn commit(self) -> Result<bool, Error> {
+        if let Some(lock) = self.lock {
+            lock.commit(self.tables.into_inner().expect("transaction lock is poisoned"));
+        }
+        Ok(false)
+    }
+
+    fn drop(self) {}
+
+    fn cursor_read<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_read<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn entries<T: Table>(&self) -> Result<usize, Error> {
+        Ok(lock(&self.tables).table(T::NAME).rows.len())
+    }
+}
+
+impl DbTxMut<'_> for Tx<'_, RW> {
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let row = (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec());
+        lock(&self.tables).table_mut(T::NAME).upsert(row);
+        Ok(())
+    }
+
+    fn delete<T: Table>(&self, key: T::Key, value: Option<T::Value>) -> Result<bool, Error> {
+        let key = key.encode();
+        let mut tables = lock(&self.tables);
+        let table = tables.table_mut(T::NAME);
+
+        // values only select the row to delete in `DUPSORT` tables
+        match value.filter(|_| table.dupsort) {
+            Some(value) => {
+                Ok(table.rows.remove(&(key.as_ref().to_vec(), value.compress().as_ref().to_vec())))
+            }
+            None => Ok(table.remove_key(key.as_ref()).is_some()),
+        }
+    }
+
+    fn clear<T: Table>(&self) -> Result<(), Error> {
+        lock(&self.tables).table_mut(T::NAME).rows.clear();
+        Ok(())
+    }
+
+    fn cursor_write<T: Table>(&self) -> Result<<Self as DbTxMutGAT<'_>>::CursorMut<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_write<T: DupSort>(
+        &self,
+    ) -> Result<<Self as DbTxMutGAT<'_>>::DupCursorMut<T>, Error> {
+        self.new_cursor()
+    }
+}
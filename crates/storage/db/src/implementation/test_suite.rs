@@ -0,0 +1,657 @@
+//! Test suite shared by all database implementations.
+
+/// Generates the tests of the database and cursor semantics for a database implementation.
+///
+/// The argument creates a new, empty database with all tables.
+macro_rules! db_test_suite {
+    ($create_db:expr) => {
+        use reth_primitives::{Address, Header, IntegerList, StorageEntry, H256, U256};
+        use std::str::FromStr;
+        use $crate::{
+            cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, ReverseWalker, Walker},
+            database::Database,
+            models::{AccountBeforeTx, ShardedKey},
+            tables::{AccountHistory, CanonicalHeaders, Headers, PlainStorageState},
+            transaction::{DbTx, DbTxMut},
+            AccountChangeSet, Error,
+        };
+
+        const ERROR_PUT: &str = "Not able to insert value into table.";
+        const ERROR_APPEND: &str = "Not able to append the value to the table.";
+        const ERROR_GET: &str = "Not able to get value from table.";
+        const ERROR_COMMIT: &str = "Not able to commit transaction.";
+        const ERROR_RETURN_VALUE: &str = "Mismatching result.";
+        const ERROR_INIT_TX: &str = "Failed to create a transaction.";
+        const ERROR_ETH_ADDRESS: &str = "Invalid address.";
+
+        #[test]
+        fn db_manual_put_get() {
+            let env = $create_db;
+
+            let value = Header::default();
+            let key = 1u64;
+
+            // PUT
+            let tx = env.tx_mut().expect(ERROR_INIT_TX);
+            tx.put::<Headers>(key, value.clone()).expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            // GET
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            let result = tx.get::<Headers>(key).expect(ERROR_GET);
+            assert!(result.expect(ERROR_RETURN_VALUE) == value);
+            tx.commit().expect(ERROR_COMMIT);
+        }
+
+        #[test]
+        fn db_entries() {
+            let env = $create_db;
+
+            let tx = env.tx_mut().expect(ERROR_INIT_TX);
+            assert_eq!(tx.entries::<CanonicalHeaders>(), Ok(0));
+            for key in 0..3 {
+                tx.put::<CanonicalHeaders>(key, H256::zero()).expect(ERROR_PUT);
+            }
+            tx.put::<CanonicalHeaders>(1, H256::zero()).expect(ERROR_PUT);
+            assert_eq!(tx.entries::<CanonicalHeaders>(), Ok(3));
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            assert_eq!(tx.entries::<CanonicalHeaders>(), Ok(3));
+        }
+
+        #[test]
+        fn db_cursor_walk() {
+            let env = $create_db;
+
+            let value = Header::default();
+            let key = 1u64;
+
+            // PUT
+            let tx = env.tx_mut().expect(ERROR_INIT_TX);
+            tx.put::<Headers>(key, value.clone()).expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            // Cursor
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<Headers>().unwrap();
+
+            let first = cursor.first().unwrap();
+            assert!(first.is_some(), "First should be our put");
+
+            // Walk
+            let walk = cursor.walk(Some(key)).unwrap();
+            let first = walk.into_iter().next().unwrap().unwrap();
+            assert_eq!(first.1, value, "First next should be put value");
+        }
+
+        #[test]
+        fn db_cursor_walk_range() {
+            let db = $create_db;
+
+            // PUT (0, 0), (1, 0), (2, 0), (3, 0)
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 2, 3]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+            // [1, 3)
+            let mut walker = cursor.walk_range(1..3).unwrap();
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((2, H256::zero()))));
+            assert_eq!(walker.next(), None);
+            // next() returns None after walker is done
+            assert_eq!(walker.next(), None);
+
+            // [1, 2]
+            let mut walker = cursor.walk_range(1..=2).unwrap();
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((2, H256::zero()))));
+            // next() returns None after walker is done
+            assert_eq!(walker.next(), None);
+
+            // [1, ∞)
+            let mut walker = cursor.walk_range(1..).unwrap();
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((2, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+            // next() returns None after walker is done
+            assert_eq!(walker.next(), None);
+
+            // [2, 4)
+            let mut walker = cursor.walk_range(2..4).unwrap();
+            assert_eq!(walker.next(), Some(Ok((2, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(walker.next(), None);
+            // next() returns None after walker is done
+            assert_eq!(walker.next(), None);
+
+            // (∞, 3)
+            let mut walker = cursor.walk_range(..3).unwrap();
+            assert_eq!(walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((2, H256::zero()))));
+            // next() returns None after walker is done
+            assert_eq!(walker.next(), None);
+
+            // (∞, ∞)
+            let mut walker = cursor.walk_range(..).unwrap();
+            assert_eq!(walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((2, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+            // next() returns None after walker is done
+            assert_eq!(walker.next(), None);
+        }
+
+        #[allow(clippy::reversed_empty_ranges)]
+        #[test]
+        fn db_cursor_walk_range_invalid() {
+            let db = $create_db;
+
+            // PUT (0, 0), (1, 0), (2, 0), (3, 0)
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 2, 3]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+            // start bound greater than end bound
+            let res = cursor.walk_range(3..1);
+            assert!(matches!(res, Err(Error::Read(2))));
+
+            // start bound greater than end bound
+            let res = cursor.walk_range(15..=2);
+            assert!(matches!(res, Err(Error::Read(2))));
+        }
+
+        #[test]
+        fn db_walker() {
+            let db = $create_db;
+
+            // PUT (0, 0), (1, 0), (3, 0)
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 3]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+            let mut walker = Walker::new(&mut cursor, None);
+
+            assert_eq!(walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(walker.next(), None);
+
+            // transform to ReverseWalker
+            let mut reverse_walker = walker.rev();
+            assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(reverse_walker.next(), None);
+        }
+
+        #[test]
+        fn db_reverse_walker() {
+            let db = $create_db;
+
+            // PUT (0, 0), (1, 0), (3, 0)
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 3]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+            let mut reverse_walker = ReverseWalker::new(&mut cursor, None);
+
+            assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(reverse_walker.next(), None);
+
+            // transform to Walker
+            let mut walker = reverse_walker.forward();
+            assert_eq!(walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(walker.next(), None);
+        }
+
+        #[test]
+        fn db_walk_back() {
+            let db = $create_db;
+
+            // PUT (0, 0), (1, 0), (3, 0)
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 3]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+
+            let mut reverse_walker = cursor.walk_back(Some(1)).unwrap();
+            assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(reverse_walker.next(), None);
+
+            let mut reverse_walker = cursor.walk_back(Some(2)).unwrap();
+            assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(reverse_walker.next(), None);
+
+            let mut reverse_walker = cursor.walk_back(Some(4)).unwrap();
+            assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(reverse_walker.next(), None);
+
+            let mut reverse_walker = cursor.walk_back(None).unwrap();
+            assert_eq!(reverse_walker.next(), Some(Ok((3, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((1, H256::zero()))));
+            assert_eq!(reverse_walker.next(), Some(Ok((0, H256::zero()))));
+            assert_eq!(reverse_walker.next(), None);
+        }
+
+        #[test]
+        fn db_cursor_seek_exact_or_previous_key() {
+            let db = $create_db;
+
+            // PUT
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 3]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            // Cursor
+            let missing_key = 2;
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+            assert_eq!(cursor.current(), Ok(None));
+
+            // Seek exact
+            let exact = cursor.seek_exact(missing_key).unwrap();
+            assert_eq!(exact, None);
+            assert_eq!(cursor.current(), Ok(Some((missing_key + 1, H256::zero()))));
+            assert_eq!(cursor.prev(), Ok(Some((missing_key - 1, H256::zero()))));
+            assert_eq!(cursor.prev(), Ok(Some((missing_key - 2, H256::zero()))));
+        }
+
+        #[test]
+        fn db_cursor_insert() {
+            let db = $create_db;
+
+            // PUT
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 3, 4, 5]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let key_to_insert = 2;
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+
+            // INSERT
+            assert_eq!(cursor.insert(key_to_insert, H256::zero()), Ok(()));
+            assert_eq!(cursor.current(), Ok(Some((key_to_insert, H256::zero()))));
+
+            // INSERT (failure)
+            assert_eq!(cursor.insert(key_to_insert, H256::zero()), Err(Error::Write(-30799)));
+            assert_eq!(cursor.current(), Ok(Some((key_to_insert, H256::zero()))));
+
+            tx.commit().expect(ERROR_COMMIT);
+
+            // Confirm the result
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+            let res = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+            assert_eq!(res, vec![0, 1, 2, 3, 4, 5]);
+            tx.commit().expect(ERROR_COMMIT);
+        }
+
+        #[test]
+        fn db_cursor_insert_wherever_cursor_is() {
+            let db = $create_db;
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+
+            // PUT
+            vec![0, 1, 3, 5, 7, 9]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+
+            // INSERT (cursor starts at last)
+            cursor.last().unwrap();
+            assert_eq!(cursor.current(), Ok(Some((9, H256::zero()))));
+
+            for pos in (2..=8).step_by(2) {
+                assert_eq!(cursor.insert(pos, H256::zero()), Ok(()));
+                assert_eq!(cursor.current(), Ok(Some((pos, H256::zero()))));
+            }
+            tx.commit().expect(ERROR_COMMIT);
+
+            // Confirm the result
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+            let res = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+            assert_eq!(res, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+            tx.commit().expect(ERROR_COMMIT);
+        }
+
+        #[test]
+        fn db_cursor_append() {
+            let db = $create_db;
+
+            // PUT
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 2, 3, 4]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            // APPEND
+            let key_to_append = 5;
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+            assert_eq!(cursor.append(key_to_append, H256::zero()), Ok(()));
+            tx.commit().expect(ERROR_COMMIT);
+
+            // Confirm the result
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+            let res = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+            assert_eq!(res, vec![0, 1, 2, 3, 4, 5]);
+            tx.commit().expect(ERROR_COMMIT);
+        }
+
+        #[test]
+        fn db_cursor_append_failure() {
+            let db = $create_db;
+
+            // PUT
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            vec![0, 1, 3, 4, 5]
+                .into_iter()
+                .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+                .expect(ERROR_PUT);
+            tx.commit().expect(ERROR_COMMIT);
+
+            // APPEND
+            let key_to_append = 2;
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+            assert_eq!(cursor.append(key_to_append, H256::zero()), Err(Error::Write(-30418)));
+            assert_eq!(cursor.current(), Ok(Some((5, H256::zero())))); // the end of table
+            tx.commit().expect(ERROR_COMMIT);
+
+            // Confirm the result
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+            let res = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+            assert_eq!(res, vec![0, 1, 3, 4, 5]);
+            tx.commit().expect(ERROR_COMMIT);
+        }
+
+        #[test]
+        fn db_cursor_dupsort_append() {
+            let db = $create_db;
+
+            let transition_id = 2;
+
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_write::<AccountChangeSet>().unwrap();
+            vec![0, 1, 3, 4, 5]
+                .into_iter()
+                .try_for_each(|val| {
+                    cursor.append(
+                        transition_id,
+                        AccountBeforeTx { address: Address::from_low_u64_be(val), info: None },
+                    )
+                })
+                .expect(ERROR_APPEND);
+            tx.commit().expect(ERROR_COMMIT);
+
+            // APPEND DUP & APPEND
+            let subkey_to_append = 2;
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_write::<AccountChangeSet>().unwrap();
+            assert_eq!(
+                cursor.append_dup(
+                    transition_id,
+                    AccountBeforeTx {
+                        address: Address::from_low_u64_be(subkey_to_append),
+                        info: None
+                    }
+                ),
+                Err(Error::Write(-30418))
+            );
+            assert_eq!(
+                cursor.append(
+                    transition_id - 1,
+                    AccountBeforeTx {
+                        address: Address::from_low_u64_be(subkey_to_append),
+                        info: None
+                    }
+                ),
+                Err(Error::Write(-30418))
+            );
+            assert_eq!(
+                cursor.append(
+                    transition_id,
+                    AccountBeforeTx {
+                        address: Address::from_low_u64_be(subkey_to_append),
+                        info: None
+                    }
+                ),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn db_dup_sort() {
+            let env = $create_db;
+            let key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047")
+                .expect(ERROR_ETH_ADDRESS);
+
+            // PUT (0,0)
+            let value00 = StorageEntry::default();
+            env.update(|tx| tx.put::<PlainStorageState>(key, value00).expect(ERROR_PUT)).unwrap();
+
+            // PUT (2,2)
+            let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
+            env.update(|tx| tx.put::<PlainStorageState>(key, value22).expect(ERROR_PUT)).unwrap();
+
+            // PUT (1,1)
+            let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
+            env.update(|tx| tx.put::<PlainStorageState>(key, value11).expect(ERROR_PUT)).unwrap();
+
+            // Iterate with cursor
+            {
+                let tx = env.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+
+                // Notice that value11 and value22 have been ordered in the DB.
+                assert!(Some(value00) == cursor.next_dup_val().unwrap());
+                assert!(Some(value11) == cursor.next_dup_val().unwrap());
+                assert!(Some(value22) == cursor.next_dup_val().unwrap());
+            }
+
+            // Seek value with exact subkey
+            {
+                let tx = env.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+                let mut walker =
+                    cursor.walk_dup(Some(key), Some(H256::from_low_u64_be(1))).unwrap();
+                assert_eq!(
+                    (key, value11),
+                    walker
+                        .next()
+                        .expect("element should exist.")
+                        .expect("should be able to retrieve it.")
+                );
+            }
+        }
+
+        #[test]
+        fn db_iterate_over_all_dup_values() {
+            let env = $create_db;
+            let key1 = Address::from_str("0x1111111111111111111111111111111111111111")
+                .expect(ERROR_ETH_ADDRESS);
+            let key2 = Address::from_str("0x2222222222222222222222222222222222222222")
+                .expect(ERROR_ETH_ADDRESS);
+
+            // PUT key1 (0,0)
+            let value00 = StorageEntry::default();
+            env.update(|tx| tx.put::<PlainStorageState>(key1, value00).expect(ERROR_PUT)).unwrap();
+
+            // PUT key1 (1,1)
+            let value11 = StorageEntry { key: H256::from_low_u64_be(1), value: U256::from(1) };
+            env.update(|tx| tx.put::<PlainStorageState>(key1, value11).expect(ERROR_PUT)).unwrap();
+
+            // PUT key2 (2,2)
+            let value22 = StorageEntry { key: H256::from_low_u64_be(2), value: U256::from(2) };
+            env.update(|tx| tx.put::<PlainStorageState>(key2, value22).expect(ERROR_PUT)).unwrap();
+
+            // Iterate with walk_dup
+            {
+                let tx = env.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+                let mut walker = cursor.walk_dup(None, None).unwrap();
+
+                // Notice that value11 and value22 have been ordered in the DB.
+                assert_eq!(Some(Ok((key1, value00))), walker.next());
+                assert_eq!(Some(Ok((key1, value11))), walker.next());
+                // NOTE: Dup cursor does NOT iterates on all values but only on duplicated values of
+                // the same key. assert_eq!(Ok(Some(value22.clone())), walker.next());
+                assert_eq!(None, walker.next());
+            }
+
+            // Iterate by using `walk`
+            {
+                let tx = env.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+                let first = cursor.first().unwrap().unwrap();
+                let mut walker = cursor.walk(Some(first.0)).unwrap();
+                assert_eq!(Some(Ok((key1, value00))), walker.next());
+                assert_eq!(Some(Ok((key1, value11))), walker.next());
+                assert_eq!(Some(Ok((key2, value22))), walker.next());
+            }
+        }
+
+        #[test]
+        fn dup_value_with_same_subkey() {
+            let env = $create_db;
+            let key1 = Address::from_str("0x1111111111111111111111111111111111111111")
+                .expect(ERROR_ETH_ADDRESS);
+
+            // PUT key1 (0,1)
+            let value01 = StorageEntry { key: H256::from_low_u64_be(0), value: U256::from(1) };
+            env.update(|tx| tx.put::<PlainStorageState>(key1, value01).expect(ERROR_PUT)).unwrap();
+
+            // PUT key1 (0,0)
+            let value00 = StorageEntry::default();
+            env.update(|tx| tx.put::<PlainStorageState>(key1, value00).expect(ERROR_PUT)).unwrap();
+
+            // Iterate with walk
+            {
+                let tx = env.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+                let first = cursor.first().unwrap().unwrap();
+                let mut walker = cursor.walk(Some(first.0)).unwrap();
+
+                // NOTE: Both values are present
+                assert_eq!(Some(Ok((key1, value00))), walker.next());
+                assert_eq!(Some(Ok((key1, value01))), walker.next());
+                assert_eq!(None, walker.next());
+            }
+
+            // seek_by_key_subkey
+            {
+                let tx = env.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+
+                // NOTE: There are two values with same SubKey but only first one is shown
+                assert_eq!(Ok(Some(value00)), cursor.seek_by_key_subkey(key1, value00.key));
+            }
+        }
+
+        #[test]
+        fn db_sharded_key() {
+            let db = $create_db;
+            let real_key = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047").unwrap();
+
+            for i in 1..5 {
+                let key = ShardedKey::new(real_key, i * 100);
+                let list: IntegerList = vec![i * 100u64].into();
+
+                db.update(|tx| tx.put::<AccountHistory>(key.clone(), list.clone()).expect(""))
+                    .unwrap();
+            }
+
+            // Seek value with non existing key.
+            {
+                let tx = db.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_read::<AccountHistory>().unwrap();
+
+                // It will seek the one greater or equal to the query. Since we have `Address |
+                // 100`, `Address | 200` in the database and we're querying `Address | 150`
+                // it will return us `Address | 200`.
+                let mut walker = cursor.walk(Some(ShardedKey::new(real_key, 150))).unwrap();
+                let (key, list) = walker
+                    .next()
+                    .expect("element should exist.")
+                    .expect("should be able to retrieve it.");
+
+                assert_eq!(ShardedKey::new(real_key, 200), key);
+                let list200: IntegerList = vec![200u64].into();
+                assert_eq!(list200, list);
+            }
+            // Seek greatest index
+            {
+                let tx = db.tx().expect(ERROR_INIT_TX);
+                let mut cursor = tx.cursor_read::<AccountHistory>().unwrap();
+
+                // It will seek the MAX value of transition index and try to use prev to get first
+                // biggers.
+                let _unknown = cursor.seek_exact(ShardedKey::new(real_key, u64::MAX)).unwrap();
+                let (key, list) = cursor
+                    .prev()
+                    .expect("element should exist.")
+                    .expect("should be able to retrieve it.");
+
+                assert_eq!(ShardedKey::new(real_key, 400), key);
+                let list400: IntegerList = vec![400u64].into();
+                assert_eq!(list400, list);
+            }
+        }
+    };
+}
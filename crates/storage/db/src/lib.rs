@@ -13,6 +13,7 @@ pub mod abstraction;
 mod implementation;
 pub mod tables;
 mod utils;
+pub mod version;
 
 #[cfg(feature = "mdbx")]
 /// Bindings for [MDBX](https://libmdbx.dqdkfa.ru/).
@@ -21,6 +22,11 @@ pub mod mdbx {
     pub use reth_libmdbx::*;
 }
 
+/// In-memory database for tests and ephemeral nodes.
+pub mod mem {
+    pub use crate::implementation::mem::*;
+}
+
 pub use abstraction::*;
 pub use reth_interfaces::db::Error;
 pub use tables::*;
@@ -40,12 +40,14 @@ impl_compression_for_compact!(
     Receipt,
     TxType,
     StorageEntry,
-    StorageTrieEntry,
+    StorageBranchEntry,
     StoredBlockBody,
     StoredBlockOmmers,
     StoredBlockWithdrawals,
     Bytecode,
-    ProofCheckpoint
+    ProofCheckpoint,
+    StageCheckpoint,
+    PruneCheckpoint
 );
 impl_compression_for_compact!(AccountBeforeTx, TransactionSigned);
 impl_compression_for_compact!(CompactU256);
@@ -6,7 +6,7 @@ pub mod utils;
 
 /// Declaration of all Database tables.
 use crate::{
-    table::DupSort,
+    table::{DupSort, Table},
     tables::{
         codecs::CompactU256,
         models::{
@@ -18,12 +18,13 @@ use crate::{
     },
 };
 use reth_primitives::{
-    Account, Address, BlockHash, BlockNumber, Bytecode, Header, IntegerList, Receipt, StorageEntry,
-    StorageTrieEntry, TransactionSigned, TransitionId, TxHash, TxNumber, H256,
+    Account, Address, BlockHash, BlockNumber, Bytecode, Header, IntegerList, PruneCheckpoint,
+    PruneSegment, Receipt, StageCheckpoint, StorageBranchEntry, StorageEntry, StoredNibbles,
+    StoredNibblesSubKey, TransactionSigned, TransitionId, TxHash, TxNumber, H256,
 };
 
 /// Enum for the types of tables present in libmdbx.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TableType {
     /// key value table
     Table,
@@ -31,36 +32,23 @@ pub enum TableType {
     DupSort,
 }
 
-/// Default tables that should be present inside database.
-pub const TABLES: [(TableType, &str); 27] = [
-    (TableType::Table, CanonicalHeaders::const_name()),
-    (TableType::Table, HeaderTD::const_name()),
-    (TableType::Table, HeaderNumbers::const_name()),
-    (TableType::Table, Headers::const_name()),
-    (TableType::Table, BlockBodies::const_name()),
-    (TableType::Table, BlockOmmers::const_name()),
-    (TableType::Table, BlockWithdrawals::const_name()),
-    (TableType::Table, TransactionBlock::const_name()),
-    (TableType::Table, Transactions::const_name()),
-    (TableType::Table, TxHashNumber::const_name()),
-    (TableType::Table, Receipts::const_name()),
-    (TableType::Table, PlainAccountState::const_name()),
-    (TableType::DupSort, PlainStorageState::const_name()),
-    (TableType::Table, Bytecodes::const_name()),
-    (TableType::Table, BlockTransitionIndex::const_name()),
-    (TableType::Table, TxTransitionIndex::const_name()),
-    (TableType::Table, AccountHistory::const_name()),
-    (TableType::Table, StorageHistory::const_name()),
-    (TableType::DupSort, AccountChangeSet::const_name()),
-    (TableType::DupSort, StorageChangeSet::const_name()),
-    (TableType::Table, HashedAccount::const_name()),
-    (TableType::DupSort, HashedStorage::const_name()),
-    (TableType::Table, AccountsTrie::const_name()),
-    (TableType::DupSort, StoragesTrie::const_name()),
-    (TableType::Table, TxSenders::const_name()),
-    (TableType::Table, SyncStage::const_name()),
-    (TableType::Table, SyncStageProgress::const_name()),
-];
+/// Visitor over the concrete key and value types of a table that is selected at runtime.
+///
+/// See [`Tables::view`].
+pub trait TableViewer<R> {
+    /// The error type returned by the viewer.
+    type Error;
+
+    /// Operates on the table with its concrete types.
+    fn view<T: Table>(&self) -> Result<R, Self::Error>;
+
+    /// Operates on a `DUPSORT` table with its concrete types, including the subkey.
+    ///
+    /// Defaults to [`TableViewer::view`].
+    fn view_dupsort<T: DupSort>(&self) -> Result<R, Self::Error> {
+        self.view::<T>()
+    }
+}
 
 #[macro_export]
 /// Macro to declare all necessary tables.
@@ -93,8 +81,9 @@ macro_rules! table {
     };
 }
 
+/// Macro to declare a `DUPSORT` table, with the field of the value that holds its subkey.
 macro_rules! dupsort {
-    ($(#[$docs:meta])+ ( $table_name:ident ) $key:ty | [$subkey:ty] $value:ty) => {
+    ($(#[$docs:meta])+ ( $table_name:ident ) $key:ty | [$subkey_field:ident: $subkey:ty] $value:ty) => {
         table!(
             $(#[$docs])+
             ///
@@ -103,6 +92,100 @@ macro_rules! dupsort {
         );
         impl DupSort for $table_name {
             type SubKey = $subkey;
+
+            fn subkey(value: &Self::Value) -> Self::SubKey {
+                value.$subkey_field.clone()
+            }
+        }
+    };
+}
+
+/// Calls the [`TableViewer`] method matching the type of the table.
+macro_rules! view_table {
+    (table, $viewer:ident, $table:ident) => {
+        $viewer.view::<$table>()
+    };
+    (dupsort, $viewer:ident, $table:ident) => {
+        $viewer.view_dupsort::<$table>()
+    };
+}
+
+/// Returns the [`TableType`] of a table declared with [`table!`] or `dupsort!`.
+macro_rules! table_type {
+    (table) => {
+        TableType::Table
+    };
+    (dupsort) => {
+        TableType::DupSort
+    };
+}
+
+/// Declares all tables with [`table!`] or `dupsort!` and generates the [`Tables`] registry from
+/// the same list, so every table of the database is part of the registry.
+macro_rules! tables {
+    ($($kind:ident ( $(#[$docs:meta])+ ( $table:ident ) $($definition:tt)+ );)*) => {
+        $(
+            $kind!( $(#[$docs])+ ( $table ) $($definition)+ );
+        )*
+
+        /// Registry of all database tables, to select a table by its name at runtime.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Tables {
+            $(
+                #[doc = concat!("The [`", stringify!($table), "`] table.")]
+                $table,
+            )*
+        }
+
+        impl Tables {
+            /// All tables of the database.
+            pub const ALL: &'static [Tables] = &[$(Tables::$table,)*];
+
+            /// Returns the name of the table as it is present inside the database.
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Tables::$table => $table::const_name(),)*
+                }
+            }
+
+            /// Returns the type of the table.
+            pub const fn table_type(&self) -> TableType {
+                match self {
+                    $(Tables::$table => table_type!($kind),)*
+                }
+            }
+
+            /// Returns true if the table is a `DUPSORT` table.
+            pub const fn is_dupsort(&self) -> bool {
+                matches!(self.table_type(), TableType::DupSort)
+            }
+
+            /// Calls the viewer with the concrete types of the table.
+            pub fn view<T, R>(&self, viewer: &T) -> Result<R, T::Error>
+            where
+                T: TableViewer<R>,
+            {
+                match self {
+                    $(Tables::$table => view_table!($kind, viewer, $table),)*
+                }
+            }
+        }
+
+        impl std::fmt::Display for Tables {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl std::str::FromStr for Tables {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $(stringify!($table) => Ok(Tables::$table),)*
+                    _ => Err(format!("unknown table: {s}")),
+                }
+            }
         }
     };
 }
@@ -111,195 +194,199 @@ macro_rules! dupsort {
 //  TABLE DEFINITIONS
 //
 
-table!(
-    /// Stores the header hashes belonging to the canonical chain.
-    ( CanonicalHeaders ) BlockNumber | HeaderHash
-);
-
-table!(
-    /// Stores the total difficulty from a block header.
-    ( HeaderTD ) BlockNumber | CompactU256
-);
-
-table!(
-    /// Stores the block number corresponding to an header.
-    ( HeaderNumbers ) BlockHash | BlockNumber
-);
-
-table!(
-    /// Stores header bodies.
-    ( Headers ) BlockNumber | Header
-);
-
-table!(
-    /// Stores block bodies.
-    ( BlockBodies ) BlockNumber | StoredBlockBody
-);
-
-table!(
-    /// Stores the uncles/ommers of the block.
-    ( BlockOmmers ) BlockNumber | StoredBlockOmmers
-);
-
-table!(
-    /// Stores the block withdrawals.
-    ( BlockWithdrawals ) BlockNumber | StoredBlockWithdrawals
-);
-
-table!(
-    /// (Canonical only) Stores the transaction body for canonical transactions.
-    (  Transactions ) TxNumber | TransactionSigned
-);
-
-table!(
-    /// Stores the mapping of the transaction hash to the transaction number.
-    ( TxHashNumber ) TxHash | TxNumber
-);
-
-table!(
-    /// Stores the mapping of transaction number to the blocks number.
-    ///
-    /// The key is the highest transaction ID in the block.
-    ( TransactionBlock ) TxNumber | BlockNumber
-);
-
-table!(
-    /// (Canonical only) Stores transaction receipts.
-    ( Receipts ) TxNumber | Receipt
-);
-
-table!(
-    /// Stores all smart contract bytecodes.
-    /// There will be multiple accounts that have same bytecode
-    /// So we would need to introduce reference counter.
-    /// This will be small optimization on state.
-    ( Bytecodes ) H256 | Bytecode
-);
-
-table!(
-    /// Stores the mapping of block number to state transition id.
-    /// The block transition marks the final state at the end of the block.
-    /// Increment the transition if the block contains an addition block reward.
-    /// If the block does not have a reward and transaction, the transition will be the same as the
-    /// transition at the last transaction of this block.
-    ( BlockTransitionIndex ) BlockNumber | TransitionId
-);
-
-table!(
-    /// Stores the mapping of transaction number to state transition id.
-    ( TxTransitionIndex ) TxNumber | TransitionId
-);
-
-table!(
-    /// Stores the current state of an [`Account`].
-    ( PlainAccountState ) Address | Account
-);
-
-dupsort!(
-    /// Stores the current value of a storage key.
-    ( PlainStorageState ) Address | [H256] StorageEntry
-);
-
-table!(
-    /// Stores pointers to transition changeset with changes for each account key.
-    ///
-    /// Last shard key of the storage will contains `u64::MAX` `TransitionId`,
-    /// this would allows us small optimization on db access when change is in plain state.
-    ///
-    /// Imagine having shards as:
-    /// * `Address | 100`
-    /// * `Address | u64::MAX`
-    ///
-    /// What we need to find is id that is one greater than N. Db `seek` function allows us to fetch
-    /// the shard that equal or more than asked. For example:
-    /// * For N=50 we would get first shard.
-    /// * for N=150 we would get second shard.
-    /// * If max transition id is 200 and we ask for N=250 we would fetch last shard and
-    ///     know that needed entry is in `AccountPlainState`.
-    /// * If there were no shard we would get `None` entry or entry of different storage key.
-    ///
-    /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
-    ( AccountHistory ) ShardedKey<Address> | TransitionList
-);
+tables! {
+    table (
+        /// Stores the header hashes belonging to the canonical chain.
+        ( CanonicalHeaders ) BlockNumber | HeaderHash
+    );
+
+    table (
+        /// Stores the total difficulty from a block header.
+        ( HeaderTD ) BlockNumber | CompactU256
+    );
+
+    table (
+        /// Stores the block number corresponding to an header.
+        ( HeaderNumbers ) BlockHash | BlockNumber
+    );
+
+    table (
+        /// Stores header bodies.
+        ( Headers ) BlockNumber | Header
+    );
+
+    table (
+        /// Stores block bodies.
+        ( BlockBodies ) BlockNumber | StoredBlockBody
+    );
+
+    table (
+        /// Stores the uncles/ommers of the block.
+        ( BlockOmmers ) BlockNumber | StoredBlockOmmers
+    );
+
+    table (
+        /// Stores the block withdrawals.
+        ( BlockWithdrawals ) BlockNumber | StoredBlockWithdrawals
+    );
+
+    table (
+        /// (Canonical only) Stores the transaction body for canonical transactions.
+        (  Transactions ) TxNumber | TransactionSigned
+    );
+
+    table (
+        /// Stores the mapping of the transaction hash to the transaction number.
+        ( TxHashNumber ) TxHash | TxNumber
+    );
+
+    table (
+        /// Stores the mapping of transaction number to the blocks number.
+        ///
+        /// The key is the highest transaction ID in the block.
+        ( TransactionBlock ) TxNumber | BlockNumber
+    );
+
+    table (
+        /// (Canonical only) Stores transaction receipts.
+        ( Receipts ) TxNumber | Receipt
+    );
+
+    table (
+        /// Stores all smart contract bytecodes.
+        /// There will be multiple accounts that have same bytecode
+        /// So we would need to introduce reference counter.
+        /// This will be small optimization on state.
+        ( Bytecodes ) H256 | Bytecode
+    );
+
+    table (
+        /// Stores the mapping of block number to state transition id.
+        /// The block transition marks the final state at the end of the block.
+        /// Increment the transition if the block contains an addition block reward.
+        /// If the block does not have a reward and transaction, the transition will be the same as the
+        /// transition at the last transaction of this block.
+        ( BlockTransitionIndex ) BlockNumber | TransitionId
+    );
+
+    table (
+        /// Stores the mapping of transaction number to state transition id.
+        ( TxTransitionIndex ) TxNumber | TransitionId
+    );
+
+    table (
+        /// Stores the current state of an [`Account`].
+        ( PlainAccountState ) Address | Account
+    );
+
+    dupsort (
+        /// Stores the current value of a storage key.
+        ( PlainStorageState ) Address | [key: H256] StorageEntry
+    );
+
+    table (
+        /// Stores pointers to transition changeset with changes for each account key.
+        ///
+        /// Last shard key of the storage will contains `u64::MAX` `TransitionId`,
+        /// this would allows us small optimization on db access when change is in plain state.
+        ///
+        /// Imagine having shards as:
+        /// * `Address | 100`
+        /// * `Address | u64::MAX`
+        ///
+        /// What we need to find is id that is one greater than N. Db `seek` function allows us to fetch
+        /// the shard that equal or more than asked. For example:
+        /// * For N=50 we would get first shard.
+        /// * for N=150 we would get second shard.
+        /// * If max transition id is 200 and we ask for N=250 we would fetch last shard and
+        ///     know that needed entry is in `AccountPlainState`.
+        /// * If there were no shard we would get `None` entry or entry of different storage key.
+        ///
+        /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
+        ( AccountHistory ) ShardedKey<Address> | TransitionList
+    );
 
-table!(
-    /// Stores pointers to transition changeset with changes for each storage key.
-    ///
-    /// Last shard key of the storage will contains `u64::MAX` `TransitionId`,
-    /// this would allows us small optimization on db access when change is in plain state.
-    ///
-    /// Imagine having shards as:
-    /// * `Address | StorageKey | 100`
-    /// * `Address | StorageKey | u64::MAX`
-    ///
-    /// What we need to find is id that is one greater than N. Db `seek` function allows us to fetch
-    /// the shard that equal or more than asked. For example:
-    /// * For N=50 we would get first shard.
-    /// * for N=150 we would get second shard.
-    /// * If max transition id is 200 and we ask for N=250 we would fetch last shard and
-    ///     know that needed entry is in `StoragePlainState`.
-    /// * If there were no shard we would get `None` entry or entry of different storage key.
-    ///
-    /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
-    ( StorageHistory ) StorageShardedKey | TransitionList
-);
-
-dupsort!(
-    /// Stores the state of an account before a certain transaction changed it.
-    /// Change on state can be: account is created, selfdestructed, touched while empty
-    /// or changed (balance,nonce).
-    ( AccountChangeSet ) TransitionId | [Address] AccountBeforeTx
-);
-
-dupsort!(
-    /// Stores the state of a storage key before a certain transaction changed it.
-    /// If [`StorageEntry::value`] is zero, this means storage was not existing
-    /// and needs to be removed.
-    ( StorageChangeSet ) TransitionIdAddress | [H256] StorageEntry
-);
-
-table!(
-    /// Stores the current state of an [`Account`] indexed with `keccak256(Address)`
-    /// This table is in preparation for merkelization and calculation of state root.
-    /// We are saving whole account data as it is needed for partial update when
-    /// part of storage is changed. Benefit for merkelization is that hashed addresses are sorted.
-    ( HashedAccount ) H256 | Account
-);
-
-dupsort!(
-    /// Stores the current storage values indexed with `keccak256(Address)` and
-    /// hash of storage key `keccak256(key)`.
-    /// This table is in preparation for merkelization and calculation of state root.
-    /// Benefit for merklization is that hashed addresses/keys are sorted.
-    ( HashedStorage ) H256 | [H256] StorageEntry
-);
-
-table!(
-    /// Stores the current state's Merkle Patricia Tree.
-    ( AccountsTrie ) H256 | Vec<u8>
-);
-
-dupsort!(
-    /// Stores the Merkle Patricia Trees of each [`Account`]'s storage.
-    ( StoragesTrie ) H256 | [H256] StorageTrieEntry
-);
-
-table!(
-    /// Stores the transaction sender for each transaction.
-    /// It is needed to speed up execution stage and allows fetching signer without doing
-    /// transaction signed recovery
-    ( TxSenders ) TxNumber | Address
-);
-
-table!(
-    /// Stores the highest synced block number of each stage.
-    ( SyncStage ) StageId | BlockNumber
-);
-
-table!(
-    /// Stores arbitrary data to keep track of a stage first-sync progress.
-    ( SyncStageProgress ) StageId | Vec<u8>
-);
+    table (
+        /// Stores pointers to transition changeset with changes for each storage key.
+        ///
+        /// Last shard key of the storage will contains `u64::MAX` `TransitionId`,
+        /// this would allows us small optimization on db access when change is in plain state.
+        ///
+        /// Imagine having shards as:
+        /// * `Address | StorageKey | 100`
+        /// * `Address | StorageKey | u64::MAX`
+        ///
+        /// What we need to find is id that is one greater than N. Db `seek` function allows us to fetch
+        /// the shard that equal or more than asked. For example:
+        /// * For N=50 we would get first shard.
+        /// * for N=150 we would get second shard.
+        /// * If max transition id is 200 and we ask for N=250 we would fetch last shard and
+        ///     know that needed entry is in `StoragePlainState`.
+        /// * If there were no shard we would get `None` entry or entry of different storage key.
+        ///
+        /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
+        ( StorageHistory ) StorageShardedKey | TransitionList
+    );
+
+    dupsort (
+        /// Stores the state of an account before a certain transaction changed it.
+        /// Change on state can be: account is created, selfdestructed, touched while empty
+        /// or changed (balance,nonce).
+        ( AccountChangeSet ) TransitionId | [address: Address] AccountBeforeTx
+    );
+
+    dupsort (
+        /// Stores the state of a storage key before a certain transaction changed it.
+        /// If [`StorageEntry::value`] is zero, this means storage was not existing
+        /// and needs to be removed.
+        ( StorageChangeSet ) TransitionIdAddress | [key: H256] StorageEntry
+    );
+
+    table (
+        /// Stores the current state of an [`Account`] indexed with `keccak256(Address)`
+        /// This table is in preparation for merkelization and calculation of state root.
+        /// We are saving whole account data as it is needed for partial update when
+        /// part of storage is changed. Benefit for merkelization is that hashed addresses are sorted.
+        ( HashedAccount ) H256 | Account
+    );
+
+    dupsort (
+        /// Stores the current storage values indexed with `keccak256(Address)` and
+        /// hash of storage key `keccak256(key)`.
+        /// This table is in preparation for merkelization and calculation of state root.
+        /// Benefit for merklization is that hashed addresses/keys are sorted.
+        ( HashedStorage ) H256 | [key: H256] StorageEntry
+    );
+
+    table (
+        /// Stores the branch nodes of the state trie, indexed by their path in the trie.
+        ( AccountBranches ) StoredNibbles | Vec<u8>
+    );
+
+    dupsort (
+        /// Stores the branch nodes of each [`Account`]'s storage trie, indexed with
+        /// `keccak256(Address)` and their path in the storage trie.
+        ( StorageBranches ) H256 | [nibbles: StoredNibblesSubKey] StorageBranchEntry
+    );
+
+    table (
+        /// Stores the transaction sender for each transaction.
+        /// It is needed to speed up execution stage and allows fetching signer without doing
+        /// transaction signed recovery
+        ( TxSenders ) TxNumber | Address
+    );
+
+    table (
+        /// Stores the checkpoint of each stage: the highest synced block number and the
+        /// stage-specific progress within the next block range.
+        ( SyncStage ) StageId | StageCheckpoint
+    );
+
+    table (
+        /// Stores the highest pruned block number and transaction number of each prunable segment.
+        ( PruneCheckpoints ) PruneSegment | PruneCheckpoint
+    );
+}
 
 ///
 /// Alias Types
@@ -312,3 +399,33 @@ pub type StageId = String;
 //
 // TODO: Temporary types, until they're properly defined alongside with the Encode and Decode Trait
 //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NameViewer;
+
+    impl TableViewer<(&'static str, bool)> for NameViewer {
+        type Error = ();
+
+        fn view<T: Table>(&self) -> Result<(&'static str, bool), Self::Error> {
+            Ok((T::NAME, false))
+        }
+
+        fn view_dupsort<T: DupSort>(&self) -> Result<(&'static str, bool), Self::Error> {
+            Ok((T::NAME, true))
+        }
+    }
+
+    #[test]
+    fn tables_registry() {
+        for table in Tables::ALL {
+            assert_eq!(table.view(&NameViewer), Ok((table.name(), table.is_dupsort())));
+            assert_eq!(table.name().parse::<Tables>(), Ok(*table));
+        }
+        assert_eq!(Tables::PlainStorageState.table_type(), TableType::DupSort);
+        assert_eq!(Tables::Headers.table_type(), TableType::Table);
+        assert!("Unknown".parse::<Tables>().is_err());
+    }
+}
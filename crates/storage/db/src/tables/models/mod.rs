@@ -5,6 +5,7 @@ pub mod blocks;
 pub mod integer_list;
 pub mod sharded_key;
 pub mod storage_sharded_key;
+pub mod trie;
 
 pub use accounts::*;
 pub use blocks::*;
@@ -14,7 +15,7 @@ use crate::{
     table::{Decode, Encode},
     Error,
 };
-use reth_primitives::{bytes::Bytes, Address, H256};
+use reth_primitives::{bytes::Bytes, Address, PruneSegment, H256};
 
 /// Macro that implements [`Encode`] and [`Decode`] for uint types.
 macro_rules! impl_uints {
@@ -96,3 +97,20 @@ impl Decode for String {
         String::from_utf8(value.into().to_vec()).map_err(|_| Error::DecodeError)
     }
 }
+
+impl Encode for PruneSegment {
+    type Encoded = [u8; 1];
+    fn encode(self) -> Self::Encoded {
+        [self.into()]
+    }
+}
+
+impl Decode for PruneSegment {
+    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        let value: Bytes = value.into();
+        match value.as_ref() {
+            [segment] => PruneSegment::try_from(*segment).map_err(|_| Error::DecodeError),
+            _ => Err(Error::DecodeError),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     Error,
 };
 use reth_primitives::{bytes::Bytes, TransitionId};
+use serde::{Deserialize, Serialize};
 
 /// Number of indices in one shard.
 pub const NUM_OF_INDICES_IN_SHARD: usize = 100;
@@ -15,7 +16,7 @@ pub const NUM_OF_INDICES_IN_SHARD: usize = 100;
 /// `Address | 200` -> data is from transition 0 to 200.
 ///
 /// `Address | 300` -> data is from transaction 201 to 300.
-#[derive(Debug, Default, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct ShardedKey<T> {
     /// The key for this type.
     pub key: T,
@@ -0,0 +1,50 @@
+//! Trie related keys
+
+use crate::{
+    table::{Decode, Encode},
+    Error,
+};
+use reth_primitives::{bytes::Bytes, StoredNibbles, StoredNibblesSubKey};
+
+impl Encode for StoredNibbles {
+    type Encoded = Vec<u8>;
+
+    fn encode(self) -> Self::Encoded {
+        self.inner
+    }
+}
+
+impl Decode for StoredNibbles {
+    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        Ok(value.into().to_vec().into())
+    }
+}
+
+impl Encode for StoredNibblesSubKey {
+    type Encoded = [u8; StoredNibblesSubKey::ENCODED_LEN];
+
+    fn encode(self) -> Self::Encoded {
+        self.to_bytes()
+    }
+}
+
+impl Decode for StoredNibblesSubKey {
+    fn decode<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        StoredNibblesSubKey::from_bytes(&value.into()).ok_or(Error::DecodeError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_nibbles_subkey() {
+        let subkey = StoredNibblesSubKey::from(vec![1, 15, 0]);
+        let encoded = subkey.clone().encode();
+        assert_eq!(encoded[..3], [1, 15, 0]);
+        assert_eq!(encoded[StoredNibblesSubKey::ENCODED_LEN - 1], 3);
+        assert_eq!(StoredNibblesSubKey::decode(encoded.to_vec()).unwrap(), subkey);
+        assert!(StoredNibblesSubKey::decode(vec![0; 3]).is_err());
+    }
+}
@@ -12,15 +12,16 @@
 mod traits;
 pub use traits::{
     AccountProvider, BlockExecutor, BlockHashProvider, BlockIdProvider, BlockProvider,
-    EvmEnvProvider, ExecutorFactory, HeaderProvider, ReceiptProvider, StateProvider,
-    StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
+    ChainSpecProvider, EvmEnvProvider, ExecutorFactory, HeaderProvider, ReceiptProvider,
+    StateProvider, StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
 };
 
 /// Provider trait implementations.
 pub mod providers;
 pub use providers::{
     HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
-    LatestStateProviderRef, ShareableDatabase,
+    LatestStateProviderRef, PrefetchCache, PrefetchTarget, PrefetchedStateProvider,
+    ShareableDatabase, StatePrefetcher,
 };
 
 /// Helper type for loading Merkle Patricia Trees from the database
@@ -29,6 +30,9 @@ pub mod trie;
 /// Execution result
 pub mod post_state;
 
+/// Storage of finalized chain data in static files
+pub mod static_files;
+
 /// Helper types for interacting with the database
 mod transaction;
 pub use transaction::{Transaction, TransactionError};
@@ -7,7 +7,7 @@ use reth_db::{
     Error as DbError,
 };
 use reth_primitives::{
-    Account, Address, Bytecode, Receipt, StorageEntry, TransitionId, H256, U256,
+    Account, Address, Bytecode, Receipt, StorageEntry, TransitionId, TxNumber, H256, U256,
 };
 use std::collections::BTreeMap;
 
@@ -450,10 +450,14 @@ impl PostState {
     }
 
     /// Write the post state to the database.
+    ///
+    /// The receipts are written starting at `first_tx_number`, the number of the first transaction
+    /// of the first block in the post state, as recorded in [tables::BlockBodies].
     pub fn write_to_db<'a, TX: DbTxMut<'a> + DbTx<'a>>(
         mut self,
         tx: &TX,
         first_transition_id: TransitionId,
+        first_tx_number: TxNumber,
     ) -> Result<(), DbError> {
         // Collect and sort changesets by their key to improve write performance
         let mut changesets = std::mem::take(&mut self.changes);
@@ -576,10 +580,10 @@ impl PostState {
 
         // write receipts
         let mut receipts_cursor = tx.cursor_write::<tables::Receipts>()?;
-        let mut tx_num = receipts_cursor.last()?.map(|(tx_num, _)| tx_num).unwrap_or_default();
+        let mut tx_num = first_tx_number;
         for receipt in self.receipts.into_iter() {
+            receipts_cursor.append(tx_num, receipt)?;
             tx_num += 1;
-            receipts_cursor.append(tx_num, receipt)?
         }
 
         Ok(())
@@ -594,6 +598,7 @@ mod tests {
         mdbx::{test_utils, Env, EnvKind, WriteMap},
         transaction::DbTx,
     };
+    use reth_primitives::TxType;
     use std::sync::Arc;
 
     #[test]
@@ -638,7 +643,7 @@ mod tests {
         post_state.create_account(address_a, account_a);
         // 0x11.. is changed (balance + 1, nonce + 1)
         post_state.change_account(address_b, account_b, account_b_changed);
-        post_state.write_to_db(&tx, 0).expect("Could not write post state to DB");
+        post_state.write_to_db(&tx, 0, 0).expect("Could not write post state to DB");
 
         // Check plain state
         assert_eq!(
@@ -670,7 +675,7 @@ mod tests {
         let mut post_state = PostState::new();
         // 0x11.. is destroyed
         post_state.destroy_account(address_b, account_b_changed);
-        post_state.write_to_db(&tx, 1).expect("Could not write second post state to DB");
+        post_state.write_to_db(&tx, 1, 0).expect("Could not write second post state to DB");
 
         // Check new plain state for account B
         assert_eq!(
@@ -709,7 +714,7 @@ mod tests {
 
         post_state.change_storage(address_a, storage_a_changeset);
         post_state.change_storage(address_b, storage_b_changeset);
-        post_state.write_to_db(&tx, 0).expect("Could not write post state to DB");
+        post_state.write_to_db(&tx, 0, 0).expect("Could not write post state to DB");
 
         // Check plain storage state
         let mut storage_cursor = tx
@@ -792,7 +797,7 @@ mod tests {
         // Delete account A
         let mut post_state = PostState::new();
         post_state.destroy_account(address_a, Account::default());
-        post_state.write_to_db(&tx, 1).expect("Could not write post state to DB");
+        post_state.write_to_db(&tx, 1, 0).expect("Could not write post state to DB");
 
         assert_eq!(
             storage_cursor.seek_exact(address_a).unwrap(),
@@ -890,4 +895,28 @@ mod tests {
         assert_eq!(state.transitions_count(), 1);
         assert_eq!(reverted_changes.len(), 1);
     }
+
+    #[test]
+    fn write_to_db_receipts() {
+        let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
+        let tx = db.tx_mut().expect("Could not get database tx");
+
+        let receipt = |cumulative_gas_used| Receipt {
+            tx_type: TxType::Legacy,
+            success: true,
+            cumulative_gas_used,
+            state_root: None,
+            logs: vec![],
+        };
+
+        // the receipts table is empty, e.g. because all previous receipts were pruned
+        let mut post_state = PostState::new();
+        post_state.add_receipt(receipt(1));
+        post_state.add_receipt(receipt(2));
+        post_state.write_to_db(&tx, 0, 5).expect("Could not write post state to DB");
+
+        assert_eq!(tx.get::<tables::Receipts>(4).unwrap(), None);
+        assert_eq!(tx.get::<tables::Receipts>(5).unwrap(), Some(receipt(1)));
+        assert_eq!(tx.get::<tables::Receipts>(6).unwrap(), Some(receipt(2)));
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    BlockHashProvider, BlockIdProvider, BlockProvider, EvmEnvProvider, HeaderProvider,
-    ProviderError, StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
+    static_files::{StaticFileProvider, StaticFileTable},
+    BlockHashProvider, BlockIdProvider, BlockProvider, ChainSpecProvider, EvmEnvProvider,
+    HeaderProvider, ProviderError, StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{
     cursor::DbCursorRO,
@@ -10,15 +11,19 @@ use reth_db::{
 };
 use reth_interfaces::Result;
 use reth_primitives::{
-    Block, BlockHash, BlockId, BlockNumber, ChainInfo, ChainSpec, Hardfork, Head, Header, Receipt,
-    TransactionMeta, TransactionSigned, TxHash, TxNumber, Withdrawal, H256, U256,
+    Block, BlockHash, BlockId, BlockNumber, ChainInfo, ChainSpec, Hardfork, Head, Header,
+    PruneSegment, Receipt, TransactionMeta, TransactionSigned, TxHash, TxNumber, Withdrawal, H256,
+    U256,
 };
 use reth_revm_primitives::{
     config::revm_spec,
     env::{fill_block_env, fill_cfg_and_block_env, fill_cfg_env},
 };
 use revm_primitives::{BlockEnv, CfgEnv, SpecId};
-use std::{ops::RangeBounds, sync::Arc};
+use std::{
+    ops::{Bound, Range, RangeBounds},
+    sync::Arc,
+};
 
 mod state;
 use crate::traits::ReceiptProvider;
@@ -26,6 +31,7 @@ pub use state::{
     chain::ChainState,
     historical::{HistoricalStateProvider, HistoricalStateProviderRef},
     latest::{LatestStateProvider, LatestStateProviderRef},
+    prefetch::{PrefetchCache, PrefetchTarget, PrefetchedStateProvider, StatePrefetcher},
 };
 
 /// A common provider that fetches data from a database.
@@ -36,18 +42,78 @@ pub struct ShareableDatabase<DB> {
     db: DB,
     /// Chain spec
     chain_spec: Arc<ChainSpec>,
+    /// Static files that finalized headers, transactions and receipts are moved to.
+    static_files: Option<StaticFileProvider>,
 }
 
 impl<DB> ShareableDatabase<DB> {
     /// create new database provider
     pub fn new(db: DB, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { db, chain_spec }
+        Self { db, chain_spec, static_files: None }
+    }
+
+    /// Reads rows that were moved out of the database from the given static files.
+    pub fn with_static_files(mut self, static_files: StaticFileProvider) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Returns the static files of the provider, if any.
+    pub fn static_files(&self) -> Option<&StaticFileProvider> {
+        self.static_files.as_ref()
+    }
+
+    /// Returns the row of the table with the given key from the static files or the database.
+    ///
+    /// The database transaction has to be opened before calling this: rows are appended to the
+    /// static files before they are deleted from the database, so either of them contains the row.
+    fn get_row<'a, T: StaticFileTable, TX: DbTx<'a>>(
+        &self,
+        tx: &TX,
+        key: u64,
+    ) -> Result<Option<T::Value>> {
+        if let Some(static_files) = self.static_files.as_ref() {
+            if static_files.contains::<T>(key) {
+                return Ok(static_files.get::<T>(key)?)
+            }
+        }
+        Ok(tx.get::<T>(key)?)
+    }
+
+    /// Returns the rows of the table in the range from the static files and the database.
+    ///
+    /// See [Self::get_row] for the required order of opening the database transaction.
+    fn get_rows<'a, T: StaticFileTable, TX: DbTx<'a>>(
+        &self,
+        tx: &TX,
+        range: Range<u64>,
+    ) -> Result<Vec<T::Value>> {
+        let mut rows = Vec::new();
+        let mut start = range.start;
+        if let Some(static_files) = self.static_files.as_ref() {
+            // rows appended after this are still in the database
+            if let Some(highest) = static_files.highest(T::SEGMENT) {
+                rows = static_files.range::<T>(start..range.end.min(highest + 1))?;
+                start = start.max(highest + 1);
+            }
+        }
+        if start < range.end {
+            let mut cursor = tx.cursor_read::<T>()?;
+            for entry in cursor.walk_range(start..range.end)? {
+                rows.push(entry?.1);
+            }
+        }
+        Ok(rows)
     }
 }
 
 impl<DB: Clone> Clone for ShareableDatabase<DB> {
     fn clone(&self) -> Self {
-        Self { db: self.db.clone(), chain_spec: Arc::clone(&self.chain_spec) }
+        Self {
+            db: self.db.clone(),
+            chain_spec: Arc::clone(&self.chain_spec),
+            static_files: self.static_files.clone(),
+        }
     }
 }
 
@@ -55,7 +121,7 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
         self.db.view(|tx| {
             if let Some(num) = tx.get::<tables::HeaderNumbers>(*block_hash)? {
-                Ok(tx.get::<tables::Headers>(num)?)
+                self.get_row::<tables::Headers, _>(tx, num)
             } else {
                 Ok(None)
             }
@@ -63,7 +129,7 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     }
 
     fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
-        Ok(self.db.view(|tx| tx.get::<tables::Headers>(num))??)
+        self.db.view(|tx| self.get_row::<tables::Headers, _>(tx, num))?
     }
 
     fn header_td(&self, hash: &BlockHash) -> Result<Option<U256>> {
@@ -81,15 +147,8 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     }
 
     fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> Result<Vec<Header>> {
-        self.db
-            .view(|tx| {
-                let mut cursor = tx.cursor_read::<tables::Headers>()?;
-                cursor
-                    .walk_range(range)?
-                    .map(|result| result.map(|(_, header)| header).map_err(Into::into))
-                    .collect::<Result<Vec<_>>>()
-            })?
-            .map_err(Into::into)
+        let range = to_range(range);
+        self.db.view(|tx| self.get_rows::<tables::Headers, _>(tx, range))?
     }
 }
 
@@ -118,7 +177,8 @@ impl<DB: Database> BlockIdProvider for ShareableDatabase<DB> {
             .db
             .view(|tx| tx.get::<tables::SyncStage>("Finish".to_string()))?
             .map_err(Into::<reth_interfaces::db::Error>::into)?
-            .unwrap_or_default();
+            .unwrap_or_default()
+            .block_number;
         let best_hash = self.block_hash(best_number)?.unwrap_or_default();
         Ok(ChainInfo { best_hash, best_number, last_finalized: None, safe_finalized: None })
     }
@@ -166,19 +226,17 @@ impl<DB: Database> BlockProvider for ShareableDatabase<DB> {
 
 impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
     fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
-        self.db.view(|tx| tx.get::<tables::Transactions>(id))?.map_err(Into::into)
+        self.db.view(|tx| self.get_row::<tables::Transactions, _>(tx, id))?
     }
 
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
-        self.db
-            .view(|tx| {
-                if let Some(id) = tx.get::<tables::TxHashNumber>(hash)? {
-                    tx.get::<tables::Transactions>(id)
-                } else {
-                    Ok(None)
-                }
-            })?
-            .map_err(Into::into)
+        self.db.view(|tx| {
+            if let Some(id) = tx.get::<tables::TxHashNumber>(hash)? {
+                self.get_row::<tables::Transactions, _>(tx, id)
+            } else {
+                Ok(None)
+            }
+        })?
     }
 
     fn transaction_by_hash_with_meta(
@@ -188,7 +246,9 @@ impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
         self.db
             .view(|tx| -> Result<_> {
                 if let Some(transaction_id) = tx.get::<tables::TxHashNumber>(tx_hash)? {
-                    if let Some(transaction) = tx.get::<tables::Transactions>(transaction_id)? {
+                    if let Some(transaction) =
+                        self.get_row::<tables::Transactions, _>(tx, transaction_id)?
+                    {
                         let mut transaction_cursor =
                             tx.cursor_read::<tables::TransactionBlock>()?;
                         if let Some(block_number) =
@@ -238,17 +298,7 @@ impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
         if let Some(number) = self.block_number_for_id(id)? {
             let tx = self.db.tx()?;
             if let Some(body) = tx.get::<tables::BlockBodies>(number)? {
-                let tx_range = body.tx_id_range();
-                return if tx_range.is_empty() {
-                    Ok(Some(Vec::new()))
-                } else {
-                    let mut tx_cursor = tx.cursor_read::<tables::Transactions>()?;
-                    let transactions = tx_cursor
-                        .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, tx)| tx))
-                        .collect::<std::result::Result<Vec<_>, _>>()?;
-                    Ok(Some(transactions))
-                }
+                return self.get_rows::<tables::Transactions, _>(&tx, body.tx_id_range()).map(Some)
             }
         }
         Ok(None)
@@ -261,20 +311,9 @@ impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
         let tx = self.db.tx()?;
         let mut results = Vec::default();
         let mut body_cursor = tx.cursor_read::<tables::BlockBodies>()?;
-        let mut tx_cursor = tx.cursor_read::<tables::Transactions>()?;
         for entry in body_cursor.walk_range(range)? {
             let (_, body) = entry?;
-            let tx_range = body.tx_id_range();
-            if body.tx_id_range().is_empty() {
-                results.push(Vec::default());
-            } else {
-                results.push(
-                    tx_cursor
-                        .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, tx)| tx))
-                        .collect::<std::result::Result<Vec<_>, _>>()?,
-                );
-            }
+            results.push(self.get_rows::<tables::Transactions, _>(&tx, body.tx_id_range())?);
         }
         Ok(results)
     }
@@ -282,36 +321,34 @@ impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
 
 impl<DB: Database> ReceiptProvider for ShareableDatabase<DB> {
     fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
-        self.db.view(|tx| tx.get::<tables::Receipts>(id))?.map_err(Into::into)
+        let tx = self.db.tx()?;
+        ensure_receipt_not_pruned(&tx, id)?;
+        self.get_row::<tables::Receipts, _>(&tx, id)
     }
 
     fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
-        self.db
-            .view(|tx| {
-                if let Some(id) = tx.get::<tables::TxHashNumber>(hash)? {
-                    tx.get::<tables::Receipts>(id)
-                } else {
-                    Ok(None)
-                }
-            })?
-            .map_err(Into::into)
+        let tx = self.db.tx()?;
+        if let Some(id) = tx.get::<tables::TxHashNumber>(hash)? {
+            ensure_receipt_not_pruned(&tx, id)?;
+            self.get_row::<tables::Receipts, _>(&tx, id)
+        } else {
+            Ok(None)
+        }
     }
 
     fn receipts_by_block(&self, block: BlockId) -> Result<Option<Vec<Receipt>>> {
         if let Some(number) = self.block_number_for_id(block)? {
             let tx = self.db.tx()?;
-            if let Some(body) = tx.get::<tables::BlockBodies>(number)? {
-                let tx_range = body.tx_id_range();
-                return if tx_range.is_empty() {
-                    Ok(Some(Vec::new()))
-                } else {
-                    let mut tx_cursor = tx.cursor_read::<tables::Receipts>()?;
-                    let transactions = tx_cursor
-                        .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, tx)| tx))
-                        .collect::<std::result::Result<Vec<_>, _>>()?;
-                    Ok(Some(transactions))
+            let checkpoint = tx.get::<tables::PruneCheckpoints>(PruneSegment::Receipts)?;
+            if checkpoint.map_or(false, |checkpoint| number <= checkpoint.block_number) {
+                return Err(ProviderError::Pruned {
+                    segment: PruneSegment::Receipts,
+                    block_number: number,
                 }
+                .into())
+            }
+            if let Some(body) = tx.get::<tables::BlockBodies>(number)? {
+                return self.get_rows::<tables::Receipts, _>(&tx, body.tx_id_range()).map(Some)
             }
         }
         Ok(None)
@@ -343,6 +380,12 @@ impl<DB: Database> WithdrawalsProvider for ShareableDatabase<DB> {
     }
 }
 
+impl<DB: Database> ChainSpecProvider for ShareableDatabase<DB> {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        Arc::clone(&self.chain_spec)
+    }
+}
+
 impl<DB: Database> EvmEnvProvider for ShareableDatabase<DB> {
     fn fill_env_at(&self, cfg: &mut CfgEnv, block_env: &mut BlockEnv, at: BlockId) -> Result<()> {
         let hash = self.block_hash_for_id(at)?.ok_or(ProviderError::HeaderNotFound)?;
@@ -413,6 +456,7 @@ impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
 
     fn history_by_block_number(&self, block_number: BlockNumber) -> Result<Self::HistorySP<'_>> {
         let tx = self.db.tx()?;
+        ensure_history_not_pruned(&tx, block_number)?;
 
         // get transition id
         let transition = tx
@@ -428,6 +472,7 @@ impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
         let block_number = tx
             .get::<tables::HeaderNumbers>(block_hash)?
             .ok_or(ProviderError::BlockHash { block_hash })?;
+        ensure_history_not_pruned(&tx, block_number)?;
 
         // get transition id
         let transition = tx
@@ -438,14 +483,62 @@ impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
     }
 }
 
+/// Converts range bounds over keys into an exclusive range.
+fn to_range(bounds: impl RangeBounds<u64>) -> Range<u64> {
+    let start = match bounds.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match bounds.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => u64::MAX,
+    };
+    start..end
+}
+
+/// Returns an error if the receipt of the transaction was pruned.
+fn ensure_receipt_not_pruned<'a, TX: DbTx<'a>>(tx: &TX, tx_number: TxNumber) -> Result<()> {
+    let checkpoint = tx.get::<tables::PruneCheckpoints>(PruneSegment::Receipts)?;
+    if checkpoint.and_then(|checkpoint| checkpoint.tx_number).map_or(false, |n| tx_number <= n) {
+        return Err(
+            ProviderError::PrunedTransaction { segment: PruneSegment::Receipts, tx_number }.into()
+        )
+    }
+    Ok(())
+}
+
+/// Returns an error if the changesets required to restore the state at the block were pruned.
+///
+/// The state at the highest pruned block itself is still available.
+fn ensure_history_not_pruned<'a, TX: DbTx<'a>>(tx: &TX, block_number: BlockNumber) -> Result<()> {
+    for segment in [PruneSegment::AccountHistory, PruneSegment::StorageHistory] {
+        if let Some(checkpoint) = tx.get::<tables::PruneCheckpoints>(segment)? {
+            if block_number < checkpoint.block_number {
+                return Err(ProviderError::Pruned { segment, block_number }.into())
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use super::ShareableDatabase;
-    use crate::{BlockIdProvider, StateProviderFactory};
-    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
-    use reth_primitives::{ChainSpecBuilder, H256};
+    use crate::{
+        static_files::StaticFileProvider, BlockIdProvider, HeaderProvider, ProviderError,
+        StateProviderFactory,
+    };
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{ChainSpecBuilder, Header, PruneCheckpoint, PruneSegment, H256};
 
     #[test]
     fn common_history_provider() {
@@ -467,4 +560,65 @@ mod tests {
         assert_eq!(chain_info.last_finalized, None);
         assert_eq!(chain_info.safe_finalized, None);
     }
+
+    #[test]
+    fn static_file_provider() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let headers = (0..10u64).map(|number| Header { number, ..Default::default() });
+        db.update(|tx| {
+            for header in headers.clone().skip(5) {
+                tx.put::<tables::Headers>(header.number, header)?;
+            }
+            Ok::<_, reth_db::Error>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = StaticFileProvider::open(dir.path()).unwrap();
+        static_files
+            .append::<tables::Headers>(
+                headers.clone().take(5).map(|header| (header.number, header)),
+            )
+            .unwrap();
+        let provider =
+            ShareableDatabase::new(db, Arc::new(chain_spec)).with_static_files(static_files);
+
+        assert_eq!(provider.header_by_number(3).unwrap(), headers.clone().nth(3));
+        assert_eq!(provider.header_by_number(7).unwrap(), headers.clone().nth(7));
+        assert_eq!(provider.header_by_number(10).unwrap(), None);
+        assert_eq!(
+            provider.headers_range(3..=7).unwrap(),
+            headers.skip(3).take(5).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn pruned_history_provider() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        db.update(|tx| {
+            for block_number in 0..10 {
+                tx.put::<tables::BlockTransitionIndex>(block_number, block_number)?;
+            }
+            tx.put::<tables::PruneCheckpoints>(
+                PruneSegment::StorageHistory,
+                PruneCheckpoint { block_number: 5, tx_number: None },
+            )
+        })
+        .unwrap()
+        .unwrap();
+        let provider = ShareableDatabase::new(db, Arc::new(chain_spec));
+
+        assert!(provider.history_by_block_number(5).is_ok());
+        assert!(provider.history_by_block_number(9).is_ok());
+        assert_eq!(
+            provider.history_by_block_number(4).err(),
+            Some(
+                ProviderError::Pruned { segment: PruneSegment::StorageHistory, block_number: 4 }
+                    .into()
+            )
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    providers::state::macros::delegate_provider_impls, AccountProvider, BlockHashProvider,
-    ProviderError, StateProvider,
+    providers::state::macros::delegate_provider_impls,
+    trie::{HashedStateChanges, StateRoot},
+    AccountProvider, BlockHashProvider, ProviderError, StateProvider,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
@@ -141,6 +142,16 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for HistoricalStateProviderRef<'a, 'b,
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         Err(ProviderError::HistoryStateRoot.into())
     }
+
+    /// The state trie only exists for the block of the merkle stage checkpoint, so the changes
+    /// take it to the state at this transition.
+    fn hashed_state(&self) -> Result<HashedStateChanges> {
+        Ok(StateRoot::new(self.tx).changes_at(Some(self.transition))?)
+    }
+
+    fn trie_overlay_root(&self, changes: &HashedStateChanges) -> Result<H256> {
+        Ok(StateRoot::new(self.tx).overlay_root(changes)?)
+    }
 }
 
 /// State provider for a given transition
@@ -1,13 +1,14 @@
 use crate::{
-    providers::state::macros::delegate_provider_impls, trie::DBTrieLoader, AccountProvider,
-    BlockHashProvider, StateProvider,
+    providers::state::macros::delegate_provider_impls,
+    trie::{HashedStateChanges, StateRoot},
+    AccountProvider, BlockHashProvider, StateProvider,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
     tables,
     transaction::DbTx,
 };
-use reth_interfaces::{provider::ProviderError, Result};
+use reth_interfaces::Result;
 use reth_primitives::{
     keccak256, Account, Address, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, H256,
     KECCAK_EMPTY,
@@ -79,18 +80,11 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
         keys: &[H256],
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         let hashed_address = keccak256(address);
-        let loader = DBTrieLoader::new(self.db);
-        let root = self
-            .db
-            .cursor_read::<tables::Headers>()?
-            .last()?
-            .ok_or(ProviderError::Header { number: 0 })?
-            .1
-            .state_root;
-
-        let (account_proof, storage_root) = loader
-            .generate_acount_proof(root, hashed_address)
-            .map_err(|_| ProviderError::StateTrie)?;
+        let state_root = StateRoot::new(self.db);
+        // the proofs are of the latest state, which the trie may not be up to date with
+        let changes = state_root.changes_at(None)?;
+
+        let (account_proof, storage_root) = state_root.account_proof(&changes, hashed_address)?;
         let account_proof = account_proof.into_iter().map(Bytes::from).collect();
 
         let storage_proof = if storage_root == KECCAK_EMPTY {
@@ -98,9 +92,8 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
             (0..keys.len()).map(|_| Vec::new()).collect()
         } else {
             let hashed_keys: Vec<H256> = keys.iter().map(keccak256).collect();
-            loader
-                .generate_storage_proofs(storage_root, hashed_address, &hashed_keys)
-                .map_err(|_| ProviderError::StateTrie)?
+            state_root
+                .storage_proofs(&changes, hashed_address, &hashed_keys)?
                 .into_iter()
                 .map(|v| v.into_iter().map(Bytes::from).collect())
                 .collect()
@@ -108,6 +101,14 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
 
         Ok((account_proof, storage_root, storage_proof))
     }
+
+    fn hashed_state(&self) -> Result<HashedStateChanges> {
+        Ok(StateRoot::new(self.db).changes_at(None)?)
+    }
+
+    fn trie_overlay_root(&self, changes: &HashedStateChanges) -> Result<H256> {
+        Ok(StateRoot::new(self.db).overlay_root(changes)?)
+    }
 }
 
 /// State provider for the latest state.
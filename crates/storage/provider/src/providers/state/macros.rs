@@ -41,6 +41,8 @@ macro_rules! delegate_provider_impls {
                 fn storage(&self, account: reth_primitives::Address, storage_key: reth_primitives::StorageKey) -> reth_interfaces::Result<Option<reth_primitives::StorageValue>>;
                 fn proof(&self, address: reth_primitives::Address, keys: &[reth_primitives::H256]) -> reth_interfaces::Result<(Vec<reth_primitives::Bytes>, reth_primitives::H256, Vec<Vec<reth_primitives::Bytes>>)>;
                 fn bytecode_by_hash(&self, code_hash: reth_primitives::H256) -> reth_interfaces::Result<Option<reth_primitives::Bytecode>>;
+                fn hashed_state(&self) -> reth_interfaces::Result<crate::trie::HashedStateChanges>;
+                fn trie_overlay_root(&self, changes: &crate::trie::HashedStateChanges) -> reth_interfaces::Result<reth_primitives::H256>;
             }
         );
     }
@@ -0,0 +1,299 @@
+use crate::{
+    trie::HashedStateChanges, AccountProvider, BlockHashProvider, LatestStateProviderRef,
+    StateProvider,
+};
+use parking_lot::Mutex;
+use reth_db::database::Database;
+use reth_interfaces::Result;
+use reth_primitives::{
+    Account, Address, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, H256, KECCAK_EMPTY,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::Scope,
+    time::Duration,
+};
+use tracing::debug;
+
+/// The maximum number of targets the workers of a [StatePrefetcher] read ahead of the position
+/// reported with [StatePrefetcher::advance].
+const MAX_LOOKAHEAD: usize = 10_000;
+
+/// A state read to warm up ahead of execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrefetchTarget {
+    /// An account, along with its bytecode if it's a contract.
+    Account(Address),
+    /// A storage slot of an account.
+    Storage(Address, StorageKey),
+}
+
+/// The state read by a [StatePrefetcher], consumed by a [PrefetchedStateProvider].
+#[derive(Debug, Default)]
+pub struct PrefetchCache {
+    accounts: Mutex<HashMap<Address, Option<Account>>>,
+    storage: Mutex<HashMap<(Address, StorageKey), Option<StorageValue>>>,
+    bytecodes: Mutex<HashMap<H256, Option<Bytecode>>>,
+    /// The hashes of the bytecodes that were already read, so that code shared by several
+    /// accounts is only read once.
+    read_bytecodes: Mutex<HashSet<H256>>,
+}
+
+impl PrefetchCache {
+    /// Reads the target from the provider into the cache.
+    fn prefetch(&self, provider: &impl StateProvider, target: PrefetchTarget) -> Result<()> {
+        match target {
+            PrefetchTarget::Account(address) => {
+                let account = provider.basic_account(address)?;
+                if let Some(code_hash) = account.and_then(|account| account.bytecode_hash) {
+                    if code_hash != KECCAK_EMPTY && self.read_bytecodes.lock().insert(code_hash) {
+                        let bytecode = provider.bytecode_by_hash(code_hash)?;
+                        self.bytecodes.lock().insert(code_hash, bytecode);
+                    }
+                }
+                self.accounts.lock().insert(address, account);
+            }
+            PrefetchTarget::Storage(address, key) => {
+                let value = provider.storage(address, key)?;
+                self.storage.lock().insert((address, key), value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The progress of the workers of a [StatePrefetcher].
+#[derive(Debug)]
+struct PrefetchProgress {
+    targets: Vec<PrefetchTarget>,
+    /// The index of the next target to read.
+    next: AtomicUsize,
+    /// The position of the consumer in the targets.
+    position: AtomicUsize,
+    stop: AtomicBool,
+}
+
+/// Reads the latest state of upcoming [PrefetchTarget]s into a [PrefetchCache] on background
+/// threads, so that execution doesn't wait for every read from the database in turn.
+///
+/// Each worker reads from its own read-only transaction, so the prefetched values are only
+/// correct if the state being executed on top of has been committed.
+///
+/// The workers stop once all targets were read, or when the prefetcher is dropped.
+#[derive(Debug)]
+pub struct StatePrefetcher {
+    progress: Arc<PrefetchProgress>,
+    cache: Arc<PrefetchCache>,
+}
+
+impl StatePrefetcher {
+    /// Spawns `threads` workers in the scope, reading the targets in order.
+    pub fn spawn<'scope, DB: Database>(
+        scope: &'scope Scope<'scope, '_>,
+        db: &'scope DB,
+        targets: Vec<PrefetchTarget>,
+        threads: usize,
+    ) -> Self {
+        let progress = Arc::new(PrefetchProgress {
+            targets,
+            next: AtomicUsize::new(0),
+            position: AtomicUsize::new(0),
+            stop: AtomicBool::new(false),
+        });
+        let cache = Arc::new(PrefetchCache::default());
+
+        for _ in 0..threads {
+            let progress = Arc::clone(&progress);
+            let cache = Arc::clone(&cache);
+            scope.spawn(move || {
+                if let Err(error) = Self::run(db, &progress, &cache) {
+                    debug!(target: "provider::prefetch", ?error, "State prefetching failed");
+                }
+            });
+        }
+
+        Self { progress, cache }
+    }
+
+    /// Returns the cache the workers read into.
+    pub fn cache(&self) -> Arc<PrefetchCache> {
+        Arc::clone(&self.cache)
+    }
+
+    /// Reports that the targets before the position were consumed, letting the workers read
+    /// further ahead.
+    pub fn advance(&self, position: usize) {
+        self.progress.position.store(position, Ordering::Relaxed);
+    }
+
+    fn run<DB: Database>(
+        db: &DB,
+        progress: &PrefetchProgress,
+        cache: &PrefetchCache,
+    ) -> Result<()> {
+        let tx = db.tx()?;
+        let provider = LatestStateProviderRef::new(&tx);
+
+        loop {
+            let index = progress.next.fetch_add(1, Ordering::Relaxed);
+            let Some(target) = progress.targets.get(index) else { return Ok(()) };
+
+            while index > progress.position.load(Ordering::Relaxed) + MAX_LOOKAHEAD {
+                if progress.stop.load(Ordering::Relaxed) {
+                    return Ok(())
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            if progress.stop.load(Ordering::Relaxed) {
+                return Ok(())
+            }
+
+            cache.prefetch(&provider, *target)?;
+        }
+    }
+}
+
+impl Drop for StatePrefetcher {
+    fn drop(&mut self) {
+        self.progress.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A [StateProvider] serving the reads of a [StatePrefetcher] before falling back to the inner
+/// provider.
+///
+/// Prefetched values are removed from the cache once read, the executor caches them itself.
+#[derive(Debug)]
+pub struct PrefetchedStateProvider<SP> {
+    inner: SP,
+    cache: Arc<PrefetchCache>,
+}
+
+impl<SP: StateProvider> PrefetchedStateProvider<SP> {
+    /// Create new state provider
+    pub fn new(inner: SP, cache: Arc<PrefetchCache>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+impl<SP: StateProvider> AccountProvider for PrefetchedStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        if let Some(account) = self.cache.accounts.lock().remove(&address) {
+            return Ok(account)
+        }
+        self.inner.basic_account(address)
+    }
+}
+
+impl<SP: StateProvider> BlockHashProvider for PrefetchedStateProvider<SP> {
+    fn block_hash(&self, number: u64) -> Result<Option<H256>> {
+        self.inner.block_hash(number)
+    }
+
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        self.inner.canonical_hashes_range(start, end)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for PrefetchedStateProvider<SP> {
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        if let Some(value) = self.cache.storage.lock().remove(&(account, storage_key)) {
+            return Ok(value)
+        }
+        self.inner.storage(account, storage_key)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytecode>> {
+        if let Some(bytecode) = self.cache.bytecodes.lock().remove(&code_hash) {
+            return Ok(bytecode)
+        }
+        self.inner.bytecode_by_hash(code_hash)
+    }
+
+    fn proof(
+        &self,
+        address: Address,
+        keys: &[H256],
+    ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
+        self.inner.proof(address, keys)
+    }
+
+    fn hashed_state(&self) -> Result<HashedStateChanges> {
+        self.inner.hashed_state()
+    }
+
+    fn trie_overlay_root(&self, changes: &HashedStateChanges) -> Result<H256> {
+        self.inner.trie_overlay_root(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{StorageEntry, U256};
+
+    #[test]
+    fn prefetched_reads_match_latest() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let contract = Address::from_low_u64_be(1);
+        let eoa = Address::from_low_u64_be(2);
+        let missing = Address::from_low_u64_be(3);
+        let bytecode = Bytecode::new_raw(vec![0x60, 0x00].into());
+        let code_hash = H256::from_low_u64_be(0xc0de);
+        let slot = H256::from_low_u64_be(4);
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::PlainAccountState>(
+            contract,
+            Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(code_hash) },
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(
+            eoa,
+            Account { nonce: 2, balance: U256::from(10), bytecode_hash: None },
+        )
+        .unwrap();
+        tx.put::<tables::Bytecodes>(code_hash, bytecode.clone()).unwrap();
+        tx.put::<tables::PlainStorageState>(
+            contract,
+            StorageEntry { key: slot, value: U256::from(5) },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let targets = vec![
+            PrefetchTarget::Account(contract),
+            PrefetchTarget::Account(eoa),
+            PrefetchTarget::Account(missing),
+            PrefetchTarget::Storage(contract, slot),
+            PrefetchTarget::Storage(contract, H256::zero()),
+        ];
+
+        std::thread::scope(|scope| {
+            let prefetcher = StatePrefetcher::spawn(scope, db.as_ref(), targets, 2);
+            let tx = db.tx().unwrap();
+            let latest = LatestStateProviderRef::new(&tx);
+            let prefetched =
+                PrefetchedStateProvider::new(LatestStateProviderRef::new(&tx), prefetcher.cache());
+
+            for address in [contract, eoa, missing] {
+                assert_eq!(
+                    prefetched.basic_account(address).unwrap(),
+                    latest.basic_account(address).unwrap()
+                );
+            }
+            assert_eq!(prefetched.bytecode_by_hash(code_hash).unwrap(), Some(bytecode));
+            assert_eq!(prefetched.storage(contract, slot).unwrap(), Some(U256::from(5)));
+            assert_eq!(prefetched.storage(contract, H256::zero()).unwrap(), None);
+        });
+    }
+}
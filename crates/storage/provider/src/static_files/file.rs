@@ -0,0 +1,226 @@
+use super::{StaticFileError, StaticFileSegment};
+use parking_lot::Mutex;
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Maximum number of rows stored in a single static file.
+pub const ROWS_PER_FILE: u64 = 500_000;
+
+/// Size of a number in the offsets file.
+const OFFSET_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+/// zstd compression level of the rows.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// The data and offsets file of consecutive rows of a segment.
+#[derive(Debug)]
+pub(crate) struct SegmentFile {
+    /// Key of the first row.
+    first_key: u64,
+    /// Number of complete rows, including the ones that are not yet synced.
+    rows: u64,
+    /// Length of the complete rows in the data file.
+    data_len: u64,
+    /// End offsets of the rows appended since the last sync.
+    ///
+    /// They are only written to the offsets file once the data file is synced, so the offsets
+    /// file never references data that may not have been persisted.
+    pending_offsets: Vec<u64>,
+    data: Mutex<File>,
+    offsets: Mutex<File>,
+}
+
+// === impl SegmentFile ===
+
+impl SegmentFile {
+    /// Returns the path of the files of the segment starting at the given key, without extension.
+    pub(crate) fn path(dir: &Path, segment: StaticFileSegment, first_key: u64) -> PathBuf {
+        dir.join(format!("{segment}_{first_key}"))
+    }
+
+    /// Parses segment and first key from the file name of an offsets file.
+    pub(crate) fn parse_offsets_file_name(path: &Path) -> Option<(StaticFileSegment, u64)> {
+        if path.extension()? != "off" {
+            return None
+        }
+        let (segment, first_key) = path.file_stem()?.to_str()?.rsplit_once('_')?;
+        Some((segment.parse().ok()?, first_key.parse().ok()?))
+    }
+
+    /// Creates empty files for rows starting at `first_key`.
+    pub(crate) fn create(
+        dir: &Path,
+        segment: StaticFileSegment,
+        first_key: u64,
+    ) -> Result<Self, StaticFileError> {
+        let path = Self::path(dir, segment, first_key);
+        let data = File::create(path.with_extension("dat"))?;
+        let mut offsets = File::create(path.with_extension("off"))?;
+        offsets.write_all(&first_key.to_le_bytes())?;
+        offsets.sync_all()?;
+
+        Ok(Self {
+            first_key,
+            rows: 0,
+            data_len: 0,
+            pending_offsets: Vec::new(),
+            data: Mutex::new(data),
+            offsets: Mutex::new(offsets),
+        })
+    }
+
+    /// Opens existing files, discarding incompletely written rows.
+    pub(crate) fn open(path: &Path) -> Result<Self, StaticFileError> {
+        let open = |path: PathBuf| OpenOptions::new().read(true).write(true).open(path);
+        let mut data = open(path.with_extension("dat"))?;
+        let mut offsets = open(path.with_extension("off"))?;
+
+        let mut buf = [0; OFFSET_SIZE as usize];
+        offsets.read_exact(&mut buf)?;
+        let first_key = u64::from_le_bytes(buf);
+
+        // offsets are synced after the data they reference, rows whose offset is beyond the data
+        // file can only be left behind by a broken file system and are discarded as well
+        let mut rows = (offsets.metadata()?.len() - OFFSET_SIZE) / OFFSET_SIZE;
+        let file_len = data.metadata()?.len();
+        let mut data_len = 0;
+        while rows > 0 {
+            data_len = read_offset(&mut offsets, rows - 1)?;
+            if data_len <= file_len {
+                break
+            }
+            rows -= 1;
+            data_len = 0;
+        }
+        offsets.set_len(OFFSET_SIZE * (rows + 1))?;
+        data.set_len(data_len)?;
+
+        Ok(Self {
+            first_key,
+            rows,
+            data_len,
+            pending_offsets: Vec::new(),
+            data: Mutex::new(data),
+            offsets: Mutex::new(offsets),
+        })
+    }
+
+    /// Returns the key of the first row.
+    pub(crate) fn first_key(&self) -> u64 {
+        self.first_key
+    }
+
+    /// Returns the key after the last row.
+    pub(crate) fn end_key(&self) -> u64 {
+        self.first_key + self.rows
+    }
+
+    /// Returns true if the file contains the given number of rows.
+    pub(crate) fn is_full(&self, rows_per_file: u64) -> bool {
+        self.rows >= rows_per_file
+    }
+
+    /// Reads and decompresses the rows in the range, which must be within the rows of the file.
+    pub(crate) fn read(&self, keys: Range<u64>) -> Result<Vec<Vec<u8>>, StaticFileError> {
+        debug_assert!(self.first_key <= keys.start && keys.end <= self.end_key());
+        if keys.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        // end offsets of the previous row and all rows in the range
+        let first_index = keys.start - self.first_key;
+        let end_index = keys.end - self.first_key;
+        let ends = match first_index.checked_sub(1) {
+            Some(previous) => self.row_ends(previous..end_index)?,
+            None => std::iter::once(0).chain(self.row_ends(0..end_index)?).collect(),
+        };
+
+        let start = ends[0];
+        let mut buf = vec![0; (ends[ends.len() - 1] - start) as usize];
+        {
+            let mut data = self.data.lock();
+            data.seek(SeekFrom::Start(start))?;
+            data.read_exact(&mut buf)?;
+        }
+
+        ends.windows(2)
+            .map(|window| {
+                let row = &buf[(window[0] - start) as usize..(window[1] - start) as usize];
+                Ok(zstd::stream::decode_all(row)?)
+            })
+            .collect()
+    }
+
+    /// Returns the end offsets of the rows with the given indices, which must be within the rows of
+    /// the file.
+    fn row_ends(&self, indices: Range<u64>) -> Result<Vec<u64>, StaticFileError> {
+        let synced_rows = self.rows - self.pending_offsets.len() as u64;
+
+        let mut ends = Vec::with_capacity((indices.end - indices.start) as usize);
+        let synced_end = indices.end.min(synced_rows);
+        if indices.start < synced_end {
+            let mut buf = vec![0; ((synced_end - indices.start) * OFFSET_SIZE) as usize];
+            let mut offsets = self.offsets.lock();
+            offsets.seek(SeekFrom::Start(OFFSET_SIZE * (indices.start + 1)))?;
+            offsets.read_exact(&mut buf)?;
+            ends.extend(buf.chunks_exact(OFFSET_SIZE as usize).map(|chunk| {
+                u64::from_le_bytes(chunk.try_into().expect("chunk has the size of an offset"))
+            }));
+        }
+        ends.extend(
+            (indices.start.max(synced_rows)..indices.end)
+                .map(|index| self.pending_offsets[(index - synced_rows) as usize]),
+        );
+        Ok(ends)
+    }
+
+    /// Compresses and appends a row.
+    ///
+    /// The row is only guaranteed to be persisted after [SegmentFile::sync].
+    pub(crate) fn append(&mut self, row: &[u8]) -> Result<(), StaticFileError> {
+        let compressed = zstd::stream::encode_all(row, COMPRESSION_LEVEL)?;
+        let end = self.data_len + compressed.len() as u64;
+
+        let data = self.data.get_mut();
+        data.seek(SeekFrom::Start(self.data_len))?;
+        data.write_all(&compressed)?;
+        self.pending_offsets.push(end);
+
+        self.data_len = end;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Flushes all appended rows to disk.
+    ///
+    /// The data file is synced before the offsets of the new rows are written, so rows are either
+    /// fully persisted or discarded on open.
+    pub(crate) fn sync(&mut self) -> Result<(), StaticFileError> {
+        if self.pending_offsets.is_empty() {
+            return Ok(())
+        }
+        self.data.get_mut().sync_all()?;
+
+        let synced_rows = self.rows - self.pending_offsets.len() as u64;
+        let buf = self.pending_offsets.iter().flat_map(|end| end.to_le_bytes()).collect::<Vec<_>>();
+        let offsets = self.offsets.get_mut();
+        offsets.seek(SeekFrom::Start(OFFSET_SIZE * (synced_rows + 1)))?;
+        offsets.write_all(&buf)?;
+        offsets.sync_all()?;
+
+        self.pending_offsets.clear();
+        Ok(())
+    }
+}
+
+/// Reads the end offset of the row with the given index.
+fn read_offset(offsets: &mut File, index: u64) -> Result<u64, StaticFileError> {
+    let mut buf = [0; OFFSET_SIZE as usize];
+    offsets.seek(SeekFrom::Start(OFFSET_SIZE * (index + 1)))?;
+    offsets.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
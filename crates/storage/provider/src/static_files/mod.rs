@@ -0,0 +1,84 @@
+//! Append-only storage of finalized chain data outside of the database.
+//!
+//! Headers, transactions and receipts of finalized blocks never change, keeping them in the
+//! database only grows its B-trees. They are instead moved into static files, one set of files per
+//! [StaticFileSegment].
+//!
+//! Every segment is split into files of at most [ROWS_PER_FILE] consecutive rows, each consisting
+//! of two parts:
+//!
+//! - `<segment>_<first key>.dat`: the rows, each encoded like the database value and compressed
+//!   with zstd.
+//! - `<segment>_<first key>.off`: the key of the first row, followed by the end offset of every row
+//!   in the data file. All numbers are little endian `u64`.
+//!
+//! Files are only ever appended to. The data file is synced before the offsets of new rows are
+//! written, rows that were not fully written are discarded when the files are opened.
+
+use reth_db::{table::Table, tables};
+
+mod file;
+mod provider;
+mod segment;
+
+pub use file::ROWS_PER_FILE;
+pub use provider::StaticFileProvider;
+pub use segment::StaticFileSegment;
+
+/// A table whose finalized rows can be moved into static files.
+pub trait StaticFileTable: Table<Key = u64> {
+    /// The segment the rows of the table are stored in.
+    const SEGMENT: StaticFileSegment;
+}
+
+impl StaticFileTable for tables::Headers {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::Headers;
+}
+
+impl StaticFileTable for tables::Transactions {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::Transactions;
+}
+
+impl StaticFileTable for tables::Receipts {
+    const SEGMENT: StaticFileSegment = StaticFileSegment::Receipts;
+}
+
+/// Errors that can occur when reading or writing static files.
+#[derive(Debug, thiserror::Error)]
+pub enum StaticFileError {
+    /// Failed to access a static file.
+    #[error("Static file io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A row could not be decoded.
+    #[error("Failed to decode row {key} of the {segment} segment")]
+    Decode {
+        /// The segment of the row.
+        segment: StaticFileSegment,
+        /// The key of the row.
+        key: u64,
+    },
+    /// Rows have to be appended in order without gaps.
+    #[error("Expected row {expected} of the {segment} segment, got {got}")]
+    UnexpectedKey {
+        /// The segment the row was appended to.
+        segment: StaticFileSegment,
+        /// The next key of the segment.
+        expected: u64,
+        /// The key of the appended row.
+        got: u64,
+    },
+    /// The files of a segment don't cover consecutive rows.
+    #[error("Static files of the {segment} segment are not consecutive at row {key}")]
+    Gap {
+        /// The segment of the files.
+        segment: StaticFileSegment,
+        /// The first missing key.
+        key: u64,
+    },
+}
+
+impl From<StaticFileError> for reth_interfaces::Error {
+    fn from(err: StaticFileError) -> Self {
+        reth_interfaces::provider::ProviderError::StaticFile(err.to_string()).into()
+    }
+}
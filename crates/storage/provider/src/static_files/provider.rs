@@ -0,0 +1,285 @@
+use super::{
+    file::SegmentFile, StaticFileError, StaticFileSegment, StaticFileTable, ROWS_PER_FILE,
+};
+use parking_lot::RwLock;
+use reth_db::table::{Compress, Decompress};
+use std::{
+    collections::HashMap,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::trace;
+
+/// Provides access to the static files in a directory.
+///
+/// This type is cheap to clone, all clones share the same files.
+#[derive(Debug, Clone)]
+pub struct StaticFileProvider {
+    inner: Arc<StaticFileProviderInner>,
+}
+
+#[derive(Debug)]
+struct StaticFileProviderInner {
+    /// Directory of the static files.
+    path: PathBuf,
+    /// Maximum number of rows of new files.
+    rows_per_file: u64,
+    /// Files of all segments, sorted by their first key.
+    segments: RwLock<HashMap<StaticFileSegment, Vec<SegmentFile>>>,
+}
+
+// === impl StaticFileProvider ===
+
+impl StaticFileProvider {
+    /// Opens the static files in the directory, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StaticFileError> {
+        Self::open_with_rows_per_file(path, ROWS_PER_FILE)
+    }
+
+    /// Opens the static files, starting new files after the given number of rows.
+    fn open_with_rows_per_file(
+        path: impl AsRef<Path>,
+        rows_per_file: u64,
+    ) -> Result<Self, StaticFileError> {
+        let path = path.as_ref().to_path_buf();
+        fs::create_dir_all(&path)?;
+
+        let mut segments = HashMap::<_, Vec<SegmentFile>>::new();
+        for entry in fs::read_dir(&path)? {
+            let file_path = entry?.path();
+            let Some((segment, _)) = SegmentFile::parse_offsets_file_name(&file_path) else {
+                continue
+            };
+            let file = SegmentFile::open(&file_path)?;
+            segments.entry(segment).or_default().push(file);
+        }
+
+        for (segment, files) in segments.iter_mut() {
+            files.sort_by_key(|file| file.first_key());
+            for pair in files.windows(2) {
+                if pair[0].end_key() != pair[1].first_key() {
+                    return Err(StaticFileError::Gap { segment: *segment, key: pair[0].end_key() })
+                }
+            }
+            trace!(target: "provider::static_files", %segment, files = files.len(), "Opened static files");
+        }
+
+        Ok(Self {
+            inner: Arc::new(StaticFileProviderInner {
+                path,
+                rows_per_file,
+                segments: RwLock::new(segments),
+            }),
+        })
+    }
+
+    /// Returns the directory of the static files.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Returns the lowest key stored in the segment, if any.
+    pub fn lowest(&self, segment: StaticFileSegment) -> Option<u64> {
+        let segments = self.inner.segments.read();
+        let file = segments.get(&segment)?.first()?;
+        (file.end_key() > file.first_key()).then(|| file.first_key())
+    }
+
+    /// Returns the highest key stored in the segment, if any.
+    pub fn highest(&self, segment: StaticFileSegment) -> Option<u64> {
+        let segments = self.inner.segments.read();
+        let files = segments.get(&segment)?;
+        let end_key = files.last()?.end_key();
+        (end_key > files.first()?.first_key()).then(|| end_key - 1)
+    }
+
+    /// Returns true if the row with the given key is stored in the static files of the table.
+    pub fn contains<T: StaticFileTable>(&self, key: u64) -> bool {
+        self.lowest(T::SEGMENT).map_or(false, |lowest| lowest <= key) &&
+            self.highest(T::SEGMENT).map_or(false, |highest| key <= highest)
+    }
+
+    /// Returns the row of the table with the given key, if it is stored in the static files.
+    pub fn get<T: StaticFileTable>(&self, key: u64) -> Result<Option<T::Value>, StaticFileError> {
+        Ok(self.range::<T>(key..key + 1)?.pop())
+    }
+
+    /// Returns all rows of the table in the range that are stored in the static files.
+    ///
+    /// Since rows are consecutive, the returned rows start at `max(range.start, lowest)`.
+    pub fn range<T: StaticFileTable>(
+        &self,
+        range: Range<u64>,
+    ) -> Result<Vec<T::Value>, StaticFileError> {
+        let segments = self.inner.segments.read();
+        let Some(files) = segments.get(&T::SEGMENT) else { return Ok(Vec::new()) };
+
+        let mut rows = Vec::new();
+        for file in files {
+            let start = range.start.max(file.first_key());
+            let end = range.end.min(file.end_key());
+            if start >= end {
+                continue
+            }
+            for (key, raw) in (start..end).zip(file.read(start..end)?) {
+                let value = T::Value::decompress(raw)
+                    .map_err(|_| StaticFileError::Decode { segment: T::SEGMENT, key })?;
+                rows.push(value);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Appends rows to the static files of the table and flushes them to disk.
+    ///
+    /// The keys have to continue the rows that are already stored without gaps.
+    pub fn append<T: StaticFileTable>(
+        &self,
+        rows: impl IntoIterator<Item = (u64, T::Value)>,
+    ) -> Result<usize, StaticFileError> {
+        let segment = T::SEGMENT;
+        let mut segments = self.inner.segments.write();
+        let files = segments.entry(segment).or_default();
+
+        let mut next_key = files.last().map(|file| file.end_key());
+        let mut appended = 0;
+        for (key, value) in rows {
+            if next_key.map_or(false, |next_key| next_key != key) {
+                return Err(StaticFileError::UnexpectedKey {
+                    segment,
+                    expected: next_key.unwrap_or_default(),
+                    got: key,
+                })
+            }
+
+            if files.last().map_or(true, |file| file.is_full(self.inner.rows_per_file)) {
+                if let Some(file) = files.last_mut() {
+                    file.sync()?;
+                }
+                files.push(SegmentFile::create(&self.inner.path, segment, key)?);
+            }
+            let file = files.last_mut().expect("file exists");
+            file.append(value.compress().as_ref())?;
+
+            next_key = Some(key + 1);
+            appended += 1;
+        }
+
+        if let Some(file) = files.last_mut() {
+            file.sync()?;
+        }
+        trace!(target: "provider::static_files", %segment, appended, highest = ?next_key.map(|key| key - 1), "Appended rows");
+        Ok(appended)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::tables;
+    use reth_primitives::Header;
+
+    fn header(number: u64) -> Header {
+        Header { number, gas_limit: number * 2, ..Default::default() }
+    }
+
+    #[test]
+    fn append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), None);
+        assert_eq!(provider.get::<tables::Headers>(0).unwrap(), None);
+
+        provider.append::<tables::Headers>((5..10).map(|number| (number, header(number)))).unwrap();
+        assert_eq!(provider.lowest(StaticFileSegment::Headers), Some(5));
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(9));
+        assert!(provider.contains::<tables::Headers>(5));
+        assert!(!provider.contains::<tables::Headers>(10));
+        assert_eq!(provider.get::<tables::Headers>(7).unwrap(), Some(header(7)));
+        assert_eq!(
+            provider.range::<tables::Headers>(0..8).unwrap(),
+            (5..8).map(header).collect::<Vec<_>>()
+        );
+
+        assert!(matches!(
+            provider.append::<tables::Headers>([(11, header(11))]),
+            Err(StaticFileError::UnexpectedKey { expected: 10, got: 11, .. })
+        ));
+
+        // reopening restores all rows
+        drop(provider);
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(
+            provider.range::<tables::Headers>(5..10).unwrap(),
+            (5..10).map(header).collect::<Vec<_>>()
+        );
+        assert_eq!(provider.highest(StaticFileSegment::Transactions), None);
+    }
+
+    #[test]
+    fn discard_incomplete_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        provider.append::<tables::Headers>((0..3).map(|number| (number, header(number)))).unwrap();
+        drop(provider);
+
+        // simulate a crash while appending the fourth row
+        let path = SegmentFile::path(dir.path(), StaticFileSegment::Headers, 0);
+        let mut data = fs::read(path.with_extension("dat")).unwrap();
+        data.extend_from_slice(&[1, 2, 3]);
+        fs::write(path.with_extension("dat"), data).unwrap();
+        let mut offsets = fs::read(path.with_extension("off")).unwrap();
+        offsets.extend_from_slice(&[1, 2]);
+        fs::write(path.with_extension("off"), offsets).unwrap();
+
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(2));
+        provider.append::<tables::Headers>([(3, header(3))]).unwrap();
+        assert_eq!(
+            provider.range::<tables::Headers>(0..4).unwrap(),
+            (0..4).map(header).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn discard_rows_beyond_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        provider.append::<tables::Headers>((0..3).map(|number| (number, header(number)))).unwrap();
+        drop(provider);
+
+        // the data of the last row was lost, its offset points beyond the data file
+        let path = SegmentFile::path(dir.path(), StaticFileSegment::Headers, 0);
+        let data = fs::read(path.with_extension("dat")).unwrap();
+        fs::write(path.with_extension("dat"), &data[..data.len() - 1]).unwrap();
+
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(1));
+        assert_eq!(
+            provider.range::<tables::Headers>(0..3).unwrap(),
+            (0..2).map(header).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn roll_over_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StaticFileProvider::open_with_rows_per_file(dir.path(), 4).unwrap();
+        provider.append::<tables::Headers>((0..6).map(|number| (number, header(number)))).unwrap();
+        provider.append::<tables::Headers>((6..10).map(|number| (number, header(number)))).unwrap();
+        for first_key in [0, 4, 8] {
+            assert!(SegmentFile::path(dir.path(), StaticFileSegment::Headers, first_key)
+                .with_extension("off")
+                .exists());
+        }
+
+        let provider = StaticFileProvider::open(dir.path()).unwrap();
+        assert_eq!(provider.highest(StaticFileSegment::Headers), Some(9));
+        assert_eq!(
+            provider.range::<tables::Headers>(3..9).unwrap(),
+            (3..9).map(header).collect::<Vec<_>>()
+        );
+    }
+}
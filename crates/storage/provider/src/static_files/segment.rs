@@ -0,0 +1,44 @@
+use std::{fmt, str::FromStr};
+
+/// A segment of finalized data that is stored in static files.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum StaticFileSegment {
+    /// Headers, keyed by block number.
+    Headers,
+    /// Transactions, keyed by transaction number.
+    Transactions,
+    /// Receipts, keyed by transaction number.
+    Receipts,
+}
+
+impl StaticFileSegment {
+    /// All segments.
+    pub const ALL: [StaticFileSegment; 3] =
+        [StaticFileSegment::Headers, StaticFileSegment::Transactions, StaticFileSegment::Receipts];
+
+    /// Returns the name of the segment that is used as prefix of its file names.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaticFileSegment::Headers => "headers",
+            StaticFileSegment::Transactions => "transactions",
+            StaticFileSegment::Receipts => "receipts",
+        }
+    }
+}
+
+impl fmt::Display for StaticFileSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StaticFileSegment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StaticFileSegment::ALL
+            .into_iter()
+            .find(|segment| segment.as_str() == s)
+            .ok_or_else(|| format!("unknown static file segment: {s}"))
+    }
+}
@@ -1,13 +1,14 @@
 use crate::{
-    traits::ReceiptProvider, AccountProvider, BlockHashProvider, BlockIdProvider, BlockProvider,
-    EvmEnvProvider, HeaderProvider, StateProvider, StateProviderFactory, TransactionsProvider,
+    traits::ReceiptProvider, trie::HashedStateChanges, AccountProvider, BlockHashProvider,
+    BlockIdProvider, BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider,
+    StateProvider, StateProviderFactory, TransactionsProvider,
 };
 use parking_lot::Mutex;
 use reth_interfaces::Result;
 use reth_primitives::{
     keccak256, Account, Address, Block, BlockHash, BlockId, BlockNumber, BlockNumberOrTag,
-    Bytecode, Bytes, ChainInfo, Header, Receipt, StorageKey, StorageValue, TransactionMeta,
-    TransactionSigned, TxHash, TxNumber, H256, U256,
+    Bytecode, Bytes, ChainInfo, ChainSpec, Header, Receipt, StorageKey, StorageValue,
+    TransactionMeta, TransactionSigned, TxHash, TxNumber, H256, MAINNET, U256,
 };
 use revm_primitives::{BlockEnv, CfgEnv};
 use std::{collections::HashMap, ops::RangeBounds, sync::Arc};
@@ -278,6 +279,20 @@ impl StateProvider for MockEthProvider {
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         todo!()
     }
+
+    fn hashed_state(&self) -> Result<HashedStateChanges> {
+        todo!()
+    }
+
+    fn trie_overlay_root(&self, _changes: &HashedStateChanges) -> Result<H256> {
+        todo!()
+    }
+}
+
+impl ChainSpecProvider for MockEthProvider {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        Arc::new(MAINNET.clone())
+    }
 }
 
 impl EvmEnvProvider for MockEthProvider {
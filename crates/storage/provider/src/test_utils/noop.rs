@@ -1,15 +1,16 @@
 use crate::{
-    traits::ReceiptProvider, AccountProvider, BlockHashProvider, BlockIdProvider, BlockProvider,
-    EvmEnvProvider, HeaderProvider, StateProvider, StateProviderFactory, TransactionsProvider,
+    traits::ReceiptProvider, trie::HashedStateChanges, AccountProvider, BlockHashProvider,
+    BlockIdProvider, BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider,
+    StateProvider, StateProviderFactory, TransactionsProvider,
 };
 use reth_interfaces::Result;
 use reth_primitives::{
-    Account, Address, Block, BlockHash, BlockId, BlockNumber, Bytecode, Bytes, ChainInfo, Header,
-    Receipt, StorageKey, StorageValue, TransactionMeta, TransactionSigned, TxHash, TxNumber, H256,
-    KECCAK_EMPTY, U256,
+    Account, Address, Block, BlockHash, BlockId, BlockNumber, Bytecode, Bytes, ChainInfo,
+    ChainSpec, Header, Receipt, StorageKey, StorageValue, TransactionMeta, TransactionSigned,
+    TxHash, TxNumber, H256, KECCAK_EMPTY, MAINNET, U256,
 };
 use revm_primitives::{BlockEnv, CfgEnv};
-use std::ops::RangeBounds;
+use std::{ops::RangeBounds, sync::Arc};
 
 /// Supports various api interfaces for testing purposes.
 #[derive(Debug, Clone, Default, Copy)]
@@ -137,6 +138,20 @@ impl StateProvider for NoopProvider {
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         Ok((vec![], KECCAK_EMPTY, vec![]))
     }
+
+    fn hashed_state(&self) -> Result<HashedStateChanges> {
+        Ok(HashedStateChanges::default())
+    }
+
+    fn trie_overlay_root(&self, _changes: &HashedStateChanges) -> Result<H256> {
+        Ok(H256::default())
+    }
+}
+
+impl ChainSpecProvider for NoopProvider {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        Arc::new(MAINNET.clone())
+    }
 }
 
 impl EvmEnvProvider for NoopProvider {
@@ -0,0 +1,10 @@
+use reth_primitives::ChainSpec;
+use std::sync::Arc;
+
+/// A provider of the [ChainSpec] of the chain, e.g. to check which hardforks are active at a
+/// block.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait ChainSpecProvider: Send + Sync {
+    /// Returns the chain spec.
+    fn chain_spec(&self) -> Arc<ChainSpec>;
+}
@@ -12,6 +12,9 @@ pub use block_hash::BlockHashProvider;
 mod block_id;
 pub use block_id::BlockIdProvider;
 
+mod chain_spec;
+pub use chain_spec::ChainSpecProvider;
+
 mod evm_env;
 pub use evm_env::EvmEnvProvider;
 
@@ -1,5 +1,5 @@
 use super::AccountProvider;
-use crate::BlockHashProvider;
+use crate::{post_state::PostState, trie::HashedStateChanges, BlockHashProvider};
 use auto_impl::auto_impl;
 use reth_interfaces::Result;
 use reth_primitives::{
@@ -20,6 +20,37 @@ pub trait StateProvider: BlockHashProvider + AccountProvider + Send + Sync {
     fn proof(&self, address: Address, keys: &[H256])
         -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)>;
 
+    /// Returns the state root of this state with the changes of the [PostState] applied on top.
+    ///
+    /// The root is computed in memory, nothing is written to the database.
+    fn state_root(&self, post_state: &PostState) -> Result<H256> {
+        self.hashed_state_root(&post_state.into())
+    }
+
+    /// Returns the state root of this state with the [HashedStateChanges] applied on top.
+    ///
+    /// See [StateProvider::state_root].
+    fn hashed_state_root(&self, changes: &HashedStateChanges) -> Result<H256> {
+        let mut state = self.hashed_state()?;
+        state.extend(changes);
+        self.trie_overlay_root(&state)
+    }
+
+    /// Returns the changes that take the hashed state of the state trie in the database to this
+    /// state.
+    ///
+    /// The state trie may not be at this state, see
+    /// [StateRoot::trie_root](crate::trie::StateRoot::trie_root).
+    fn hashed_state(&self) -> Result<HashedStateChanges>;
+
+    /// Returns the root of the state trie in the database with the [HashedStateChanges] applied on
+    /// top.
+    ///
+    /// Unlike [StateProvider::hashed_state_root], the changes have to start with those of
+    /// [StateProvider::hashed_state], so the roots of several changes to the same state can be
+    /// computed without gathering them again.
+    fn trie_overlay_root(&self, changes: &HashedStateChanges) -> Result<H256>;
+
     /// Get account code by its address.
     ///
     /// Returns `None` if the account doesn't exist or account is not a contract
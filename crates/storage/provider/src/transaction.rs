@@ -1,9 +1,10 @@
 use crate::{
     insert_canonical_block,
     post_state::{Change, PostState, StorageChangeset},
-    trie::{DBTrieLoader, TrieError},
+    trie::{StateRoot, TrieError},
 };
 use itertools::{izip, Itertools};
+use once_cell::sync::OnceCell;
 use reth_db::{
     common::KeyValue,
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
@@ -21,8 +22,8 @@ use reth_db::{
 use reth_interfaces::{db::Error as DbError, provider::ProviderError};
 use reth_primitives::{
     keccak256, proofs::EMPTY_ROOT, Account, Address, BlockHash, BlockNumber, ChainSpec, Hardfork,
-    Header, SealedBlock, SealedBlockWithSenders, StorageEntry, TransactionSignedEcRecovered,
-    TransitionId, TxNumber, H256, U256,
+    Header, PruneCheckpoint, PruneSegment, SealedBlock, SealedBlockWithSenders, StageCheckpoint,
+    StorageEntry, TransactionSignedEcRecovered, TransitionId, TxNumber, H256, U256,
 };
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet},
@@ -32,6 +33,9 @@ use std::{
 
 /// A container for any DB transaction that will open a new inner transaction when the current
 /// one is committed.
+///
+/// A lazy container (see [Transaction::new_lazy]) only opens its inner transaction once it is
+/// used, so it doesn't hold the write lock of the database before that.
 // NOTE: This container is needed since `Transaction::commit` takes `mut self`, so methods in
 // the pipeline that just take a reference will not be able to commit their transaction and let
 // the pipeline continue. Is there a better way to do this?
@@ -41,7 +45,9 @@ use std::{
 pub struct Transaction<'this, DB: Database> {
     /// A handle to the DB.
     pub(crate) db: &'this DB,
-    tx: Option<<DB as DatabaseGAT<'this>>::TXMut>,
+    tx: OnceCell<<DB as DatabaseGAT<'this>>::TXMut>,
+    /// Whether the inner transaction is opened on first use.
+    lazy: bool,
 }
 
 impl<'a, DB: Database> Debug for Transaction<'a, DB> {
@@ -59,8 +65,13 @@ impl<'a, DB: Database> Deref for Transaction<'a, DB> {
     ///
     /// Panics if an inner transaction does not exist. This should never be the case unless
     /// [Transaction::close] was called without following up with a call to [Transaction::open].
+    ///
+    /// A lazy container opens the inner transaction instead, and panics if that fails.
     fn deref(&self) -> &Self::Target {
-        self.tx.as_ref().expect("Tried getting a reference to a non-existent transaction")
+        if self.lazy {
+            return self.tx.get_or_init(|| self.db.tx_mut().expect("Failed to open a transaction"))
+        }
+        self.tx.get().expect("Tried getting a reference to a non-existent transaction")
     }
 }
 
@@ -71,8 +82,13 @@ impl<'a, DB: Database> DerefMut for Transaction<'a, DB> {
     ///
     /// Panics if an inner transaction does not exist. This should never be the case unless
     /// [Transaction::close] was called without following up with a call to [Transaction::open].
+    ///
+    /// A lazy container opens the inner transaction instead, and panics if that fails.
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.tx.as_mut().expect("Tried getting a mutable reference to a non-existent transaction")
+        if self.lazy && self.tx.get().is_none() {
+            let _ = self.tx.set(self.db.tx_mut().expect("Failed to open a transaction"));
+        }
+        self.tx.get_mut().expect("Tried getting a mutable reference to a non-existent transaction")
     }
 }
 
@@ -84,12 +100,20 @@ where
     ///
     /// A new inner transaction will be opened.
     pub fn new(db: &'this DB) -> Result<Self, DbError> {
-        Ok(Self { db, tx: Some(db.tx_mut()?) })
+        Ok(Self { db, tx: OnceCell::with_value(db.tx_mut()?), lazy: false })
+    }
+
+    /// Create a new container with the given database handle, which opens its inner transaction
+    /// once it is used.
+    ///
+    /// The inner transaction is not reopened after a commit until it is used again.
+    pub fn new_lazy(db: &'this DB) -> Self {
+        Self { db, tx: OnceCell::new(), lazy: true }
     }
 
     /// Creates a new container with given database and transaction handles.
     pub fn new_raw(db: &'this DB, tx: <DB as DatabaseGAT<'this>>::TXMut) -> Self {
-        Self { db, tx: Some(tx) }
+        Self { db, tx: OnceCell::with_value(tx), lazy: false }
     }
 
     /// Accessor to the internal Database
@@ -99,13 +123,17 @@ where
 
     /// Commit the current inner transaction and open a new one.
     ///
+    /// A lazy container opens the new one once it is used.
+    ///
     /// # Panics
     ///
     /// Panics if an inner transaction does not exist. This should never be the case unless
     /// [Transaction::close] was called without following up with a call to [Transaction::open].
     pub fn commit(&mut self) -> Result<bool, DbError> {
         let success = if let Some(tx) = self.tx.take() { tx.commit()? } else { false };
-        self.tx = Some(self.db.tx_mut()?);
+        if !self.lazy {
+            self.open()?;
+        }
         Ok(success)
     }
 
@@ -115,14 +143,16 @@ where
             drop(tx);
         }
 
-        self.tx = Some(self.db.tx_mut()?);
+        if !self.lazy {
+            self.open()?;
+        }
 
         Ok(())
     }
 
     /// Open a new inner transaction.
     pub fn open(&mut self) -> Result<(), DbError> {
-        self.tx = Some(self.db.tx_mut()?);
+        self.tx = OnceCell::with_value(self.db.tx_mut()?);
         Ok(())
     }
 
@@ -266,6 +296,184 @@ where
         }
         Ok(Vec::new())
     }
+
+    /// Returns the prune checkpoint of the segment, if it was pruned before.
+    pub fn get_prune_checkpoint(
+        &self,
+        segment: PruneSegment,
+    ) -> Result<Option<PruneCheckpoint>, DbError> {
+        self.get::<tables::PruneCheckpoints>(segment)
+    }
+
+    /// Prunes the data of the segment for all blocks up to and including `to_block` and updates the
+    /// prune checkpoint of the segment.
+    ///
+    /// Data that was pruned before is skipped. Returns the number of deleted entries.
+    pub fn prune_segment(
+        &self,
+        segment: PruneSegment,
+        to_block: BlockNumber,
+    ) -> Result<usize, TransactionError> {
+        let checkpoint = self.get_prune_checkpoint(segment)?;
+        if checkpoint.map_or(false, |checkpoint| checkpoint.block_number >= to_block) {
+            return Ok(0)
+        }
+
+        let (deleted, tx_number) = match segment {
+            PruneSegment::Receipts | PruneSegment::TransactionLookup => {
+                let start = checkpoint
+                    .and_then(|checkpoint| checkpoint.tx_number)
+                    .map_or(0, |tx_number| tx_number + 1);
+                let body = self.get_block_body(to_block)?;
+                let end = body.start_tx_id + body.tx_count;
+
+                let deleted = if segment == PruneSegment::Receipts {
+                    self.prune_receipts(start..end)?
+                } else {
+                    self.prune_transaction_lookup(start..end)?
+                };
+                (deleted, end.checked_sub(1))
+            }
+            PruneSegment::AccountHistory => {
+                (self.prune_account_history(self.get_block_transition(to_block)?)?, None)
+            }
+            PruneSegment::StorageHistory => {
+                (self.prune_storage_history(self.get_block_transition(to_block)?)?, None)
+            }
+        };
+
+        self.put::<tables::PruneCheckpoints>(
+            segment,
+            PruneCheckpoint { block_number: to_block, tx_number },
+        )?;
+        Ok(deleted)
+    }
+
+    /// Deletes the receipts of the transactions in the range.
+    fn prune_receipts(&self, range: Range<TxNumber>) -> Result<usize, TransactionError> {
+        let mut cursor = self.cursor_write::<tables::Receipts>()?;
+        let mut walker = cursor.walk_range(range)?;
+        let mut deleted = 0;
+        while walker.next().transpose()?.is_some() {
+            walker.delete_current()?;
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes the hash to number lookups of the transactions in the range.
+    fn prune_transaction_lookup(&self, range: Range<TxNumber>) -> Result<usize, TransactionError> {
+        let mut cursor = self.cursor_read::<tables::Transactions>()?;
+        let mut deleted = 0;
+        for entry in cursor.walk_range(range)? {
+            let (_, transaction) = entry?;
+            if self.delete::<tables::TxHashNumber>(transaction.hash(), None)? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes all account changesets before the transition and removes them from the account
+    /// history index.
+    fn prune_account_history(
+        &self,
+        transition_id: TransitionId,
+    ) -> Result<usize, TransactionError> {
+        let mut addresses = BTreeSet::new();
+        let mut deleted = 0;
+        let mut changeset_cursor = self.cursor_dup_write::<tables::AccountChangeSet>()?;
+        let mut walker = changeset_cursor.walk_range(..transition_id)?;
+        while let Some((_, account)) = walker.next().transpose()? {
+            addresses.insert(account.address);
+            walker.delete_current()?;
+            deleted += 1;
+        }
+
+        let mut cursor = self.cursor_write::<tables::AccountHistory>()?;
+        let mut updated_shards = Vec::new();
+        for address in addresses {
+            let mut walker = cursor.walk(Some(ShardedKey::new(address, 0)))?;
+            while let Some((sharded_key, list)) = walker.next().transpose()? {
+                if sharded_key.key != address {
+                    break
+                }
+                walker.delete_current()?;
+                if sharded_key.highest_transition_id < transition_id {
+                    deleted += 1;
+                    continue
+                }
+                // boundary shard, keep the transitions that are not pruned.
+                let list = list
+                    .iter(0)
+                    .skip_while(|id| (*id as TransitionId) < transition_id)
+                    .collect::<Vec<_>>();
+                if !list.is_empty() {
+                    updated_shards.push((sharded_key, list));
+                }
+                break
+            }
+        }
+
+        for (sharded_key, list) in updated_shards {
+            cursor
+                .upsert(sharded_key, TransitionList::new(list).expect("Indices are presorted"))?;
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes all storage changesets before the transition and removes them from the storage
+    /// history index.
+    fn prune_storage_history(
+        &self,
+        transition_id: TransitionId,
+    ) -> Result<usize, TransactionError> {
+        let mut slots = BTreeSet::new();
+        let mut deleted = 0;
+        let mut changeset_cursor = self.cursor_dup_write::<tables::StorageChangeSet>()?;
+        let mut walker =
+            changeset_cursor.walk_range(..TransitionIdAddress((transition_id, Address::zero())))?;
+        while let Some((key, storage)) = walker.next().transpose()? {
+            slots.insert((key.address(), storage.key));
+            walker.delete_current()?;
+            deleted += 1;
+        }
+
+        let mut cursor = self.cursor_write::<tables::StorageHistory>()?;
+        let mut updated_shards = Vec::new();
+        for (address, storage_key) in slots {
+            let mut walker = cursor.walk(Some(StorageShardedKey::new(address, storage_key, 0)))?;
+            while let Some((storage_sharded_key, list)) = walker.next().transpose()? {
+                if storage_sharded_key.address != address ||
+                    storage_sharded_key.sharded_key.key != storage_key
+                {
+                    break
+                }
+                walker.delete_current()?;
+                if storage_sharded_key.sharded_key.highest_transition_id < transition_id {
+                    deleted += 1;
+                    continue
+                }
+                // boundary shard, keep the transitions that are not pruned.
+                let list = list
+                    .iter(0)
+                    .skip_while(|id| (*id as TransitionId) < transition_id)
+                    .collect::<Vec<_>>();
+                if !list.is_empty() {
+                    updated_shards.push((storage_sharded_key, list));
+                }
+                break
+            }
+        }
+
+        for (storage_sharded_key, list) in updated_shards {
+            cursor.upsert(
+                storage_sharded_key,
+                TransitionList::new(list).expect("Indices are presorted"),
+            )?;
+        }
+        Ok(deleted)
+    }
 }
 
 /// Stages impl
@@ -525,21 +733,20 @@ where
         let new_tip_hash = tip.hash;
         let expected_state_root = tip.state_root;
 
-        let fork_block_number = blocks.first().unwrap().number.saturating_sub(1);
+        let first_block_number = blocks.first().unwrap().number;
 
-        let first_transition_id = self.get_block_transition(fork_block_number)?;
+        let (first_tx_number, first_transition_id) = self.get_next_block_ids(first_block_number)?;
 
         let num_transitions = state.transitions_count();
 
         // Write state and changesets to the database
-        state.write_to_db(self.deref_mut(), first_transition_id)?;
+        state.write_to_db(self.deref_mut(), first_transition_id, first_tx_number)?;
 
         // Insert the blocks
         for block in blocks {
             self.insert_block(block)?;
         }
         self.insert_hashes(
-            fork_block_number,
             first_transition_id,
             first_transition_id + num_transitions as u64,
             new_tip_number,
@@ -588,13 +795,12 @@ where
     /// Calculate the hashes of all changed accounts and storages, and finally calculate the state
     /// root.
     ///
-    /// The chain goes from `fork_block_number + 1` to `current_block_number`, and hashes are
-    /// calculated from `from_transition_id` to `to_transition_id`.
+    /// The hashes are calculated from `from_transition_id` to `to_transition_id`, which are the
+    /// transitions of the chain up to `current_block_number`.
     ///
     /// The resulting state root is compared with `expected_state_root`.
     pub fn insert_hashes(
         &mut self,
-        fork_block_number: BlockNumber,
         from_transition_id: TransitionId,
         to_transition_id: TransitionId,
         current_block_number: BlockNumber,
@@ -619,11 +825,8 @@ where
 
         // merkle tree
         {
-            let current_root = self.get_header(fork_block_number)?.state_root;
-            let mut loader = DBTrieLoader::new(self.deref_mut());
-            let root = loader
-                .update_root(current_root, from_transition_id..to_transition_id)
-                .and_then(|e| e.root())?;
+            let root = StateRoot::new(self.deref())
+                .update_root_for_range(from_transition_id..to_transition_id)?;
             if root != expected_state_root {
                 return Err(TransactionError::StateTrieRootMismatch {
                     got: root,
@@ -1043,7 +1246,7 @@ where
                 next_transition_id += 1;
             }
 
-            let Some((_,block_transition)) = block_transition_iter.next() else { break};
+            let Some((_, block_transition)) = block_transition_iter.next() else { break };
             // if block transition points to 1+next transition id it means that there is block
             // changeset.
             if block_transition == next_transition_id + 1 {
@@ -1100,15 +1303,8 @@ where
             self.unwind_storage_history_indices(transition_storage_range)?;
 
             // merkle tree
-            let new_state_root;
-            {
-                let (tip_number, _) =
-                    self.cursor_read::<tables::CanonicalHeaders>()?.last()?.unwrap_or_default();
-                let current_root = self.get_header(tip_number)?.state_root;
-                let mut loader = DBTrieLoader::new(self.deref());
-                new_state_root =
-                    loader.update_root(current_root, transition_range).and_then(|e| e.root())?;
-            }
+            let new_state_root =
+                StateRoot::new(self.deref()).update_root_for_range(transition_range)?;
             // state root should be always correct as we are reverting state.
             // but for sake of double verification we will check it again.
             if new_state_root != parent_state_root {
@@ -1154,7 +1350,7 @@ where
         // iterate over
         let mut cursor = self.cursor_write::<tables::SyncStage>()?;
         while let Some((stage_name, _)) = cursor.next()? {
-            cursor.upsert(stage_name, block_number)?
+            cursor.upsert(stage_name, StageCheckpoint::new(block_number))?
         }
 
         Ok(())
@@ -1554,8 +1750,17 @@ mod test {
         insert_canonical_block, test_utils::blocks::*, ShareableDatabase, Transaction,
         TransactionsProvider,
     };
-    use reth_db::{mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut};
-    use reth_primitives::{proofs::EMPTY_ROOT, ChainSpecBuilder, TransitionId, MAINNET};
+    use reth_db::{
+        cursor::DbCursorRO,
+        mdbx::test_utils::create_test_rw_db,
+        models::{AccountBeforeTx, ShardedKey},
+        tables,
+        transaction::{DbTx, DbTxMut},
+        TransitionList,
+    };
+    use reth_primitives::{
+        Address, ChainSpecBuilder, PruneCheckpoint, PruneSegment, TransitionId, MAINNET,
+    };
     use std::{ops::DerefMut, sync::Arc};
 
     #[test]
@@ -1577,13 +1782,11 @@ mod test {
 
         insert_canonical_block(tx.deref_mut(), data.genesis.clone(), None, false).unwrap();
 
-        tx.put::<tables::AccountsTrie>(EMPTY_ROOT, vec![0x80]).unwrap();
         assert_genesis_block(&tx, data.genesis);
 
-        exec_res1.clone().write_to_db(tx.deref_mut(), 0).unwrap();
+        exec_res1.clone().write_to_db(tx.deref_mut(), 0, 0).unwrap();
         tx.insert_block(block1.clone()).unwrap();
         tx.insert_hashes(
-            genesis.number,
             0,
             exec_res1.transitions_count() as TransitionId,
             block1.number,
@@ -1601,10 +1804,9 @@ mod test {
         assert_eq!(take, vec![(block1.clone(), exec_res1.clone())]);
         assert_genesis_block(&tx, genesis.clone());
 
-        exec_res1.clone().write_to_db(tx.deref_mut(), 0).unwrap();
+        exec_res1.clone().write_to_db(tx.deref_mut(), 0, 0).unwrap();
         tx.insert_block(block1.clone()).unwrap();
         tx.insert_hashes(
-            genesis.number,
             0,
             exec_res1.transitions_count() as TransitionId,
             block1.number,
@@ -1613,13 +1815,17 @@ mod test {
         )
         .unwrap();
 
+        let (first_tx_number, _) = tx.get_next_block_ids(block2.number).unwrap();
         exec_res2
             .clone()
-            .write_to_db(tx.deref_mut(), exec_res1.transitions_count() as TransitionId)
+            .write_to_db(
+                tx.deref_mut(),
+                exec_res1.transitions_count() as TransitionId,
+                first_tx_number,
+            )
             .unwrap();
         tx.insert_block(block2.clone()).unwrap();
         tx.insert_hashes(
-            block1.number,
             exec_res1.transitions_count() as TransitionId,
             exec_res2.transitions_count() as TransitionId,
             2,
@@ -1688,7 +1894,6 @@ mod test {
 
         insert_canonical_block(tx.deref_mut(), data.genesis.clone(), None, false).unwrap();
 
-        tx.put::<tables::AccountsTrie>(EMPTY_ROOT, vec![0x80]).unwrap();
         assert_genesis_block(&tx, data.genesis);
 
         tx.append_blocks_with_post_state(vec![block1.clone()], exec_res1.clone()).unwrap();
@@ -1729,4 +1934,48 @@ mod test {
         // assert genesis state
         assert_genesis_block(&tx, genesis);
     }
+
+    #[test]
+    fn prune_account_history() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        let address = Address::random();
+        let account = AccountBeforeTx { address, info: None };
+
+        // every block has a single transition
+        for block_number in 0..4 {
+            tx.put::<tables::BlockTransitionIndex>(block_number, block_number + 1).unwrap();
+            tx.put::<tables::AccountChangeSet>(block_number, account.clone()).unwrap();
+        }
+        tx.put::<tables::AccountHistory>(
+            ShardedKey::new(address, 1),
+            TransitionList::new([0, 1]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::AccountHistory>(
+            ShardedKey::new(address, u64::MAX),
+            TransitionList::new([2, 3]).unwrap(),
+        )
+        .unwrap();
+
+        // three changesets and the first shard
+        assert_eq!(tx.prune_segment(PruneSegment::AccountHistory, 2).unwrap(), 4);
+        assert_eq!(tx.table::<tables::AccountChangeSet>().unwrap(), vec![(3, account)]);
+        let shards = tx
+            .cursor_read::<tables::AccountHistory>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|entry| entry.map(|(key, list)| (key, list.iter(0).collect::<Vec<_>>())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(shards, vec![(ShardedKey::new(address, u64::MAX), vec![3])]);
+        assert_eq!(
+            tx.get_prune_checkpoint(PruneSegment::AccountHistory).unwrap(),
+            Some(PruneCheckpoint { block_number: 2, tx_number: None })
+        );
+
+        // already pruned
+        assert_eq!(tx.prune_segment(PruneSegment::AccountHistory, 1).unwrap(), 0);
+    }
 }
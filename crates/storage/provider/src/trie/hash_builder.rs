@@ -0,0 +1,348 @@
+use super::nibbles::Nibbles;
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, H256};
+use reth_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use std::collections::BTreeMap;
+
+/// The pending item of a [HashBuilder].
+#[derive(Debug, Clone)]
+enum HashBuilderValue {
+    /// The RLP-encoded value of a leaf.
+    Leaf(Vec<u8>),
+    /// The reference to the branch node at the root of an unchanged subtrie.
+    Branch(Vec<u8>),
+}
+
+/// Computes the root of a Merkle Patricia Trie from its leaves, which have to be added in
+/// ascending order of their keys.
+///
+/// Nodes are built as soon as the next key shows that their subtrie is complete, so only the
+/// references of the nodes whose parent isn't built yet are kept in memory. Subtries that didn't
+/// change since the last computation don't have to be walked: they are added as a whole with the
+/// reference to their root branch node, see [HashBuilder::add_branch].
+///
+/// Every branch node that is built is recorded by its path, so it can be stored and reused by the
+/// next computation.
+///
+/// The hash builder can also collect the proof of a key, see [HashBuilder::with_proof_target].
+#[derive(Debug, Default)]
+pub struct HashBuilder {
+    /// The key of the pending item.
+    key: Nibbles,
+    /// The pending item, added to the trie once the next key is known.
+    value: Option<HashBuilderValue>,
+    /// The children of the branch nodes that are being built, one mask per depth.
+    groups: Vec<u16>,
+    /// The references of the nodes whose parent isn't built yet.
+    stack: Vec<Vec<u8>>,
+    /// The branch nodes built so far, by path.
+    updates: BTreeMap<Nibbles, Vec<u8>>,
+    /// The key whose proof is collected.
+    proof_target: Option<Nibbles>,
+    /// The RLP of the nodes built on the path to the proof target, by path.
+    proof_nodes: BTreeMap<Nibbles, Vec<u8>>,
+}
+
+impl HashBuilder {
+    /// Creates a hash builder that collects the proof of the key, see [HashBuilder::take_proof].
+    ///
+    /// Only the nodes that are built are part of the proof, so the key must not be below a branch
+    /// node added with [HashBuilder::add_branch].
+    pub fn with_proof_target(key: Nibbles) -> Self {
+        Self { proof_target: Some(key), ..Default::default() }
+    }
+
+    /// Takes the proof of the target key: the RLP of the nodes on its path, starting at the root.
+    ///
+    /// Nodes that are inlined in their parent aren't part of the proof, apart from the root.
+    pub fn take_proof(&mut self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut self.proof_nodes)
+            .into_iter()
+            .filter(|(path, node)| path.is_empty() || node.len() >= H256::len_bytes())
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// Adds a leaf with the RLP-encoded value.
+    pub fn add_leaf(&mut self, key: Nibbles, value: &[u8]) {
+        self.add(key, HashBuilderValue::Leaf(value.to_vec()));
+    }
+
+    /// Adds the subtrie under the branch node at the path, given the reference to the node.
+    ///
+    /// No other key with the path as prefix may be added.
+    pub fn add_branch(&mut self, path: Nibbles, node: Vec<u8>) {
+        if path.is_empty() {
+            // the branch node is the root of the trie
+            assert!(self.key.is_empty() && self.stack.is_empty(), "root added to a non-empty trie");
+            self.stack.push(node);
+            return
+        }
+        self.add(path, HashBuilderValue::Branch(node));
+    }
+
+    /// Returns the root of the trie, building the remaining nodes.
+    pub fn root(&mut self) -> H256 {
+        if !self.key.is_empty() {
+            self.update(&Nibbles::default());
+            self.key = Nibbles::default();
+            self.value = None;
+        }
+
+        match self.stack.last() {
+            Some(node) if node.len() == H256::len_bytes() + 1 => H256::from_slice(&node[1..]),
+            Some(node) => keccak256(node),
+            None => EMPTY_ROOT,
+        }
+    }
+
+    /// Returns the number of branch nodes built since the last [HashBuilder::take_updates].
+    pub fn pending_updates(&self) -> usize {
+        self.updates.len()
+    }
+
+    /// Takes the branch nodes built so far, by path.
+    pub fn take_updates(&mut self) -> BTreeMap<Nibbles, Vec<u8>> {
+        std::mem::take(&mut self.updates)
+    }
+
+    fn add(&mut self, key: Nibbles, value: HashBuilderValue) {
+        assert!(key > self.key, "keys have to be added in ascending order");
+        if !self.key.is_empty() {
+            self.update(&key);
+        }
+        self.key = key;
+        self.value = Some(value);
+    }
+
+    /// Builds the nodes of the pending item that are complete, given the key that follows it.
+    ///
+    /// An empty key means that there is no item left.
+    fn update(&mut self, succeeding: &Nibbles) {
+        let mut build_extensions = false;
+        let mut current = self.key.clone();
+
+        loop {
+            let preceding_exists = !self.groups.is_empty();
+            let preceding_len = self.groups.len().saturating_sub(1);
+
+            let common_prefix_len = succeeding.common_prefix_length(&current);
+            let len = std::cmp::max(preceding_len, common_prefix_len);
+            assert!(len < current.len(), "keys of the trie can't be prefixes of each other");
+
+            // the pending node is a child of the branch node at `len`
+            if self.groups.len() <= len {
+                self.groups.resize(len + 1, 0);
+            }
+            self.groups[len] |= 1 << current[len];
+
+            let mut len_from = len;
+            if !succeeding.is_empty() || preceding_exists {
+                len_from += 1;
+            }
+            let short_node_key = current.slice_from(len_from);
+            let node_path = current.prefix(len_from);
+
+            if !build_extensions {
+                match self.value.as_ref().expect("pending item is set") {
+                    HashBuilderValue::Leaf(value) => {
+                        let node = leaf_node_rlp(&short_node_key, value);
+                        self.retain_proof_node(&node_path, &node);
+                        self.stack.push(rlp_node(node));
+                    }
+                    HashBuilderValue::Branch(node) => {
+                        self.stack.push(node.clone());
+                        build_extensions = true;
+                    }
+                }
+            }
+
+            if build_extensions && !short_node_key.is_empty() {
+                let child = self.stack.pop().expect("stack holds the child of the extension");
+                let node = extension_node_rlp(&short_node_key, &child);
+                self.retain_proof_node(&node_path, &node);
+                self.stack.push(rlp_node(node));
+            }
+
+            // the branch node is only complete once the succeeding key leaves its subtrie
+            if preceding_len <= common_prefix_len && !succeeding.is_empty() {
+                return
+            }
+
+            if !succeeding.is_empty() || preceding_exists {
+                self.push_branch_node(current.prefix(len), self.groups[len]);
+            }
+
+            self.groups.truncate(len);
+            if preceding_len == 0 {
+                return
+            }
+
+            current = current.prefix(preceding_len);
+            while self.groups.last() == Some(&0) {
+                self.groups.pop();
+            }
+            build_extensions = true;
+        }
+    }
+
+    /// Replaces the children on top of the stack with the branch node at the path.
+    fn push_branch_node(&mut self, path: Nibbles, children: u16) {
+        let first_child = self.stack.len() - children.count_ones() as usize;
+        let node = branch_node_rlp(&self.stack[first_child..], children);
+        self.retain_proof_node(&path, &node);
+        let node = rlp_node(node);
+        self.stack.truncate(first_child);
+        self.stack.push(node.clone());
+        self.updates.insert(path, node);
+    }
+
+    /// Keeps the RLP of the node at the path if it's on the path to the proof target.
+    fn retain_proof_node(&mut self, path: &Nibbles, node: &[u8]) {
+        if self.proof_target.as_ref().map_or(false, |target| target.has_prefix(path)) {
+            self.proof_nodes.insert(path.clone(), node.to_vec());
+        }
+    }
+}
+
+/// Returns the reference to a node: its RLP if it's shorter than 32 bytes, the RLP-encoded hash of
+/// the RLP otherwise.
+fn rlp_node(rlp: Vec<u8>) -> Vec<u8> {
+    if rlp.len() < H256::len_bytes() {
+        return rlp
+    }
+    let mut node = Vec::with_capacity(H256::len_bytes() + 1);
+    keccak256(&rlp).as_bytes().encode(&mut node);
+    node
+}
+
+fn leaf_node_rlp(path: &Nibbles, value: &[u8]) -> Vec<u8> {
+    let path = path.encode_path(true);
+    let payload_length = path.as_slice().length() + value.length();
+    let mut out = Vec::with_capacity(payload_length + 3);
+    Header { list: true, payload_length }.encode(&mut out);
+    path.as_slice().encode(&mut out);
+    value.encode(&mut out);
+    out
+}
+
+fn extension_node_rlp(path: &Nibbles, child: &[u8]) -> Vec<u8> {
+    let path = path.encode_path(false);
+    let payload_length = path.as_slice().length() + child.len();
+    let mut out = Vec::with_capacity(payload_length + 3);
+    Header { list: true, payload_length }.encode(&mut out);
+    path.as_slice().encode(&mut out);
+    out.extend_from_slice(child);
+    out
+}
+
+fn branch_node_rlp(children: &[Vec<u8>], mask: u16) -> Vec<u8> {
+    let mut children = children.iter();
+    let mut payload = Vec::with_capacity(children.len() * (H256::len_bytes() + 1) + 16);
+    for nibble in 0..16 {
+        if mask & (1 << nibble) != 0 {
+            payload.extend_from_slice(children.next().expect("child of the branch node"));
+        } else {
+            payload.push(EMPTY_STRING_CODE);
+        }
+    }
+    // branch nodes of hashed keys never have a value
+    payload.push(EMPTY_STRING_CODE);
+
+    let mut out = Vec::with_capacity(payload.len() + 3);
+    Header { list: true, payload_length: payload.len() }.encode(&mut out);
+    out.extend_from_slice(&payload);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{prelude::ProptestConfig, proptest};
+    use reth_primitives::{proofs::KeccakHasher, StorageEntry};
+    use reth_rlp::encode_fixed_size;
+    use std::collections::BTreeSet;
+    use triehash::trie_root;
+
+    fn expected_root(leaves: &BTreeMap<H256, Vec<u8>>) -> H256 {
+        H256(trie_root::<KeccakHasher, _, _, _>(leaves.iter().map(|(k, v)| (k.0, v.clone()))).0)
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(HashBuilder::default().root(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn matches_triehash() {
+        proptest!(ProptestConfig::with_cases(100), |(storage: BTreeSet<StorageEntry>)| {
+            let leaves = storage
+                .into_iter()
+                .map(|StorageEntry { key, value }| (key, encode_fixed_size(&value).to_vec()))
+                .collect::<BTreeMap<_, _>>();
+
+            let mut hb = HashBuilder::default();
+            for (key, value) in &leaves {
+                hb.add_leaf(Nibbles::unpack(key), value);
+            }
+            assert_eq!(hb.root(), expected_root(&leaves));
+        });
+    }
+
+    #[test]
+    fn shared_prefixes_and_inline_nodes() {
+        // keys sharing long prefixes with short values produce nodes that are inlined in their
+        // parents
+        let leaves = (0..40u8)
+            .map(|i| {
+                let mut key = [0u8; 32];
+                key[31] = i & 0x13;
+                key[30] = i / 7;
+                (H256(key), vec![i])
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut hb = HashBuilder::default();
+        for (key, value) in &leaves {
+            hb.add_leaf(Nibbles::unpack(key), value);
+        }
+        assert_eq!(hb.root(), expected_root(&leaves));
+    }
+
+    #[test]
+    fn add_unchanged_branches() {
+        let leaves = (0..100u64)
+            .map(|i| (keccak256(H256::from_low_u64_be(i)), vec![0x80 + (i % 50) as u8]))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut hb = HashBuilder::default();
+        for (key, value) in &leaves {
+            hb.add_leaf(Nibbles::unpack(key), value);
+        }
+        let root = hb.root();
+        let branches = hb.take_updates();
+        assert_eq!(root, expected_root(&leaves));
+        assert!(branches.contains_key(&Nibbles::default()));
+
+        // replace the subtries of the first level by their branch nodes
+        let mut hb = HashBuilder::default();
+        let mut replaced = 0;
+        for nibble in 0..16 {
+            let path = Nibbles::from(vec![nibble]);
+            if let Some(node) = branches.get(&path) {
+                hb.add_branch(path, node.clone());
+                replaced += 1;
+            } else {
+                for (key, value) in leaves.iter().filter(|(key, _)| key.0[0] >> 4 == nibble) {
+                    hb.add_leaf(Nibbles::unpack(key), value);
+                }
+            }
+        }
+        assert!(replaced > 0);
+        assert_eq!(hb.root(), root);
+
+        // the root node alone is the whole trie
+        let mut hb = HashBuilder::default();
+        hb.add_branch(Nibbles::default(), branches[&Nibbles::default()].clone());
+        assert_eq!(hb.root(), root);
+    }
+}
@@ -0,0 +1,161 @@
+use reth_primitives::{StoredNibbles, StoredNibblesSubKey, H256};
+use std::ops::Deref;
+
+/// The path of a node in a Merkle Patricia Trie, with one nibble per byte.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nibbles(Vec<u8>);
+
+impl Nibbles {
+    /// Unpacks the bytes of a key into its nibbles.
+    pub fn unpack(bytes: impl AsRef<[u8]>) -> Self {
+        Self(bytes.as_ref().iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect())
+    }
+
+    /// Packs the nibbles into a hashed key, padding the missing nibbles with zeros.
+    ///
+    /// The result is the lowest key that has the nibbles as prefix.
+    pub fn pack_padded(&self) -> H256 {
+        let mut key = [0u8; 32];
+        for (index, nibble) in self.0.iter().take(64).enumerate() {
+            key[index / 2] |= if index % 2 == 0 { nibble << 4 } else { *nibble };
+        }
+        H256(key)
+    }
+
+    /// Returns the length of the common prefix of two paths.
+    pub fn common_prefix_length(&self, other: &Self) -> usize {
+        self.0.iter().zip(other.0.iter()).take_while(|(a, b)| a == b).count()
+    }
+
+    /// Returns true if the path starts with the given prefix.
+    pub fn has_prefix(&self, prefix: &Self) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Returns the nibbles from `start` to the end of the path.
+    pub fn slice_from(&self, start: usize) -> Self {
+        Self(self.0[start..].to_vec())
+    }
+
+    /// Returns the first `len` nibbles of the path.
+    pub fn prefix(&self, len: usize) -> Self {
+        Self(self.0[..len].to_vec())
+    }
+
+    /// Returns the path extended by one nibble.
+    pub fn join(&self, nibble: u8) -> Self {
+        let mut nibbles = self.0.clone();
+        nibbles.push(nibble);
+        Self(nibbles)
+    }
+
+    /// Returns the lowest path that is greater than all paths with this prefix, or `None` if
+    /// there is no such path.
+    pub fn increment(&self) -> Option<Self> {
+        let mut nibbles = self.0.clone();
+        while let Some(last) = nibbles.pop() {
+            if last < 0x0f {
+                nibbles.push(last + 1);
+                return Some(Self(nibbles))
+            }
+        }
+        None
+    }
+
+    /// Encodes the path with the hex-prefix encoding of leaf and extension nodes.
+    pub fn encode_path(&self, is_leaf: bool) -> Vec<u8> {
+        let odd = self.0.len() % 2 == 1;
+        let flag = if is_leaf { 0x20 } else { 0x00 } | if odd { 0x10 } else { 0x00 };
+
+        let mut encoded = Vec::with_capacity(self.0.len() / 2 + 1);
+        let rest = if odd {
+            encoded.push(flag | self.0[0]);
+            &self.0[1..]
+        } else {
+            encoded.push(flag);
+            &self.0[..]
+        };
+        encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+        encoded
+    }
+}
+
+impl Deref for Nibbles {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Nibbles {
+    fn from(nibbles: Vec<u8>) -> Self {
+        Self(nibbles)
+    }
+}
+
+impl From<StoredNibbles> for Nibbles {
+    fn from(stored: StoredNibbles) -> Self {
+        Self(stored.inner)
+    }
+}
+
+impl From<Nibbles> for StoredNibbles {
+    fn from(nibbles: Nibbles) -> Self {
+        nibbles.0.into()
+    }
+}
+
+impl From<StoredNibblesSubKey> for Nibbles {
+    fn from(stored: StoredNibblesSubKey) -> Self {
+        stored.0.into()
+    }
+}
+
+impl From<Nibbles> for StoredNibblesSubKey {
+    fn from(nibbles: Nibbles) -> Self {
+        nibbles.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_and_unpack() {
+        let key = H256::from_low_u64_be(0x12ab);
+        let nibbles = Nibbles::unpack(key);
+        assert_eq!(nibbles.len(), 64);
+        assert_eq!(nibbles[60..], [1, 2, 10, 11]);
+        assert_eq!(nibbles.pack_padded(), key);
+        assert_eq!(Nibbles::from(vec![0xf, 0x1]).pack_padded().0[0], 0xf1);
+        assert_eq!(Nibbles::from(vec![0xf]).pack_padded().0[0], 0xf0);
+    }
+
+    #[test]
+    fn increment() {
+        assert_eq!(Nibbles::from(vec![1, 2]).increment(), Some(vec![1, 3].into()));
+        assert_eq!(Nibbles::from(vec![1, 0xf]).increment(), Some(vec![2].into()));
+        assert_eq!(Nibbles::from(vec![0xf, 0xf]).increment(), None);
+        assert_eq!(Nibbles::default().increment(), None);
+    }
+
+    #[test]
+    fn encode_path() {
+        // examples from the yellow paper, appendix C
+        assert_eq!(Nibbles::from(vec![1, 2, 3, 4, 5]).encode_path(false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(
+            Nibbles::from(vec![0, 1, 2, 3, 4, 5]).encode_path(false),
+            vec![0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            Nibbles::from(vec![0xf, 1, 0xc, 0xb, 8]).encode_path(true),
+            vec![0x3f, 0x1c, 0xb8]
+        );
+        assert_eq!(
+            Nibbles::from(vec![0, 0xf, 1, 0xc, 0xb, 8]).encode_path(true),
+            vec![0x20, 0x0f, 0x1c, 0xb8]
+        );
+    }
+}
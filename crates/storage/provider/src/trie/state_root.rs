@@ -0,0 +1,1695 @@
+use super::{
+    hash_builder::HashBuilder, nibbles::Nibbles, EthAccount, HashedStateChanges,
+    HashedStorageChanges, MerkleProof, TrieError, MERKLE_STAGE_ID,
+};
+use crate::ProviderError;
+use rayon::prelude::*;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    database::Database,
+    models::{AccountBeforeTx, TransitionIdAddress},
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{
+    keccak256, Address, StorageBranchEntry, StorageEntry, StoredNibblesSubKey, TransitionId, H256,
+    KECCAK_EMPTY, U256,
+};
+use reth_rlp::{encode_fixed_size, Encodable};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter::Peekable,
+    ops::Range,
+};
+
+/// The number of built branch nodes that are kept in memory before they are written.
+const MAX_PENDING_BRANCHES: usize = 10_000;
+
+/// The number of accounts whose storage tries are computed in parallel before being written, see
+/// [StateRoot::update_root_parallel].
+const PARALLEL_STORAGE_TRIES_CHUNK: usize = 1_000;
+
+/// Calculates the state root from the hashed state in [tables::HashedAccount] and
+/// [tables::HashedStorage].
+///
+/// The branch nodes of the state trie and of the storage tries are stored by their path in
+/// [tables::AccountBranches] and [tables::StorageBranches]. When the root is updated, the
+/// subtries without changed keys are taken from their stored branch nodes, so only the paths to
+/// the changed keys are rebuilt. Branch nodes that don't exist anymore are removed.
+///
+/// The stored branch nodes are those of the state at the merkle stage checkpoint, see
+/// [StateRoot::trie_root]. Roots and proofs of other states are computed by applying the
+/// differences to that state in memory, see [StateRoot::state_root_at].
+#[derive(Debug)]
+pub struct StateRoot<'tx, TX> {
+    /// The transaction to read the hashed state and the branch nodes.
+    tx: &'tx TX,
+}
+
+impl<'tx, TX> StateRoot<'tx, TX> {
+    /// Creates a new state root calculator.
+    pub fn new(tx: &'tx TX) -> Self {
+        Self { tx }
+    }
+}
+
+// Read-write impls
+impl<'tx, 'db, TX> StateRoot<'tx, TX>
+where
+    TX: DbTxMut<'db> + DbTx<'db> + Send + Sync,
+{
+    /// Calculates the state root from scratch, replacing all stored branch nodes.
+    pub fn calculate_root(&self) -> Result<H256, TrieError> {
+        self.clear()?;
+
+        let mut accounts = self.tx.cursor_read::<tables::HashedAccount>()?;
+        let mut walker = accounts.walk(None)?;
+        while let Some((hashed_address, _)) = walker.next().transpose()? {
+            self.write_storage_trie(storage_trie_update(self.tx, hashed_address, None)?)?;
+        }
+
+        self.update_account_root(&BTreeMap::new())
+    }
+
+    /// Calculates the state root from scratch like [StateRoot::calculate_root], computing the
+    /// storage tries of the accounts in parallel.
+    ///
+    /// See [StateRoot::update_root_parallel] for the requirements on `db`.
+    pub fn calculate_root_parallel<R: Database>(&self, db: &R) -> Result<H256, TrieError> {
+        self.clear()?;
+
+        let mut accounts = self.tx.cursor_read::<tables::HashedAccount>()?;
+        let mut walker = accounts.walk(None)?;
+        loop {
+            let chunk = walker
+                .by_ref()
+                .take(PARALLEL_STORAGE_TRIES_CHUNK)
+                .map(|entry| entry.map(|(hashed_address, _)| hashed_address))
+                .collect::<Result<Vec<_>, _>>()?;
+            if chunk.is_empty() {
+                break
+            }
+
+            let updates = chunk
+                .into_par_iter()
+                .map_init(
+                    || db.tx(),
+                    |reader, hashed_address| {
+                        let reader = reader.as_ref().map_err(Clone::clone)?;
+                        storage_trie_update(reader, hashed_address, None)
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+            for update in updates {
+                self.write_storage_trie(update)?;
+            }
+        }
+
+        self.update_account_root(&BTreeMap::new())
+    }
+
+    /// Updates the state root after the changes to the hashed state within the transition range.
+    pub fn update_root_for_range(&self, tid_range: Range<TransitionId>) -> Result<H256, TrieError> {
+        let changes = self.gather_changes(tid_range)?;
+        self.update_root(&changes)
+    }
+
+    /// Updates the state root after changes to the hashed state, given as the changed storage
+    /// keys by changed account, both hashed.
+    ///
+    /// The stored branch nodes have to be those of the state before the changes.
+    pub fn update_root(&self, changes: &BTreeMap<H256, BTreeSet<H256>>) -> Result<H256, TrieError> {
+        for (hashed_address, keys) in self.changed_storages(changes)? {
+            self.write_storage_trie(storage_trie_update(self.tx, hashed_address, Some(keys))?)?;
+        }
+
+        self.update_account_root(changes)
+    }
+
+    /// Updates the state root like [StateRoot::update_root], computing the storage tries of the
+    /// changed accounts in parallel.
+    ///
+    /// The storage tries are computed by the workers with their own read-only transactions of `db`,
+    /// which have to see the same hashed storage and the same storage branch nodes as the
+    /// transaction of the calculator. Their branch nodes are then written with the transaction of
+    /// the calculator.
+    pub fn update_root_parallel<R: Database>(
+        &self,
+        db: &R,
+        changes: &BTreeMap<H256, BTreeSet<H256>>,
+    ) -> Result<H256, TrieError> {
+        for chunk in self.changed_storages(changes)?.chunks(PARALLEL_STORAGE_TRIES_CHUNK) {
+            let updates = chunk
+                .par_iter()
+                .map_init(
+                    || db.tx(),
+                    |reader, (hashed_address, keys)| {
+                        let reader = reader.as_ref().map_err(Clone::clone)?;
+                        storage_trie_update(reader, *hashed_address, Some(*keys))
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+            for update in updates {
+                self.write_storage_trie(update)?;
+            }
+        }
+
+        self.update_account_root(changes)
+    }
+
+    /// Removes the storage tries of the removed accounts, and returns the changed storage keys of
+    /// the remaining accounts.
+    fn changed_storages<'a>(
+        &self,
+        changes: &'a BTreeMap<H256, BTreeSet<H256>>,
+    ) -> Result<Vec<(H256, &'a BTreeSet<H256>)>, TrieError> {
+        let mut accounts = self.tx.cursor_read::<tables::HashedAccount>()?;
+        let mut changed = Vec::new();
+        for (hashed_address, keys) in changes {
+            if accounts.seek_exact(*hashed_address)?.is_none() {
+                // the storage tries of removed accounts aren't reached by the walk
+                self.tx.delete::<tables::StorageBranches>(*hashed_address, None)?;
+            } else if !keys.is_empty() {
+                changed.push((*hashed_address, keys));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Writes the changes to the branch nodes of a storage trie.
+    fn write_storage_trie(&self, update: StorageTrieUpdate) -> Result<(), TrieError> {
+        let StorageTrieUpdate { hashed_address, removed, branches } = update;
+        let mut cursor = self.tx.cursor_dup_write::<tables::StorageBranches>()?;
+
+        // the subkey of a dup table is part of the value, so updated nodes are removed first
+        for path in removed.iter().chain(branches.keys()) {
+            let nibbles = StoredNibblesSubKey::from(path.clone());
+            if cursor
+                .seek_by_key_subkey(hashed_address, nibbles.clone())?
+                .filter(|entry| entry.nibbles == nibbles)
+                .is_some()
+            {
+                cursor.delete_current()?;
+            }
+        }
+        for (path, node) in branches {
+            cursor.upsert(hashed_address, StorageBranchEntry { nibbles: path.into(), node })?;
+        }
+        Ok(())
+    }
+
+    /// Updates the state trie after the changes to the accounts, once their storage tries are up
+    /// to date.
+    fn update_account_root(
+        &self,
+        changes: &BTreeMap<H256, BTreeSet<H256>>,
+    ) -> Result<H256, TrieError> {
+        let changed: BTreeSet<Nibbles> = changes.keys().map(Nibbles::unpack).collect();
+        walk(&mut AccountTrieMut { tx: self.tx }, &changed, &mut HashBuilder::default())
+    }
+
+    fn clear(&self) -> Result<(), TrieError> {
+        self.tx.clear::<tables::AccountBranches>()?;
+        self.tx.clear::<tables::StorageBranches>()?;
+        Ok(())
+    }
+}
+
+// Read-only impls
+impl<'tx, 'db, TX> StateRoot<'tx, TX>
+where
+    TX: DbTx<'db>,
+{
+    /// Returns the root of the state trie from the stored branch nodes.
+    pub fn stored_root(&self) -> Result<H256, TrieError> {
+        self.overlay_root(&HashedStateChanges::default())
+    }
+
+    /// Returns the root of the state trie with the changes applied on top of the hashed state.
+    ///
+    /// The changes are only applied in memory, nothing is written to the database.
+    pub fn overlay_root(&self, changes: &HashedStateChanges) -> Result<H256, TrieError> {
+        let changed = changes.changed_keys();
+        walk(
+            &mut AccountTrie { tx: self.tx, state: changes },
+            &changed,
+            &mut HashBuilder::default(),
+        )
+    }
+
+    /// Returns a Merkle proof of the account with the changes applied on top of the hashed state,
+    /// plus its storage root hash.
+    pub fn account_proof(
+        &self,
+        changes: &HashedStateChanges,
+        hashed_address: H256,
+    ) -> Result<(MerkleProof, H256), TrieError> {
+        let key = Nibbles::unpack(hashed_address);
+        let mut changed = changes.changed_keys();
+        changed.insert(key.clone());
+
+        let mut hash_builder = HashBuilder::with_proof_target(key);
+        walk(&mut AccountTrie { tx: self.tx, state: changes }, &changed, &mut hash_builder)?;
+        let proof = hash_builder.take_proof();
+
+        let account = match changes.accounts.get(&hashed_address) {
+            Some(account) => *account,
+            None => self.tx.get::<tables::HashedAccount>(hashed_address)?,
+        };
+        if account.is_none() {
+            return Ok((proof, KECCAK_EMPTY))
+        }
+
+        let storage = changes.storages.get(&hashed_address);
+        Ok((proof, storage_root(self.tx, hashed_address, storage)?))
+    }
+
+    /// Returns Merkle proofs of the given storage keys of the account, with the changes applied on
+    /// top of the hashed state.
+    pub fn storage_proofs(
+        &self,
+        changes: &HashedStateChanges,
+        hashed_address: H256,
+        keys: &[H256],
+    ) -> Result<Vec<MerkleProof>, TrieError> {
+        let storage = changes.storages.get(&hashed_address);
+        keys.iter()
+            .map(|key| {
+                let key = Nibbles::unpack(key);
+                let mut changed =
+                    storage.map(HashedStorageChanges::changed_keys).unwrap_or_default();
+                changed.insert(key.clone());
+
+                let mut hash_builder = HashBuilder::with_proof_target(key);
+                let mut trie = StorageTrie::new(self.tx, hashed_address, storage);
+                walk(&mut trie, &changed, &mut hash_builder)?;
+                Ok(hash_builder.take_proof())
+            })
+            .collect()
+    }
+
+    /// Returns the changed storage keys by changed account within the transition range, both
+    /// hashed.
+    pub fn gather_changes(
+        &self,
+        tid_range: Range<TransitionId>,
+    ) -> Result<BTreeMap<H256, BTreeSet<H256>>, TrieError> {
+        let mut account_cursor = self.tx.cursor_read::<tables::AccountChangeSet>()?;
+
+        let mut account_changes: BTreeMap<Address, BTreeSet<H256>> = BTreeMap::new();
+
+        let mut walker = account_cursor.walk_range(tid_range.clone())?;
+
+        while let Some((_, AccountBeforeTx { address, .. })) = walker.next().transpose()? {
+            account_changes.insert(address, Default::default());
+        }
+
+        let mut storage_cursor = self.tx.cursor_dup_read::<tables::StorageChangeSet>()?;
+
+        let start = TransitionIdAddress((tid_range.start, Address::zero()));
+        let end = TransitionIdAddress((tid_range.end, Address::zero()));
+        let mut walker = storage_cursor.walk_range(start..end)?;
+
+        while let Some((TransitionIdAddress((_, address)), StorageEntry { key, .. })) =
+            walker.next().transpose()?
+        {
+            account_changes.entry(address).or_default().insert(key);
+        }
+
+        let hashed_changes = account_changes
+            .into_iter()
+            .map(|(address, storage)| {
+                (keccak256(address), storage.into_iter().map(keccak256).collect())
+            })
+            .collect();
+
+        Ok(hashed_changes)
+    }
+
+    /// Returns the root of the state trie in the database, and the transition the state of the trie
+    /// is at.
+    ///
+    /// The trie is at the end of the block of the merkle stage checkpoint, or of the canonical tip
+    /// if the stage has no checkpoint. The changes of the transition that is returned, and of the
+    /// following ones, aren't part of the trie.
+    pub fn trie_root(&self) -> Result<(H256, TransitionId), TrieError> {
+        let block_number = match self.tx.get::<tables::SyncStage>(MERKLE_STAGE_ID.to_string())? {
+            Some(checkpoint) => checkpoint.block_number,
+            None => self
+                .tx
+                .cursor_read::<tables::CanonicalHeaders>()?
+                .last()?
+                .map(|(number, _)| number)
+                .unwrap_or_default(),
+        };
+
+        let root = self
+            .tx
+            .get::<tables::Headers>(block_number)?
+            .ok_or(ProviderError::Header { number: block_number })?
+            .state_root;
+        let transition = self
+            .tx
+            .get::<tables::BlockTransitionIndex>(block_number)?
+            .ok_or(ProviderError::BlockTransition { block_number })?;
+
+        Ok((root, transition))
+    }
+
+    /// Returns the changes that take the hashed state of the trie, see [StateRoot::trie_root], to
+    /// the state before the given transition, or to the latest state if it's `None`.
+    pub fn changes_at(
+        &self,
+        transition: Option<TransitionId>,
+    ) -> Result<HashedStateChanges, TrieError> {
+        let (_, trie_transition) = self.trie_root()?;
+
+        // the accounts and slots that changed since the earlier of both states get the latest
+        // value, which is reverted if they changed again after the requested transition
+        let from = transition.map_or(trie_transition, |transition| transition.min(trie_transition));
+        let mut state = self.gather_latest_changes(from)?;
+        if let Some(transition) = transition {
+            state.extend(&self.gather_reverts(transition)?);
+        }
+        Ok(state)
+    }
+
+    /// Returns the root of the state before the given transition, or of the latest state if it's
+    /// `None`, with the changes applied on top.
+    ///
+    /// The state is computed from the state trie in the database, see [StateRoot::trie_root], so
+    /// the trie doesn't have to be up to date with the plain state.
+    pub fn state_root_at(
+        &self,
+        transition: Option<TransitionId>,
+        changes: &HashedStateChanges,
+    ) -> Result<H256, TrieError> {
+        let mut state = self.changes_at(transition)?;
+        if state.is_empty() {
+            // the trie is at the requested state
+            return self.overlay_root(changes)
+        }
+        state.extend(changes);
+
+        self.overlay_root(&state)
+    }
+
+    /// Returns the changes setting the hashed state of the accounts and slots that changed since
+    /// the given transition to their latest values in the plain state.
+    pub fn gather_latest_changes(
+        &self,
+        from: TransitionId,
+    ) -> Result<HashedStateChanges, TrieError> {
+        let mut changes = HashedStateChanges::default();
+
+        let mut plain_accounts = self.tx.cursor_read::<tables::PlainAccountState>()?;
+        let mut account_cursor = self.tx.cursor_read::<tables::AccountChangeSet>()?;
+        let mut walker = account_cursor.walk(Some(from))?;
+        while let Some((_, AccountBeforeTx { address, .. })) = walker.next().transpose()? {
+            let hashed_address = keccak256(address);
+            if !changes.accounts.contains_key(&hashed_address) {
+                let account = plain_accounts.seek_exact(address)?.map(|(_, account)| account);
+                changes.accounts.insert(hashed_address, account);
+            }
+        }
+
+        let mut plain_storage = self.tx.cursor_dup_read::<tables::PlainStorageState>()?;
+        let mut storage_cursor = self.tx.cursor_dup_read::<tables::StorageChangeSet>()?;
+        let mut walker = storage_cursor.walk(Some(TransitionIdAddress((from, Address::zero()))))?;
+        while let Some((TransitionIdAddress((_, address)), StorageEntry { key, .. })) =
+            walker.next().transpose()?
+        {
+            let storage = &mut changes.storages.entry(keccak256(address)).or_default().storage;
+            let hashed_key = keccak256(key);
+            if !storage.contains_key(&hashed_key) {
+                let value = plain_storage
+                    .seek_by_key_subkey(address, key)?
+                    .filter(|entry| entry.key == key)
+                    .map(|entry| entry.value)
+                    .unwrap_or(U256::ZERO);
+                storage.insert(hashed_key, value);
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Returns the changes reverting the hashed state to the state before the given transition,
+    /// from the changesets of the transitions that followed.
+    pub fn gather_reverts(&self, from: TransitionId) -> Result<HashedStateChanges, TrieError> {
+        let mut reverts = HashedStateChanges::default();
+
+        // the first changeset of each account and slot holds its value before the transition
+        let mut account_cursor = self.tx.cursor_read::<tables::AccountChangeSet>()?;
+        let mut walker = account_cursor.walk(Some(from))?;
+        while let Some((_, AccountBeforeTx { address, info })) = walker.next().transpose()? {
+            reverts.accounts.entry(keccak256(address)).or_insert(info);
+        }
+
+        let mut storage_cursor = self.tx.cursor_dup_read::<tables::StorageChangeSet>()?;
+        let mut walker = storage_cursor.walk(Some(TransitionIdAddress((from, Address::zero()))))?;
+        while let Some((TransitionIdAddress((_, address)), StorageEntry { key, value })) =
+            walker.next().transpose()?
+        {
+            reverts
+                .storages
+                .entry(keccak256(address))
+                .or_default()
+                .storage
+                .entry(keccak256(key))
+                .or_insert(value);
+        }
+
+        Ok(reverts)
+    }
+}
+
+/// A trie of the hashed state, together with its stored branch nodes.
+trait StoredTrie {
+    /// Returns the first stored branch node at or after the path.
+    fn seek_branch(&mut self, path: &Nibbles) -> Result<Option<(Nibbles, Vec<u8>)>, TrieError>;
+
+    /// Removes the stored branch node at the path.
+    fn delete_branch(&mut self, path: &Nibbles) -> Result<(), TrieError>;
+
+    /// Stores the branch nodes built by the hash builder.
+    fn store_branches(&mut self, branches: BTreeMap<Nibbles, Vec<u8>>) -> Result<(), TrieError>;
+
+    /// Adds the leaves from the key `from` up to the key `to` to the hash builder, or up to the
+    /// last leaf if `to` is `None`.
+    fn add_leaves(
+        &mut self,
+        hash_builder: &mut HashBuilder,
+        from: &Nibbles,
+        to: Option<&Nibbles>,
+    ) -> Result<(), TrieError>;
+
+    /// Adds a leaf to the hash builder, storing the built branch nodes once there are enough of
+    /// them.
+    fn add_leaf(
+        &mut self,
+        hash_builder: &mut HashBuilder,
+        key: Nibbles,
+        value: &[u8],
+    ) -> Result<(), TrieError> {
+        hash_builder.add_leaf(key, value);
+        if hash_builder.pending_updates() >= MAX_PENDING_BRANCHES {
+            self.store_branches(hash_builder.take_updates())?;
+        }
+        Ok(())
+    }
+}
+
+/// Calculates the root of the trie after the changes to the keys.
+///
+/// Stored branch nodes without changed keys in their subtrie are added to the hash builder as a
+/// whole. All others are removed, and the leaves that aren't below an unchanged branch node are
+/// added instead, rebuilding the branch nodes that still exist.
+fn walk(
+    trie: &mut impl StoredTrie,
+    changed: &BTreeSet<Nibbles>,
+    hash_builder: &mut HashBuilder,
+) -> Result<H256, TrieError> {
+    // the lowest key that is neither added nor below an added branch node
+    let mut next_key = Some(Nibbles::default());
+    let mut next_branch = trie.seek_branch(&Nibbles::default())?;
+
+    while let Some((path, node)) = next_branch {
+        let subtrie_end = path.increment();
+        if changed.range(path.clone()..).next().map_or(false, |key| key.has_prefix(&path)) {
+            // the branch node is rebuilt from its children, if it still exists
+            trie.delete_branch(&path)?;
+            next_branch = trie.seek_branch(&path.join(0))?;
+        } else {
+            if let Some(from) = &next_key {
+                trie.add_leaves(hash_builder, from, Some(&path))?;
+            }
+            hash_builder.add_branch(path, node);
+            next_branch = match &subtrie_end {
+                Some(end) => trie.seek_branch(end)?,
+                None => None,
+            };
+            next_key = subtrie_end;
+        }
+    }
+
+    if let Some(from) = &next_key {
+        trie.add_leaves(hash_builder, from, None)?;
+    }
+
+    let root = hash_builder.root();
+    trie.store_branches(hash_builder.take_updates())?;
+    Ok(root)
+}
+
+/// The state trie, with leaves from [tables::HashedAccount] and branch nodes in
+/// [tables::AccountBranches], and changes applied on top of both.
+///
+/// Nothing is written: the branch nodes built by the walk are dropped.
+struct AccountTrie<'a, 'tx, TX> {
+    tx: &'tx TX,
+    /// The changes to the hashed state.
+    state: &'a HashedStateChanges,
+}
+
+impl<'a, 'tx, 'db, TX> StoredTrie for AccountTrie<'a, 'tx, TX>
+where
+    TX: DbTx<'db>,
+{
+    fn seek_branch(&mut self, path: &Nibbles) -> Result<Option<(Nibbles, Vec<u8>)>, TrieError> {
+        seek_account_branch(self.tx, path)
+    }
+
+    fn delete_branch(&mut self, _path: &Nibbles) -> Result<(), TrieError> {
+        Ok(())
+    }
+
+    fn store_branches(&mut self, _branches: BTreeMap<Nibbles, Vec<u8>>) -> Result<(), TrieError> {
+        Ok(())
+    }
+
+    fn add_leaves(
+        &mut self,
+        hash_builder: &mut HashBuilder,
+        from: &Nibbles,
+        to: Option<&Nibbles>,
+    ) -> Result<(), TrieError> {
+        let (tx, state) = (self.tx, self.state);
+        add_account_leaves(self, tx, state, hash_builder, from, to)
+    }
+}
+
+/// The state trie, writing the branch nodes in [tables::AccountBranches].
+struct AccountTrieMut<'tx, TX> {
+    tx: &'tx TX,
+}
+
+impl<'tx, 'db, TX> StoredTrie for AccountTrieMut<'tx, TX>
+where
+    TX: DbTxMut<'db> + DbTx<'db>,
+{
+    fn seek_branch(&mut self, path: &Nibbles) -> Result<Option<(Nibbles, Vec<u8>)>, TrieError> {
+        seek_account_branch(self.tx, path)
+    }
+
+    fn delete_branch(&mut self, path: &Nibbles) -> Result<(), TrieError> {
+        self.tx.delete::<tables::AccountBranches>(path.clone().into(), None)?;
+        Ok(())
+    }
+
+    fn store_branches(&mut self, branches: BTreeMap<Nibbles, Vec<u8>>) -> Result<(), TrieError> {
+        for (path, node) in branches {
+            self.tx.put::<tables::AccountBranches>(path.into(), node)?;
+        }
+        Ok(())
+    }
+
+    fn add_leaves(
+        &mut self,
+        hash_builder: &mut HashBuilder,
+        from: &Nibbles,
+        to: Option<&Nibbles>,
+    ) -> Result<(), TrieError> {
+        let tx = self.tx;
+        add_account_leaves(self, tx, &HashedStateChanges::default(), hash_builder, from, to)
+    }
+}
+
+fn seek_account_branch<'db, TX: DbTx<'db>>(
+    tx: &TX,
+    path: &Nibbles,
+) -> Result<Option<(Nibbles, Vec<u8>)>, TrieError> {
+    let mut cursor = tx.cursor_read::<tables::AccountBranches>()?;
+    Ok(cursor.seek(path.clone().into())?.map(|(path, node)| (path.into(), node)))
+}
+
+/// Adds the accounts from the key `from` up to the key `to` to the hash builder of the state trie,
+/// with their storage roots from the stored storage tries.
+fn add_account_leaves<'db, TX: DbTx<'db>>(
+    trie: &mut impl StoredTrie,
+    tx: &TX,
+    state: &HashedStateChanges,
+    hash_builder: &mut HashBuilder,
+    from: &Nibbles,
+    to: Option<&Nibbles>,
+) -> Result<(), TrieError> {
+    let from = from.pack_padded();
+    let mut cursor = tx.cursor_read::<tables::HashedAccount>()?;
+    let accounts = MergedEntries::new(
+        cursor.walk(Some(from))?,
+        state.accounts.range(from..).map(|(hashed_address, account)| (*hashed_address, *account)),
+    );
+
+    for entry in accounts {
+        let (hashed_address, account) = entry?;
+        let key = Nibbles::unpack(hashed_address);
+        if to.map_or(false, |to| key >= *to) {
+            break
+        }
+        let Some(account) = account else { continue };
+
+        let storage_root = storage_root(tx, hashed_address, state.storages.get(&hashed_address))?;
+        let mut value = Vec::new();
+        EthAccount::from(account).with_storage_root(storage_root).encode(&mut value);
+        trie.add_leaf(hash_builder, key, &value)?;
+    }
+    Ok(())
+}
+
+/// The changes to the branch nodes of a storage trie, computed with a read-only transaction.
+#[derive(Debug)]
+struct StorageTrieUpdate {
+    /// The hashed address of the account.
+    hashed_address: H256,
+    /// The paths of the removed branch nodes.
+    removed: Vec<Nibbles>,
+    /// The built branch nodes, by path.
+    branches: BTreeMap<Nibbles, Vec<u8>>,
+}
+
+/// Calculates the storage trie of an account after the changes to the keys, or from scratch if
+/// `changed` is `None`.
+fn storage_trie_update<'db, TX: DbTx<'db>>(
+    tx: &TX,
+    hashed_address: H256,
+    changed: Option<&BTreeSet<H256>>,
+) -> Result<StorageTrieUpdate, TrieError> {
+    let mut trie = StorageTrie::new(tx, hashed_address, None);
+    let changed = match changed {
+        Some(keys) => keys.iter().map(Nibbles::unpack).collect(),
+        None => {
+            trie.rebuild = true;
+            BTreeSet::new()
+        }
+    };
+    walk(&mut trie, &changed, &mut HashBuilder::default())?;
+
+    let StorageTrie { removed, branches, .. } = trie;
+    Ok(StorageTrieUpdate { hashed_address, removed, branches })
+}
+
+/// Returns the root of an account's storage trie, with the changes applied on top of the hashed
+/// storage.
+fn storage_root<'db, TX: DbTx<'db>>(
+    tx: &TX,
+    hashed_address: H256,
+    changes: Option<&HashedStorageChanges>,
+) -> Result<H256, TrieError> {
+    let changed = changes.map(HashedStorageChanges::changed_keys).unwrap_or_default();
+    let mut trie = StorageTrie::new(tx, hashed_address, changes);
+    walk(&mut trie, &changed, &mut HashBuilder::default())
+}
+
+/// The storage trie of an account, with leaves from [tables::HashedStorage] and branch nodes in
+/// [tables::StorageBranches], and changes applied on top of both.
+///
+/// The branch nodes aren't written, but collected by the walk, so storage tries can be computed
+/// with read-only transactions.
+struct StorageTrie<'a, 'tx, TX> {
+    tx: &'tx TX,
+    /// The hashed address of the account.
+    hashed_address: H256,
+    /// The changes to the hashed storage.
+    changes: Option<&'a HashedStorageChanges>,
+    /// Whether the trie is built from scratch, ignoring the stored branch nodes.
+    rebuild: bool,
+    /// The paths of the removed branch nodes.
+    removed: Vec<Nibbles>,
+    /// The built branch nodes, by path.
+    branches: BTreeMap<Nibbles, Vec<u8>>,
+}
+
+impl<'a, 'tx, TX> StorageTrie<'a, 'tx, TX> {
+    fn new(tx: &'tx TX, hashed_address: H256, changes: Option<&'a HashedStorageChanges>) -> Self {
+        Self {
+            tx,
+            hashed_address,
+            changes,
+            rebuild: false,
+            removed: Vec::new(),
+            branches: BTreeMap::new(),
+        }
+    }
+
+    /// Returns `true` if the storage in the database was wiped by the changes.
+    fn wiped(&self) -> bool {
+        self.changes.map_or(false, |changes| changes.wiped)
+    }
+}
+
+impl<'a, 'tx, 'db, TX> StoredTrie for StorageTrie<'a, 'tx, TX>
+where
+    TX: DbTx<'db>,
+{
+    fn seek_branch(&mut self, path: &Nibbles) -> Result<Option<(Nibbles, Vec<u8>)>, TrieError> {
+        if self.rebuild || self.wiped() {
+            return Ok(None)
+        }
+        let mut cursor = self.tx.cursor_dup_read::<tables::StorageBranches>()?;
+        Ok(cursor
+            .seek_by_key_subkey(self.hashed_address, path.clone().into())?
+            .map(|entry| (entry.nibbles.into(), entry.node)))
+    }
+
+    fn delete_branch(&mut self, path: &Nibbles) -> Result<(), TrieError> {
+        self.removed.push(path.clone());
+        Ok(())
+    }
+
+    fn store_branches(&mut self, branches: BTreeMap<Nibbles, Vec<u8>>) -> Result<(), TrieError> {
+        self.branches.extend(branches);
+        Ok(())
+    }
+
+    fn add_leaves(
+        &mut self,
+        hash_builder: &mut HashBuilder,
+        from: &Nibbles,
+        to: Option<&Nibbles>,
+    ) -> Result<(), TrieError> {
+        let from = from.pack_padded();
+        let mut cursor = self.tx.cursor_dup_read::<tables::HashedStorage>()?;
+        let mut next = if self.wiped() {
+            None
+        } else {
+            cursor.seek_by_key_subkey(self.hashed_address, from).transpose()
+        };
+        // Should be able to use walk_dup, but any call to next() causes an assert fail in mdbx.c
+        let stored = std::iter::from_fn(|| {
+            let entry = next.take()?;
+            next = cursor.next_dup().map(|entry| entry.map(|(_, value)| value)).transpose();
+            Some(entry.map(|StorageEntry { key, value }| (key, value)))
+        });
+        let changes = self.changes.map(|changes| &changes.storage);
+        let slots = MergedEntries::new(
+            stored,
+            changes
+                .into_iter()
+                .flat_map(|storage| storage.range(from..))
+                .map(|(key, value)| (*key, (*value != U256::ZERO).then_some(*value))),
+        );
+
+        for entry in slots {
+            let (key, value) = entry?;
+            let key = Nibbles::unpack(key);
+            if to.map_or(false, |to| key >= *to) {
+                break
+            }
+            let Some(value) = value else { continue };
+
+            hash_builder.add_leaf(key, &encode_fixed_size(&value));
+            if hash_builder.pending_updates() >= MAX_PENDING_BRANCHES {
+                self.branches.extend(hash_builder.take_updates());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The entries of a hashed table with changes applied on top, in ascending order of their keys.
+///
+/// The value of an entry is `None` if it was removed by the changes.
+struct MergedEntries<I: Iterator, C: Iterator> {
+    /// The entries in the database.
+    stored: Peekable<I>,
+    /// The changed entries, `None` if removed.
+    changes: Peekable<C>,
+}
+
+impl<I: Iterator, C: Iterator> MergedEntries<I, C> {
+    fn new(stored: I, changes: C) -> Self {
+        Self { stored: stored.peekable(), changes: changes.peekable() }
+    }
+}
+
+impl<V, I, C> Iterator for MergedEntries<I, C>
+where
+    I: Iterator<Item = Result<(H256, V), reth_db::Error>>,
+    C: Iterator<Item = (H256, Option<V>)>,
+{
+    type Item = Result<(H256, Option<V>), TrieError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let stored_key = match self.stored.peek() {
+            Some(Ok((key, _))) => Some(*key),
+            Some(Err(_)) => return self.next_stored(),
+            None => None,
+        };
+        let changed_key = self.changes.peek().map(|(key, _)| *key);
+
+        match (stored_key, changed_key) {
+            (Some(stored), Some(changed)) if changed <= stored => {
+                if changed == stored {
+                    // the change replaces the stored entry
+                    self.stored.next();
+                }
+                self.changes.next().map(Ok)
+            }
+            (Some(_), _) => self.next_stored(),
+            (None, _) => self.changes.next().map(Ok),
+        }
+    }
+}
+
+impl<V, I, C> MergedEntries<I, C>
+where
+    I: Iterator<Item = Result<(H256, V), reth_db::Error>>,
+    C: Iterator,
+{
+    fn next_stored(&mut self) -> Option<Result<(H256, Option<V>), TrieError>> {
+        self.stored
+            .next()
+            .map(|entry| entry.map(|(key, value)| (key, Some(value))).map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transaction;
+    use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+    use hasher::HasherKeccak;
+    use proptest::{prelude::ProptestConfig, proptest};
+    use reth_db::{
+        database::{Database, DatabaseGAT},
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+    };
+    use reth_primitives::{
+        hex_literal::hex,
+        proofs::{genesis_state_root, KeccakHasher, EMPTY_ROOT},
+        Account, Bytes, ChainSpec, Genesis, Header, StageCheckpoint, MAINNET,
+    };
+    use std::{collections::HashMap, ops::Deref, str::FromStr, sync::Arc};
+    use triehash::sec_trie_root;
+
+    fn load_mainnet_genesis_root<DB: Database>(tx: &mut Transaction<'_, DB>) -> Genesis {
+        let ChainSpec { genesis, .. } = MAINNET.clone();
+
+        // Insert account state
+        for (address, account) in &genesis.alloc {
+            tx.put::<tables::PlainAccountState>(
+                *address,
+                Account {
+                    nonce: account.nonce.unwrap_or_default(),
+                    balance: account.balance,
+                    bytecode_hash: None,
+                },
+            )
+            .unwrap();
+            tx.put::<tables::HashedAccount>(
+                keccak256(address),
+                Account {
+                    nonce: account.nonce.unwrap_or_default(),
+                    balance: account.balance,
+                    bytecode_hash: None,
+                },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        genesis
+    }
+
+    fn create_test_state_root<'tx, 'db>(
+        tx: &'tx Transaction<'db, Env<WriteMap>>,
+    ) -> StateRoot<'tx, <Arc<Env<WriteMap>> as DatabaseGAT<'db>>::TXMut> {
+        StateRoot::new(tx.deref())
+    }
+
+    /// Returns the root of the hashed state computed with `cita_trie`, which calculated the state
+    /// root before the native trie.
+    fn reference_root<DB: Database>(tx: &Transaction<'_, DB>) -> H256 {
+        let new_trie =
+            || PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::new(HasherKeccak::new()));
+
+        let mut accounts_trie = new_trie();
+        let mut accounts = tx.cursor_read::<tables::HashedAccount>().unwrap();
+        let mut storages = tx.cursor_dup_read::<tables::HashedStorage>().unwrap();
+        let mut walker = accounts.walk(None).unwrap();
+        while let Some((hashed_address, account)) = walker.next().transpose().unwrap() {
+            let mut storage_trie = new_trie();
+            let mut entry = storages.seek_by_key_subkey(hashed_address, H256::zero()).unwrap();
+            while let Some(StorageEntry { key, value }) = entry {
+                storage_trie.insert(key.to_vec(), encode_fixed_size(&value).to_vec()).unwrap();
+                entry = storages.next_dup().unwrap().map(|(_, entry)| entry);
+            }
+            let storage_root = H256::from_slice(&storage_trie.root().unwrap());
+
+            let mut out = Vec::new();
+            EthAccount::from(account).with_storage_root(storage_root).encode(&mut out);
+            accounts_trie.insert(hashed_address.to_vec(), out).unwrap();
+        }
+
+        H256::from_slice(&accounts_trie.root().unwrap())
+    }
+
+    /// Writes the changes to the hashed state.
+    fn apply_changes<DB: Database>(tx: &Transaction<'_, DB>, changes: &HashedStateChanges) {
+        for (hashed_address, account) in &changes.accounts {
+            if let Some(account) = account {
+                tx.put::<tables::HashedAccount>(*hashed_address, *account).unwrap();
+            } else {
+                tx.delete::<tables::HashedAccount>(*hashed_address, None).unwrap();
+            }
+        }
+        for (hashed_address, storage) in &changes.storages {
+            if storage.wiped {
+                tx.delete::<tables::HashedStorage>(*hashed_address, None).unwrap();
+            }
+            let mut cursor = tx.cursor_dup_write::<tables::HashedStorage>().unwrap();
+            for (key, value) in &storage.storage {
+                if cursor
+                    .seek_by_key_subkey(*hashed_address, *key)
+                    .unwrap()
+                    .filter(|e| e.key == *key)
+                    .is_some()
+                {
+                    cursor.delete_current().unwrap();
+                }
+                if *value != U256::ZERO {
+                    cursor
+                        .upsert(*hashed_address, StorageEntry { key: *key, value: *value })
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_trie() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert_eq!(create_test_state_root(&tx).calculate_root().unwrap(), EMPTY_ROOT);
+        assert_eq!(reference_root(&tx), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn single_account_trie() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
+        let account = Account { nonce: 0, balance: U256::ZERO, bytecode_hash: None };
+        tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+        let mut encoded_account = Vec::new();
+        EthAccount::from(account).encode(&mut encoded_account);
+        let expected = H256(sec_trie_root::<KeccakHasher, _, _, _>([(address, encoded_account)]).0);
+        assert_eq!(create_test_state_root(&tx).calculate_root().unwrap(), expected);
+    }
+
+    #[test]
+    fn two_accounts_trie() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let accounts = [
+            (
+                Address::from(hex!("9fe4abd71ad081f091bd06dd1c16f7e92927561e")),
+                Account { nonce: 155, balance: U256::from(414241124), bytecode_hash: None },
+            ),
+            (
+                Address::from(hex!("f8a6edaad4a332e6e550d0915a7fd5300b0b12d1")),
+                Account { nonce: 3, balance: U256::from(78978), bytecode_hash: None },
+            ),
+        ];
+        for (address, account) in accounts {
+            tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+        }
+        let encoded_accounts = accounts.iter().map(|(k, v)| {
+            let mut out = Vec::new();
+            EthAccount::from(*v).encode(&mut out);
+            (k, out)
+        });
+        let expected = H256(sec_trie_root::<KeccakHasher, _, _, _>(encoded_accounts).0);
+        assert_eq!(create_test_state_root(&tx).calculate_root().unwrap(), expected);
+    }
+
+    #[test]
+    fn single_storage_trie() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
+        let hashed_address = keccak256(address);
+
+        let storage = Vec::from([(H256::from_low_u64_be(2), U256::from(1))]);
+        for (k, v) in storage.clone() {
+            tx.put::<tables::HashedStorage>(
+                hashed_address,
+                StorageEntry { key: keccak256(k), value: v },
+            )
+            .unwrap();
+        }
+        let encoded_storage = storage.iter().map(|(k, v)| {
+            let out = encode_fixed_size(v).to_vec();
+            (k, out)
+        });
+        let expected = H256(sec_trie_root::<KeccakHasher, _, _, _>(encoded_storage).0);
+        assert_eq!(storage_root(tx.deref(), hashed_address, None).unwrap(), expected);
+    }
+
+    #[test]
+    fn single_account_with_storage_trie() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
+        let hashed_address = keccak256(address);
+
+        let storage = HashMap::from([
+            (H256::zero(), U256::from(3)),
+            (H256::from_low_u64_be(2), U256::from(1)),
+        ]);
+        let code = "el buen fla";
+        let account = Account {
+            nonce: 155,
+            balance: U256::from(414241124u32),
+            bytecode_hash: Some(keccak256(code)),
+        };
+        tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+
+        for (k, v) in storage.clone() {
+            tx.put::<tables::HashedStorage>(
+                hashed_address,
+                StorageEntry { key: keccak256(k), value: v },
+            )
+            .unwrap();
+        }
+        let mut out = Vec::new();
+
+        let encoded_storage = storage.iter().map(|(k, v)| {
+            let out = encode_fixed_size(v).to_vec();
+            (k, out)
+        });
+
+        let storage_root = H256(sec_trie_root::<KeccakHasher, _, _, _>(encoded_storage).0);
+        let eth_account = EthAccount::from(account).with_storage_root(storage_root);
+        eth_account.encode(&mut out);
+
+        let expected = H256(sec_trie_root::<KeccakHasher, _, _, _>([(address, out)]).0);
+        assert_eq!(create_test_state_root(&tx).calculate_root().unwrap(), expected);
+    }
+
+    #[test]
+    fn verify_genesis() {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let genesis = load_mainnet_genesis_root(&mut tx);
+
+        let state_root = genesis_state_root(&genesis.alloc);
+
+        assert_eq!(create_test_state_root(&tx).calculate_root().unwrap(), state_root);
+    }
+
+    #[test]
+    fn gather_changes() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
+        let hashed_address = keccak256(address);
+
+        let storage = HashMap::from([
+            (H256::zero(), U256::from(3)),
+            (H256::from_low_u64_be(2), U256::from(1)),
+        ]);
+        let code = "el buen fla";
+        let account = Account {
+            nonce: 155,
+            balance: U256::from(414241124u32),
+            bytecode_hash: Some(keccak256(code)),
+        };
+        tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+        tx.put::<tables::AccountChangeSet>(31, AccountBeforeTx { address, info: None }).unwrap();
+
+        for (k, v) in storage {
+            tx.put::<tables::HashedStorage>(
+                hashed_address,
+                StorageEntry { key: keccak256(k), value: v },
+            )
+            .unwrap();
+            tx.put::<tables::StorageChangeSet>(
+                (32, address).into(),
+                StorageEntry { key: k, value: U256::ZERO },
+            )
+            .unwrap();
+        }
+
+        let expected = BTreeMap::from([(
+            hashed_address,
+            BTreeSet::from([keccak256(H256::zero()), keccak256(H256::from_low_u64_be(2))]),
+        )]);
+        assert_eq!(create_test_state_root(&tx).gather_changes(32..33).unwrap(), expected);
+    }
+
+    fn test_with_accounts(accounts: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>) {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let encoded_accounts = accounts
+            .into_iter()
+            .map(|(address, (account, storage))| {
+                let hashed_address = keccak256(address);
+                tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+                // This is to mimic real data. Only contract accounts have storage.
+                let storage_root = if account.has_bytecode() {
+                    let encoded_storage = storage.into_iter().map(|StorageEntry { key, value }| {
+                        let hashed_key = keccak256(key);
+                        let out = encode_fixed_size(&value).to_vec();
+                        tx.put::<tables::HashedStorage>(
+                            hashed_address,
+                            StorageEntry { key: hashed_key, value },
+                        )
+                        .unwrap();
+                        (key, out)
+                    });
+                    H256(sec_trie_root::<KeccakHasher, _, _, _>(encoded_storage).0)
+                } else {
+                    EMPTY_ROOT
+                };
+                let mut out = Vec::new();
+                EthAccount::from(account).with_storage_root(storage_root).encode(&mut out);
+                (address, out)
+            })
+            .collect::<Vec<(Address, Vec<u8>)>>();
+
+        let expected = H256(sec_trie_root::<KeccakHasher, _, _, _>(encoded_accounts).0);
+        assert_eq!(
+            create_test_state_root(&tx).calculate_root().unwrap(),
+            expected,
+            "where expected is {expected:?}"
+        );
+        assert_eq!(reference_root(&tx), expected);
+        assert_eq!(create_test_state_root(&tx).stored_root().unwrap(), expected);
+
+        // computing the storage tries in parallel
+        tx.commit().unwrap();
+        let account_branches = tx.table::<tables::AccountBranches>().unwrap();
+        let storage_branches = tx.table::<tables::StorageBranches>().unwrap();
+        assert_eq!(
+            create_test_state_root(&tx).calculate_root_parallel(db.as_ref()).unwrap(),
+            expected
+        );
+        assert_eq!(tx.table::<tables::AccountBranches>().unwrap(), account_branches);
+        assert_eq!(tx.table::<tables::StorageBranches>().unwrap(), storage_branches);
+    }
+
+    #[test]
+    fn arbitrary() {
+        proptest!(ProptestConfig::with_cases(10), |(accounts: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>)| {
+            test_with_accounts(accounts);
+        });
+    }
+
+    /// Removes a third of the accounts and changes the account and storage of another third,
+    /// checking the updated root against the root of the changed state computed with `cita_trie`,
+    /// and the updated branch nodes against those of a calculation from scratch.
+    fn test_update_root(
+        initial: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>,
+        added: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>,
+        parallel: bool,
+    ) {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let mut changes = HashedStateChanges::default();
+        let mut changed_keys: BTreeMap<H256, BTreeSet<H256>> = BTreeMap::new();
+        for (index, (address, (account, storage))) in initial.iter().enumerate() {
+            let hashed_address = keccak256(address);
+            tx.put::<tables::HashedAccount>(hashed_address, *account).unwrap();
+            let hashed_storage = storage
+                .iter()
+                .map(|StorageEntry { key, value }| (keccak256(key), *value))
+                .collect::<BTreeMap<_, _>>();
+            for (key, value) in &hashed_storage {
+                let entry = StorageEntry { key: *key, value: *value };
+                tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+            }
+
+            let storage_changes = match index % 3 {
+                0 => {
+                    changes.accounts.insert(hashed_address, None);
+                    HashedStorageChanges { wiped: true, storage: BTreeMap::new() }
+                }
+                1 => {
+                    let account = Account { nonce: account.nonce.wrapping_add(1), ..*account };
+                    changes.accounts.insert(hashed_address, Some(account));
+                    // change every other slot, and clear the others
+                    let storage = hashed_storage
+                        .iter()
+                        .enumerate()
+                        .map(|(index, (key, value))| {
+                            let value =
+                                if index % 2 == 0 { *value ^ U256::from(1) } else { U256::ZERO };
+                            (*key, value)
+                        })
+                        .collect();
+                    HashedStorageChanges { wiped: false, storage }
+                }
+                _ => continue,
+            };
+            changes.storages.insert(hashed_address, storage_changes);
+            changed_keys.insert(hashed_address, hashed_storage.into_keys().collect());
+        }
+        let initial_root = create_test_state_root(&tx).calculate_root().unwrap();
+        assert_eq!(initial_root, reference_root(&tx));
+
+        // added accounts replace the initial ones with the same address
+        for (address, (account, storage)) in &added {
+            let hashed_address = keccak256(address);
+            changes.accounts.insert(hashed_address, Some(*account));
+            let storage = storage
+                .iter()
+                .map(|StorageEntry { key, value }| (keccak256(key), *value))
+                .collect::<BTreeMap<_, _>>();
+            let keys = changed_keys.entry(hashed_address).or_default();
+            keys.extend(storage.keys());
+            if let Some((_, initial_storage)) = initial.get(address) {
+                keys.extend(initial_storage.iter().map(|entry| keccak256(entry.key)));
+            }
+            changes.storages.insert(hashed_address, HashedStorageChanges { wiped: true, storage });
+        }
+        tx.commit().unwrap();
+
+        // the overlay doesn't write the changes
+        let account_branches = tx.table::<tables::AccountBranches>().unwrap();
+        let storage_branches = tx.table::<tables::StorageBranches>().unwrap();
+        let overlay_root = create_test_state_root(&tx).overlay_root(&changes).unwrap();
+        assert_eq!(tx.table::<tables::AccountBranches>().unwrap(), account_branches);
+        assert_eq!(tx.table::<tables::StorageBranches>().unwrap(), storage_branches);
+
+        apply_changes(&tx, &changes);
+        tx.commit().unwrap();
+        assert_eq!(overlay_root, reference_root(&tx));
+
+        let root = if parallel {
+            create_test_state_root(&tx).update_root_parallel(db.as_ref(), &changed_keys).unwrap()
+        } else {
+            create_test_state_root(&tx).update_root(&changed_keys).unwrap()
+        };
+        assert_eq!(root, overlay_root);
+        assert_eq!(create_test_state_root(&tx).stored_root().unwrap(), root);
+
+        // the updated branch nodes are those of the changed state
+        let account_branches = tx.table::<tables::AccountBranches>().unwrap();
+        let storage_branches = tx.table::<tables::StorageBranches>().unwrap();
+        assert_eq!(create_test_state_root(&tx).calculate_root().unwrap(), root);
+        assert_eq!(tx.table::<tables::AccountBranches>().unwrap(), account_branches);
+        assert_eq!(tx.table::<tables::StorageBranches>().unwrap(), storage_branches);
+    }
+
+    #[test]
+    fn arbitrary_update_root() {
+        proptest!(ProptestConfig::with_cases(10), |(initial: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>, added: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>)| {
+            test_update_root(initial, added, false);
+        });
+    }
+
+    #[test]
+    fn arbitrary_update_root_parallel() {
+        proptest!(ProptestConfig::with_cases(10), |(initial: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>, added: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>)| {
+            test_update_root(initial, added, true);
+        });
+    }
+
+    #[test]
+    fn update_root_parallel() {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let addresses = (0..20u64).map(Address::from_low_u64_be).collect::<Vec<_>>();
+        for (i, address) in addresses.iter().enumerate() {
+            let hashed_address = keccak256(address);
+            let account = Account { nonce: i as u64, ..Default::default() };
+            tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+            for slot in 0..i as u64 {
+                let entry = StorageEntry {
+                    key: keccak256(H256::from_low_u64_be(slot)),
+                    value: U256::from(slot + 1),
+                };
+                tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+            }
+        }
+        let previous_root = create_test_state_root(&tx).calculate_root().unwrap();
+        tx.commit().unwrap();
+
+        // update the first slot of every other account, clearing it for half of them
+        for (i, address) in addresses.iter().enumerate().step_by(2) {
+            let key = H256::zero();
+            let entry = StorageEntry { key, value: U256::ZERO };
+            tx.put::<tables::StorageChangeSet>(TransitionIdAddress((0, *address)), entry).unwrap();
+
+            let hashed_address = keccak256(address);
+            let hashed_key = keccak256(key);
+            let mut cursor = tx.cursor_dup_write::<tables::HashedStorage>().unwrap();
+            if cursor
+                .seek_by_key_subkey(hashed_address, hashed_key)
+                .unwrap()
+                .filter(|e| e.key == hashed_key)
+                .is_some()
+            {
+                cursor.delete_current().unwrap();
+            }
+            if i % 4 == 0 {
+                let entry = StorageEntry { key: hashed_key, value: U256::from(100) };
+                cursor.upsert(hashed_address, entry).unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        let changes = create_test_state_root(&tx).gather_changes(0..1).unwrap();
+        let root = create_test_state_root(&tx).update_root_parallel(db.as_ref(), &changes).unwrap();
+        assert_ne!(root, previous_root);
+
+        tx.drop().unwrap();
+        let sequential_root = create_test_state_root(&tx).update_root_for_range(0..1).unwrap();
+        assert_eq!(root, sequential_root);
+        assert_eq!(root, reference_root(&tx));
+        assert_eq!(root, create_test_state_root(&tx).calculate_root().unwrap());
+    }
+
+    #[test]
+    fn update_storage_root_keeps_unchanged_slots() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_low_u64_be(1);
+        let hashed_address = keccak256(address);
+        tx.put::<tables::HashedAccount>(hashed_address, Account::default()).unwrap();
+        for slot in 0..3u64 {
+            let entry = StorageEntry {
+                key: keccak256(H256::from_low_u64_be(slot)),
+                value: U256::from(slot + 1),
+            };
+            tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+        }
+        let previous_root = create_test_state_root(&tx).calculate_root().unwrap();
+
+        // only the second slot changes
+        let key = H256::from_low_u64_be(1);
+        let entry = StorageEntry { key, value: U256::from(2) };
+        tx.put::<tables::StorageChangeSet>(TransitionIdAddress((0, address)), entry).unwrap();
+        let mut cursor = tx.cursor_dup_write::<tables::HashedStorage>().unwrap();
+        cursor.seek_by_key_subkey(hashed_address, keccak256(key)).unwrap();
+        cursor.delete_current().unwrap();
+        cursor
+            .upsert(hashed_address, StorageEntry { key: keccak256(key), value: U256::from(100) })
+            .unwrap();
+
+        let root = create_test_state_root(&tx).update_root_for_range(0..1).unwrap();
+        assert_ne!(root, previous_root);
+        assert_eq!(root, create_test_state_root(&tx).calculate_root().unwrap());
+    }
+
+    #[test]
+    fn overlay_root() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let addresses = (0..10u64).map(|i| keccak256(H256::from_low_u64_be(i))).collect::<Vec<_>>();
+        for (i, hashed_address) in addresses.iter().enumerate() {
+            let account = Account { nonce: i as u64, ..Default::default() };
+            tx.put::<tables::HashedAccount>(*hashed_address, account).unwrap();
+            for slot in 0..i as u64 {
+                let entry = StorageEntry {
+                    key: keccak256(H256::from_low_u64_be(slot)),
+                    value: U256::from(slot + 1),
+                };
+                tx.put::<tables::HashedStorage>(*hashed_address, entry).unwrap();
+            }
+        }
+        let previous_root = create_test_state_root(&tx).calculate_root().unwrap();
+
+        let new_account = Account { nonce: 1, balance: U256::from(10), bytecode_hash: None };
+        let changes = HashedStateChanges {
+            accounts: BTreeMap::from([
+                (addresses[0], Some(new_account)),
+                (addresses[1], None),
+                (keccak256(H256::from_low_u64_be(10)), Some(new_account)),
+            ]),
+            storages: BTreeMap::from([
+                (
+                    addresses[5],
+                    HashedStorageChanges {
+                        wiped: false,
+                        storage: BTreeMap::from([
+                            (keccak256(H256::from_low_u64_be(0)), U256::ZERO),
+                            (keccak256(H256::from_low_u64_be(1)), U256::from(100)),
+                            (keccak256(H256::from_low_u64_be(20)), U256::from(200)),
+                        ]),
+                    },
+                ),
+                (
+                    addresses[9],
+                    HashedStorageChanges {
+                        wiped: true,
+                        storage: BTreeMap::from([(
+                            keccak256(H256::from_low_u64_be(30)),
+                            U256::from(300),
+                        )]),
+                    },
+                ),
+            ]),
+        };
+
+        let account_branches = tx.table::<tables::AccountBranches>().unwrap();
+        let storage_branches = tx.table::<tables::StorageBranches>().unwrap();
+        let root = create_test_state_root(&tx).overlay_root(&changes).unwrap();
+        assert_ne!(root, previous_root);
+        // nothing is written
+        assert_eq!(tx.table::<tables::AccountBranches>().unwrap(), account_branches);
+        assert_eq!(tx.table::<tables::StorageBranches>().unwrap(), storage_branches);
+
+        // apply the changes to the hashed state and rebuild the trie from scratch
+        for (hashed_address, account) in &changes.accounts {
+            if let Some(account) = account {
+                tx.put::<tables::HashedAccount>(*hashed_address, *account).unwrap();
+            } else {
+                tx.delete::<tables::HashedAccount>(*hashed_address, None).unwrap();
+            }
+        }
+        tx.delete::<tables::HashedStorage>(addresses[9], None).unwrap();
+        for (hashed_address, storage) in &changes.storages {
+            let mut cursor = tx.cursor_dup_write::<tables::HashedStorage>().unwrap();
+            for (key, value) in &storage.storage {
+                if cursor
+                    .seek_by_key_subkey(*hashed_address, *key)
+                    .unwrap()
+                    .filter(|e| e.key == *key)
+                    .is_some()
+                {
+                    cursor.delete_current().unwrap();
+                }
+                if *value != U256::ZERO {
+                    cursor
+                        .upsert(*hashed_address, StorageEntry { key: *key, value: *value })
+                        .unwrap();
+                }
+            }
+        }
+        assert_eq!(root, create_test_state_root(&tx).calculate_root().unwrap());
+        assert_eq!(root, reference_root(&tx));
+    }
+
+    #[test]
+    fn gather_reverts() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_low_u64_be(1);
+        let account = |nonce| Some(Account { nonce, ..Default::default() });
+        for transition in 0..3 {
+            tx.put::<tables::AccountChangeSet>(
+                transition,
+                AccountBeforeTx { address, info: account(transition) },
+            )
+            .unwrap();
+            tx.put::<tables::StorageChangeSet>(
+                (transition, address).into(),
+                StorageEntry { key: H256::zero(), value: U256::from(transition) },
+            )
+            .unwrap();
+        }
+
+        // the values before the given transition are kept
+        let reverts = create_test_state_root(&tx).gather_reverts(1).unwrap();
+        assert_eq!(reverts.accounts, BTreeMap::from([(keccak256(address), account(1))]));
+        assert_eq!(
+            reverts.storages[&keccak256(address)].storage,
+            BTreeMap::from([(keccak256(H256::zero()), U256::from(1))])
+        );
+    }
+
+    #[test]
+    fn state_root_at_merkle_checkpoint() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        let (a, b) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let key = H256::from_low_u64_be(1);
+        for address in [a, b] {
+            tx.put::<tables::PlainAccountState>(address, Account::default()).unwrap();
+            tx.put::<tables::HashedAccount>(keccak256(address), Account::default()).unwrap();
+        }
+
+        // the trie is at the genesis block
+        let genesis_root = create_test_state_root(&tx).calculate_root().unwrap();
+        let header = Header { state_root: genesis_root, ..Default::default() };
+        tx.put::<tables::Headers>(0, header).unwrap();
+        tx.put::<tables::BlockTransitionIndex>(0, 0).unwrap();
+        tx.put::<tables::SyncStage>(MERKLE_STAGE_ID.to_string(), StageCheckpoint::new(0)).unwrap();
+
+        // the next transition is only in the plain state and the changesets
+        let nonce = Account { nonce: 1, ..Default::default() };
+        tx.put::<tables::AccountChangeSet>(
+            0,
+            AccountBeforeTx { address: a, info: Some(Account::default()) },
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(a, nonce).unwrap();
+        tx.put::<tables::StorageChangeSet>((0, b).into(), StorageEntry { key, value: U256::ZERO })
+            .unwrap();
+        tx.put::<tables::PlainStorageState>(b, StorageEntry { key, value: U256::from(5) }).unwrap();
+
+        let state_root = create_test_state_root(&tx);
+        assert_eq!(state_root.trie_root().unwrap(), (genesis_root, 0));
+        assert_eq!(
+            state_root.state_root_at(Some(0), &HashedStateChanges::default()).unwrap(),
+            genesis_root
+        );
+        let latest_root = state_root.state_root_at(None, &HashedStateChanges::default()).unwrap();
+
+        // the hashed state can be ahead of the trie
+        tx.put::<tables::HashedAccount>(keccak256(a), nonce).unwrap();
+        tx.put::<tables::HashedStorage>(
+            keccak256(b),
+            StorageEntry { key: keccak256(key), value: U256::from(5) },
+        )
+        .unwrap();
+        assert_eq!(
+            state_root.state_root_at(Some(0), &HashedStateChanges::default()).unwrap(),
+            genesis_root
+        );
+        assert_eq!(
+            state_root.state_root_at(None, &HashedStateChanges::default()).unwrap(),
+            latest_root
+        );
+        assert_eq!(latest_root, reference_root(&tx));
+        assert_eq!(latest_root, create_test_state_root(&tx).calculate_root().unwrap());
+    }
+
+    #[test]
+    fn get_proof() {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        load_mainnet_genesis_root(&mut tx);
+
+        create_test_state_root(&tx).calculate_root().expect("should be able to load trie");
+
+        tx.commit().unwrap();
+
+        let address = Address::from(hex!("000d836201318ec6899a67540690382780743280"));
+
+        let trie = create_test_state_root(&tx);
+        let (proof, storage_root) = trie
+            .account_proof(&HashedStateChanges::default(), keccak256(address))
+            .expect("failed to generate proof");
+
+        // values extracted from geth via rpc:
+        // {
+        //  "method": "eth_getProof",
+        //  "params": ["0x000d836201318ec6899a67540690382780743280", [], "0x0"]
+        // }
+        let expected = [
+            hex!("f90211a090dcaf88c40c7bbc95a912cbdde67c175767b31173df9ee4b0d733bfdd511c43a0babe369f6b12092f49181ae04ca173fb68d1a5456f18d20fa32cba73954052bda0473ecf8a7e36a829e75039a3b055e51b8332cbf03324ab4af2066bbd6fbf0021a0bbda34753d7aa6c38e603f360244e8f59611921d9e1f128372fec0d586d4f9e0a04e44caecff45c9891f74f6a2156735886eedf6f1a733628ebc802ec79d844648a0a5f3f2f7542148c973977c8a1e154c4300fec92f755f7846f1b734d3ab1d90e7a0e823850f50bf72baae9d1733a36a444ab65d0a6faaba404f0583ce0ca4dad92da0f7a00cbe7d4b30b11faea3ae61b7f1f2b315b61d9f6bd68bfe587ad0eeceb721a07117ef9fc932f1a88e908eaead8565c19b5645dc9e5b1b6e841c5edbdfd71681a069eb2de283f32c11f859d7bcf93da23990d3e662935ed4d6b39ce3673ec84472a0203d26456312bbc4da5cd293b75b840fc5045e493d6f904d180823ec22bfed8ea09287b5c21f2254af4e64fca76acc5cd87399c7f1ede818db4326c98ce2dc2208a06fc2d754e304c48ce6a517753c62b1a9c1d5925b89707486d7fc08919e0a94eca07b1c54f15e299bd58bdfef9741538c7828b5d7d11a489f9c20d052b3471df475a051f9dd3739a927c89e357580a4c97b40234aa01ed3d5e0390dc982a7975880a0a089d613f26159af43616fd9455bb461f4869bfede26f2130835ed067a8b967bfb80").as_slice(),
+            hex!("f90211a0dae48f5b47930c28bb116fbd55e52cd47242c71bf55373b55eb2805ee2e4a929a00f1f37f337ec800e2e5974e2e7355f10f1a4832b39b846d916c3597a460e0676a0da8f627bb8fbeead17b318e0a8e4f528db310f591bb6ab2deda4a9f7ca902ab5a0971c662648d58295d0d0aa4b8055588da0037619951217c22052802549d94a2fa0ccc701efe4b3413fd6a61a6c9f40e955af774649a8d9fd212d046a5a39ddbb67a0d607cdb32e2bd635ee7f2f9e07bc94ddbd09b10ec0901b66628e15667aec570ba05b89203dc940e6fa70ec19ad4e01d01849d3a5baa0a8f9c0525256ed490b159fa0b84227d48df68aecc772939a59afa9e1a4ab578f7b698bdb1289e29b6044668ea0fd1c992070b94ace57e48cbf6511a16aa770c645f9f5efba87bbe59d0a042913a0e16a7ccea6748ae90de92f8aef3b3dc248a557b9ac4e296934313f24f7fced5fa042373cf4a00630d94de90d0a23b8f38ced6b0f7cb818b8925fee8f0c2a28a25aa05f89d2161c1741ff428864f7889866484cef622de5023a46e795dfdec336319fa07597a017664526c8c795ce1da27b8b72455c49657113e0455552dbc068c5ba31a0d5be9089012fda2c585a1b961e988ea5efcd3a06988e150a8682091f694b37c5a0f7b0352e38c315b2d9a14d51baea4ddee1770974c806e209355233c3c89dce6ea049bf6e8df0acafd0eff86defeeb305568e44d52d2235cf340ae15c6034e2b24180").as_slice(),
+            hex!("f901f1a0cf67e0f5d5f8d70e53a6278056a14ddca46846f5ef69c7bde6810d058d4a9eda80a06732ada65afd192197fe7ce57792a7f25d26978e64e954b7b84a1f7857ac279da05439f8d011683a6fc07efb90afca198fd7270c795c835c7c85d91402cda992eaa0449b93033b6152d289045fdb0bf3f44926f831566faa0e616b7be1abaad2cb2da031be6c3752bcd7afb99b1bb102baf200f8567c394d464315323a363697646616a0a40e3ed11d906749aa501279392ffde868bd35102db41364d9c601fd651f974aa0044bfa4fe8dd1a58e6c7144da79326e94d1331c0b00373f6ae7f3662f45534b7a098005e3e48db68cb1dc9b9f034ff74d2392028ddf718b0f2084133017da2c2e7a02a62bc40414ee95b02e202a9e89babbabd24bef0abc3fc6dcd3e9144ceb0b725a0239facd895bbf092830390a8676f34b35b29792ae561f196f86614e0448a5792a0a4080f88925daff6b4ce26d188428841bd65655d8e93509f2106020e76d41eefa04918987904be42a6894256ca60203283d1b89139cf21f09f5719c44b8cdbb8f7a06201fc3ef0827e594d953b5e3165520af4fceb719e11cc95fd8d3481519bfd8ca05d0e353d596bd725b09de49c01ede0f29023f0153d7b6d401556aeb525b2959ba0cd367d0679950e9c5f2aa4298fd4b081ade2ea429d71ff390c50f8520e16e30880").as_slice(),
+            hex!("f87180808080808080a0dbee8b33c73b86df839f309f7ac92eee19836e08b39302ffa33921b3c6a09f66a06068b283d51aeeee682b8fb5458354315d0b91737441ede5e137c18b4775174a8080808080a0fe7779c7d58c2fda43eba0a6644043c86ebb9ceb4836f89e30831f23eb059ece8080").as_slice(),
+            hex!("f8719f20b71c90b0d523dd5004cf206f325748da347685071b34812e21801f5270c4b84ff84d80890ad78ebc5ac6200000a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470").as_slice(),
+        ];
+
+        assert_eq!(storage_root, EMPTY_ROOT);
+
+        assert_eq!(proof.len(), 5);
+
+        for (node, expected) in proof.into_iter().zip(expected.into_iter()) {
+            assert_eq!(Bytes::from(node.as_slice()), Bytes::from(expected));
+        }
+    }
+
+    #[test]
+    fn get_storage_proofs() {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
+        let hashed_address = keccak256(address);
+
+        let storage = HashMap::from([
+            (H256::zero(), U256::from(3)),
+            (H256::from_low_u64_be(2), U256::from(1)),
+        ]);
+
+        let code = "el buen fla";
+        let account = Account {
+            nonce: 155,
+            balance: U256::from(414241124u32),
+            bytecode_hash: Some(keccak256(code)),
+        };
+        tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+
+        for (k, v) in storage {
+            tx.put::<tables::HashedStorage>(
+                hashed_address,
+                StorageEntry { key: keccak256(k), value: v },
+            )
+            .unwrap();
+        }
+
+        create_test_state_root(&tx).calculate_root().expect("should be able to load trie");
+
+        tx.commit().unwrap();
+
+        let trie = create_test_state_root(&tx);
+        let changes = HashedStateChanges::default();
+        let (account_proof, storage_root) =
+            trie.account_proof(&changes, hashed_address).expect("failed to generate proof");
+
+        // values extracted from geth via rpc:
+        let expected_account = hex!("f86fa1205126413e7857595763591580306b3f228f999498c4c5dfa74f633364936e7651b84bf849819b8418b0d164a029ff6f4d518044318d75b118cf439d8d3d7249c8afcba06ba9ecdf8959410571a02ce1a85814ad94a94ed2a1abaf7c57e9b64326622c1b8c21b4ba4d0e7df61392").as_slice();
+        let expected_storage = [
+            [
+                // 0x0000000000000000000000000000000000000000000000000000000000000002
+                hex!("f8518080a04355bd3061ad2d17e0782413925b4fd81a56bd162d91eedb2a00d6c87611471480a015503e91f9250654cf72906e38a7cb14c3f1cc06658379d37f0c5b5c32482880808080808080808080808080").as_slice(),
+                hex!("e2a0305787fa12a823e0f2b7631cc41b3ba8828b3321ca811111fa75cd3aa3bb5ace01").as_slice(),
+            ],
+            [
+                // 0x0000000000000000000000000000000000000000000000000000000000000000
+                hex!("f8518080a04355bd3061ad2d17e0782413925b4fd81a56bd162d91eedb2a00d6c87611471480a015503e91f9250654cf72906e38a7cb14c3f1cc06658379d37f0c5b5c32482880808080808080808080808080").as_slice(),
+                hex!("e2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e56303").as_slice(),
+            ]
+        ];
+
+        assert!(storage_root != EMPTY_ROOT);
+
+        assert_eq!(account_proof.len(), 1);
+        assert_eq!(account_proof[0], expected_account);
+
+        let storage_proofs = trie
+            .storage_proofs(
+                &changes,
+                hashed_address,
+                &[keccak256(H256::from_low_u64_be(2)), keccak256(H256::zero())],
+            )
+            .expect("couldn't generate storage proof");
+
+        for (proof, expected) in storage_proofs.into_iter().zip(expected_storage) {
+            assert_eq!(proof.len(), expected.len());
+            for (got_node, expected_node) in proof.into_iter().zip(expected) {
+                assert_eq!(got_node, expected_node);
+            }
+        }
+    }
+}